@@ -0,0 +1,44 @@
+//! Compares `status` and `status_fast` on a repository with many files.
+//!
+//! Run with `cargo bench --bench status`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+use GitPilot::testing::TempRepo;
+
+/// Creates a repository with `files` committed files spread over 100
+/// directories, a tenth of them modified and as many untracked files again.
+fn large_repo(files: usize) -> TempRepo {
+    let repo = TempRepo::new();
+    for i in 0..files {
+        let dir = repo.path().join(format!("dir{:03}", i % 100));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("file{}.txt", i)), format!("{}\n", i)).unwrap();
+    }
+    repo.cmd(["add", "--all"]).unwrap();
+    repo.commit_staged("many files").unwrap();
+    for i in (0..files).step_by(10) {
+        let dir = repo.path().join(format!("dir{:03}", i % 100));
+        fs::write(dir.join(format!("file{}.txt", i)), "changed\n").unwrap();
+        fs::write(dir.join(format!("new{}.txt", i)), "new\n").unwrap();
+    }
+    repo
+}
+
+fn bench_status(c: &mut Criterion) {
+    let mut group = c.benchmark_group("status");
+    group.sample_size(20);
+    for files in [1_000, 20_000] {
+        let repo = large_repo(files);
+        group.bench_with_input(BenchmarkId::new("status", files), &repo, |b, repo| {
+            b.iter(|| repo.status().unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("status_fast", files), &repo, |b, repo| {
+            b.iter(|| repo.status_fast().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_status);
+criterion_main!(benches);
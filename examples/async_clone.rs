@@ -120,8 +120,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             println!("  Hash: {}", commit.hash);
             println!("  Short hash: {}", commit.short_hash);
             println!("  Author: {} <{}>", commit.author_name, commit.author_email);
-            // Assuming commit message is single line from format %s
-            println!("  Message: {}", commit.message);
+            println!("  Subject: {}", commit.subject);
         }
         Err(e) => eprintln!("Failed to get current commit: {}", e),
     }
@@ -142,7 +141,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 println!("  Changed Files: {}", status.files.len());
                 // Optionally print file details
                 // for entry in status.files.iter().take(5) { // Print first 5
-                //     println!("    - {:?}: {}", entry.status, entry.path.display());
+                //     println!("    - {:?}: {}", entry.status(), entry.path.display());
                 // }
                 // if status.files.len() > 5 { println!("    ..."); }
             } else {
@@ -43,23 +43,19 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 
     let repo_path = PathBuf::from(&args[1]);
-    let mut repo = None;
 
     // Repository opening/cloning logic remains the same
-    if args.len() >= 4 && args[2] == "--clone" {
+    let repo = if args.len() >= 4 && args[2] == "--clone" {
         let url = GitUrl::from_str(&args[3])?;
         println!("Cloning repository from {} to {}...", url, repo_path.display());
-        repo = Some(Repository::clone(url, &repo_path)?);
+        Repository::clone(url, &repo_path)?
     } else if repo_path.exists() {
-        repo = Some(Repository::new(&repo_path));
+        Repository::new(&repo_path)
     } else {
         eprintln!("Error: Directory does not exist: {}. Use --clone to clone a repository.", repo_path.display());
         // Return Ok to avoid panic
         return Ok(());
-    }
-
-    // Use expect for simplicity in example, real code might handle None better
-    let repo = repo.expect("Repository should have been opened or cloned");
+    };
 
     // Get basic repository information
     println!("Repository Analysis for: {}", repo_path.display());
@@ -123,7 +119,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                           .unwrap_or_else(|| "Invalid Date".to_string())
             );
             println!("  Author: {} <{}>", head_commit.author_name, head_commit.author_email);
-            println!("  Message: {}", head_commit.message);
+            println!("  Subject: {}", head_commit.subject);
         },
         Err(e) => eprintln!("Failed to get HEAD commit: {}", e),
     }
@@ -131,78 +127,26 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Calculate commit statistics
     println!("\nAnalyzing repository history...");
 
-    // Get all commit hashes
-    let log_output = repo.cmd_out(["log", "--format=%H"])?; // Returns Vec<String>
-    let total_commits = log_output.len();
+    // Count commits cheaply before loading any history
+    let total_commits = repo.count_commits(None)?;
     println!("Total commits found: {}", total_commits);
 
-    // Limiting analysis for performance in example
-    let limit = std::cmp::min(100, total_commits);
-    if limit < total_commits {
-        println!("Analyzing stats for the latest {} commits...", limit);
-    } else {
-        println!("Analyzing stats for all {} commits...", limit);
-    }
-
-
-    let mut commit_stats = Vec::new();
-    for i in 0..limit {
-        let commit_hash_str = &log_output[i]; // This is &String
-
-        // Get commit details
-        // get_commit takes Option<&str>, &String derefs to &str - OK
-        if let Ok(commit) = repo.get_commit(Some(commit_hash_str)) {
-            // For each commit, calculate the diff statistics
-            let mut stats = CommitStats {
-                author: commit.author_name.clone(),
-                timestamp: commit.timestamp,
-                added_lines: 0,
-                removed_lines: 0,
-                files_changed: 0,
-            };
-
-            // Calculate diff with the first parent if it exists
-            // commit.parents is Vec<CommitHash>
-            if let Some(parent_hash) = commit.parents.first() { // Use first() to get Option<&CommitHash>
-                // --- FIX: Pass refs correctly to cmd_out ---
-                // parent_hash is &CommitHash, use as_ref() -> &str
-                // commit_hash_str is &String, use as_ref() -> &str or rely on deref
-                let diff_output = repo.cmd_out([
-                    "diff",
-                    "--numstat",
-                    parent_hash.as_ref(), // &str from &CommitHash
-                    commit_hash_str.as_ref(), // &str from &String
-                ])?;
-                // --- End Fix ---
-
-                stats.files_changed = diff_output.len();
-
-                for diff_line in diff_output {
-                    let parts: Vec<&str> = diff_line.split_whitespace().collect();
-                    if parts.len() >= 3 {
-                        // numstat format is <added> <removed> <path>
-                        // Handle '-' for binary files
-                        if let Ok(added) = parts[0].parse::<usize>() {
-                            stats.added_lines += added;
-                        }
-                        if let Ok(removed) = parts[1].parse::<usize>() {
-                            stats.removed_lines += removed;
-                        }
-                    }
-                }
-            } else {
-                // Initial commit - try diffing against the empty tree?
-                // `git diff --numstat 4b825dc642cb6eb9a060e54bf8d69288fbee4904` (empty tree hash)
-                // Or just count lines in the commit using `git show --numstat <commit>`
-                // For simplicity in example, we'll skip diff for initial commit.
-                stats.files_changed = 0; // Assume 0 diff for initial commit in this example
-            }
-
-            commit_stats.push(stats);
-        } else {
-            eprintln!("Warning: Failed to get commit details for {}", commit_hash_str);
-        }
-    }
+    // A single `git log --numstat` invocation covers the whole history,
+    // so there's no need to cap the number of commits analyzed.
+    let history = repo.log_with_stats(None)?;
+    let limit = history.len();
+    println!("Analyzing stats for all {} commits...", limit);
+
+    let commit_stats: Vec<CommitStats> = history
+        .iter()
+        .map(|entry| CommitStats {
+            author: entry.commit.author_name.clone(),
+            timestamp: entry.commit.timestamp,
+            added_lines: entry.added_lines(),
+            removed_lines: entry.removed_lines(),
+            files_changed: entry.files.len(),
+        })
+        .collect();
 
     // Aggregate statistics by author
     let mut author_stats = HashMap::new();
@@ -212,7 +156,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             added_lines: 0,
             removed_lines: 0,
             files_changed: 0,
-            first_commit: std::u64::MAX,
+            first_commit: u64::MAX,
             last_commit: 0,
         });
 
@@ -232,7 +176,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Sort authors for consistent output, e.g., by commit count
     let mut sorted_authors: Vec<_> = author_stats.iter().collect();
-    sorted_authors.sort_by(|a, b| b.1.commits.cmp(&a.1.commits));
+    sorted_authors.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.commits));
 
     for (author, stats) in sorted_authors {
         let first_date = Local.timestamp_opt(stats.first_commit as i64, 0)
@@ -293,15 +237,25 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .map(|b| b.to_string())
                 .unwrap_or_else(|| "(Detached HEAD)".to_string());
             println!("  Branch: {}", branch_display);
+            if let Some(upstream) = &status.upstream {
+                println!("  Upstream: {} (ahead {}, behind {})", upstream, status.ahead, status.behind);
+            }
             println!("  Is Clean: {}", status.is_clean);
 
+            if let Ok(diff) = repo.diff_stats("HEAD", None) {
+                if diff.files_changed > 0 {
+                    println!("  Uncommitted: {} files changed, +{} / -{} lines",
+                             diff.files_changed, diff.insertions, diff.deletions);
+                }
+            }
+
             if !status.files.is_empty() {
                 println!("  Working Directory Changes: {}", status.files.len());
 
                 // Added use GitPilot::models::FileStatus; at the top
                 for entry in status.files.iter().take(10) { // Limit output
                     // Use simple match for display
-                    let status_str = match entry.status {
+                    let status_str = match entry.status() {
                         FileStatus::Modified => "Modified",
                         FileStatus::Added => "Added",
                         FileStatus::Deleted => "Deleted (WT)", // Clarify Working Tree delete
@@ -1,18 +1,18 @@
 //! Provides asynchronous versions of the Git operations using tokio.
 
 use crate::error::GitError;
+use crate::options::{BranchFilter, CloneOptions, FetchOptions, PushOptions};
+use crate::commands::{self, GitCommand};
+use crate::repository::{normalize_location, with_default_remote, GitContext, Location, Repository};
 // Import specific types for integration
-use crate::types::{BranchName, CommitHash, GitUrl, Remote, Result}; // Added CommitHash, Remote
-use crate::models::{
-                     Commit, StatusEntry, FileStatus, Branch, StatusResult,
-};
-use std::ffi::OsStr;
+use crate::types::{BranchName, CommitHash, GitUrl, Pathspec, Remote, Result}; // Added CommitHash, Remote
+use crate::models::{Commit, Branch, FetchResult, PushResult, StatusResult};
+use futures_util::future::join_all;
+use std::ffi::{OsStr, OsString};
 use std::io::ErrorKind; // Needed for GitNotFound check
 use std::path::{Path, PathBuf};
-use std::str::{self, FromStr}; // Added FromStr for parsing
-
-// Use tokio::process::Command for async execution
-use tokio::process::Command;
+use std::str;
+use std::time::Instant;
 
 /// Represents a local Git repository with async operations.
 ///
@@ -20,7 +20,7 @@ use tokio::process::Command;
 /// but uses asynchronous I/O (via tokio) for Git operations.
 #[derive(Debug, Clone)]
 pub struct AsyncRepository {
-    location: PathBuf,
+    location: Location,
 }
 
 impl AsyncRepository {
@@ -33,34 +33,53 @@ impl AsyncRepository {
     /// * `p` - The path to the local repository's root directory.
     pub fn new<P: AsRef<Path>>(p: P) -> AsyncRepository {
         AsyncRepository {
-            location: PathBuf::from(p.as_ref()),
+            location: Location::new(p.as_ref()),
         }
     }
 
+    /// Returns the root of the repository.
+    pub fn path(&self) -> &Path {
+        self.location.path()
+    }
+
+    /// Returns the remote set with `RepositoryBuilder::default_remote`, if any.
+    pub fn default_remote(&self) -> Option<&Remote> {
+        self.location.settings.default_remote.as_ref()
+    }
+
     /// Clones a remote Git repository into a specified local path asynchronously.
     ///
-    /// Equivalent to `git clone <url> <path>`.
+    /// Equivalent to `git clone -- <url> <path>`.
     ///
     /// # Arguments
-    /// * `url` - The URL of the remote repository.
+    /// * `url` - The URL of the remote repository. Local repositories can be cloned
+    ///   through `GitUrl::from_path` or a `file://` URL.
     /// * `p` - The target local path where the repository should be cloned.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn clone<P: AsRef<Path>>(url: GitUrl, p: P) -> Result<AsyncRepository> {
-        let p_ref = p.as_ref();
-        // Use tokio's async canonicalize or just get current dir if needed
-        // Note: Cloning doesn't strictly need the CWD if target path is absolute or relative to process CWD.
-        // Using "." might be sufficient if running from expected location.
-        let cwd = PathBuf::from("."); // Simplified CWD handling for clone
-
-        let args: Vec<&OsStr> = vec!["clone".as_ref(), url.as_ref(), p_ref.as_os_str()];
-
-        execute_git_async(cwd, args).await?; // Execute in CWD, cloning *into* p
+        let cwd = std::env::current_dir().map_err(|_| GitError::WorkingDirectoryInaccessible)?;
+        let target = normalize_location(p.as_ref());
+        commands::clone(&url, &target).run_async(&cwd).await?; // Execute in CWD, cloning *into* p
+        Ok(AsyncRepository::new(target))
+    }
 
-        Ok(AsyncRepository {
-            location: PathBuf::from(p_ref),
-        })
+    /// Clones a remote Git repository asynchronously with options such as a
+    /// branch, a depth or a partial clone filter; see `Repository::clone_with`.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the remote repository.
+    /// * `p` - The target local path where the repository should be cloned.
+    /// * `opts` - A `CloneOptions`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub async fn clone_with<P: AsRef<Path>>(url: &GitUrl, p: P, opts: &CloneOptions) -> Result<AsyncRepository> {
+        let cwd = std::env::current_dir().map_err(|_| GitError::WorkingDirectoryInaccessible)?;
+        let target = normalize_location(p.as_ref());
+        commands::clone_with(url, &target, opts).run_async(&cwd).await?;
+        Ok(AsyncRepository::new(target))
     }
 
     /// Initializes a new Git repository in the specified directory asynchronously.
@@ -73,11 +92,9 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn init<P: AsRef<Path>>(p: P) -> Result<AsyncRepository> {
-        let p_ref = p.as_ref();
-        execute_git_async(&p_ref, &["init"]).await?;
-        Ok(AsyncRepository {
-            location: PathBuf::from(p_ref),
-        })
+        let location = normalize_location(p.as_ref());
+        commands::init().run_async(&location).await?;
+        Ok(AsyncRepository::new(location))
     }
 
     /// Creates and checks out a new local branch asynchronously.
@@ -90,10 +107,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn create_local_branch(&self, branch_name: &BranchName) -> Result<()> {
-        execute_git_async(
-            &self.location,
-            &["checkout", "-b", branch_name.as_ref()],
-        ).await
+        commands::create_branch(branch_name, None).run_async(&self.location).await
     }
 
     /// Checks out an existing local branch asynchronously.
@@ -106,45 +120,35 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn switch_branch(&self, branch_name: &BranchName) -> Result<()> {
-        execute_git_async(&self.location, &["checkout", branch_name.as_ref()]).await
+        commands::switch_branch(branch_name).run_async(&self.location).await
     }
 
     /// Adds file contents to the Git index (staging area) asynchronously.
     ///
-    /// Equivalent to `git add <pathspec>...`.
+    /// Equivalent to `git add -- <pathspec>...`.
     ///
     /// # Arguments
-    /// * `pathspecs` - A vector of file paths or patterns to add.
+    /// * `pathspecs` - A vector of file paths or `Pathspec`s to add.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
-    pub async fn add<S: AsRef<OsStr>>(&self, pathspecs: Vec<S>) -> Result<()> {
-        let mut args: Vec<&OsStr> = Vec::with_capacity(pathspecs.len() + 1);
-        args.push("add".as_ref());
-        // Note: Need to handle lifetime if pathspecs is consumed. Cloning or iterating refs is safer.
-        let refs: Vec<_> = pathspecs.iter().map(|s| s.as_ref()).collect();
-        args.extend(refs);
-        execute_git_async(&self.location, args).await
+    pub async fn add<S: Into<Pathspec>>(&self, pathspecs: Vec<S>) -> Result<()> {
+        let pathspecs: Vec<Pathspec> = pathspecs.into_iter().map(Into::into).collect();
+        commands::add(&pathspecs)?.run_async(&self.location).await
     }
 
     /// Removes files from the working tree and the index asynchronously.
     /// Added based on sync Repository.
     ///
     /// # Arguments
-    /// * `pathspecs` - A vector of file paths or patterns to remove.
+    /// * `pathspecs` - A vector of file paths or `Pathspec`s to remove.
     /// * `force` - If `true`, corresponds to the `-f` flag (force removal).
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
-    pub async fn remove<S: AsRef<OsStr>>(&self, pathspecs: Vec<S>, force: bool) -> Result<()> {
-        let mut args: Vec<&OsStr> = Vec::with_capacity(pathspecs.len() + 2);
-        args.push("rm".as_ref());
-        if force {
-            args.push("-f".as_ref());
-        }
-        let refs: Vec<_> = pathspecs.iter().map(|s| s.as_ref()).collect();
-        args.extend(refs);
-        execute_git_async(&self.location, args).await
+    pub async fn remove<S: Into<Pathspec>>(&self, pathspecs: Vec<S>, force: bool) -> Result<()> {
+        let pathspecs: Vec<Pathspec> = pathspecs.into_iter().map(Into::into).collect();
+        commands::remove(&pathspecs, force)?.run_async(&self.location).await
     }
 
     /// Stages all tracked, modified/deleted files and commits them asynchronously.
@@ -158,7 +162,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn stage_and_commit_all_modified(&self, message: &str) -> Result<()> {
-        execute_git_async(&self.location, &["commit", "-am", message]).await
+        commands::commit_all_modified(message).run_async(&self.location).await
     }
 
 
@@ -172,17 +176,21 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn commit_staged(&self, message: &str) -> Result<()> {
-        execute_git_async(&self.location, &["commit", "-m", message]).await
+        commands::commit_staged(message).run_async(&self.location).await
     }
 
     /// Pushes the current branch to its configured upstream remote branch asynchronously.
     ///
     /// Equivalent to `git push`.
     ///
+    /// # Returns
+    /// A `PushResult` with the outcome for each pushed ref.
+    ///
     /// # Errors
+    /// Returns `GitError::PushRejected` if the remote refuses a ref.
     /// Returns `GitError` (including `GitNotFound`).
-    pub async fn push(&self) -> Result<()> {
-        execute_git_async(&self.location, &["push"]).await
+    pub async fn push(&self) -> Result<PushResult> {
+        commands::push().run_async(&self.location).await
     }
 
     /// Pushes the current branch to a specified remote and sets the upstream configuration asynchronously.
@@ -193,37 +201,67 @@ impl AsyncRepository {
     /// * `upstream_remote` - The name of the remote. (Changed to &Remote)
     /// * `upstream_branch` - The name of the branch on the remote.
     ///
+    /// # Returns
+    /// A `PushResult` with the outcome for each pushed ref.
+    ///
     /// # Errors
+    /// Returns `GitError::PushRejected` if the remote refuses a ref.
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn push_to_upstream(
         &self,
         upstream_remote: &Remote, // Changed type
         upstream_branch: &BranchName,
-    ) -> Result<()> {
-        execute_git_async(
-            &self.location,
-            &[
-                "push",
-                "-u",
-                upstream_remote.as_ref(), // Use AsRef
-                upstream_branch.as_ref(),
-            ],
-        ).await
+    ) -> Result<PushResult> {
+        commands::push_to_upstream(upstream_remote, upstream_branch).run_async(&self.location).await
+    }
+
+    /// Pushes the same branches to several remotes concurrently.
+    ///
+    /// Equivalent to `git push <remote> <branch>...` for each remote, with all
+    /// pushes running at once. A failed push does not affect the others. With
+    /// `set_upstream` the pushes run one after another, as each one writes the
+    /// branches' upstream to the repository configuration.
+    ///
+    /// # Arguments
+    /// * `branches` - The local branches to push to the branches of the same name.
+    /// * `remotes` - The remotes to push to.
+    /// * `opts` - The push options applied to every remote.
+    ///
+    /// # Returns
+    /// Each remote with the outcome of the push to it, in the order given.
+    pub async fn push_to_all(
+        &self,
+        branches: &[BranchName],
+        remotes: &[Remote],
+        opts: &PushOptions,
+    ) -> Vec<(Remote, Result<PushResult>)> {
+        let push = |remote| commands::push_with_options(Some(remote), branches, opts).run_async(&self.location);
+        let results = if opts.set_upstream {
+            let mut results = Vec::with_capacity(remotes.len());
+            for remote in remotes {
+                results.push(push(remote).await);
+            }
+            results
+        } else {
+            join_all(remotes.iter().map(push)).await
+        };
+        remotes.iter().cloned().zip(results).collect()
     }
 
     /// Adds a new remote repository reference asynchronously.
     /// Added based on sync Repository.
     ///
-    /// Equivalent to `git remote add <name> <url>`.
+    /// Equivalent to `git remote add -- <name> <url>`.
     ///
     /// # Arguments
     /// * `name` - The name for the new remote.
-    /// * `url` - The URL of the remote repository.
+    /// * `url` - The URL of the remote repository, which may be a local path
+    ///   created with `GitUrl::from_path`.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn add_remote(&self, name: &Remote, url: &GitUrl) -> Result<()> { // Changed type
-        execute_git_async(&self.location, &["remote", "add", name.as_ref(), url.as_ref()]).await // Use AsRef
+        commands::add_remote(name, url).run_async(&self.location).await
     }
 
     /// Fetches updates from a specified remote repository asynchronously.
@@ -237,7 +275,27 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn fetch_remote(&self, remote: &Remote) -> Result<()> { // Changed type
-        execute_git_async(&self.location, &["fetch", remote.as_ref()]).await // Use AsRef
+        commands::fetch_remote(remote).run_async(&self.location).await
+    }
+
+    /// Fetches from one or all remotes asynchronously, with pruning, tag, depth
+    /// and refspec control.
+    ///
+    /// Equivalent to `git fetch [--all] [--prune] [--prune-tags] [--tags] [--depth=<n>]
+    /// [<remote> [<refspec>...]]`.
+    ///
+    /// # Arguments
+    /// * `opts` - What to fetch; `FetchOptions::default()` fetches the default remote,
+    ///   which is the repository's (see `RepositoryBuilder::default_remote`) if set.
+    ///
+    /// # Returns
+    /// A `FetchResult` listing the refs the fetch created, moved or deleted.
+    ///
+    /// # Errors
+    /// Returns `GitError::FetchRejected` if some refs could not be updated.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub async fn fetch(&self, opts: &FetchOptions) -> Result<FetchResult> {
+        commands::fetch(&with_default_remote(opts, self.default_remote())).run_async(&self.location).await
     }
 
     /// Creates and checks out a new branch starting from a given point asynchronously.
@@ -256,15 +314,7 @@ impl AsyncRepository {
         branch_name: &BranchName,
         startpoint: &str, // Keeping as &str for flexibility
     ) -> Result<()> {
-        execute_git_async(
-            &self.location,
-            &[
-                "checkout",
-                "-b",
-                branch_name.as_ref(),
-                startpoint,
-            ],
-        ).await
+        commands::create_branch(branch_name, Some(startpoint)).run_async(&self.location).await
     }
 
 
@@ -278,22 +328,13 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn list_branches(&self) -> Result<Vec<BranchName>> { // Changed return type
-        execute_git_fn_async(
-            &self.location,
-            &["branch", "--list", "--format=%(refname:short)"],
-            |output| {
-                output
-                    .lines()
-                    .map(|line| BranchName::from_str(line.trim())) // Parse each line
-                    .collect::<Result<Vec<BranchName>>>() // Collect into Result<Vec<...>>
-            },
-        ).await
+        commands::list_branches().run_async(&self.location).await
     }
 
     /// Lists all files currently tracked by Git in the working directory asynchronously.
     /// Added based on sync Repository.
     ///
-    /// Equivalent to `git ls-files`.
+    /// Equivalent to `git ls-files -z`.
     ///
     /// # Returns
     /// A `Vec<String>` containing the paths of tracked files relative to the repository root.
@@ -301,9 +342,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn list_tracked(&self) -> Result<Vec<String>> {
-        execute_git_fn_async(&self.location, &["ls-files"], |output| {
-            Ok(output.lines().map(|line| line.to_owned()).collect())
-        }).await
+        commands::list_tracked().run_async(&self.location).await
     }
 
 
@@ -320,16 +359,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn show_remote_uri(&self, remote_name: &Remote) -> Result<GitUrl> { // Changed args & return type
-        execute_git_fn_async(
-            &self.location,
-            &[
-                "config",
-                "--get",
-                // --- FIX: Pass remote_name directly ---
-                &format!("remote.{}.url", remote_name),
-            ],
-            |output| GitUrl::from_str(output.trim()), // Parse output into GitUrl
-        ).await
+        commands::show_remote_uri(remote_name).run_async(&self.location).await
     }
 
     /// Lists the names of all configured remotes asynchronously.
@@ -344,21 +374,7 @@ impl AsyncRepository {
     /// Returns `GitError::NoRemoteRepositorySet` if no remotes are configured.
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn list_remotes(&self) -> Result<Vec<Remote>> { // Changed return type
-        execute_git_fn_async(&self.location, &["remote"], |output| {
-            let remote_names: Vec<&str> = output.lines().map(|line| line.trim()).collect();
-            if remote_names.is_empty() {
-                // Re-check using config asynchronously
-                // This requires cmd_out_async or similar - let's implement that first
-                // For now, return error if empty, assuming config check happens elsewhere or is deferred
-                Err(GitError::NoRemoteRepositorySet)
-                // TODO: Implement async config check later if needed
-            } else {
-                remote_names
-                    .into_iter()
-                    .map(Remote::from_str) // Parse each name
-                    .collect::<Result<Vec<Remote>>>() // Collect into Result<Vec<...>>
-            }
-        }).await
+        commands::list_remotes().run_async(&self.location).await
     }
 
     /// Obtains the commit hash (SHA-1) of the current `HEAD` asynchronously.
@@ -375,16 +391,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn get_hash(&self, short: bool) -> Result<CommitHash> { // Changed return type
-        let args: &[&str] = if short {
-            &["rev-parse", "--short", "HEAD"]
-        } else {
-            &["rev-parse", "HEAD"]
-        };
-        execute_git_fn_async(
-            &self.location,
-            args,
-            |output| CommitHash::from_str(output.trim()), // Parse output
-        ).await
+        commands::get_hash(short).run_async(&self.location).await
     }
 
 
@@ -396,46 +403,21 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn list_branches_info(&self) -> Result<Vec<Branch>> { // Assuming Branch uses CommitHash
-        execute_git_fn_async(
-            &self.location,
-            &["branch", "--list", "-v", "--format=%(refname:short) %(objectname) %(HEAD) %(upstream:short)"],
-            |output| {
-                let mut branches = Vec::new();
-
-                for line in output.lines() {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 3 {
-                        let name_str = parts[0];
-                        let commit_str = parts[1]; // &str
-                        let is_head = parts[2] == "*";
-
-                        let upstream = if parts.len() >= 4 {
-                            Some(parts[3].to_string())
-                        } else {
-                            None
-                        };
-
-                        // --- FIX: Parse commit_str into CommitHash ---
-                        if let Ok(name) = BranchName::from_str(name_str) {
-                            if let Ok(commit_hash) = CommitHash::from_str(commit_str) { // Parse here
-                                branches.push(Branch {
-                                    name,
-                                    commit: commit_hash, // Assign CommitHash
-                                    is_head,
-                                    upstream,
-                                });
-                            } else {
-                                eprintln!("Warning: Could not parse commit hash '{}' for async branch '{}'", commit_str, name_str);
-                            }
-                        } else {
-                            eprintln!("Warning: Could not parse branch name '{}' for async", name_str);
-                        }
-                        // --- End Fix ---
-                    }
-                }
-                Ok(branches)
-            }
-        ).await
+        commands::list_branches_info().run_async(&self.location).await
+    }
+
+    /// Lists branches matching a filter asynchronously.
+    ///
+    /// Equivalent to `git branch --list` with the filter's options; see
+    /// `Repository::list_branches_filtered`.
+    ///
+    /// # Arguments
+    /// * `filter` - A `BranchFilter` selecting and ordering the branches.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub async fn list_branches_filtered(&self, filter: &BranchFilter) -> Result<Vec<Branch>> {
+        commands::list_branches_filtered(filter).run_async(&self.location).await
     }
 
     /// Gets detailed information about a commit asynchronously.
@@ -444,133 +426,70 @@ impl AsyncRepository {
     /// * `commit_ref` - The commit reference. If `None`, uses HEAD.
     ///
     /// # Returns
-    /// A `Commit` struct with commit details. (Note: Assumes Commit fields updated)
+    /// A `Commit` struct with commit details, including the full message and the
+    /// signature verification status.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn get_commit(&self, commit_ref: Option<&str>) -> Result<Commit> {
-        let format = "%H%n\
-                     shortcommit %h%n\
-                     author_name %an%n\
-                     author_email %ae%n\
-                     timestamp %at%n\
-                     %P%n\
-                     message %s";
-
-        // --- FIX: Use let binding for format string ---
-        let format_arg = format!("--format={}", format);
-
-        let args = match commit_ref {
-            Some(c) => vec!["show", "--no-patch", &format_arg, c],
-            None => vec!["show", "--no-patch", &format_arg],
-        };
-        // --- End Fix ---
+        commands::get_commit(commit_ref).run_async(&self.location).await
+    }
 
-        execute_git_fn_async(&self.location, args, |output| {
-            Commit::from_show_format(output).ok_or_else(|| GitError::GitError {
-                stdout: output.to_string(),
-                stderr: "Failed to parse commit information".to_string(),
-            })
-        }).await
+    /// Resolves the repository's Git directory asynchronously; see
+    /// `Repository::git_dir`.
+    ///
+    /// # Returns
+    /// The absolute path of the Git directory.
+    ///
+    /// # Errors
+    /// Returns `GitError` if this is not a repository (including `GitNotFound`).
+    pub async fn git_dir(&self) -> Result<PathBuf> {
+        commands::git_dir().run_async(&self.location).await
     }
 
     /// Gets the current status of the repository asynchronously.
     ///
     /// # Returns
-    /// A `StatusResult` struct with status details. (Note: Assumes StatusResult fields updated)
+    /// A `StatusResult` struct with branch, upstream tracking and file status details.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn status(&self) -> Result<StatusResult> {
-        let porcelain_output = execute_git_fn_async(
-            &self.location,
-            &["status", "--porcelain=v2", "--branch"],
-            |output| Ok(output.to_string())
-        ).await?;
-
-        let mut branch_name_str = None;
-        let mut files = Vec::new();
-        let mut merging = false;
-        let mut rebasing = false;
-        let mut cherry_picking = false;
-
-        for line in porcelain_output.lines() {
-            if line.starts_with("# branch.head ") {
-                branch_name_str = Some(line.trim_start_matches("# branch.head ").to_string());
-            } else if line.starts_with("# branch.oid ") { // Ignore
-            } else if line.starts_with("# branch.upstream ") { // Ignore
-            } else if line.starts_with("1 ") || line.starts_with("2 ") || line.starts_with("u ") {
-                let parts: Vec<&str> = line.split(' ').collect();
-                if parts.len() >= 2 {
-                    let xy = parts[1];
-                    let status_code = if xy.len() >= 2 {
-                        (xy.chars().nth(0).unwrap(), xy.chars().nth(1).unwrap())
-                    } else {
-                        (' ', ' ')
-                    };
-                    let status = FileStatus::from_porcelain_code(status_code.0, status_code.1);
-
-                    let path_part = line.split('\t').next().unwrap_or(line);
-                    let path_components: Vec<&str> = path_part.split(' ').collect();
-
-                    if let Some(path_str) = path_components.iter().rev().find(|s| !s.is_empty()) {
-                        let original_path_str = if line.contains('\t') {
-                            line.split('\t').nth(1)
-                        } else {
-                            None
-                        };
-
-                        files.push(StatusEntry {
-                            path: PathBuf::from(path_str),
-                            status,
-                            original_path: original_path_str.map(PathBuf::from),
-                        });
-                    }
-                }
-            } else if line.starts_with("? ") {
-                if line.len() > 2 {
-                    let path = line[2..].to_string();
-                    files.push(StatusEntry {
-                        path: PathBuf::from(path),
-                        status: FileStatus::Untracked,
-                        original_path: None,
-                    });
-                }
-            }
-        }
+        let result = commands::status().run_async(&self.location).await?;
+        self.with_operation_flags(result).await
+    }
 
-        // Parse the branch name string into Option<BranchName>
-        let branch = branch_name_str.and_then(|s| BranchName::from_str(&s).ok());
+    /// Gets the status of tracked files asynchronously, tuned for worktrees with
+    /// hundreds of thousands of files.
+    ///
+    /// Equivalent to `git status --porcelain=v2 --branch -z --no-renames
+    /// --untracked-files=no`; see `Repository::status_fast`.
+    ///
+    /// # Returns
+    /// A `StatusResult` like `status`, except that no entries are untracked and a
+    /// renamed file is reported as a deletion and an addition.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub async fn status_fast(&self) -> Result<StatusResult> {
+        let result = commands::status_fast().run_async(&self.location).await?;
+        self.with_operation_flags(result).await
+    }
 
+    /// Sets the in-progress operation flags of a parsed status.
+    async fn with_operation_flags(&self, mut result: StatusResult) -> Result<StatusResult> {
         // Check for special states asynchronously
-        let git_dir = self.location.join(".git");
+        let git_dir = self.git_dir().await?;
 
-        // Use tokio::fs::try_exists for async checks
-        let merge_exists = tokio::fs::try_exists(git_dir.join("MERGE_HEAD")).await.unwrap_or(false);
-        if merge_exists { merging = true; }
+        result.merging = runtime::exists(&git_dir.join("MERGE_HEAD")).await;
 
-        let rebase_apply_exists = tokio::fs::try_exists(git_dir.join("rebase-apply")).await.unwrap_or(false);
-        let rebase_merge_exists = tokio::fs::try_exists(git_dir.join("rebase-merge")).await.unwrap_or(false);
-        if rebase_apply_exists || rebase_merge_exists { rebasing = true; }
+        let rebase_apply_exists = runtime::exists(&git_dir.join("rebase-apply")).await;
+        let rebase_merge_exists = runtime::exists(&git_dir.join("rebase-merge")).await;
+        result.rebasing = rebase_apply_exists || rebase_merge_exists;
 
-        let cherry_pick_exists = tokio::fs::try_exists(git_dir.join("CHERRY_PICK_HEAD")).await.unwrap_or(false);
-        if cherry_pick_exists { cherry_picking = true; }
+        result.cherry_picking = runtime::exists(&git_dir.join("CHERRY_PICK_HEAD")).await;
 
-        // Determine if clean (ignoring untracked/ignored)
-        let is_clean = files.iter().all(|f|
-            matches!(f.status, FileStatus::Unmodified | FileStatus::Ignored)
-        );
-
-        // --- FIX: Removed duplicate field and assuming StatusResult expects Option<BranchName> ---
-        Ok(StatusResult {
-            branch: branch, // Assign the Option<BranchName> directly
-            files,
-            merging,
-            rebasing,
-            cherry_picking,
-            is_clean,
-        })
-        // --- End Fix ---
+        Ok(result)
     }
 
     /// Executes an arbitrary Git command asynchronously within the repository context.
@@ -585,7 +504,7 @@ impl AsyncRepository {
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        execute_git_async(&self.location, args).await
+        commands::cmd(args).run_async(&self.location).await
     }
 
     /// Executes an arbitrary Git command asynchronously and returns its standard output.
@@ -603,9 +522,7 @@ impl AsyncRepository {
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        execute_git_fn_async(&self.location, args, |output| {
-            Ok(output.lines().map(|line| line.to_owned()).collect())
-        }).await
+        commands::cmd_out(args).run_async(&self.location).await
     }
 }
 
@@ -620,7 +537,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn rebase(&self, target_branch: &str) -> Result<()> {
-        execute_git_async(&self.location, &["rebase", target_branch]).await
+        commands::rebase(target_branch).run_async(&self.location).await
     }
 
     /// Continues a rebase operation after resolving conflicts asynchronously.
@@ -628,7 +545,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn rebase_continue(&self) -> Result<()> {
-        execute_git_async(&self.location, &["rebase", "--continue"]).await
+        commands::rebase_continue().run_async(&self.location).await
     }
 
     /// Aborts a rebase operation asynchronously.
@@ -636,7 +553,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn rebase_abort(&self) -> Result<()> {
-        execute_git_async(&self.location, &["rebase", "--abort"]).await
+        commands::rebase_abort().run_async(&self.location).await
     }
 }
 
@@ -651,11 +568,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn cherry_pick<S: AsRef<OsStr>>(&self, commits: Vec<S>) -> Result<()> {
-        let mut args: Vec<&OsStr> = Vec::with_capacity(commits.len() + 1);
-        args.push("cherry-pick".as_ref());
-        let refs: Vec<_> = commits.iter().map(|s| s.as_ref()).collect();
-        args.extend(refs);
-        execute_git_async(&self.location, args).await
+        commands::cherry_pick(&commits).run_async(&self.location).await
     }
 
     /// Continues a cherry-pick operation after resolving conflicts asynchronously.
@@ -663,7 +576,7 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn cherry_pick_continue(&self) -> Result<()> {
-        execute_git_async(&self.location, &["cherry-pick", "--continue"]).await
+        commands::cherry_pick_continue().run_async(&self.location).await
     }
 
     /// Aborts a cherry-pick operation asynchronously.
@@ -671,64 +584,265 @@ impl AsyncRepository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub async fn cherry_pick_abort(&self) -> Result<()> {
-        execute_git_async(&self.location, &["cherry-pick", "--abort"]).await
+        commands::cherry_pick_abort().run_async(&self.location).await
+    }
+}
+
+// --- Interoperability with the synchronous API ---
+
+impl AsyncRepository {
+    /// Returns a synchronous `Repository` handle for the same repository.
+    pub fn to_sync(&self) -> Repository {
+        Repository { location: self.location.clone() }
+    }
+
+    /// Runs synchronous repository operations on Tokio's blocking thread pool.
+    ///
+    /// This gives async code access to every `Repository` method, including
+    /// those without an async counterpart, without stalling the runtime's
+    /// worker threads while Git runs.
+    ///
+    /// ```no_run
+    /// # use GitPilot::AsyncRepository;
+    /// # async fn run() -> GitPilot::Result<()> {
+    /// let repo = AsyncRepository::new("./my_project");
+    /// let dirty = repo.run_blocking(|r| r.is_dirty()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `f` - The operations to run, given a `Repository` for this repository.
+    ///
+    /// # Errors
+    /// Returns whatever `f` returns.
+    ///
+    /// # Panics
+    /// Panics if `f` panics, or if called outside a Tokio runtime.
+    pub async fn run_blocking<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Repository) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.to_sync().run_blocking(f).await
+    }
+}
+
+impl Repository {
+    /// Converts this handle into an `AsyncRepository` for the same repository.
+    pub fn to_async(self) -> AsyncRepository {
+        AsyncRepository { location: self.location }
+    }
+
+    /// Runs synchronous repository operations on Tokio's blocking thread pool,
+    /// so they can be awaited from async code without stalling the runtime.
+    ///
+    /// # Arguments
+    /// * `f` - The operations to run, given this repository.
+    ///
+    /// # Errors
+    /// Returns whatever `f` returns.
+    ///
+    /// # Panics
+    /// Panics if `f` panics, or if called outside a Tokio runtime.
+    pub async fn run_blocking<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Repository) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let repo = self.clone();
+        runtime::spawn_blocking(move || f(&repo)).await
+    }
+}
+
+impl From<Repository> for AsyncRepository {
+    fn from(repo: Repository) -> AsyncRepository {
+        repo.to_async()
+    }
+}
+
+impl From<AsyncRepository> for Repository {
+    fn from(repo: AsyncRepository) -> Repository {
+        Repository { location: repo.location }
     }
 }
 
 
 // --- Private Helper Functions for async operations ---
 
-/// Executes a Git command asynchronously, discarding successful output.
-async fn execute_git_async<I, S, P>(p: P, args: I) -> Result<()>
-where
-    I: IntoIterator<Item = S>,
-    S: AsRef<OsStr>,
-    P: AsRef<Path>,
-{
-    execute_git_fn_async(p, args, |_| Ok(())).await
+impl<T> GitCommand<T> {
+    /// Runs the command in `p` without blocking the async runtime.
+    pub(crate) async fn run_async(self, p: impl GitContext) -> Result<T> {
+        execute_git_fn_async(p, self.args, self.parse_stderr, self.parse)
+            .await
+            .map_err(|error| match &error {
+                GitError::GitError { stdout, .. } => (self.refine_error)(&stdout.clone(), error),
+                _ => error,
+            })
+    }
 }
 
-/// Executes a Git command asynchronously and processes its stdout on success using a closure.
+/// Executes a Git command asynchronously and processes its stdout (or, with
+/// `parse_stderr`, its stderr) on success using a closure.
 /// Handles errors, including capturing stderr on failure.
-async fn execute_git_fn_async<I, S, P, F, R>(p: P, args: I, process: F) -> Result<R>
+async fn execute_git_fn_async<I, S, P, F, R>(p: P, args: I, parse_stderr: bool, process: F) -> Result<R>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
-    P: AsRef<Path>,
+    P: GitContext,
     F: FnOnce(&str) -> Result<R>,
 {
-    let command_result = Command::new("git")
-        .current_dir(p.as_ref())
-        .args(args)
-        .output()
-        .await; // Use .await for tokio::process::Command
-
-    match command_result {
-        Ok(output) => {
-            if output.status.success() {
-                match str::from_utf8(&output.stdout) {
-                    Ok(stdout_str) => process(stdout_str),
-                    Err(_) => Err(GitError::Undecodable),
-                }
-            } else {
-                let stdout = str::from_utf8(&output.stdout)
-                    .map(|s| s.trim_end().to_owned())
-                    .unwrap_or_else(|_| String::from("[stdout: undecodable UTF-8]"));
-                let stderr = str::from_utf8(&output.stderr)
-                    .map(|s| s.trim_end().to_owned())
-                    .unwrap_or_else(|_| String::from("[stderr: undecodable UTF-8]"));
-                Err(GitError::GitError { stdout, stderr })
-            }
+    let settings = p.settings();
+    let args: Vec<OsString> = args.into_iter().map(|arg| arg.as_ref().to_os_string()).collect();
+    let mut retries = 0;
+    let output = loop {
+        let mut command = settings.command(p.dir());
+        command.args(&args);
+        let started = Instant::now();
+        let output = runtime::output(command, settings.max_output);
+        let result = match settings.timeout {
+            None => output.await.map_err(spawn_error),
+            Some(timeout) => match runtime::timeout(timeout, output).await {
+                Some(output) => output.map_err(spawn_error),
+                None => Err(GitError::Timeout(timeout)),
+            },
         }
-        Err(e) => {
-            // --- FIX: Added GitNotFound Check ---
-            if e.kind() == ErrorKind::NotFound {
-                Err(GitError::GitNotFound) // Return the specific error
-            } else {
-                eprintln!("Failed to execute async git command: {}", e); // Log the OS error
-                Err(GitError::Execution) // Return the original generic execution error
+        .and_then(|output| match settings.max_output {
+            Some(limit) if output.stdout.len() > limit || output.stderr.len() > limit => {
+                Err(GitError::OutputTooLarge { limit })
             }
-            // --- End of Fix ---
+            _ => Ok(output),
+        });
+        settings.record(p.dir(), &args, started, result.as_ref());
+        match result {
+            Ok(output) if !output.status.success() && settings.should_retry(retries, &output.stderr) => {
+                retries += 1;
+                runtime::sleep(settings.retry.delay).await;
+            }
+            result => break result?,
+        }
+    };
+    if output.status.success() {
+        let parsed = if parse_stderr { &output.stderr } else { &output.stdout };
+        process(&settings.decode.decode(parsed)?)
+    } else {
+        let stdout = str::from_utf8(&output.stdout)
+            .map(|s| s.trim_end().to_owned())
+            .unwrap_or_else(|_| String::from("[stdout: undecodable UTF-8]"));
+        let stderr = str::from_utf8(&output.stderr)
+            .map(|s| s.trim_end().to_owned())
+            .unwrap_or_else(|_| String::from("[stderr: undecodable UTF-8]"));
+        Err(GitError::GitError { stdout, stderr })
+    }
+}
+
+/// Maps a failure to start Git to `GitNotFound` or `Execution`.
+fn spawn_error(e: std::io::Error) -> GitError {
+    // --- FIX: Added GitNotFound Check ---
+    if e.kind() == ErrorKind::NotFound {
+        GitError::GitNotFound // Return the specific error
+    } else {
+        eprintln!("Failed to execute async git command: {}", e); // Log the OS error
+        GitError::Execution // Return the original generic execution error
+    }
+    // --- End of Fix ---
+}
+
+/// The async runtime primitives the async API is built on.
+///
+/// Everything runtime-specific goes through this module, including the tasks
+/// spawned by `workspace` and `auto_fetch`, so supporting another runtime
+/// (async-std, smol, ...) means providing these functions and `Task` for it
+/// behind its own feature. Channels and semaphores come from `tokio::sync`,
+/// which works on any runtime.
+pub(crate) mod runtime {
+    use std::future::Future;
+    use std::io;
+    use std::path::Path;
+    use std::process::{Command, Output, Stdio};
+    use std::time::Duration;
+    use tokio::io::{AsyncRead, AsyncReadExt};
+
+    /// Runs `command` and collects its output, reading at most `limit + 1` bytes
+    /// of stdout and stderr each. The process is killed if the returned future
+    /// is dropped before it exits.
+    pub(super) async fn output(command: Command, limit: Option<usize>) -> io::Result<Output> {
+        let mut command = tokio::process::Command::from(command);
+        command.kill_on_drop(true);
+        let Some(limit) = limit else { return command.output().await };
+        let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        // Closing a pipe after the limit stops Git the next time it writes.
+        let (stdout, stderr) = tokio::try_join!(
+            read_limited(child.stdout.take(), limit),
+            read_limited(child.stderr.take(), limit)
+        )?;
+        Ok(Output { status: child.wait().await?, stdout, stderr })
+    }
+
+    async fn read_limited(pipe: Option<impl AsyncRead + Unpin>, limit: usize) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        if let Some(pipe) = pipe {
+            pipe.take(limit as u64 + 1).read_to_end(&mut buf).await?;
+        }
+        Ok(buf)
+    }
+
+    /// Awaits `future` for at most `duration`, returning `None` if it takes longer.
+    pub(super) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+        tokio::time::timeout(duration, future).await.ok()
+    }
+
+    /// Waits for `duration` without blocking the runtime.
+    pub(crate) async fn sleep(duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+
+    /// Checks whether `path` exists, treating errors as `false`.
+    pub(super) async fn exists(path: &Path) -> bool {
+        tokio::fs::try_exists(path).await.unwrap_or(false)
+    }
+
+    /// Runs `f` on a thread where blocking is allowed, propagating its panics.
+    pub(super) async fn spawn_blocking<F, T>(f: F) -> T
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        tokio::task::spawn_blocking(f)
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+
+    /// Runs `future` concurrently as a new task.
+    pub(crate) fn spawn<F>(future: F) -> Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        Task(tokio::spawn(future))
+    }
+
+    /// A task started by `spawn`, which is aborted when dropped.
+    #[derive(Debug)]
+    pub(crate) struct Task<T>(tokio::task::JoinHandle<T>);
+
+    impl<T> Task<T> {
+        /// Returns `true` once the task has completed.
+        pub(crate) fn is_finished(&self) -> bool {
+            self.0.is_finished()
+        }
+
+        /// Waits for the task's output, propagating its panics.
+        pub(crate) async fn join(mut self) -> T {
+            (&mut self.0)
+                .await
+                .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
         }
     }
-}
\ No newline at end of file
+
+    impl<T> Drop for Task<T> {
+        fn drop(&mut self) {
+            self.0.abort();
+        }
+    }
+}
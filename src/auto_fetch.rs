@@ -0,0 +1,141 @@
+//! Keeping a repository's remote-tracking branches fresh with a periodic
+//! background fetch.
+//!
+//! ```no_run
+//! # use GitPilot::{AsyncRepository, Remote};
+//! # use std::str::FromStr;
+//! # use std::time::Duration;
+//! # async fn run() -> GitPilot::Result<()> {
+//! let repo = AsyncRepository::new("./mirror");
+//! let origin = Remote::from_str("origin")?;
+//! let (auto_fetch, mut events) = repo.spawn_auto_fetch(Duration::from_secs(300), origin, std::future::pending());
+//! while let Ok(event) = events.recv().await {
+//!     match &event.result {
+//!         Ok(fetched) => {
+//!             for branch in fetched.new_branches() {
+//!                 println!("new branch {}", branch.local_ref);
+//!             }
+//!         }
+//!         Err(e) => eprintln!("fetch from {} failed: {}", event.remote, e),
+//!     }
+//! }
+//! # drop(auto_fetch);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::async_git::runtime::{self, Task};
+use crate::async_git::AsyncRepository;
+use crate::error::GitError;
+use crate::models::FetchResult;
+use crate::options::FetchOptions;
+use crate::types::Remote;
+use std::future::{self, Future};
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::broadcast;
+
+/// How many events a subscriber may fall behind before it misses some.
+const EVENT_CAPACITY: usize = 16;
+
+/// The outcome of one fetch made by an `AutoFetch` task.
+#[derive(Debug, Clone)]
+pub struct AutoFetchEvent {
+    /// The remote that was fetched.
+    pub remote: Remote,
+    /// When the fetch finished.
+    pub fetched_at: SystemTime,
+    /// What the fetch changed; the error is shared between subscribers.
+    pub result: std::result::Result<FetchResult, Arc<GitError>>,
+}
+
+/// A background task fetching a remote at a fixed interval, created by
+/// `AsyncRepository::spawn_auto_fetch`.
+///
+/// The task stops when its cancellation future completes or when the
+/// `AutoFetch` is dropped.
+#[derive(Debug)]
+pub struct AutoFetch {
+    task: Task<()>,
+    events: broadcast::Sender<AutoFetchEvent>,
+}
+
+impl AutoFetch {
+    /// Returns a new receiver for the events of fetches finishing from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AutoFetchEvent> {
+        self.events.subscribe()
+    }
+
+    /// Returns `true` until the task has been cancelled.
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Stops the task. A fetch in progress is abandoned, and Git cleans up
+    /// after it as for an interrupted fetch.
+    pub fn stop(self) {
+        // Dropping aborts the task.
+    }
+}
+
+impl AsyncRepository {
+    /// Spawns a task that fetches `remote` now and then every `interval`, so
+    /// long-running services keep their remote-tracking branches fresh.
+    ///
+    /// Fetches never overlap: if one takes longer than `interval`, the next
+    /// starts as soon as it finishes. A failed fetch is reported and retried
+    /// at the next tick.
+    ///
+    /// # Arguments
+    /// * `interval` - The time between the starts of consecutive fetches.
+    /// * `remote` - The remote to fetch.
+    /// * `cancellation` - A future that stops the task when it completes, such
+    ///   as a shutdown signal; `std::future::pending()` to rely on dropping the
+    ///   returned `AutoFetch` instead.
+    ///
+    /// # Returns
+    /// The `AutoFetch` controlling the task, and a receiver of an
+    /// `AutoFetchEvent` per fetch, starting with the first.
+    ///
+    /// # Panics
+    /// Panics if `interval` is zero, or if called outside a Tokio runtime.
+    pub fn spawn_auto_fetch<C>(
+        &self,
+        interval: Duration,
+        remote: Remote,
+        cancellation: C,
+    ) -> (AutoFetch, broadcast::Receiver<AutoFetchEvent>)
+    where
+        C: Future<Output = ()> + Send + 'static,
+    {
+        let (events, receiver) = broadcast::channel(EVENT_CAPACITY);
+        let sender = events.clone();
+        let repo = self.clone();
+        let opts = FetchOptions { remote: Some(remote.clone()), ..Default::default() };
+        assert!(!interval.is_zero(), "the auto-fetch interval must be non-zero");
+
+        let task = runtime::spawn(async move {
+            let fetch_loop = async {
+                loop {
+                    let started = Instant::now();
+                    let result = repo.fetch(&opts).await.map_err(Arc::new);
+                    let event = AutoFetchEvent { remote: remote.clone(), fetched_at: SystemTime::now(), result };
+                    // Having no subscribers is not an error.
+                    let _ = sender.send(event);
+                    runtime::sleep(interval.saturating_sub(started.elapsed())).await;
+                }
+            };
+            let mut fetch_loop = Box::pin(fetch_loop);
+            let mut cancellation = Box::pin(cancellation);
+            future::poll_fn(|cx| {
+                if cancellation.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(());
+                }
+                fetch_loop.as_mut().poll(cx)
+            })
+            .await
+        });
+        (AutoFetch { task, events }, receiver)
+    }
+}
@@ -0,0 +1,327 @@
+//! Long-running Git processes that answer many queries over stdin/stdout,
+//! avoiding a process spawn per query for high-volume workloads.
+
+use crate::error::GitError;
+use crate::models::{AttrResult, GitObject, ObjectType};
+use crate::repository::{pathspec, Location, Repository};
+use crate::types::Result;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Output, Stdio};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// A `git` child process with piped stdin and stdout, terminated on drop.
+#[derive(Debug)]
+struct BatchProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    // For reporting the process to the repository's `on_command` callback on drop.
+    location: Location,
+    args: Vec<OsString>,
+    started: Instant,
+}
+
+impl BatchProcess {
+    fn spawn(p: &Location, args: &[&str]) -> Result<BatchProcess> {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        let started = Instant::now();
+        let spawned = p
+            .settings
+            .command(p)
+            .args(&args)
+            // Answer every query as soon as it is read instead of buffering output.
+            .env("GIT_FLUSH", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == ErrorKind::NotFound {
+                    GitError::GitNotFound
+                } else {
+                    GitError::Execution
+                }
+            });
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                p.settings.record(p, &args, started, Err(&e));
+                return Err(e);
+            }
+        };
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(BatchProcess { child, stdin: Some(stdin), stdout, location: p.clone(), args, started })
+    }
+
+    /// Writes one query and flushes it so the process answers immediately.
+    fn send(&mut self, query: &[u8]) -> Result<()> {
+        let stdin = self.stdin.as_mut().expect("stdin is open until drop");
+        stdin.write_all(query)?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    /// Reads up to and including `delimiter`, which is stripped.
+    fn read_until(&mut self, delimiter: u8) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.stdout.read_until(delimiter, &mut buf)?;
+        if buf.pop() != Some(delimiter) {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        Ok(buf)
+    }
+}
+
+impl Drop for BatchProcess {
+    fn drop(&mut self) {
+        // Closing stdin ends the batch; the process then exits on its own.
+        drop(self.stdin.take());
+        if let Ok(status) = self.child.wait() {
+            let output = Output { status, stdout: Vec::new(), stderr: Vec::new() };
+            self.location.settings.record(&self.location, &self.args, self.started, Ok(&output));
+        }
+    }
+}
+
+/// Encodes a path as a NUL-terminated query for `-z` batch commands.
+fn path_query(path: &Path) -> Result<Vec<u8>> {
+    let mut query = pathspec(path)?.as_encoded_bytes().to_vec();
+    if query.contains(&0) {
+        return Err(GitError::PathEncodingError(path.to_path_buf()));
+    }
+    query.push(0);
+    Ok(query)
+}
+
+/// Reads one NUL-terminated field of `-z` output as UTF-8.
+fn read_field(process: &mut BatchProcess) -> Result<String> {
+    let field = process.read_until(0)?;
+    Ok(process.location.settings.decode.decode(&field)?.into_owned())
+}
+
+/// Rejects queries that would be split across several lines of input.
+fn check_query(query: &str) -> Result<()> {
+    if query.contains(['\n', '\0']) {
+        return Err(GitError::GitError {
+            stdout: String::new(),
+            stderr: format!("Batch query must be a single line: {:?}", query),
+        });
+    }
+    Ok(())
+}
+
+/// Reads objects through a single `git cat-file --batch` process.
+///
+/// Spawning Git once per object dominates the cost of reading thousands of
+/// objects; an `ObjectReader` pays it once and then answers each lookup over the
+/// process's stdin and stdout. The process exits when the reader is dropped.
+///
+/// ```no_run
+/// # use GitPilot::Repository;
+/// # fn main() -> GitPilot::Result<()> {
+/// let repo = Repository::new("./my_project");
+/// let mut reader = repo.object_reader()?;
+/// for path in ["README.md", "Cargo.toml"] {
+///     if let Some(object) = reader.read(&format!("HEAD:{}", path))? {
+///         println!("{}: {} bytes", path, object.data.len());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ObjectReader {
+    process: BatchProcess,
+}
+
+impl ObjectReader {
+    /// Reads an object by id or by any revision expression Git accepts, such as
+    /// `HEAD`, `v1.0^{tree}` or `main:src/lib.rs`.
+    ///
+    /// # Returns
+    /// The object, or `None` if the revision does not name an object.
+    ///
+    /// # Errors
+    /// Returns `GitError::GitError` if `rev` spans several lines or is ambiguous,
+    /// and `GitError::Io` if the process has exited.
+    pub fn read(&mut self, rev: &str) -> Result<Option<GitObject>> {
+        check_query(rev)?;
+        self.process.send(format!("{}\n", rev).as_bytes())?;
+
+        // The header is `<id> <type> <size>`, or `<rev> missing` / `<rev> ambiguous`.
+        let header = self.process.read_until(b'\n')?;
+        let header = self.process.location.settings.decode.decode(&header)?.into_owned();
+        if header.ends_with(" missing") {
+            return Ok(None);
+        }
+        let parse_error = || GitError::GitError {
+            stdout: header.clone(),
+            stderr: format!("Failed to read object {:?}", rev),
+        };
+        let mut fields = header.split(' ');
+        let (Some(id), Some(object_type), Some(size), None) = (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(parse_error());
+        };
+        let object_type = ObjectType::from_str(object_type).map_err(|_| parse_error())?;
+        let size: usize = size.parse().map_err(|_| parse_error())?;
+
+        // The contents are followed by a newline.
+        let mut data = vec![0; size + 1];
+        self.process.stdout.read_exact(&mut data)?;
+        data.pop();
+        Ok(Some(GitObject { id: id.to_string(), object_type, data }))
+    }
+}
+
+impl Repository {
+    /// Starts a `git cat-file --batch` process for reading many objects.
+    ///
+    /// # Errors
+    /// Returns `GitError` if Git cannot be started (including `GitNotFound`).
+    pub fn object_reader(&self) -> Result<ObjectReader> {
+        Ok(ObjectReader { process: BatchProcess::spawn(&self.location, &["cat-file", "--batch"])? })
+    }
+
+    /// Starts a `git check-ignore` process for checking many paths against the
+    /// ignore rules.
+    ///
+    /// # Errors
+    /// Returns `GitError` if Git cannot be started (including `GitNotFound`).
+    pub fn ignore_checker(&self) -> Result<IgnoreChecker> {
+        let args = ["check-ignore", "--stdin", "-z", "--verbose", "--non-matching"];
+        Ok(IgnoreChecker { process: BatchProcess::spawn(&self.location, &args)? })
+    }
+
+    /// Starts a `git check-attr` process for looking up the given attributes of
+    /// many paths.
+    ///
+    /// # Arguments
+    /// * `attrs` - The attributes to report for every path; must not be empty.
+    ///
+    /// # Errors
+    /// Returns `GitError::GitError` if `attrs` is empty.
+    /// Returns `GitError` if Git cannot be started (including `GitNotFound`).
+    pub fn attr_checker(&self, attrs: &[&str]) -> Result<AttrChecker> {
+        // With `--all` the number of records per path varies, so answers could
+        // not be told apart on a shared stream.
+        if attrs.is_empty() {
+            return Err(GitError::GitError {
+                stdout: String::new(),
+                stderr: "attr_checker needs at least one attribute".to_string(),
+            });
+        }
+        let mut args = vec!["check-attr", "--stdin", "-z"];
+        args.extend_from_slice(attrs);
+        Ok(AttrChecker { process: BatchProcess::spawn(&self.location, &args)?, attr_count: attrs.len() })
+    }
+
+    /// Starts a process for resolving many revisions to object ids.
+    ///
+    /// # Errors
+    /// Returns `GitError` if Git cannot be started (including `GitNotFound`).
+    pub fn rev_resolver(&self) -> Result<RevResolver> {
+        let args = ["cat-file", "--batch-check=%(objectname)"];
+        Ok(RevResolver { process: BatchProcess::spawn(&self.location, &args)? })
+    }
+}
+
+/// Checks paths against ignore rules through a single
+/// `git check-ignore --stdin -z --verbose --non-matching` process.
+///
+/// The batch equivalent of `Repository::check_ignore` for callers that learn
+/// paths one at a time, such as file-system walkers.
+#[derive(Debug)]
+pub struct IgnoreChecker {
+    process: BatchProcess,
+}
+
+impl IgnoreChecker {
+    /// Checks whether `path`, relative to the repository root, is ignored.
+    /// The path need not exist.
+    ///
+    /// # Errors
+    /// Returns `GitError::PathEncodingError` if the path is not valid UTF-8.
+    /// Returns `GitError::Io` if the process has exited.
+    pub fn is_ignored<P: AsRef<Path>>(&mut self, path: P) -> Result<bool> {
+        self.process.send(&path_query(path.as_ref())?)?;
+        // <source> NUL <linenum> NUL <pattern> NUL <pathname> NUL; the first three
+        // fields are empty for non-matching paths.
+        let source = read_field(&mut self.process)?;
+        let _line = read_field(&mut self.process)?;
+        let pattern = read_field(&mut self.process)?;
+        let _path = read_field(&mut self.process)?;
+        Ok(!source.is_empty() && !pattern.starts_with('!'))
+    }
+}
+
+/// Looks up gitattributes through a single `git check-attr --stdin -z` process.
+///
+/// The batch equivalent of `Repository::check_attr` for a fixed set of attributes.
+#[derive(Debug)]
+pub struct AttrChecker {
+    process: BatchProcess,
+    attr_count: usize,
+}
+
+impl AttrChecker {
+    /// Looks up the checker's attributes for `path`, relative to the repository
+    /// root. The path need not exist.
+    ///
+    /// # Returns
+    /// One `AttrResult` per attribute, in the order the attributes were given.
+    ///
+    /// # Errors
+    /// Returns `GitError::PathEncodingError` if the path is not valid UTF-8.
+    /// Returns `GitError::Io` if the process has exited.
+    pub fn check<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<AttrResult>> {
+        self.process.send(&path_query(path.as_ref())?)?;
+        let mut output = String::new();
+        for _ in 0..self.attr_count * 3 {
+            output.push_str(&read_field(&mut self.process)?);
+            output.push('\0');
+        }
+        Ok(AttrResult::from_check_attr_z(&output))
+    }
+}
+
+/// Resolves revisions to object ids through a single `git cat-file --batch-check`
+/// process.
+///
+/// `git rev-parse` has no batch mode, so this is the batch equivalent of
+/// `git rev-parse --verify <rev>^{object}`.
+#[derive(Debug)]
+pub struct RevResolver {
+    process: BatchProcess,
+}
+
+impl RevResolver {
+    /// Resolves a revision such as `HEAD~2`, `v1.0` or `main:src/lib.rs`.
+    ///
+    /// # Returns
+    /// The full object id, or `None` if the revision does not name an object.
+    ///
+    /// # Errors
+    /// Returns `GitError::GitError` if `rev` spans several lines or is ambiguous,
+    /// and `GitError::Io` if the process has exited.
+    pub fn resolve(&mut self, rev: &str) -> Result<Option<String>> {
+        check_query(rev)?;
+        self.process.send(format!("{}\n", rev).as_bytes())?;
+        let line = self.process.read_until(b'\n')?;
+        let line = self.process.location.settings.decode.decode(&line)?.into_owned();
+        if line.ends_with(" missing") {
+            return Ok(None);
+        }
+        if line.ends_with(" ambiguous") {
+            return Err(GitError::GitError {
+                stdout: line,
+                stderr: format!("Ambiguous revision {:?}", rev),
+            });
+        }
+        Ok(Some(line))
+    }
+}
@@ -0,0 +1,403 @@
+//! Configuring how a repository runs Git, once, when the handle is created.
+//!
+//! `Repository::new` runs the `git` found on the `PATH` with the inherited
+//! environment. `Repository::builder` instead sets the Git executable, extra
+//! environment variables, the locale, a default remote, a timeout, a retry
+//! policy, an output limit and how output that is not UTF-8 is decoded, which
+//! then apply to every command the repository runs. An `on_command` callback
+//! sees each command run, for audit logs of what GitPilot did, and
+//! `Repository::explain` shows the commands behind a single call.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # use GitPilot::builder::RetryPolicy;
+//! # use GitPilot::types::Remote;
+//! # use std::str::FromStr;
+//! # use std::time::Duration;
+//! # fn main() -> GitPilot::Result<()> {
+//! let repo = Repository::builder("./my_project")
+//!     .git_binary("/opt/git/bin/git")
+//!     .env("GIT_TERMINAL_PROMPT", "0")
+//!     .default_remote(Remote::from_str("upstream")?)
+//!     .timeout(Duration::from_secs(60))
+//!     .retry(RetryPolicy { max_retries: 3, delay: Duration::from_millis(500) })
+//!     .on_command(|record| eprintln!("git {} ({:?}, {:?})", record.args.join(" "), record.duration, record.outcome))
+//!     .build();
+//! repo.fetch(&Default::default())?; // fetches `upstream`, giving up after a minute
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "async")]
+use crate::async_git::AsyncRepository;
+use crate::error::GitError;
+use crate::repository::{Location, Repository};
+use crate::types::Remote;
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The output limit of repositories that do not set one: 256 MiB per stream.
+pub const DEFAULT_MAX_OUTPUT: usize = 256 * 1024 * 1024;
+
+/// When to run a failed Git command again.
+///
+/// Only failures that are known to be transient are retried: another process
+/// holding one of Git's lock files (such as `index.lock`), and network errors
+/// such as an unresolvable host or a connection dropped mid-transfer. Git
+/// changes nothing in either case, so running the command again is safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetryPolicy {
+    /// How many times to run a command again after its first attempt; 0 never retries.
+    pub max_retries: u32,
+    /// How long to wait before each retry.
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Never retries.
+    pub const NONE: RetryPolicy = RetryPolicy { max_retries: 0, delay: Duration::ZERO };
+}
+
+/// How Git output that is not valid UTF-8 is turned into text.
+///
+/// Git passes through bytes it does not interpret, such as author names and
+/// commit messages written in Latin-1 and file names from other systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodePolicy {
+    /// Fail the command with `GitError::Undecodable`.
+    Strict,
+    /// Replace each invalid sequence with `U+FFFD`. Paths returned this way may
+    /// not name the files they came from.
+    #[default]
+    Lossy,
+    /// Decode each invalid byte as the character with the same value, as
+    /// ISO-8859-1 does. No byte is dropped, so Latin-1 text reads correctly and
+    /// distinct names stay distinct. Use `Repository::cmd_to_writer` for the
+    /// exact bytes.
+    Bytes,
+}
+
+impl DecodePolicy {
+    /// Decodes `bytes`, borrowing them when they are valid UTF-8.
+    ///
+    /// # Errors
+    /// Returns `GitError::Undecodable` for invalid UTF-8 under `Strict`.
+    pub fn decode(self, bytes: &[u8]) -> Result<Cow<'_, str>, GitError> {
+        match self {
+            DecodePolicy::Strict => str::from_utf8(bytes).map(Cow::Borrowed).map_err(|_| GitError::Undecodable),
+            DecodePolicy::Lossy => Ok(String::from_utf8_lossy(bytes)),
+            DecodePolicy::Bytes => {
+                if let Ok(text) = str::from_utf8(bytes) {
+                    return Ok(Cow::Borrowed(text));
+                }
+                let mut text = String::with_capacity(bytes.len());
+                for chunk in bytes.utf8_chunks() {
+                    text.push_str(chunk.valid());
+                    text.extend(chunk.invalid().iter().map(|&byte| char::from(byte)));
+                }
+                Ok(Cow::Owned(text))
+            }
+        }
+    }
+}
+
+/// A Git command run by a repository, as passed to the callback installed with
+/// `RepositoryBuilder::on_command`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    /// The directory the command ran in.
+    pub dir: PathBuf,
+    /// The arguments after `git`, including `-c` settings GitPilot adds. Invalid
+    /// UTF-8 is replaced with `U+FFFD`.
+    pub args: Vec<String>,
+    /// How long the command ran, or until it failed to start.
+    pub duration: Duration,
+    pub outcome: CommandOutcome,
+}
+
+/// How a recorded Git command ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandOutcome {
+    /// Git exited with status 0.
+    Succeeded,
+    /// Git exited with another status, or was killed by a signal (`code` is `None`).
+    Failed {
+        code: Option<i32>,
+        /// The last line Git wrote to stderr, usually its `fatal:` or `error:` message.
+        message: String,
+    },
+    /// Git was killed after the repository's timeout.
+    TimedOut,
+    /// Git was stopped after exceeding the repository's output limit.
+    OutputTooLarge,
+    /// Git could not be started at all.
+    NotStarted,
+}
+
+impl CommandOutcome {
+    fn from_status(status: ExitStatus, stderr: &[u8]) -> CommandOutcome {
+        if status.success() {
+            return CommandOutcome::Succeeded;
+        }
+        let stderr = String::from_utf8_lossy(stderr);
+        let message = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or_default();
+        CommandOutcome::Failed { code: status.code(), message: message.trim_end().to_string() }
+    }
+}
+
+/// The callback installed with `RepositoryBuilder::on_command`.
+#[derive(Clone)]
+pub(crate) struct CommandHook(Arc<dyn Fn(&CommandRecord) + Send + Sync>);
+
+impl fmt::Debug for CommandHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CommandHook")
+    }
+}
+
+/// The settings a repository runs Git with, shared by its clones.
+#[derive(Debug, Clone)]
+pub(crate) struct Settings {
+    pub(crate) git_binary: Option<PathBuf>,
+    pub(crate) envs: Vec<(OsString, OsString)>,
+    /// The locale Git runs in; `None` to inherit the user's.
+    pub(crate) locale: Option<Cow<'static, str>>,
+    pub(crate) default_remote: Option<Remote>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) on_command: Option<CommandHook>,
+    /// The most bytes accepted on stdout or stderr; `None` for no limit.
+    pub(crate) max_output: Option<usize>,
+    pub(crate) decode: DecodePolicy,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings::DEFAULT
+    }
+}
+
+impl Settings {
+    /// The settings of repositories created without a builder.
+    pub(crate) const DEFAULT: Settings = Settings {
+        git_binary: None,
+        envs: Vec::new(),
+        locale: Some(Cow::Borrowed("C")),
+        default_remote: None,
+        timeout: None,
+        retry: RetryPolicy::NONE,
+        on_command: None,
+        max_output: Some(DEFAULT_MAX_OUTPUT),
+        decode: DecodePolicy::Lossy,
+    };
+
+    /// Returns a command running the configured Git in `dir` with the configured
+    /// environment; arguments and per-call variables are added by the caller.
+    pub(crate) fn command(&self, dir: &Path) -> Command {
+        let mut command = Command::new(self.git_binary.as_deref().unwrap_or(Path::new("git")));
+        command.current_dir(dir);
+        if let Some(locale) = &self.locale {
+            // `LC_ALL` overrides every `LC_*` category; `LANGUAGE` would still
+            // pick the message language for locales other than `C`.
+            command.env("LC_ALL", locale.as_ref()).env("LANG", locale.as_ref()).env_remove("LANGUAGE");
+        }
+        command.envs(self.envs.iter().map(|(key, value)| (key, value)));
+        command
+    }
+
+    /// Reports a command started at `started` to the `on_command` callback, if any.
+    pub(crate) fn record(&self, dir: &Path, args: &[OsString], started: Instant, result: Result<&Output, &GitError>) {
+        let Some(CommandHook(hook)) = &self.on_command else { return };
+        let outcome = match result {
+            Ok(output) => CommandOutcome::from_status(output.status, &output.stderr),
+            Err(GitError::Timeout(_)) => CommandOutcome::TimedOut,
+            Err(GitError::OutputTooLarge { .. }) => CommandOutcome::OutputTooLarge,
+            Err(_) => CommandOutcome::NotStarted,
+        };
+        hook(&CommandRecord {
+            dir: dir.to_path_buf(),
+            args: args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+            duration: started.elapsed(),
+            outcome,
+        });
+    }
+
+    /// Checks whether a command that failed with `stderr` should be run again.
+    pub(crate) fn should_retry(&self, retries: u32, stderr: &[u8]) -> bool {
+        retries < self.retry.max_retries && is_transient(&String::from_utf8_lossy(stderr))
+    }
+}
+
+/// Recognises failures after which Git has changed nothing and a later attempt
+/// may succeed.
+fn is_transient(stderr: &str) -> bool {
+    const TRANSIENT: [&str; 7] = [
+        ".lock': File exists",
+        "Could not resolve host",
+        "Connection timed out",
+        "Connection reset",
+        "The remote end hung up unexpectedly",
+        "early EOF",
+        "RPC failed",
+    ];
+    TRANSIENT.iter().any(|message| stderr.contains(message))
+}
+
+/// Sets up a `Repository` or `AsyncRepository` that runs Git with non-default
+/// settings; created by `Repository::builder`.
+#[derive(Debug, Clone)]
+pub struct RepositoryBuilder {
+    path: PathBuf,
+    settings: Settings,
+}
+
+impl RepositoryBuilder {
+    pub(crate) fn new(path: PathBuf) -> RepositoryBuilder {
+        RepositoryBuilder { path, settings: Settings::default() }
+    }
+
+    /// Runs the Git executable at `path` instead of the `git` found on the `PATH`.
+    pub fn git_binary(mut self, path: impl Into<PathBuf>) -> RepositoryBuilder {
+        self.settings.git_binary = Some(path.into());
+        self
+    }
+
+    /// Sets an environment variable for every Git command, e.g.
+    /// `GIT_SSH_COMMAND`. Variables a method sets for itself take precedence;
+    /// these take precedence over the locale's.
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> RepositoryBuilder {
+        self.settings.envs.push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Runs Git in `locale` instead of the default `C` locale, e.g. `C.UTF-8`.
+    ///
+    /// GitPilot parses Git's messages in English, so a locale with translated
+    /// messages breaks methods that interpret them.
+    pub fn locale(mut self, locale: impl Into<String>) -> RepositoryBuilder {
+        self.settings.locale = Some(Cow::Owned(locale.into()));
+        self
+    }
+
+    /// Runs Git in the user's locale (from `LC_ALL`, `LANG` and friends), so
+    /// messages shown to the user are in their language. See `locale` for the
+    /// cost.
+    pub fn inherit_locale(mut self) -> RepositoryBuilder {
+        self.settings.locale = None;
+        self
+    }
+
+    /// Makes `remote` the remote that `fetch` and `push_with_options` use when
+    /// none is given, instead of the one Git picks from the current branch.
+    pub fn default_remote(mut self, remote: Remote) -> RepositoryBuilder {
+        self.settings.default_remote = Some(remote);
+        self
+    }
+
+    /// Kills Git commands that run longer than `timeout`, which then fail with
+    /// `GitError::Timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> RepositoryBuilder {
+        self.settings.timeout = Some(timeout);
+        self
+    }
+
+    /// Runs commands that failed transiently again, as described by `RetryPolicy`.
+    pub fn retry(mut self, retry: RetryPolicy) -> RepositoryBuilder {
+        self.settings.retry = retry;
+        self
+    }
+
+    /// Fails commands that write more than `limit` bytes to stdout or stderr
+    /// with `GitError::OutputTooLarge`, instead of the default
+    /// `DEFAULT_MAX_OUTPUT`. Git is stopped once the limit is exceeded, so a
+    /// runaway command cannot exhaust memory.
+    pub fn max_output_bytes(mut self, limit: usize) -> RepositoryBuilder {
+        self.settings.max_output = Some(limit);
+        self
+    }
+
+    /// Accepts output of any size. Prefer the streaming methods, such as
+    /// `Repository::cmd_lines`, for commands whose output may be huge.
+    pub fn unlimited_output(mut self) -> RepositoryBuilder {
+        self.settings.max_output = None;
+        self
+    }
+
+    /// Chooses how output that is not valid UTF-8 is decoded, instead of the
+    /// default `DecodePolicy::Lossy`.
+    pub fn decode(mut self, policy: DecodePolicy) -> RepositoryBuilder {
+        self.settings.decode = policy;
+        self
+    }
+
+    /// Calls `hook` after every Git command the repository runs, including each
+    /// retry, with what was run and how it ended. The hook runs on the thread
+    /// (or task) that ran the command, so it should return quickly.
+    pub fn on_command<F>(mut self, hook: F) -> RepositoryBuilder
+    where
+        F: Fn(&CommandRecord) + Send + Sync + 'static,
+    {
+        self.settings.on_command = Some(CommandHook(Arc::new(hook)));
+        self
+    }
+
+    /// Creates the `Repository`. Like `Repository::new`, this does not check
+    /// that the path is a repository.
+    pub fn build(self) -> Repository {
+        Repository { location: self.location() }
+    }
+
+    /// Creates an `AsyncRepository` with the same settings.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> AsyncRepository {
+        self.build().to_async()
+    }
+
+    fn location(self) -> Location {
+        Location::with_settings(&self.path, Arc::new(self.settings))
+    }
+}
+
+impl Repository {
+    /// Calls `f` with a handle on this repository, and returns what it returned
+    /// together with every Git command it ran, in order, so the commands behind
+    /// a high-level call can be logged or inspected.
+    ///
+    /// The handle has the same settings; an `on_command` callback still sees the
+    /// commands too. Commands run through other handles at the same time are not
+    /// included.
+    ///
+    /// # Arguments
+    /// * `f` - The calls to explain, e.g. `|repo| repo.status()`.
+    ///
+    /// # Returns
+    /// The result of `f`, and a `CommandRecord` per command, including retries
+    /// and commands that failed.
+    pub fn explain<T, F>(&self, f: F) -> (T, Vec<CommandRecord>)
+    where
+        F: FnOnce(&Repository) -> T,
+    {
+        let records: Arc<Mutex<Vec<CommandRecord>>> = Arc::default();
+        let sink = Arc::clone(&records);
+        let mut settings = Settings::clone(&self.location.settings);
+        let previous = settings.on_command.take();
+        settings.on_command = Some(CommandHook(Arc::new(move |record: &CommandRecord| {
+            sink.lock().unwrap_or_else(|e| e.into_inner()).push(record.clone());
+            if let Some(CommandHook(hook)) = &previous {
+                hook(record);
+            }
+        })));
+        let repo = Repository { location: Location::with_settings(self.location.path(), Arc::new(settings)) };
+
+        let result = f(&repo);
+        drop(repo);
+        let records = std::mem::take(&mut *records.lock().unwrap_or_else(|e| e.into_inner()));
+        (result, records)
+    }
+}
@@ -0,0 +1,488 @@
+//! The Git invocations shared by `Repository` and `AsyncRepository`.
+//!
+//! Each function here returns a `GitCommand`: the arguments to pass to `git`
+//! and the parser for its stdout, independent of how the process is run. The
+//! sync API runs commands with `GitCommand::run`, and the async API (behind the
+//! `async` feature) with `GitCommand::run_async`, so an operation offered by
+//! both APIs is defined once and cannot drift between them.
+
+use crate::error::GitError;
+use crate::models::{Branch, Commit, FetchResult, PushResult, SignatureStatus, StatusResult, LOG_RECORD_FORMAT, SIGNATURE_FORMAT};
+use crate::options::{BranchFilter, CloneOptions, FetchOptions, PushOptions};
+use crate::repository::{failure_error, pathspec_args, run_git, GitContext};
+use crate::types::{BranchName, CommitHash, GitUrl, Pathspec, Remote, Result};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::str::{self, FromStr};
+
+/// A `git` invocation: its arguments and the parser for its output on success.
+pub(crate) struct GitCommand<T> {
+    pub(crate) args: Vec<OsString>,
+    pub(crate) parse: fn(&str) -> Result<T>,
+    /// Whether `parse` reads stderr rather than stdout, for commands such as
+    /// `fetch` that report their results there.
+    pub(crate) parse_stderr: bool,
+    /// Turns the generic error for a failed run into a more specific one,
+    /// given the command's stdout.
+    pub(crate) refine_error: fn(&str, GitError) -> GitError,
+}
+
+impl<T> GitCommand<T> {
+    /// Runs the command in `p`, blocking until Git exits.
+    pub(crate) fn run(self, p: impl GitContext) -> Result<T> {
+        let decode = p.settings().decode;
+        let output = run_git(p, self.args, &[], None)?;
+        if !output.status.success() {
+            return Err((self.refine_error)(&String::from_utf8_lossy(&output.stdout), failure_error(&output)));
+        }
+        let parsed = if self.parse_stderr { &output.stderr } else { &output.stdout };
+        (self.parse)(&decode.decode(parsed)?)
+    }
+}
+
+impl GitCommand<()> {
+    /// A command whose output is discarded.
+    fn unit<I, S>(args: I) -> GitCommand<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        GitCommand::parsed(args, |_| Ok(()))
+    }
+}
+
+impl<T> GitCommand<T> {
+    fn parsed<I, S>(args: I, parse: fn(&str) -> Result<T>) -> GitCommand<T>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        GitCommand {
+            args: args.into_iter().map(|arg| arg.as_ref().to_os_string()).collect(),
+            parse,
+            parse_stderr: false,
+            refine_error: |_, error| error,
+        }
+    }
+
+    /// A command whose stderr is parsed instead of its stdout.
+    fn parsed_stderr<I, S>(args: I, parse: fn(&str) -> Result<T>) -> GitCommand<T>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        GitCommand { parse_stderr: true, ..GitCommand::parsed(args, parse) }
+    }
+}
+
+/// `git clone -- <url> <target>`, run from the current directory.
+pub(crate) fn clone(url: &GitUrl, target: &Path) -> GitCommand<()> {
+    GitCommand::unit([OsStr::new("clone"), OsStr::new("--"), url.as_ref(), target.as_os_str()])
+}
+
+/// `git clone [--branch=<name>] [--depth=<n>] [--filter=<spec>] [--no-checkout] -- <url> <target>`,
+/// run from the current directory.
+pub(crate) fn clone_with(url: &GitUrl, target: &Path, opts: &CloneOptions) -> GitCommand<()> {
+    let mut args: Vec<OsString> = vec!["clone".into()];
+    if let Some(branch) = &opts.branch {
+        args.push(format!("--branch={}", branch).into());
+    }
+    if let Some(depth) = opts.depth {
+        args.push(format!("--depth={}", depth).into());
+    }
+    if let Some(filter) = &opts.filter {
+        args.push(format!("--filter={}", filter).into());
+    }
+    if opts.no_checkout {
+        args.push("--no-checkout".into());
+    }
+    args.extend(["--".into(), url.to_string().into(), target.into()]);
+    GitCommand::unit(args)
+}
+
+/// `git init`
+pub(crate) fn init() -> GitCommand<()> {
+    GitCommand::unit(["init"])
+}
+
+/// `git checkout -b <branch> [<startpoint>]`
+pub(crate) fn create_branch(branch: &BranchName, startpoint: Option<&str>) -> GitCommand<()> {
+    GitCommand::unit(["checkout", "-b", branch.as_ref()].into_iter().chain(startpoint))
+}
+
+/// `git checkout <branch>`
+pub(crate) fn switch_branch(branch: &BranchName) -> GitCommand<()> {
+    GitCommand::unit(["checkout", branch.as_ref()])
+}
+
+/// `git add -- <pathspec>...`
+pub(crate) fn add(pathspecs: &[Pathspec]) -> Result<GitCommand<()>> {
+    let args = pathspec_args(pathspecs)?;
+    Ok(GitCommand::unit([OsStr::new("add"), OsStr::new("--")].into_iter().chain(args)))
+}
+
+/// `git rm [-f] -- <pathspec>...`
+pub(crate) fn remove(pathspecs: &[Pathspec], force: bool) -> Result<GitCommand<()>> {
+    let flags = [OsStr::new("rm"), OsStr::new("-f")];
+    let flags = if force { &flags[..] } else { &flags[..1] };
+    let args = pathspec_args(pathspecs)?;
+    Ok(GitCommand::unit(flags.iter().copied().chain([OsStr::new("--")]).chain(args)))
+}
+
+/// `git commit -am <message>`
+pub(crate) fn commit_all_modified(message: &str) -> GitCommand<()> {
+    GitCommand::unit(["commit", "-am", message])
+}
+
+/// `git commit -m <message>`
+pub(crate) fn commit_staged(message: &str) -> GitCommand<()> {
+    GitCommand::unit(["commit", "-m", message])
+}
+
+/// `git push`
+pub(crate) fn push() -> GitCommand<PushResult> {
+    push_command(["push"])
+}
+
+/// `git push -u <remote> <branch>`
+pub(crate) fn push_to_upstream(remote: &Remote, branch: &BranchName) -> GitCommand<PushResult> {
+    push_command(["push", "-u", remote.as_ref(), branch.as_ref()])
+}
+
+/// `git push [<remote> [<branch>...]]` with the flags selected by `opts`; the
+/// branches are ignored without a remote.
+pub(crate) fn push_with_options(remote: Option<&Remote>, branches: &[BranchName], opts: &PushOptions) -> GitCommand<PushResult> {
+    let mut args: Vec<&str> = vec!["push"];
+    if opts.set_upstream {
+        args.push("--set-upstream");
+    }
+    if opts.no_verify {
+        args.push("--no-verify");
+    }
+    if opts.no_signed {
+        args.push("--no-signed");
+    }
+    if opts.dry_run {
+        args.push("--dry-run");
+    }
+    if let Some(remote) = remote {
+        args.push(remote.as_ref());
+        args.extend(branches.iter().map(AsRef::<str>::as_ref));
+    }
+    push_command(args)
+}
+
+/// Adds `--porcelain` and full hashes to a `git push` and parses its per-ref
+/// report, which is printed even when some refs are rejected.
+fn push_command<'a, I: IntoIterator<Item = &'a str>>(args: I) -> GitCommand<PushResult> {
+    let mut args: Vec<&str> = ["-c", "core.abbrev=no"].into_iter().chain(args).collect();
+    args.insert(3, "--porcelain");
+    GitCommand {
+        refine_error: |stdout, error| {
+            let result = PushResult::from_porcelain(stdout);
+            if result.rejected().next().is_some() {
+                GitError::PushRejected(result)
+            } else {
+                error
+            }
+        },
+        ..GitCommand::parsed(args, |output| Ok(PushResult::from_porcelain(output)))
+    }
+}
+
+/// `git remote add -- <name> <url>`
+pub(crate) fn add_remote(name: &Remote, url: &GitUrl) -> GitCommand<()> {
+    GitCommand::unit(["remote", "add", "--", name.as_ref(), url.as_ref()])
+}
+
+/// `git fetch <remote>`
+pub(crate) fn fetch_remote(remote: &Remote) -> GitCommand<()> {
+    GitCommand::unit(["fetch", remote.as_ref()])
+}
+
+/// `git fetch` with the flags, remote and refspecs selected by `opts`.
+pub(crate) fn fetch(opts: &FetchOptions) -> GitCommand<FetchResult> {
+    // Full hashes and ref names in the per-ref lines on stderr.
+    let mut args = ["-c", "core.abbrev=no", "-c", "fetch.output=full", "fetch"].map(String::from).to_vec();
+    if opts.all {
+        args.push("--all".to_string());
+    }
+    if opts.prune {
+        args.push("--prune".to_string());
+    }
+    if opts.prune_tags {
+        args.push("--prune-tags".to_string());
+    }
+    if opts.tags {
+        args.push("--tags".to_string());
+    }
+    if let Some(depth) = opts.depth {
+        args.push(format!("--depth={}", depth));
+    }
+    if opts.dry_run {
+        args.push("--dry-run".to_string());
+    }
+    if let (Some(remote), false) = (&opts.remote, opts.all) {
+        args.push(remote.to_string());
+        args.extend(opts.refspecs.iter().cloned());
+    }
+    GitCommand {
+        // Git exits with 1 when any ref is rejected; keep the refs it did update.
+        refine_error: |_, error| {
+            let GitError::GitError { stderr, .. } = &error else {
+                return error;
+            };
+            let result = FetchResult::from_fetch_output(stderr);
+            if result.rejected().next().is_some() {
+                GitError::FetchRejected(result)
+            } else {
+                error
+            }
+        },
+        ..GitCommand::parsed_stderr(args, |output| Ok(FetchResult::from_fetch_output(output)))
+    }
+}
+
+/// `git branch --list --format=%(refname:short)`
+pub(crate) fn list_branches() -> GitCommand<Vec<BranchName>> {
+    GitCommand::parsed(["branch", "--list", "--format=%(refname:short)"], |output| {
+        output.lines().map(|line| BranchName::from_str(line.trim())).collect()
+    })
+}
+
+/// `git branch --list` with the name, tip, HEAD marker and upstream of each branch.
+pub(crate) fn list_branches_info() -> GitCommand<Vec<Branch>> {
+    list_branches_filtered(&BranchFilter::default())
+}
+
+/// `git branch --list` of the branches selected by `filter`, with the same
+/// details as `list_branches_info`.
+pub(crate) fn list_branches_filtered(filter: &BranchFilter) -> GitCommand<Vec<Branch>> {
+    // NUL-separated, since `%(HEAD)` is a space for every branch but the current one.
+    let mut args = vec![
+        "branch".to_string(),
+        "--list".to_string(),
+        "--format=%(refname:short)%00%(objectname)%00%(HEAD)%00%(upstream:short)".to_string(),
+    ];
+    if filter.remotes {
+        args.push("--remotes".to_string());
+    }
+    if let Some(commit) = &filter.merged {
+        args.push(format!("--merged={}", commit));
+    }
+    if let Some(commit) = &filter.no_merged {
+        args.push(format!("--no-merged={}", commit));
+    }
+    if let Some(commit) = &filter.contains {
+        args.push(format!("--contains={}", commit));
+    }
+    if let Some(key) = &filter.sort {
+        args.push(format!("--sort={}", key));
+    }
+    args.extend(filter.patterns.iter().cloned());
+    GitCommand::parsed(args, |output| {
+        let mut branches = Vec::new();
+        for line in output.lines() {
+            let fields: Vec<&str> = line.split('\0').collect();
+            let [name_str, commit_str, head, upstream] = fields[..] else {
+                continue;
+            };
+            let Ok(name) = BranchName::from_str(name_str) else {
+                eprintln!("Warning: Could not parse branch name '{}'", name_str);
+                continue;
+            };
+            let Ok(commit) = CommitHash::from_str(commit_str) else {
+                eprintln!("Warning: Could not parse commit hash '{}' for branch '{}'", commit_str, name_str);
+                continue;
+            };
+            branches.push(Branch {
+                name,
+                commit,
+                is_head: head == "*",
+                upstream: Some(upstream).filter(|upstream| !upstream.is_empty()).map(str::to_string),
+            });
+        }
+        Ok(branches)
+    })
+}
+
+/// `git ls-files -z --cached`
+pub(crate) fn list_tracked() -> GitCommand<Vec<String>> {
+    GitCommand::parsed(["ls-files", "-z", "--cached"], |output| {
+        Ok(output.split('\0').filter(|path| !path.is_empty()).map(str::to_owned).collect())
+    })
+}
+
+/// `git config --get remote.<name>.url`
+pub(crate) fn show_remote_uri(remote: &Remote) -> GitCommand<GitUrl> {
+    // Git already accepted the stored URL.
+    GitCommand::parsed(["config", "--get", &format!("remote.{}.url", remote)], |output| {
+        Ok(GitUrl::from_config(output))
+    })
+}
+
+/// `git remote`, failing with `NoRemoteRepositorySet` if there are none.
+pub(crate) fn list_remotes() -> GitCommand<Vec<Remote>> {
+    GitCommand::parsed(["remote"], |output| {
+        let names: Vec<&str> = output.lines().map(str::trim).filter(|name| !name.is_empty()).collect();
+        if names.is_empty() {
+            return Err(GitError::NoRemoteRepositorySet);
+        }
+        names.into_iter().map(Remote::from_str).collect()
+    })
+}
+
+/// `git rev-parse [--short] HEAD`
+pub(crate) fn get_hash(short: bool) -> GitCommand<CommitHash> {
+    let args: &[&str] = if short { &["rev-parse", "--short", "HEAD"] } else { &["rev-parse", "HEAD"] };
+    GitCommand::parsed(args, |output| CommitHash::from_str(output.trim()))
+}
+
+/// `git log -1` of `commit_ref` (default `HEAD`) with its signature status.
+pub(crate) fn get_commit(commit_ref: Option<&str>) -> GitCommand<Commit> {
+    let format_arg = format!("--format={}{}", LOG_RECORD_FORMAT, SIGNATURE_FORMAT);
+    GitCommand::parsed(["log", "-1", &format_arg, commit_ref.unwrap_or("HEAD")], |output| {
+        output
+            .strip_prefix('\x1e')
+            .and_then(Commit::from_log_record)
+            .map(|(mut commit, rest)| {
+                commit.signature = rest.split('\0').next().and_then(SignatureStatus::from_code);
+                commit
+            })
+            .ok_or_else(|| GitError::GitError {
+                stdout: output.to_string(),
+                stderr: "Failed to parse commit information".to_string(),
+            })
+    })
+}
+
+/// `git rev-parse --absolute-git-dir`
+pub(crate) fn git_dir() -> GitCommand<PathBuf> {
+    GitCommand::parsed(["rev-parse", "--absolute-git-dir"], |output| {
+        Ok(PathBuf::from(output.trim_end_matches('\n')))
+    })
+}
+
+/// `git status --porcelain=v2 --branch -z`.
+///
+/// The in-progress merge, rebase and cherry-pick flags are left unset; each
+/// executor fills them in from the Git directory found by `git_dir`.
+pub(crate) fn status() -> GitCommand<StatusResult> {
+    GitCommand::parsed(["status", "--porcelain=v2", "--branch", "-z"], |output| {
+        Ok(StatusResult::from_porcelain_v2_z(output))
+    })
+}
+
+/// `git status --porcelain=v2 --branch -z --no-renames --untracked-files=no`.
+///
+/// Like `status`, the operation flags are left for the executor.
+pub(crate) fn status_fast() -> GitCommand<StatusResult> {
+    GitCommand::parsed(
+        ["status", "--porcelain=v2", "--branch", "-z", "--no-renames", "--untracked-files=no"],
+        |output| Ok(StatusResult::from_porcelain_v2_z(output)),
+    )
+}
+
+/// Any command, with its stdout split into lines.
+pub(crate) fn cmd_out<I, S>(args: I) -> GitCommand<Vec<String>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    GitCommand::parsed(args, |output| Ok(output.lines().map(str::to_owned).collect()))
+}
+
+/// Any command, with its stdout discarded.
+pub(crate) fn cmd<I, S>(args: I) -> GitCommand<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    GitCommand::unit(args)
+}
+
+/// `git rebase <target>`
+pub(crate) fn rebase(target: &str) -> GitCommand<()> {
+    GitCommand::unit(["rebase", target])
+}
+
+/// `git rebase --continue`
+pub(crate) fn rebase_continue() -> GitCommand<()> {
+    GitCommand::unit(["rebase", "--continue"])
+}
+
+/// `git rebase --abort`
+pub(crate) fn rebase_abort() -> GitCommand<()> {
+    GitCommand::unit(["rebase", "--abort"])
+}
+
+/// `git cherry-pick <commit>...`
+pub(crate) fn cherry_pick<S: AsRef<OsStr>>(commits: &[S]) -> GitCommand<()> {
+    GitCommand::unit([OsStr::new("cherry-pick")].into_iter().chain(commits.iter().map(AsRef::as_ref)))
+}
+
+/// `git cherry-pick --continue`
+pub(crate) fn cherry_pick_continue() -> GitCommand<()> {
+    GitCommand::unit(["cherry-pick", "--continue"])
+}
+
+/// `git cherry-pick --abort`
+pub(crate) fn cherry_pick_abort() -> GitCommand<()> {
+    GitCommand::unit(["cherry-pick", "--abort"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args<T>(command: &GitCommand<T>) -> Vec<&str> {
+        command.args.iter().map(|arg| arg.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_argument_building() {
+        let branch = BranchName::from_str("topic").unwrap();
+        assert_eq!(args(&create_branch(&branch, None)), ["checkout", "-b", "topic"]);
+        assert_eq!(args(&create_branch(&branch, Some("main"))), ["checkout", "-b", "topic", "main"]);
+        let url = GitUrl::parse_lenient("../upstream").unwrap();
+        assert_eq!(args(&clone(&url, Path::new("work"))), ["clone", "--", "../upstream", "work"]);
+        let origin = Remote::from_str("origin").unwrap();
+        assert_eq!(args(&add_remote(&origin, &url)), ["remote", "add", "--", "origin", "../upstream"]);
+        let paths = [Pathspec::new("a"), Pathspec::new("b")];
+        assert_eq!(args(&remove(&paths, false).unwrap()), ["rm", "--", "a", "b"]);
+        assert_eq!(args(&remove(&paths[..1], true).unwrap()), ["rm", "-f", "--", "a"]);
+
+        let opts = FetchOptions {
+            remote: Some(Remote::from_str("origin").unwrap()),
+            prune: true,
+            depth: Some(1),
+            refspecs: vec!["main".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(args(&fetch(&opts))[4..], ["fetch", "--prune", "--depth=1", "origin", "main"]);
+        // `--all` takes no remote or refspecs.
+        let opts = FetchOptions { all: true, ..opts };
+        assert_eq!(args(&fetch(&opts))[4..], ["fetch", "--all", "--prune", "--depth=1"]);
+    }
+
+    #[test]
+    fn test_parsers() {
+        let remotes = (list_remotes().parse)("origin\nupstream\n").unwrap();
+        assert_eq!(remotes.len(), 2);
+        assert!(matches!((list_remotes().parse)(""), Err(GitError::NoRemoteRepositorySet)));
+
+        let tracked = (list_tracked().parse)("a.txt\0dir/b c.txt\0").unwrap();
+        assert_eq!(tracked, ["a.txt", "dir/b c.txt"]);
+
+        let hash = "0123456789abcdef0123456789abcdef01234567";
+        let output = format!("main\0{hash}\0*\0origin/main\ntopic\0{hash}\0 \0\nfeature\0{hash}\0 \0origin/feature\n");
+        let branches = (list_branches_info().parse)(&output).unwrap();
+        assert_eq!(branches.len(), 3);
+        assert!(branches[0].is_head);
+        assert_eq!(branches[0].upstream.as_deref(), Some("origin/main"));
+        assert!(!branches[1].is_head);
+        assert_eq!(branches[1].upstream, None);
+        assert!(!branches[2].is_head);
+        assert_eq!(branches[2].upstream.as_deref(), Some("origin/feature"));
+    }
+}
@@ -47,6 +47,111 @@ pub enum GitError {
     #[error("Stash reference is invalid: {0}")]
     InvalidStashRef(String),
 
+    /// The name is not one of the hooks Git runs.
+    #[error("Hook name is invalid: {0}")]
+    InvalidHookName(String),
+
+    /// The string is not a semantic version such as `1.2.3` or `2.0.0-rc.1`.
+    #[error("Version is invalid: {0}")]
+    InvalidVersion(String),
+
+    /// A `CommitMessage` breaks one of its rules; holds the reason.
+    #[error("Commit message is invalid: {0}")]
+    InvalidCommitMessage(String),
+
+    /// A filesystem operation performed by the library itself failed,
+    /// e.g. writing a scratch file handed to Git.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The working tree or index has changes, as reported by `Repository::ensure_clean`.
+    #[error("Working tree has uncommitted changes in {} path(s): {files:?}", files.len())]
+    DirtyWorkingTree {
+        /// The paths with changes, relative to the repository root.
+        files: Vec<std::path::PathBuf>,
+    },
+
+    /// A lease-protected force push was rejected because the remote branch no longer
+    /// points to the expected commit.
+    #[error("Remote branch {remote}/{branch} has moved since it was last seen; refusing to overwrite it")]
+    StaleRemoteRef { remote: String, branch: String },
+
+    /// The remote refused some of the pushed refs. The `PushResult` also lists
+    /// the refs that were pushed, as pushes are not atomic.
+    #[error("Push was rejected for {}", .0.rejection_summary())]
+    PushRejected(crate::models::PushResult),
+
+    /// The fetch refused to update some refs, e.g. a tag that would be clobbered.
+    /// The `FetchResult` also lists the refs that were updated.
+    #[error("Fetch was rejected for {}", .0.rejection_summary())]
+    FetchRejected(crate::models::FetchResult),
+
+    /// A compare-and-swap ref update was rejected because the ref no longer had
+    /// the expected value.
+    #[error("Ref {reference} does not have the expected value; refusing to update it")]
+    StaleRef { reference: String },
+
+    /// Another process holds the repository lock taken by `Repository::lock`.
+    #[error("Repository is locked by {} ({})", pid.map_or_else(|| "another process".to_string(), |pid| format!("process {}", pid)), path.display())]
+    RepositoryLocked {
+        /// The lock file.
+        path: std::path::PathBuf,
+        /// The holder's process id, if the lock file records one.
+        pid: Option<u32>,
+    },
+
+    /// A step inside `Repository::transaction` failed and restoring the
+    /// repository's original state failed as well.
+    #[error("{error}; rolling back the transaction also failed: {rollback}")]
+    RollbackFailed {
+        /// The error that aborted the transaction.
+        error: Box<GitError>,
+        /// The error raised while rolling back.
+        rollback: Box<GitError>,
+    },
+
+    /// A Git command ran longer than the timeout set with
+    /// `RepositoryBuilder::timeout` and was killed.
+    #[error("git did not finish within {0:?}")]
+    Timeout(std::time::Duration),
+
+    /// A Git command wrote more than the output limit set with
+    /// `RepositoryBuilder::max_output_bytes` to stdout or stderr, and was stopped.
+    #[error("git produced more than {limit} bytes of output; use a streaming method such as `Repository::cmd_lines`, or raise the limit with `RepositoryBuilder::max_output_bytes`")]
+    OutputTooLarge { limit: usize },
+
+    /// The path passed to `Repository::open_worktree` is not one of the
+    /// repository's worktrees.
+    #[error("{0:?} is not a worktree of this repository")]
+    NotAWorktree(std::path::PathBuf),
+
+    /// The named local branch does not exist.
+    #[error("Branch {0} does not exist")]
+    BranchNotFound(String),
+
+    /// The remote's `HEAD` does not point to a branch, e.g. because the remote
+    /// repository is empty.
+    #[error("Remote {0} has no default branch")]
+    NoDefaultBranch(String),
+
+    /// A pattern passed to `Repository::add_to_gitignore` is blank or spans
+    /// several lines.
+    #[error("Not a single gitignore pattern: {0:?}")]
+    InvalidGitignorePattern(String),
+
+    /// Setting up a `watch::RepoWatch` on the Git directory failed.
+    #[error("Unable to watch the repository: {0}")]
+    Watch(String),
+
+    /// The `git lfs` extension is not installed.
+    #[error("git-lfs is not installed. Please install Git LFS to work with LFS-tracked files.")]
+    LfsNotInstalled,
+
+    /// A command-line tool other than Git, such as the `gh` client used by
+    /// `hosting::CliProvider`, was not found.
+    #[error("'{0}' command not found. Please ensure it is installed and on your PATH.")]
+    ToolNotFound(String),
+
     /// The 'git' executable was not found in the system's PATH.
     #[error("'git' command not found. Please ensure Git is installed and that its executable is included in your system's PATH environment variable.")]
     GitNotFound,
@@ -0,0 +1,487 @@
+//! Web URLs for repositories hosted on GitHub, GitLab and Bitbucket: commit
+//! pages, files at a revision, comparisons and new pull requests.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # use GitPilot::types::Remote;
+//! # use std::str::FromStr;
+//! # fn main() -> GitPilot::Result<()> {
+//! let repo = Repository::new("./my_project");
+//! if let Some(web) = repo.hosted_repo(&Remote::from_str("origin")?)? {
+//!     let head = repo.get_hash(false)?;
+//!     println!("{}", web.commit_url(&head));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "json")]
+use crate::error::GitError;
+#[cfg(feature = "json")]
+use crate::repository::failure_error;
+use crate::repository::Repository;
+use crate::types::{BranchName, CommitHash, GitUrl, Remote, Result};
+use std::fmt::Write;
+#[cfg(feature = "json")]
+use std::io::ErrorKind;
+#[cfg(feature = "json")]
+use std::process::Command;
+
+/// A code hosting service with a known URL layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HostingService {
+    /// GitHub, including GitHub Enterprise Server.
+    GitHub,
+    /// GitLab, including self-managed instances.
+    GitLab,
+    /// Bitbucket Cloud.
+    Bitbucket,
+}
+
+impl HostingService {
+    /// Recognises the public instances, `github.com`, `gitlab.com` and
+    /// `bitbucket.org`, and hosts named `gitlab.<domain>`.
+    pub fn detect(host: &str) -> Option<HostingService> {
+        let host = host.to_ascii_lowercase();
+        match host.as_str() {
+            "github.com" | "www.github.com" => Some(HostingService::GitHub),
+            "bitbucket.org" | "www.bitbucket.org" => Some(HostingService::Bitbucket),
+            _ if host == "gitlab.com" || host.starts_with("gitlab.") => Some(HostingService::GitLab),
+            _ => None,
+        }
+    }
+}
+
+/// A repository on a hosting service, from which its web URLs are built.
+///
+/// Revisions and paths are inserted into the URLs percent-encoded, so branch
+/// names and file names with spaces or `#` produce working links.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostedRepo {
+    service: HostingService,
+    base: String,
+}
+
+impl HostedRepo {
+    /// Recognises the hosting service from the URL's host, as `HostingService::detect` does.
+    ///
+    /// # Returns
+    /// `None` for local repositories and unrecognised hosts; use `with_service`
+    /// for those, e.g. GitHub Enterprise at `code.example.com`.
+    pub fn from_url(url: &GitUrl) -> Option<HostedRepo> {
+        HostedRepo::with_service(url, HostingService::detect(url.host()?)?)
+    }
+
+    /// Treats the URL as a repository on `service`, whatever its host.
+    ///
+    /// # Returns
+    /// `None` for local repositories and URLs without an owner and name.
+    pub fn with_service(url: &GitUrl, service: HostingService) -> Option<HostedRepo> {
+        let (host, path) = (url.host()?, url.repo_path()?);
+        url.owner()?;
+        Some(HostedRepo { service, base: format!("https://{}/{}", host, path) })
+    }
+
+    /// Returns the hosting service.
+    pub fn service(&self) -> HostingService {
+        self.service
+    }
+
+    /// Returns the repository's home page, e.g. `https://github.com/owner/project`.
+    pub fn home_url(&self) -> &str {
+        &self.base
+    }
+
+    /// Returns the page showing a commit.
+    pub fn commit_url(&self, commit: &CommitHash) -> String {
+        match self.service {
+            HostingService::GitHub => format!("{}/commit/{}", self.base, commit),
+            HostingService::GitLab => format!("{}/-/commit/{}", self.base, commit),
+            HostingService::Bitbucket => format!("{}/commits/{}", self.base, commit),
+        }
+    }
+
+    /// Returns the page showing a file as of a revision.
+    ///
+    /// # Arguments
+    /// * `revision` - A branch, tag or commit, e.g. `main` or `v1.0`.
+    /// * `path` - The file, relative to the repository root, with `/` separators.
+    pub fn file_url(&self, revision: &str, path: &str) -> String {
+        let (revision, path) = (encode(revision), encode(path.trim_start_matches('/')));
+        match self.service {
+            HostingService::GitHub => format!("{}/blob/{}/{}", self.base, revision, path),
+            HostingService::GitLab => format!("{}/-/blob/{}/{}", self.base, revision, path),
+            HostingService::Bitbucket => format!("{}/src/{}/{}", self.base, revision, path),
+        }
+    }
+
+    /// Returns the page comparing `head` with `base`, showing the changes `head`
+    /// would bring into `base`.
+    pub fn compare_url(&self, base: &str, head: &str) -> String {
+        let (base, head) = (encode(base), encode(head));
+        match self.service {
+            HostingService::GitHub => format!("{}/compare/{}...{}", self.base, base, head),
+            HostingService::GitLab => format!("{}/-/compare/{}...{}", self.base, base, head),
+            HostingService::Bitbucket => format!("{}/branches/compare/{}%0D{}", self.base, head, base),
+        }
+    }
+
+    /// Returns the form for opening a pull request (a merge request on GitLab)
+    /// from branch `head` into branch `base`.
+    pub fn new_pull_request_url(&self, base: &str, head: &str) -> String {
+        let (base, head) = (encode(base), encode(head));
+        match self.service {
+            HostingService::GitHub => format!("{}/compare/{}...{}?expand=1", self.base, base, head),
+            HostingService::GitLab => format!(
+                "{}/-/merge_requests/new?merge_request%5Bsource_branch%5D={}&merge_request%5Btarget_branch%5D={}",
+                self.base, head, base
+            ),
+            HostingService::Bitbucket => format!("{}/pull-requests/new?source={}&dest={}", self.base, head, base),
+        }
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and `/`.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+impl Repository {
+    /// Describes a remote as a repository on a hosting service, for building web URLs.
+    ///
+    /// Equivalent to `git config --get remote.<remote>.url` followed by
+    /// `HostedRepo::from_url`.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote, usually `origin`.
+    ///
+    /// # Returns
+    /// `None` if the remote's host is not a recognised hosting service.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the remote does not exist (including `GitNotFound`).
+    pub fn hosted_repo(&self, remote: &Remote) -> Result<Option<HostedRepo>> {
+        Ok(HostedRepo::from_url(&self.show_remote_uri(remote)?))
+    }
+}
+
+/// The state of a pull request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PullRequestState {
+    Open,
+    /// Closed without being merged.
+    Closed,
+    Merged,
+}
+
+/// A pull request, or a merge request on GitLab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PullRequest {
+    /// The number shown on the hosting service, e.g. `42` for `#42` or `!42`.
+    pub number: u64,
+    pub title: String,
+    /// The pull request's web page.
+    pub url: String,
+    /// The branch with the changes.
+    pub head: String,
+    /// The branch the changes are proposed for.
+    pub base: String,
+    pub state: PullRequestState,
+}
+
+/// The pull request to open with `HostingProvider::create_pull_request`.
+#[derive(Debug, Clone, Default)]
+pub struct NewPullRequest {
+    pub title: String,
+    /// The description, in the service's Markdown flavour.
+    pub body: String,
+    /// The branch with the changes; it must already be pushed.
+    pub head: String,
+    /// The branch the changes are proposed for, e.g. `main`.
+    pub base: String,
+    /// Open the pull request as a draft.
+    pub draft: bool,
+}
+
+/// Opens and looks up pull requests on a hosting service.
+///
+/// Implement this over a service's HTTP API to use an access token directly;
+/// `CliProvider` (behind the `json` feature) instead drives the service's
+/// command-line client, reusing the credentials it is logged in with.
+pub trait HostingProvider {
+    /// Opens a pull request for the repository's remote.
+    fn create_pull_request(&self, repo: &Repository, request: &NewPullRequest) -> Result<PullRequest>;
+
+    /// Lists the pull requests, in any state, whose head is `branch`.
+    fn pull_requests_for_branch(&self, repo: &Repository, branch: &BranchName) -> Result<Vec<PullRequest>>;
+}
+
+/// A `HostingProvider` that runs GitHub's `gh` or GitLab's `glab` client in the
+/// repository, which picks the remote and the logged-in account as it does on
+/// the command line.
+///
+/// ```no_run
+/// # use GitPilot::Repository;
+/// # use GitPilot::hosting::{CliProvider, HostingProvider, HostingService, NewPullRequest};
+/// # fn main() -> GitPilot::Result<()> {
+/// let repo = Repository::new("./my_project");
+/// let github = CliProvider::new(HostingService::GitHub).expect("gh drives GitHub");
+/// let request = NewPullRequest {
+///     title: "Release 1.2.0".to_string(),
+///     head: "release/1.2.0".to_string(),
+///     base: "main".to_string(),
+///     ..Default::default()
+/// };
+/// let pr = github.create_pull_request(&repo, &request)?;
+/// println!("Opened #{}: {}", pr.number, pr.url);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliProvider {
+    service: HostingService,
+    program: String,
+}
+
+#[cfg(feature = "json")]
+impl CliProvider {
+    /// Uses `gh` for GitHub and `glab` for GitLab, found on the `PATH`.
+    ///
+    /// # Returns
+    /// `None` for services without a supported client (Bitbucket).
+    pub fn new(service: HostingService) -> Option<CliProvider> {
+        let program = match service {
+            HostingService::GitHub => "gh",
+            HostingService::GitLab => "glab",
+            HostingService::Bitbucket => return None,
+        };
+        Some(CliProvider::with_program(service, program))
+    }
+
+    /// Uses the client at `program`, e.g. an absolute path to `gh`.
+    pub fn with_program(service: HostingService, program: impl Into<String>) -> CliProvider {
+        CliProvider { service, program: program.into() }
+    }
+
+    /// Returns the hosting service this provider talks to.
+    pub fn service(&self) -> HostingService {
+        self.service
+    }
+
+    /// Checks whether the client can be run, by running `<program> --version`.
+    pub fn is_available(&self) -> bool {
+        Command::new(&self.program).arg("--version").output().is_ok_and(|output| output.status.success())
+    }
+
+    /// Runs the client in the repository and returns its stdout.
+    fn run(&self, repo: &Repository, args: &[&str]) -> Result<String> {
+        let output = Command::new(&self.program).current_dir(repo.location.path()).args(args).output().map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                GitError::ToolNotFound(self.program.clone())
+            } else {
+                GitError::Execution
+            }
+        })?;
+        if !output.status.success() {
+            return Err(failure_error(&output));
+        }
+        String::from_utf8(output.stdout).map_err(|_| GitError::Undecodable)
+    }
+}
+
+#[cfg(feature = "json")]
+impl HostingProvider for CliProvider {
+    /// Runs `gh pr create` or `glab mr create --yes`.
+    fn create_pull_request(&self, repo: &Repository, request: &NewPullRequest) -> Result<PullRequest> {
+        let mut args = match self.service {
+            HostingService::GitLab => vec![
+                "mr",
+                "create",
+                "--yes",
+                "--title",
+                &request.title,
+                "--description",
+                &request.body,
+                "--source-branch",
+                &request.head,
+                "--target-branch",
+                &request.base,
+            ],
+            _ => vec![
+                "pr",
+                "create",
+                "--title",
+                &request.title,
+                "--body",
+                &request.body,
+                "--head",
+                &request.head,
+                "--base",
+                &request.base,
+            ],
+        };
+        if request.draft {
+            args.push("--draft");
+        }
+        let output = self.run(repo, &args)?;
+        // Both clients finish by printing the new pull request's URL, which ends in its number.
+        let url = output.split_whitespace().rev().find(|word| word.starts_with("http"));
+        let number = url.and_then(|url| url.trim_end_matches('/').rsplit('/').next()?.parse().ok());
+        let (Some(url), Some(number)) = (url, number) else {
+            return Err(GitError::GitError {
+                stdout: output.trim_end().to_string(),
+                stderr: format!("{} did not report the new pull request's URL", self.program),
+            });
+        };
+        Ok(PullRequest {
+            number,
+            title: request.title.clone(),
+            url: url.to_string(),
+            head: request.head.clone(),
+            base: request.base.clone(),
+            state: PullRequestState::Open,
+        })
+    }
+
+    /// Runs `gh pr list --state all --json ...` or `glab mr list --all --output json`.
+    fn pull_requests_for_branch(&self, repo: &Repository, branch: &BranchName) -> Result<Vec<PullRequest>> {
+        let branch = branch.as_ref();
+        let (args, fields) = match self.service {
+            HostingService::GitLab => (
+                vec!["mr", "list", "--all", "--source-branch", branch, "--output", "json"],
+                ["iid", "title", "web_url", "source_branch", "target_branch", "state"],
+            ),
+            _ => (
+                vec!["pr", "list", "--state", "all", "--head", branch, "--json", "number,title,url,headRefName,baseRefName,state"],
+                ["number", "title", "url", "headRefName", "baseRefName", "state"],
+            ),
+        };
+        let output = self.run(repo, &args)?;
+        parse_pull_requests(&output, fields).ok_or_else(|| GitError::GitError {
+            stdout: output.trim_end().to_string(),
+            stderr: format!("Unexpected pull request list from {}", self.program),
+        })
+    }
+}
+
+/// Parses a JSON array of pull requests, reading the number, title, URL, head,
+/// base and state from the fields named in `fields`.
+#[cfg(feature = "json")]
+fn parse_pull_requests(output: &str, fields: [&str; 6]) -> Option<Vec<PullRequest>> {
+    let list: serde_json::Value = serde_json::from_str(output).ok()?;
+    list.as_array()?
+        .iter()
+        .map(|pr| {
+            let text = |field: &str| pr.get(field)?.as_str().map(str::to_string);
+            Some(PullRequest {
+                number: pr.get(fields[0])?.as_u64()?,
+                title: text(fields[1])?,
+                url: text(fields[2])?,
+                head: text(fields[3])?,
+                base: text(fields[4])?,
+                state: match text(fields[5])?.to_ascii_lowercase().as_str() {
+                    "open" | "opened" => PullRequestState::Open,
+                    "merged" => PullRequestState::Merged,
+                    _ => PullRequestState::Closed,
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn hosted(url: &str) -> HostedRepo {
+        HostedRepo::from_url(&GitUrl::parse_lenient(url).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_detect_service() {
+        assert_eq!(hosted("git@github.com:owner/project.git").service(), HostingService::GitHub);
+        assert_eq!(hosted("https://gitlab.com/group/sub/project").service(), HostingService::GitLab);
+        assert_eq!(hosted("ssh://git@gitlab.example.com:2222/team/project.git").service(), HostingService::GitLab);
+        assert_eq!(hosted("https://user@bitbucket.org/team/project.git").service(), HostingService::Bitbucket);
+        assert_eq!(hosted("git@github.com:owner/project.git").home_url(), "https://github.com/owner/project");
+
+        let enterprise = GitUrl::parse_lenient("git@code.example.com:owner/project.git").unwrap();
+        assert_eq!(HostedRepo::from_url(&enterprise), None);
+        let web = HostedRepo::with_service(&enterprise, HostingService::GitHub).unwrap();
+        assert_eq!(web.home_url(), "https://code.example.com/owner/project");
+        assert_eq!(HostedRepo::from_url(&GitUrl::parse_lenient("/srv/git/project.git").unwrap()), None);
+    }
+
+    #[test]
+    fn test_web_urls() {
+        let commit = CommitHash::from_str("0b8ff0fafea0f9850980dccf6287ba24ecaeacaa").unwrap();
+        let github = hosted("https://github.com/owner/project.git");
+        let gitlab = hosted("git@gitlab.com:group/sub/project.git");
+        let bitbucket = hosted("git@bitbucket.org:team/project.git");
+
+        assert_eq!(github.commit_url(&commit), format!("https://github.com/owner/project/commit/{}", commit));
+        assert_eq!(gitlab.commit_url(&commit), format!("https://gitlab.com/group/sub/project/-/commit/{}", commit));
+        assert_eq!(bitbucket.commit_url(&commit), format!("https://bitbucket.org/team/project/commits/{}", commit));
+
+        assert_eq!(
+            github.file_url("feature/x", "docs/read me#1.md"),
+            "https://github.com/owner/project/blob/feature/x/docs/read%20me%231.md"
+        );
+        assert_eq!(gitlab.file_url("v1.0", "src/lib.rs"), "https://gitlab.com/group/sub/project/-/blob/v1.0/src/lib.rs");
+        assert_eq!(bitbucket.file_url("main", "/a.txt"), "https://bitbucket.org/team/project/src/main/a.txt");
+
+        assert_eq!(github.compare_url("main", "topic"), "https://github.com/owner/project/compare/main...topic");
+        assert_eq!(gitlab.compare_url("main", "topic"), "https://gitlab.com/group/sub/project/-/compare/main...topic");
+        assert_eq!(
+            bitbucket.compare_url("main", "topic"),
+            "https://bitbucket.org/team/project/branches/compare/topic%0Dmain"
+        );
+
+        assert_eq!(
+            github.new_pull_request_url("main", "topic"),
+            "https://github.com/owner/project/compare/main...topic?expand=1"
+        );
+        assert_eq!(
+            gitlab.new_pull_request_url("main", "topic"),
+            "https://gitlab.com/group/sub/project/-/merge_requests/new\
+             ?merge_request%5Bsource_branch%5D=topic&merge_request%5Btarget_branch%5D=main"
+        );
+        assert_eq!(
+            bitbucket.new_pull_request_url("main", "topic"),
+            "https://bitbucket.org/team/project/pull-requests/new?source=topic&dest=main"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_parse_pull_requests() {
+        let gh = r#"[{"number":7,"title":"Add x","url":"https://github.com/o/p/pull/7",
+            "headRefName":"topic","baseRefName":"main","state":"MERGED"}]"#;
+        let fields = ["number", "title", "url", "headRefName", "baseRefName", "state"];
+        let prs = parse_pull_requests(gh, fields).unwrap();
+        assert_eq!(prs.len(), 1);
+        assert_eq!((prs[0].number, prs[0].head.as_str(), prs[0].state), (7, "topic", PullRequestState::Merged));
+
+        let glab = r#"[{"iid":3,"title":"Add y","web_url":"https://gitlab.com/g/p/-/merge_requests/3",
+            "source_branch":"topic","target_branch":"main","state":"opened"}]"#;
+        let fields = ["iid", "title", "web_url", "source_branch", "target_branch", "state"];
+        let prs = parse_pull_requests(glab, fields).unwrap();
+        assert_eq!((prs[0].number, prs[0].base.as_str(), prs[0].state), (3, "main", PullRequestState::Open));
+        assert_eq!(parse_pull_requests("[]", fields), Some(Vec::new()));
+        assert_eq!(parse_pull_requests("not json", fields), None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Git LFS support, wrapping the `git lfs` extension.
+//!
+//! Every operation here requires `git-lfs` to be installed alongside Git and
+//! fails with `GitError::LfsNotInstalled` when it is not; use
+//! `Repository::is_lfs_available` to check up front.
+
+use crate::error::GitError;
+use crate::models::LfsFile;
+use crate::repository::{failure_error, pathspec, run_git, Location, Repository};
+use crate::types::Result;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
+impl Repository {
+    /// Checks whether the `git lfs` extension is installed.
+    ///
+    /// Equivalent to `git lfs version`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if Git itself cannot be run (including `GitNotFound`).
+    pub fn is_lfs_available(&self) -> Result<bool> {
+        match execute_lfs(&self.location, ["version"]) {
+            Ok(_) => Ok(true),
+            Err(GitError::LfsNotInstalled) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Installs the LFS filters and hooks for this repository only, leaving the
+    /// global Git configuration untouched.
+    ///
+    /// Equivalent to `git lfs install --local`.
+    ///
+    /// # Errors
+    /// Returns `GitError::LfsNotInstalled` if `git-lfs` is missing.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn lfs_install(&self) -> Result<()> {
+        execute_lfs(&self.location, ["install", "--local"]).map(|_| ())
+    }
+
+    /// Starts tracking files matching `pattern` with LFS by adding a
+    /// `filter=lfs` rule to `.gitattributes`. The updated `.gitattributes` is
+    /// not staged.
+    ///
+    /// Equivalent to `git lfs track <pattern>`.
+    ///
+    /// # Arguments
+    /// * `pattern` - A gitattributes pattern, e.g. `"*.psd"`.
+    ///
+    /// # Errors
+    /// Returns `GitError::LfsNotInstalled` if `git-lfs` is missing.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn lfs_track(&self, pattern: &str) -> Result<()> {
+        execute_lfs(&self.location, ["track", "--", pattern]).map(|_| ())
+    }
+
+    /// Lists the files in HEAD that are stored in LFS.
+    ///
+    /// Equivalent to `git lfs ls-files --long`.
+    ///
+    /// # Returns
+    /// A `Vec<LfsFile>`; `downloaded` tells whether the content is checked out or
+    /// only the pointer file is present.
+    ///
+    /// # Errors
+    /// Returns `GitError::LfsNotInstalled` if `git-lfs` is missing.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn lfs_ls_files(&self) -> Result<Vec<LfsFile>> {
+        let output = execute_lfs(&self.location, ["ls-files", "--long"])?;
+        Ok(LfsFile::from_ls_files_long(&output))
+    }
+
+    /// Downloads the LFS content for the current checkout from the default remote
+    /// and replaces pointer files with it.
+    ///
+    /// Equivalent to `git lfs pull`, or `git lfs pull --include=<paths>` when
+    /// `paths` is not empty.
+    ///
+    /// # Arguments
+    /// * `paths` - Limit the download to these paths; empty for everything. Git LFS
+    ///   reads them as one comma-separated list without a way to escape commas,
+    ///   so a comma in a path matches any single character instead.
+    ///
+    /// # Errors
+    /// Returns `GitError::LfsNotInstalled` if `git-lfs` is missing.
+    /// Returns `GitError::PathEncodingError` if a path is not valid UTF-8.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn lfs_pull<P: AsRef<Path>>(&self, paths: &[P]) -> Result<()> {
+        if paths.is_empty() {
+            return execute_lfs(&self.location, ["pull"]).map(|_| ());
+        }
+        let mut include = Vec::with_capacity(paths.len());
+        for path in paths {
+            let spec = pathspec(path.as_ref())?;
+            let spec = spec.to_str().ok_or_else(|| GitError::PathEncodingError(path.as_ref().to_path_buf()))?;
+            include.push(spec.replace(',', "?"));
+        }
+        let include = format!("--include={}", include.join(","));
+        execute_lfs(&self.location, ["pull", include.as_str()]).map(|_| ())
+    }
+}
+
+/// Runs `git lfs <args>` and returns its stdout, mapping Git's "not a git
+/// command" failure to `GitError::LfsNotInstalled`.
+fn execute_lfs<I, S>(p: &Location, args: I) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut cmd_args: Vec<OsString> = vec!["lfs".into()];
+    cmd_args.extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+    let output = run_git(p, cmd_args, &[], None)?;
+    if output.status.success() {
+        return Ok(p.settings.decode.decode(&output.stdout)?.into_owned());
+    }
+    if String::from_utf8_lossy(&output.stderr).contains("'lfs' is not a git command") {
+        return Err(GitError::LfsNotInstalled);
+    }
+    Err(failure_error(&output))
+}
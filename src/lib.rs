@@ -4,20 +4,60 @@
 //! This library requires the `git` executable to be installed and accessible
 //! in the system's PATH where the Rust program is executed.
 //!
+//! The main types are re-exported at the crate root, and `prelude` adds the
+//! result models and option structs:
+//!
+//! ```no_run
+//! use GitPilot::prelude::*;
+//! use std::str::FromStr;
+//!
+//! # fn main() -> Result<()> {
+//! let repo = Repository::new("./my_project");
+//! let origin = Remote::from_str("origin")?;
+//! repo.fetch(&FetchOptions { remote: Some(origin), prune: true, ..Default::default() })?;
+//! let status: StatusResult = repo.status()?;
+//! println!("{} changed files", status.files.len());
+//! # Ok(())
+//! # }
+//! ```
+
+// The crate is published under its product name, `GitPilot`.
+#![allow(non_snake_case)]
 
 pub mod error;
 pub mod types;
 pub mod models;
+pub mod options;
+mod commands;
 pub mod repository;
+pub mod builder;
+pub mod lfs;
+pub mod batch;
+pub mod hosting;
+pub mod lock;
+pub mod maintenance;
+pub mod message;
+pub mod stream;
+pub mod versioning;
 
 // Feature-gated modules
 #[cfg(feature = "async")]
 pub mod async_git;
+#[cfg(feature = "async")]
+pub mod workspace;
+#[cfg(feature = "async")]
+pub mod auto_fetch;
+#[cfg(feature = "json")]
+pub mod report;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "notify")]
+pub mod watch;
 
 // Re-export key types
 pub use crate::error::GitError;
 pub use crate::repository::Repository;
-pub use crate::types::{BranchName, GitUrl, Result};
+pub use crate::types::{BranchName, CommitHash, GitUrl, HookKind, Remote, Result};
 
 // Conditional re-exports based on features
 #[cfg(feature = "async")]
@@ -28,9 +68,12 @@ pub mod prelude {
     //! Convenient import for common GitPilot types and traits.
     pub use crate::error::GitError;
     pub use crate::repository::Repository;
-    pub use crate::types::{BranchName, GitUrl, Result};
+    pub use crate::types::{BranchName, CommitHash, GitUrl, HookKind, Remote, Result};
     pub use crate::models::*;
+    pub use crate::options::*;
 
     #[cfg(feature = "async")]
     pub use crate::async_git::AsyncRepository;
+    #[cfg(feature = "async")]
+    pub use crate::workspace::RepoSet;
 }
\ No newline at end of file
@@ -0,0 +1,196 @@
+//! An advisory lock for serializing GitPilot processes that modify the same
+//! working copy.
+//!
+//! Git protects each ref and the index with its own short-lived lock files,
+//! but nothing stops two tools from interleaving multi-step operations, such as
+//! one process committing while another rebases. Processes that take the
+//! repository lock before such operations run them one at a time. The lock is
+//! advisory: Git itself and tools that do not take it are unaffected.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # use std::time::Duration;
+//! # fn main() -> GitPilot::Result<()> {
+//! let repo = Repository::new("./my_project");
+//! let _guard = repo.lock_wait(Duration::from_secs(30))?;
+//! repo.stage_and_commit_all_modified("Update generated files")?;
+//! repo.push()?;
+//! // The lock is released when `_guard` goes out of scope.
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::GitError;
+use crate::repository::Repository;
+use crate::types::Result;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The name of the lock file inside the Git directory.
+const LOCK_FILE: &str = "GitPilot.lock";
+
+/// How long `lock_wait` sleeps between attempts.
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Holds the repository lock taken by `Repository::lock`; dropping it releases
+/// the lock.
+#[derive(Debug)]
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct RepoLockGuard {
+    path: PathBuf,
+    contents: String,
+}
+
+impl RepoLockGuard {
+    /// Returns the lock file's path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Releases the lock, reporting failures that dropping the guard ignores.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the lock file cannot be removed.
+    pub fn release(mut self) -> Result<()> {
+        self.remove()
+    }
+
+    fn remove(&mut self) -> Result<()> {
+        // Only remove the file if it is still ours, not a lock another process
+        // took over after deeming ours stale.
+        if fs::read_to_string(&self.path).is_ok_and(|contents| contents == self.contents) {
+            fs::remove_file(&self.path)?;
+        }
+        self.contents.clear();
+        Ok(())
+    }
+}
+
+impl Drop for RepoLockGuard {
+    fn drop(&mut self) {
+        if !self.contents.is_empty() {
+            let _ = self.remove();
+        }
+    }
+}
+
+impl Repository {
+    /// Takes the repository lock, failing immediately if another process holds it.
+    ///
+    /// The lock is a `GitPilot.lock` file in the Git directory recording the
+    /// holder's process id. A lock left behind by a process that has exited is
+    /// stale and taken over. Exited holders are only detected on Linux; elsewhere
+    /// a stale lock file has to be removed by hand.
+    ///
+    /// # Returns
+    /// A guard that releases the lock when dropped.
+    ///
+    /// # Errors
+    /// Returns `GitError::RepositoryLocked` if another process holds the lock.
+    /// Returns `GitError::Io` if the lock file cannot be created.
+    /// Returns `GitError` if this is not a repository (including `GitNotFound`).
+    pub fn lock(&self) -> Result<RepoLockGuard> {
+        try_lock(&self.git_dir()?.join(LOCK_FILE))
+    }
+
+    /// Takes the repository lock, waiting up to `timeout` for another process
+    /// to release it.
+    ///
+    /// # Errors
+    /// Returns `GitError::RepositoryLocked` if the lock is still held after `timeout`.
+    /// Returns `GitError` as for `lock`.
+    pub fn lock_wait(&self, timeout: Duration) -> Result<RepoLockGuard> {
+        let deadline = SystemTime::now() + timeout;
+        loop {
+            match self.lock() {
+                Err(GitError::RepositoryLocked { .. }) if SystemTime::now() < deadline => {
+                    thread::sleep(RETRY_INTERVAL)
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Creates the lock file, taking it over first if it is stale.
+fn try_lock(path: &Path) -> Result<RepoLockGuard> {
+    let created = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let contents = format!("{}\n{}\n", process::id(), created);
+    match create(path, &contents) {
+        Err(GitError::Io(e)) if e.kind() == ErrorKind::AlreadyExists => take_over(path, contents),
+        result => result.map(|()| RepoLockGuard { path: path.to_path_buf(), contents }),
+    }
+}
+
+/// Creates the lock file holding `contents`, failing if it already exists.
+fn create(path: &Path, contents: &str) -> Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+/// Replaces the lock file with one holding `contents` if its holder has exited.
+///
+/// Takeovers run one at a time under an OS lock on a companion file. Since only
+/// a takeover replaces an existing lock file, the stale lock cannot change
+/// between the check and the rename that atomically replaces it.
+fn take_over(path: &Path, contents: String) -> Result<RepoLockGuard> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".takeover");
+    let takeover = OpenOptions::new().write(true).create(true).truncate(false).open(PathBuf::from(name))?;
+    takeover.lock()?;
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        // Released since the first attempt.
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return match create(path, &contents) {
+                Err(GitError::Io(e)) if e.kind() == ErrorKind::AlreadyExists => Err(locked(path)),
+                result => result.map(|()| RepoLockGuard { path: path.to_path_buf(), contents }),
+            };
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let pid = existing.lines().next().and_then(|pid| pid.parse().ok());
+    if !is_stale(pid) {
+        return Err(GitError::RepositoryLocked { path: path.to_path_buf(), pid });
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}", process::id()));
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, &contents)?;
+    if let Err(e) = fs::rename(&temp, path) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    Ok(RepoLockGuard { path: path.to_path_buf(), contents })
+}
+
+/// The error for a lock held by someone else, naming the holder if known.
+fn locked(path: &Path) -> GitError {
+    let pid = fs::read_to_string(path).ok().and_then(|contents| contents.lines().next()?.parse().ok());
+    GitError::RepositoryLocked { path: path.to_path_buf(), pid }
+}
+
+/// Checks whether a lock held by `pid` has been abandoned, which is only the
+/// case once the holder has exited.
+fn is_stale(pid: Option<u32>) -> bool {
+    match pid {
+        Some(pid) => !process_exists(pid),
+        // Unreadable or half-written: give the holder the benefit of the doubt.
+        None => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_exists(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_exists(_pid: u32) -> bool {
+    true
+}
@@ -0,0 +1,348 @@
+//! Housekeeping for long-lived repositories: garbage collection, pruning,
+//! repacking, commit-graphs and scheduled `git maintenance`, object statistics
+//! and integrity checks, and the caches that keep `status` fast on large worktrees.
+
+use crate::error::GitError;
+use crate::models::{FsckReport, LargeBlob, ObjectCountStats, RepoSizeReport};
+use crate::options::{FsckOptions, MaintenanceTask, RepackOptions};
+use crate::repository::{execute_git, execute_git_fn, execute_git_fn_with, failure_error, git_path, run_git, Repository};
+use crate::types::Result;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+impl Repository {
+    /// Cleans up unnecessary files and optimizes the repository.
+    ///
+    /// Equivalent to `git gc --quiet [--aggressive]`.
+    ///
+    /// # Arguments
+    /// * `aggressive` - Recompute deltas more thoroughly, at a much higher CPU cost.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn gc(&self, aggressive: bool) -> Result<()> {
+        let mut args = vec!["gc", "--quiet"];
+        if aggressive {
+            args.push("--aggressive");
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Deletes unreachable loose objects.
+    ///
+    /// Equivalent to `git prune [--expire=<n>.seconds.ago]`.
+    ///
+    /// # Arguments
+    /// * `expire` - Only delete objects older than this; `None` deletes all
+    ///   unreachable loose objects, which can race with concurrent writers.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn prune(&self, expire: Option<Duration>) -> Result<()> {
+        let mut args = vec!["prune".to_string()];
+        if let Some(expire) = expire {
+            args.push(format!("--expire={}.seconds.ago", expire.as_secs()));
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Packs loose objects and optionally consolidates existing packs.
+    ///
+    /// Equivalent to `git repack -q` plus the flags selected in `opts`.
+    ///
+    /// # Arguments
+    /// * `opts` - Options such as `all` and `delete_redundant` for a full repack.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn repack(&self, opts: &RepackOptions) -> Result<()> {
+        let mut args = vec!["repack".to_string(), "-q".to_string()];
+        if opts.all {
+            args.push("-a".to_string());
+        }
+        if opts.delete_redundant {
+            args.push("-d".to_string());
+        }
+        if opts.write_bitmap {
+            args.push("--write-bitmap-index".to_string());
+        }
+        if let Some(depth) = opts.depth {
+            args.push(format!("--depth={}", depth));
+        }
+        if let Some(window) = opts.window {
+            args.push(format!("--window={}", window));
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Writes a commit-graph file for every reachable commit, which speeds up
+    /// history walks such as `log`, `merge-base` and ahead/behind counts.
+    ///
+    /// Equivalent to `git commit-graph write --reachable [--changed-paths]`.
+    ///
+    /// # Arguments
+    /// * `changed_paths` - Also record which paths each commit changed, which
+    ///   speeds up history queries limited to a path at the cost of a larger file.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn write_commit_graph(&self, changed_paths: bool) -> Result<()> {
+        let mut args = vec!["commit-graph", "write", "--reachable", "--no-progress"];
+        if changed_paths {
+            args.push("--changed-paths");
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Runs maintenance tasks now, in the order given.
+    ///
+    /// Equivalent to `git maintenance run --quiet [--task=<task>...]`.
+    ///
+    /// # Arguments
+    /// * `tasks` - The tasks to run; empty for the tasks enabled in the
+    ///   `maintenance.<task>.enabled` settings, which default to `gc` alone.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn maintenance_run(&self, tasks: &[MaintenanceTask]) -> Result<()> {
+        let mut args = vec!["maintenance".to_string(), "run".to_string(), "--quiet".to_string()];
+        args.extend(tasks.iter().map(|task| format!("--task={}", task)));
+        execute_git(&self.location, args)
+    }
+
+    /// Registers the repository for background maintenance and schedules
+    /// `git maintenance run` hourly, daily and weekly with the platform's
+    /// scheduler (systemd timers or cron, launchd, or the Windows task scheduler).
+    ///
+    /// Equivalent to `git maintenance start`. Git adds the repository to
+    /// `maintenance.repo` in the global configuration, and switches it to the
+    /// `incremental` strategy with automatic `gc` turned off.
+    ///
+    /// # Errors
+    /// Returns `GitError` if no scheduler is available (including `GitNotFound`).
+    pub fn maintenance_start(&self) -> Result<()> {
+        execute_git(&self.location, ["maintenance", "start"])
+    }
+
+    /// Removes the background maintenance schedule.
+    ///
+    /// Equivalent to `git maintenance stop`. The schedule is shared by every
+    /// registered repository, so this stops maintaining all of them; to stop
+    /// only this one, run `git maintenance unregister` instead. The repository
+    /// stays registered for a later `maintenance_start`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn maintenance_stop(&self) -> Result<()> {
+        execute_git(&self.location, ["maintenance", "stop"])
+    }
+
+    /// Counts loose and packed objects and the disk space they use.
+    ///
+    /// Equivalent to `git count-objects -v`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`), or a `GitError::GitError`
+    /// if the output cannot be parsed.
+    pub fn count_objects(&self) -> Result<ObjectCountStats> {
+        execute_git_fn(&self.location, ["count-objects", "-v"], |output| {
+            ObjectCountStats::from_count_objects(output).ok_or_else(|| GitError::GitError {
+                stdout: output.to_string(),
+                stderr: "Failed to parse object counts".to_string(),
+            })
+        })
+    }
+
+    /// Measures how much disk space the repository's objects use, for quota
+    /// enforcement and reporting. The working tree is not included.
+    ///
+    /// Combines `git count-objects -v` with the sizes of the files in
+    /// `objects/pack` and in the LFS object store (`lfs/objects`). LFS does not
+    /// need to be installed; without it the LFS figures are zero.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if a directory cannot be read.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn repo_size(&self) -> Result<RepoSizeReport> {
+        let objects = self.count_objects()?;
+        let (_, pack_dir_size) = dir_usage(&git_path(&self.location, "objects/pack")?)?;
+        let (lfs_objects, lfs_size) = dir_usage(&git_path(&self.location, "lfs/objects")?)?;
+        Ok(RepoSizeReport {
+            objects,
+            pack_dir_size,
+            lfs_objects,
+            lfs_size,
+        })
+    }
+
+    /// Finds the largest blobs anywhere in history, with a path each appeared
+    /// under, e.g. to audit a repository before migrating it to LFS.
+    ///
+    /// Lists every object reachable from any ref with `git rev-list --objects --all`
+    /// and sizes them in a single `git cat-file --batch-check` process.
+    ///
+    /// # Arguments
+    /// * `threshold_bytes` - Ignore blobs smaller than this.
+    /// * `limit` - Return at most this many blobs.
+    ///
+    /// # Returns
+    /// A `Vec<LargeBlob>`, largest first.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn find_large_blobs(&self, threshold_bytes: u64, limit: usize) -> Result<Vec<LargeBlob>> {
+        let objects = execute_git_fn(&self.location, ["rev-list", "--objects", "--all"], |output| {
+            Ok(output.to_string())
+        })?;
+        let mut blobs = execute_git_fn_with(
+            &self.location,
+            ["cat-file", "--batch-check=%(objecttype) %(objectname) %(objectsize) %(rest)"],
+            Some(objects.as_bytes()),
+            &[0],
+            |output| Ok(LargeBlob::from_batch_check(output, threshold_bytes)),
+        )?;
+        blobs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        blobs.truncate(limit);
+        Ok(blobs)
+    }
+
+    /// Verifies the connectivity and validity of every object in the repository.
+    ///
+    /// Equivalent to `git fsck --full` plus the flags selected in `opts`. Problems
+    /// with the repository are reported in the `FsckReport`, not as errors.
+    ///
+    /// # Arguments
+    /// * `opts` - Options such as `unreachable` to list all unreachable objects.
+    ///
+    /// # Returns
+    /// A `FsckReport`; see `FsckReport::is_healthy`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if fsck could not run at all (including `GitNotFound`).
+    pub fn fsck(&self, opts: &FsckOptions) -> Result<FsckReport> {
+        let mut args = vec!["fsck", "--full"];
+        if opts.unreachable {
+            args.push("--unreachable");
+        }
+        if opts.no_dangling {
+            args.push("--no-dangling");
+        }
+        if opts.connectivity_only {
+            args.push("--connectivity-only");
+        }
+        if opts.strict {
+            args.push("--strict");
+        }
+        let output = run_git(&self.location, args, &[], None)?;
+        // fsck exits with a bit mask of the problem classes it found; 128 and
+        // above mean it failed to run.
+        match output.status.code() {
+            Some(code) if code < 128 => Ok(FsckReport::from_fsck(
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+                code,
+            )),
+            _ => Err(failure_error(&output)),
+        }
+    }
+
+    /// Makes Git use its built-in filesystem monitor, so `status` and `diff`
+    /// only look at files changed since the last command instead of scanning
+    /// the whole worktree.
+    ///
+    /// Equivalent to `git config core.fsmonitor true`. Git starts the
+    /// `fsmonitor--daemon` on the next command that needs it.
+    ///
+    /// # Errors
+    /// Returns `GitError::GitError` if this Git does not have a built-in monitor
+    /// for the platform, as on Linux or before Git 2.36 (including `GitNotFound`).
+    pub fn enable_fsmonitor(&self) -> Result<()> {
+        // `status` exits with 1 when no daemon is running yet; only supported
+        // platforms report on it.
+        let output = run_git(&self.location, ["fsmonitor--daemon", "status"], &[], None)?;
+        if !String::from_utf8_lossy(&output.stdout).contains("fsmonitor-daemon is") {
+            return Err(failure_error(&output));
+        }
+        execute_git(&self.location, ["config", "core.fsmonitor", "true"])
+    }
+
+    /// Stops Git from using a filesystem monitor.
+    ///
+    /// Equivalent to `git config --unset core.fsmonitor`, then `git fsmonitor--daemon
+    /// stop` where a daemon may be running. Does nothing if none was configured.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn disable_fsmonitor(&self) -> Result<()> {
+        // Exit code 5 means the key was not set.
+        execute_git_fn_with(&self.location, ["config", "--unset", "core.fsmonitor"], None, &[0, 5], |_| Ok(()))?;
+        // Fails when the platform has no daemon or none is running; either way
+        // nothing is left watching.
+        let _ = run_git(&self.location, ["fsmonitor--daemon", "stop"], &[], None)?;
+        Ok(())
+    }
+
+    /// Makes Git cache the untracked files of each directory in the index, so
+    /// `status` only rescans directories whose modification time changed.
+    ///
+    /// Equivalent to `git config core.untrackedCache true` followed by
+    /// `git update-index --untracked-cache`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn enable_untracked_cache(&self) -> Result<()> {
+        execute_git(&self.location, ["config", "core.untrackedCache", "true"])?;
+        execute_git(&self.location, ["update-index", "--untracked-cache"])
+    }
+
+    /// Removes the untracked cache from the index and stops Git from adding it back.
+    ///
+    /// Equivalent to `git config core.untrackedCache false` followed by
+    /// `git update-index --no-untracked-cache`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn disable_untracked_cache(&self) -> Result<()> {
+        execute_git(&self.location, ["config", "core.untrackedCache", "false"])?;
+        execute_git(&self.location, ["update-index", "--no-untracked-cache"])
+    }
+
+    /// Refreshes the file metadata recorded in the index, so the next `status`
+    /// does not re-read files that were touched but not changed, e.g. after a
+    /// checkout by another tool or a copy of the worktree.
+    ///
+    /// Equivalent to `git update-index -q --refresh`. Files that really changed
+    /// stay modified; refreshing only updates the cached metadata.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn update_index_refresh(&self) -> Result<()> {
+        execute_git(&self.location, ["update-index", "-q", "--refresh"])
+    }
+}
+
+/// Returns the number of files below `dir` and their total size, or zeros if
+/// `dir` does not exist.
+fn dir_usage(dir: &Path) -> Result<(u64, u64)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e.into()),
+    };
+    let (mut files, mut size) = (0, 0);
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            let (sub_files, sub_size) = dir_usage(&entry.path())?;
+            files += sub_files;
+            size += sub_size;
+        } else {
+            files += 1;
+            size += metadata.len();
+        }
+    }
+    Ok((files, size))
+}
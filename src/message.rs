@@ -0,0 +1,331 @@
+//! Building and validating commit messages before they are committed.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # use GitPilot::message::CommitMessage;
+//! # fn main() -> GitPilot::Result<()> {
+//! let repo = Repository::new("./my_project");
+//! let message = CommitMessage::new("fix(parser): handle empty input")
+//!     .body("An empty file used to panic instead of returning no records.")
+//!     .conventional(true)
+//!     .max_subject_length(72);
+//! repo.commit_message(&message)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::GitError;
+use crate::repository::{execute_git, execute_git_fn_with, Repository};
+use crate::types::Result;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// A commit message made of a subject line, an optional body and trailers
+/// such as `Signed-off-by: Jane Doe <jane@example.com>`.
+///
+/// The message renders in the layout Git expects: the subject, then the body
+/// and the trailer block, each separated by a blank line. Rules enabled with
+/// `conventional` and `max_subject_length` are checked by `validate`, which
+/// `Repository::commit_message` calls before committing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitMessage {
+    subject: String,
+    body: Option<String>,
+    trailers: Vec<(String, String)>,
+    sign_off: bool,
+    conventional: bool,
+    max_subject_length: Option<usize>,
+}
+
+impl CommitMessage {
+    /// Creates a message with the given subject line and no body or trailers.
+    pub fn new(subject: impl Into<String>) -> CommitMessage {
+        CommitMessage {
+            subject: subject.into(),
+            body: None,
+            trailers: Vec::new(),
+            sign_off: false,
+            conventional: false,
+            max_subject_length: None,
+        }
+    }
+
+    /// Sets the body, the free-form text after the subject.
+    pub fn body(mut self, body: impl Into<String>) -> CommitMessage {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Appends a trailer, e.g. `trailer("Reviewed-by", "Jane Doe <jane@example.com>")`.
+    pub fn trailer(mut self, key: impl Into<String>, value: impl Into<String>) -> CommitMessage {
+        self.trailers.push((key.into(), value.into()));
+        self
+    }
+
+    /// Appends a `Signed-off-by` trailer certifying the Developer Certificate of
+    /// Origin, e.g. `signed_off_by("Jane Doe <jane@example.com>")`.
+    pub fn signed_off_by(self, identity: impl Into<String>) -> CommitMessage {
+        self.trailer("Signed-off-by", identity)
+    }
+
+    /// Appends a `Co-authored-by` trailer crediting another author of the change.
+    pub fn co_authored_by(self, identity: impl Into<String>) -> CommitMessage {
+        self.trailer("Co-authored-by", identity)
+    }
+
+    /// Signs off the commit as the committer configured in the repository when
+    /// it is created by `Repository::commit_message` (`--signoff`).
+    ///
+    /// Unlike `signed_off_by`, the identity is only known at commit time, so the
+    /// trailer is not part of the rendered message.
+    pub fn sign_off(mut self, sign_off: bool) -> CommitMessage {
+        self.sign_off = sign_off;
+        self
+    }
+
+    /// Requires the subject to follow the Conventional Commits format,
+    /// `<type>[(<scope>)][!]: <description>`.
+    pub fn conventional(mut self, conventional: bool) -> CommitMessage {
+        self.conventional = conventional;
+        self
+    }
+
+    /// Limits the subject line to `max` characters.
+    pub fn max_subject_length(mut self, max: usize) -> CommitMessage {
+        self.max_subject_length = Some(max);
+        self
+    }
+
+    /// Returns the subject line.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the body, if any.
+    pub fn body_text(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Returns the trailers as key-value pairs, in the order they were added.
+    pub fn trailers(&self) -> &[(String, String)] {
+        &self.trailers
+    }
+
+    /// Checks the message against its rules.
+    ///
+    /// The subject must be a single non-empty line, and every trailer needs a
+    /// key of letters, digits and `-` and a single-line value. The Conventional
+    /// Commits format and the subject length are only checked when enabled.
+    ///
+    /// # Errors
+    /// Returns `GitError::InvalidCommitMessage` describing the first violation.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(GitError::InvalidCommitMessage(reason));
+        if self.subject.trim().is_empty() {
+            return invalid("the subject is empty".to_string());
+        }
+        if self.subject.contains('\n') {
+            return invalid("the subject spans several lines".to_string());
+        }
+        if let Some(max) = self.max_subject_length {
+            let length = self.subject.chars().count();
+            if length > max {
+                return invalid(format!("the subject is {} characters long, the limit is {}", length, max));
+            }
+        }
+        if self.conventional {
+            check_conventional_subject(&self.subject).map_err(GitError::InvalidCommitMessage)?;
+        }
+        for (key, value) in &self.trailers {
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return invalid(format!("the trailer key {:?} is not a single word", key));
+            }
+            if value.trim().is_empty() || value.contains('\n') {
+                return invalid(format!("the {} trailer needs a single-line value", key));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for CommitMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.subject)?;
+        if let Some(body) = self.body.as_deref().map(str::trim_end).filter(|body| !body.is_empty()) {
+            write!(f, "\n\n{}", body)?;
+        }
+        if !self.trailers.is_empty() {
+            writeln!(f)?;
+            for (key, value) in &self.trailers {
+                write!(f, "\n{}: {}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks `<type>[(<scope>)][!]: <description>`, returning why it does not match.
+fn check_conventional_subject(subject: &str) -> std::result::Result<(), String> {
+    let not_conventional = || format!("the subject {:?} is not a conventional commit header", subject);
+    let (header, description) = subject.split_once(": ").ok_or_else(not_conventional)?;
+    if description.trim().is_empty() {
+        return Err("the conventional commit description is empty".to_string());
+    }
+    let header = header.strip_suffix('!').unwrap_or(header);
+    let commit_type = match header.split_once('(') {
+        Some((commit_type, scope)) => {
+            let scope = scope.strip_suffix(')').ok_or_else(not_conventional)?;
+            if scope.is_empty() || scope.contains(['(', ')']) {
+                return Err(not_conventional());
+            }
+            commit_type
+        }
+        None => header,
+    };
+    if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(not_conventional());
+    }
+    Ok(())
+}
+
+impl Repository {
+    /// Validates a message and commits the staged changes with it.
+    ///
+    /// Equivalent to `git commit --cleanup=verbatim -F -`, with the rendered
+    /// message on stdin, so it is recorded exactly as built.
+    ///
+    /// # Arguments
+    /// * `message` - The message to commit with.
+    ///
+    /// # Errors
+    /// Returns `GitError::InvalidCommitMessage` if the message breaks its rules,
+    /// in which case nothing is committed.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn commit_message(&self, message: &CommitMessage) -> Result<()> {
+        message.validate()?;
+        let rendered = message.to_string();
+        let mut args = vec!["commit", "--cleanup=verbatim", "-F", "-"];
+        if message.sign_off {
+            args.push("--signoff");
+        }
+        execute_git_fn_with(&self.location, args, Some(rendered.as_bytes()), &[0], |_| Ok(()))
+    }
+
+    /// Reads the commit message template offered to authors as a starting point.
+    ///
+    /// The template is the file named by the `commit.template` configuration,
+    /// resolved as Git does (`~` is expanded, relative paths start at the
+    /// repository root). Without that setting, a `.gitmessage` file at the
+    /// repository root is used, a common convention for shared templates.
+    ///
+    /// # Returns
+    /// The template's contents, or `None` if no template is configured or present.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the configured template cannot be read.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn commit_template(&self) -> Result<Option<String>> {
+        // Exit code 1 means the key is not set.
+        let configured = execute_git_fn_with(
+            &self.location,
+            ["config", "--get", "--path", "commit.template"],
+            None,
+            &[0, 1],
+            |output| Ok(Some(output.trim_end_matches('\n')).filter(|path| !path.is_empty()).map(PathBuf::from)),
+        )?;
+        if let Some(path) = configured {
+            return Ok(Some(fs::read_to_string(self.location.join(path))?));
+        }
+        match fs::read_to_string(self.location.join(".gitmessage")) {
+            Ok(template) => Ok(Some(template)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Commits the staged changes with a message prepared in a file, without
+    /// opening an editor.
+    ///
+    /// Equivalent to `git commit --cleanup=strip -F <path>`: as when committing
+    /// from the editor, comment lines starting with `#` and surrounding blank
+    /// lines are removed, so a filled-in template can be committed as is.
+    ///
+    /// # Arguments
+    /// * `path` - The file holding the message, relative to the repository root
+    ///   or absolute.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the file cannot be read or the message is empty
+    /// after cleanup (including `GitNotFound`).
+    pub fn commit_from_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = self.location.join(path);
+        execute_git(&self.location, ["commit".as_ref(), "--cleanup=strip".as_ref(), "-F".as_ref(), path.as_os_str()])
+    }
+
+    /// Adds trailers to a commit message, following Git's rules for where the
+    /// trailer block goes and how it is formatted.
+    ///
+    /// Equivalent to `git interpret-trailers --trailer <key>=<value>...` with
+    /// `message` on stdin. The `trailer.*` configuration of the repository
+    /// applies, e.g. `trailer.ifExists` decides whether a duplicate is added.
+    ///
+    /// # Arguments
+    /// * `message` - The commit message, which may already end in trailers.
+    /// * `trailers` - The trailers to add, as key-value pairs.
+    ///
+    /// # Returns
+    /// The message with the trailers added.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn interpret_trailers(&self, message: &str, trailers: &[(&str, &str)]) -> Result<String> {
+        let mut args = vec!["interpret-trailers".to_string()];
+        for (key, value) in trailers {
+            args.push("--trailer".to_string());
+            args.push(format!("{}={}", key, value));
+        }
+        execute_git_fn_with(&self.location, args, Some(message.as_bytes()), &[0], |stdout| {
+            Ok(stdout.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_message() {
+        assert_eq!(CommitMessage::new("Fix typo").to_string(), "Fix typo");
+        let message = CommitMessage::new("Fix typo")
+            .body("Details.\n")
+            .trailer("Signed-off-by", "A U Thor <author@example.com>")
+            .trailer("Refs", "#12");
+        assert_eq!(
+            message.to_string(),
+            "Fix typo\n\nDetails.\n\nSigned-off-by: A U Thor <author@example.com>\nRefs: #12"
+        );
+        assert_eq!(CommitMessage::new("Fix typo").trailer("Refs", "#12").to_string(), "Fix typo\n\nRefs: #12");
+    }
+
+    #[test]
+    fn test_validate_message() {
+        assert!(CommitMessage::new("Fix typo").validate().is_ok());
+        assert!(CommitMessage::new("  ").validate().is_err());
+        assert!(CommitMessage::new("one\ntwo").validate().is_err());
+        assert!(CommitMessage::new("Fix typo").max_subject_length(8).validate().is_ok());
+        assert!(CommitMessage::new("Fix typos").max_subject_length(8).validate().is_err());
+        assert!(CommitMessage::new("Fix").trailer("Signed off by", "me").validate().is_err());
+        assert!(CommitMessage::new("Fix").trailer("Refs", "").validate().is_err());
+
+        for valid in ["feat: add x", "fix(parser): y", "feat(api)!: drop v1", "refactor!: z"] {
+            assert!(CommitMessage::new(valid).conventional(true).validate().is_ok(), "{}", valid);
+        }
+        for invalid in ["Add x", "feat:add x", "feat: ", "feat(): x", "(api): x", "feat(api: x", "my feat: x"] {
+            assert!(CommitMessage::new(invalid).conventional(true).validate().is_err(), "{}", invalid);
+        }
+    }
+}
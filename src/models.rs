@@ -2,12 +2,13 @@
 
 // Updated imports to include specific types
 use crate::types::{BranchName, CommitHash, GitUrl, Remote, Stash, Tag}; // Added specific types
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr; // Needed for parsing within models
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Represents a Git commit.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Commit {
     /// The commit hash. (Now CommitHash)
     pub hash: CommitHash,
@@ -17,73 +18,280 @@ pub struct Commit {
     pub author_name: String,
     /// The commit author's email.
     pub author_email: String,
-    /// The commit timestamp (seconds since Unix epoch).
+    /// The author timestamp (seconds since Unix epoch), i.e. when the change was written.
     pub timestamp: u64,
-    /// The commit message.
+    /// The name of the committer (who applied the commit, e.g. during a rebase or `am`).
+    pub committer_name: String,
+    /// The committer's email.
+    pub committer_email: String,
+    /// The committer timestamp (seconds since Unix epoch), i.e. when the commit was created.
+    pub committer_timestamp: u64,
+    /// The subject (first paragraph) of the commit message.
+    pub subject: String,
+    /// The body of the commit message, without the subject. Empty if there is none.
+    pub body: String,
+    /// The full commit message (subject and body).
     pub message: String,
     /// Parent commit hashes. (Now Vec<CommitHash>)
     pub parents: Vec<CommitHash>,
+    /// The GPG signature verification result, if it was requested.
+    ///
+    /// Only `Repository::get_commit` verifies signatures; batched queries leave this
+    /// as `None` to avoid invoking GPG once per commit.
+    pub signature: Option<SignatureStatus>,
 }
 
 impl Commit {
-    /// Parses a commit from the output of `git show --format=...`.
-    pub(crate) fn from_show_format(output: &str) -> Option<Commit> {
-        let mut hash_str = None;
-        let mut short_hash_str = None;
-        let mut author_name = String::new();
-        let mut author_email = String::new();
-        let mut timestamp = 0;
-        let mut message = String::new();
-        let mut parent_hashes_str = String::new();
+    /// Parses a single commit record produced by `git log --format=<LOG_RECORD_FORMAT>`.
+    ///
+    /// The record must already have its leading `\x1e` marker removed. Returns the
+    /// commit together with whatever follows the header fields (e.g. `--numstat` output).
+    pub(crate) fn from_log_record(record: &str) -> Option<(Commit, &str)> {
+        let mut fields = record.splitn(LOG_RECORD_FIELDS + 1, '\0');
 
-        for line in output.lines() {
-            if hash_str.is_none() && !line.is_empty() {
-                hash_str = Some(line.to_string());
-            } else if line.starts_with("shortcommit ") {
-                short_hash_str = Some(line.trim_start_matches("shortcommit ").to_string());
-            } else if line.starts_with("author_name ") {
-                author_name = line.trim_start_matches("author_name ").to_string();
-            } else if line.starts_with("author_email ") {
-                author_email = line.trim_start_matches("author_email ").to_string();
-            } else if line.starts_with("timestamp ") {
-                timestamp = line.trim_start_matches("timestamp ").parse::<u64>().ok()?;
-            } else if !line.starts_with("message ") && parent_hashes_str.is_empty() && hash_str.is_some() && short_hash_str.is_some() {
-                parent_hashes_str = line.to_string();
-            } else if line.starts_with("message ") {
-                message = line.trim_start_matches("message ").to_string();
-            }
-        }
-
-        // --- FIX START ---
-        // Add '&' to pass a reference (&str) to from_str
-        let hash = CommitHash::from_str(&hash_str?).ok()?;
-        let short_hash = CommitHash::from_str(&short_hash_str?).ok()?;
-        // --- FIX END ---
-
-        let parents = parent_hashes_str
+        let hash = CommitHash::from_str(fields.next()?).ok()?;
+        let short_hash = CommitHash::from_str(fields.next()?).ok()?;
+        let author_name = fields.next()?.to_string();
+        let author_email = fields.next()?.to_string();
+        let timestamp = fields.next()?.parse::<u64>().ok()?;
+        let committer_name = fields.next()?.to_string();
+        let committer_email = fields.next()?.to_string();
+        let committer_timestamp = fields.next()?.parse::<u64>().ok()?;
+        let parents = fields
+            .next()?
             .split_whitespace()
-            .map(CommitHash::from_str) // from_str expects &str, split_whitespace yields &str - OK
+            .map(CommitHash::from_str)
             .collect::<std::result::Result<Vec<_>, _>>()
             .ok()?;
+        let subject = fields.next()?.to_string();
+        let body = fields.next()?.trim_end().to_string();
+        // The raw message, unlike `%s`, keeps a subject wrapped over several lines.
+        let message = fields.next()?.trim_end().to_string();
+        let rest = fields.next().unwrap_or("");
 
-        Some(Commit {
-            hash,
-            short_hash,
-            author_name,
-            author_email,
-            timestamp,
-            message,
-            parents,
-        })
+        Some((
+            Commit {
+                hash,
+                short_hash,
+                author_name,
+                author_email,
+                timestamp,
+                committer_name,
+                committer_email,
+                committer_timestamp,
+                subject,
+                body,
+                message,
+                parents,
+                signature: None,
+            },
+            rest,
+        ))
     }
 
-    // date() method remains the same
+    /// Returns the author date as a `SystemTime`.
     pub fn date(&self) -> SystemTime {
         UNIX_EPOCH + std::time::Duration::from_secs(self.timestamp)
     }
+
+    /// Returns the committer date as a `SystemTime`.
+    pub fn committer_date(&self) -> SystemTime {
+        UNIX_EPOCH + std::time::Duration::from_secs(self.committer_timestamp)
+    }
+
+    /// Whether the commit was applied by someone other than its author.
+    pub fn is_committed_by_other(&self) -> bool {
+        self.author_name != self.committer_name || self.author_email != self.committer_email
+    }
+}
+
+/// Represents the result of verifying a commit's GPG signature (`%G?`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SignatureStatus {
+    /// A good (valid) signature (`G`).
+    Good,
+    /// A bad signature (`B`).
+    Bad,
+    /// A good signature with unknown validity (`U`).
+    GoodUnknownValidity,
+    /// A good signature that has expired (`X`).
+    ExpiredSignature,
+    /// A good signature made by an expired key (`Y`).
+    ExpiredKey,
+    /// A good signature made by a revoked key (`R`).
+    RevokedKey,
+    /// The signature cannot be checked, e.g. the key is missing (`E`).
+    CannotCheck,
+    /// The commit is not signed (`N`).
+    Unsigned,
+}
+
+impl SignatureStatus {
+    /// Parses the single-letter code produced by the `%G?` format placeholder.
+    pub(crate) fn from_code(code: &str) -> Option<SignatureStatus> {
+        match code.trim() {
+            "G" => Some(SignatureStatus::Good),
+            "B" => Some(SignatureStatus::Bad),
+            "U" => Some(SignatureStatus::GoodUnknownValidity),
+            "X" => Some(SignatureStatus::ExpiredSignature),
+            "Y" => Some(SignatureStatus::ExpiredKey),
+            "R" => Some(SignatureStatus::RevokedKey),
+            "E" => Some(SignatureStatus::CannotCheck),
+            "N" => Some(SignatureStatus::Unsigned),
+            _ => None,
+        }
+    }
+
+    /// Whether the signature was made with a valid, trusted-or-unknown key.
+    pub fn is_good(&self) -> bool {
+        matches!(self, SignatureStatus::Good | SignatureStatus::GoodUnknownValidity)
+    }
+}
+
+/// `git log` format producing one `\x1e`-prefixed, NUL-delimited record per commit.
+/// Parsed by `Commit::from_log_record`.
+pub(crate) const LOG_RECORD_FORMAT: &str =
+    "%x1e%H%x00%h%x00%an%x00%ae%x00%at%x00%cn%x00%ce%x00%ct%x00%P%x00%s%x00%b%x00%B%x00";
+
+/// Number of NUL-terminated fields in `LOG_RECORD_FORMAT`.
+const LOG_RECORD_FIELDS: usize = 12;
+
+/// Placeholder appended to `LOG_RECORD_FORMAT` to request signature verification.
+pub(crate) const SIGNATURE_FORMAT: &str = "%G?%x00";
+
+/// Represents the line counts for a single file, as reported by `git diff --numstat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileStat {
+    /// The path of the file (the new path for renames).
+    pub path: PathBuf,
+    /// The previous path, if the file was renamed or copied.
+    pub old_path: Option<PathBuf>,
+    /// Lines added, or `None` for binary files.
+    pub added: Option<usize>,
+    /// Lines removed, or `None` for binary files.
+    pub removed: Option<usize>,
+}
+
+impl FileStat {
+    /// Whether git reported the file as binary (`-` counts).
+    pub fn is_binary(&self) -> bool {
+        self.added.is_none() && self.removed.is_none()
+    }
+
+    /// Parses the output of `--numstat -z`.
+    ///
+    /// Each entry is `<added>\t<removed>\t<path>\0`, or for renames and copies
+    /// `<added>\t<removed>\t\0<old_path>\0<new_path>\0`.
+    pub(crate) fn from_numstat_z(output: &str) -> Vec<FileStat> {
+        let mut stats = Vec::new();
+        let mut tokens = output.split('\0');
+
+        while let Some(token) = tokens.next() {
+            if token.is_empty() {
+                continue;
+            }
+            let mut parts = token.splitn(3, '\t');
+            let (added, removed, path) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(a), Some(r), Some(p)) => (a, r, p),
+                _ => continue,
+            };
+
+            let (path, old_path) = if path.is_empty() {
+                match (tokens.next(), tokens.next()) {
+                    (Some(old), Some(new)) => (PathBuf::from(new), Some(PathBuf::from(old))),
+                    _ => break,
+                }
+            } else {
+                (PathBuf::from(path), None)
+            };
+
+            stats.push(FileStat {
+                path,
+                old_path,
+                added: added.parse().ok(),
+                removed: removed.parse().ok(),
+            });
+        }
+        stats
+    }
+}
+
+/// Represents per-file line counts and totals, as reported by `git diff --numstat --shortstat`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffStats {
+    /// Per-file line counts.
+    pub files: Vec<FileStat>,
+    /// Number of files changed (including binary files).
+    pub files_changed: usize,
+    /// Total lines added.
+    pub insertions: usize,
+    /// Total lines removed.
+    pub deletions: usize,
 }
-/// Represents a file status from `git status`.
+
+impl DiffStats {
+    /// Parses the output of `git diff -z --numstat --shortstat`.
+    ///
+    /// The NUL-terminated numstat entries are followed by a single shortstat line,
+    /// e.g. ` 2 files changed, 3 insertions(+), 1 deletion(-)`.
+    pub(crate) fn from_numstat_shortstat_z(output: &str) -> DiffStats {
+        let (numstat, shortstat) = match output.rfind('\0') {
+            Some(idx) => (&output[..=idx], &output[idx + 1..]),
+            None => ("", output),
+        };
+
+        let mut stats = DiffStats {
+            files: FileStat::from_numstat_z(numstat),
+            ..Default::default()
+        };
+
+        for part in shortstat.trim().split(',') {
+            let mut words = part.split_whitespace();
+            let count = match words.next().and_then(|n| n.parse::<usize>().ok()) {
+                Some(count) => count,
+                None => continue,
+            };
+            match words.next() {
+                Some(w) if w.starts_with("file") => stats.files_changed = count,
+                Some(w) if w.starts_with("insertion") => stats.insertions = count,
+                Some(w) if w.starts_with("deletion") => stats.deletions = count,
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Represents a commit together with the per-file line counts it introduced.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitWithStats {
+    /// The commit itself.
+    pub commit: Commit,
+    /// Per-file line counts relative to the first parent.
+    pub files: Vec<FileStat>,
+}
+
+impl CommitWithStats {
+    /// Total lines added across all non-binary files.
+    pub fn added_lines(&self) -> usize {
+        self.files.iter().filter_map(|f| f.added).sum()
+    }
+
+    /// Total lines removed across all non-binary files.
+    pub fn removed_lines(&self) -> usize {
+        self.files.iter().filter_map(|f| f.removed).sum()
+    }
+}
+/// Represents the combined status of a file from `git status`.
+///
+/// This collapses the index and worktree sides into a single value; use
+/// `StatusEntry::index_status` and `StatusEntry::worktree_status` for the full picture.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileStatus {
     Unmodified,
     Modified,
@@ -98,38 +306,195 @@ pub enum FileStatus {
 }
 
 impl FileStatus {
-    /// Parses a file status from a git status porcelain v1/v2 XY code.
-    pub(crate) fn from_porcelain_code(index: char, worktree: char) -> FileStatus {
-        // Based on git-status(1) man page documentation for --porcelain=v1
+    /// Combines the index and worktree sides of a status entry into a single status.
+    ///
+    /// Conflicts take precedence, then staged changes, then worktree changes.
+    pub(crate) fn from_codes(index: StatusCode, worktree: StatusCode) -> FileStatus {
+        use StatusCode::*;
         match (index, worktree) {
-            (' ', 'M') => FileStatus::Modified,         // WT modified
-            ('M', _)   => FileStatus::Added,            // Index modified (staged)
-            ('A', _)   => FileStatus::Added,            // Index added (staged)
-            ('D', _)   => FileStatus::DeletedStaged,    // Index deleted (staged)
-            ('R', _)   => FileStatus::Renamed,          // Index renamed (staged)
-            ('C', _)   => FileStatus::Copied,           // Index copied (staged)
-            ('U', _)   => FileStatus::UpdatedButUnmerged, // Unmerged
-            (_,   'D') => FileStatus::Deleted,          // WT deleted
-            // Note: (' ', ' ') should be unmodified, handled below.
-            // ('T', _) => FileStatus::TypeChanged, // Type Change (Staged) - Add if needed
-            // (_, 'T') => FileStatus::TypeChanged, // Type Change (WT) - Add if needed
-            ('?', '?') => FileStatus::Untracked,
-            ('!', '!') => FileStatus::Ignored,
-            _          => FileStatus::Unmodified, // Includes (' ', ' ')
+            // Unmerged entries: any side 'U', or both sides added/deleted (AA, DD)
+            (Unmerged, _) | (_, Unmerged) | (Added, Added) | (Deleted, Deleted) => {
+                FileStatus::UpdatedButUnmerged
+            }
+            (Untracked, _) => FileStatus::Untracked,
+            (Ignored, _) => FileStatus::Ignored,
+            (Added, _) => FileStatus::Added,
+            (Deleted, _) => FileStatus::DeletedStaged,
+            (Renamed, _) => FileStatus::Renamed,
+            (Copied, _) => FileStatus::Copied,
+            (_, Deleted) => FileStatus::Deleted,
+            (Modified | TypeChanged, _) | (_, Modified | TypeChanged) => FileStatus::Modified,
+            _ => FileStatus::Unmodified,
         }
     }
 }
 
+/// Represents one side (index or worktree) of a porcelain `XY` status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatusCode {
+    /// No change on this side (`.` in porcelain v2, ` ` in v1).
+    Unmodified,
+    /// Contents modified (`M`).
+    Modified,
+    /// File type changed, e.g. regular file to symlink (`T`).
+    TypeChanged,
+    /// Newly added (`A`).
+    Added,
+    /// Deleted (`D`).
+    Deleted,
+    /// Renamed (`R`).
+    Renamed,
+    /// Copied (`C`).
+    Copied,
+    /// Updated but unmerged (`U`).
+    Unmerged,
+    /// Not tracked (`?`).
+    Untracked,
+    /// Ignored (`!`).
+    Ignored,
+}
+
+impl StatusCode {
+    /// Parses one character of a porcelain v1/v2 `XY` status code.
+    pub(crate) fn from_porcelain_char(c: char) -> StatusCode {
+        match c {
+            'M' => StatusCode::Modified,
+            'T' => StatusCode::TypeChanged,
+            'A' => StatusCode::Added,
+            'D' => StatusCode::Deleted,
+            'R' => StatusCode::Renamed,
+            'C' => StatusCode::Copied,
+            'U' => StatusCode::Unmerged,
+            '?' => StatusCode::Untracked,
+            '!' => StatusCode::Ignored,
+            _ => StatusCode::Unmodified, // '.' (v2) or ' ' (v1)
+        }
+    }
+
+    /// Whether this side records a change to a tracked file.
+    pub fn is_changed(&self) -> bool {
+        !matches!(self, StatusCode::Unmodified | StatusCode::Untracked | StatusCode::Ignored)
+    }
+}
+
+/// The kind of entry a path holds, from the octal file mode Git reports for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntryKind {
+    /// A regular file (`100644`).
+    Regular,
+    /// A regular file with the executable bit set (`100755`).
+    Executable,
+    /// A symbolic link (`120000`); its content is the link target.
+    Symlink,
+    /// A submodule (`160000`), recorded as a commit of another repository.
+    Submodule,
+}
+
+impl EntryKind {
+    /// Parses a file mode as Git prints it, e.g. `100644`.
+    ///
+    /// Returns `None` for `000000`, which Git uses for a side where the path does
+    /// not exist, and for modes that are not one of the kinds above.
+    pub fn from_mode(mode: &str) -> Option<EntryKind> {
+        let mode = u32::from_str_radix(mode, 8).ok()?;
+        match mode & 0o170000 {
+            0o100000 if mode & 0o111 != 0 => Some(EntryKind::Executable),
+            0o100000 => Some(EntryKind::Regular),
+            0o120000 => Some(EntryKind::Symlink),
+            0o160000 => Some(EntryKind::Submodule),
+            _ => None,
+        }
+    }
+}
+
+/// What changed in a submodule listed by `git status`, from the `S<c><m><u>`
+/// field of porcelain v2 output. This is not named `SubmoduleState` because that
+/// name is taken by the state reported by `git submodule status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubmoduleChanges {
+    /// The submodule's checked-out commit differs from the one recorded.
+    pub commit_changed: bool,
+    /// The submodule has modified tracked files.
+    pub has_modifications: bool,
+    /// The submodule has untracked files.
+    pub has_untracked: bool,
+}
+
+impl SubmoduleChanges {
+    /// Parses the submodule field of a porcelain v2 entry, e.g. `SC.U`.
+    ///
+    /// Returns `None` for `N...`, the field of entries that are not submodules.
+    pub(crate) fn from_porcelain_v2(field: &str) -> Option<SubmoduleChanges> {
+        let flags = field.strip_prefix('S')?.as_bytes();
+        Some(SubmoduleChanges {
+            commit_changed: flags.first() == Some(&b'C'),
+            has_modifications: flags.get(1) == Some(&b'M'),
+            has_untracked: flags.get(2) == Some(&b'U'),
+        })
+    }
+}
+
 /// Represents a file in the repository with its status.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatusEntry {
     pub path: PathBuf,
-    pub status: FileStatus,
+    /// The status of the file in the index, i.e. staged changes.
+    pub index_status: StatusCode,
+    /// The status of the file in the working tree, i.e. unstaged changes.
+    pub worktree_status: StatusCode,
+    /// The path before a staged rename or copy.
     pub original_path: Option<PathBuf>,
+    /// The rename or copy similarity score (0-100) for entries with an `original_path`.
+    pub similarity: Option<u8>,
+    /// The kind of entry in HEAD; `None` if the path is not in HEAD. For unmerged
+    /// entries this is our side of the conflict.
+    pub head_kind: Option<EntryKind>,
+    /// The kind of entry staged in the index; `None` if the path is not in the
+    /// index or is unmerged.
+    pub index_kind: Option<EntryKind>,
+    /// The kind of entry in the working tree; `None` if the path was deleted.
+    ///
+    /// Git reports no modes for untracked files, so all three kinds are `None` for them.
+    pub worktree_kind: Option<EntryKind>,
+    /// The state of the submodule, for entries that are submodules.
+    pub submodule: Option<SubmoduleChanges>,
+}
+
+impl StatusEntry {
+    /// Returns the newest known kind of the entry: the working tree's, else the
+    /// index's, else HEAD's.
+    pub fn kind(&self) -> Option<EntryKind> {
+        self.worktree_kind.or(self.index_kind).or(self.head_kind)
+    }
+
+    /// Returns the combined status, collapsing the index and worktree sides.
+    pub fn status(&self) -> FileStatus {
+        FileStatus::from_codes(self.index_status, self.worktree_status)
+    }
+
+    /// Whether the file has changes staged in the index.
+    pub fn is_staged(&self) -> bool {
+        !self.is_unmerged() && self.index_status.is_changed()
+    }
+
+    /// Whether the file has changes in the working tree that are not staged.
+    pub fn is_unstaged(&self) -> bool {
+        !self.is_unmerged() && self.worktree_status.is_changed()
+    }
+
+    /// Whether the file has unresolved merge conflicts.
+    pub fn is_unmerged(&self) -> bool {
+        self.status() == FileStatus::UpdatedButUnmerged
+    }
 }
 
 /// Represents a Git tag (distinct from the Tag type). Renamed to avoid conflict.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TagInfo { // Renamed from Tag to avoid conflict with types::Tag
     /// The name of the tag. (Now types::Tag)
     pub name: Tag,
@@ -143,6 +508,7 @@ pub struct TagInfo { // Renamed from Tag to avoid conflict with types::Tag
 
 /// Represents a Git remote (distinct from the Remote type). Renamed to avoid conflict.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoteInfo { // Renamed from Remote to avoid conflict with types::Remote
     /// The name of the remote. (Now types::Remote)
     pub name: Remote,
@@ -152,8 +518,261 @@ pub struct RemoteInfo { // Renamed from Remote to avoid conflict with types::Rem
     pub fetch: Option<String>,
 }
 
+/// How `git fetch` changed, or declined to change, one local ref.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FetchRefStatus {
+    /// The ref moved forward to a descendant of its old value.
+    FastForward,
+    /// The ref was rewound or rewritten (`+` refspecs, e.g. remote-tracking branches).
+    Forced,
+    /// A new branch was created.
+    NewBranch,
+    /// A new tag was created.
+    NewTag,
+    /// Another kind of ref was created.
+    NewRef,
+    /// The ref was deleted because the remote no longer has it (`--prune`).
+    Pruned,
+    /// An existing tag was moved.
+    TagUpdated,
+    /// The update was refused; holds Git's reason, e.g. `would clobber existing tag`.
+    Rejected(String),
+    /// The ref already had the fetched value.
+    UpToDate,
+}
+
+/// One ref line of the `git fetch` report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FetchedRef {
+    pub status: FetchRefStatus,
+    /// The value before the fetch; only known for updated refs.
+    pub old: Option<CommitHash>,
+    /// The value after the fetch; only known for updated refs.
+    pub new: Option<CommitHash>,
+    /// The ref on the remote as Git displays it, e.g. `main`; `None` for pruned refs.
+    pub remote_ref: Option<String>,
+    /// The local ref as Git displays it, e.g. `origin/main` or `v1.0`.
+    pub local_ref: String,
+}
+
+/// What a fetch changed, as returned by `Repository::fetch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FetchResult {
+    /// One entry per ref Git reported, in the order it reported them.
+    pub refs: Vec<FetchedRef>,
+}
+
+impl FetchResult {
+    /// Parses the ref lines `git fetch` prints on stderr, which look like
+    /// ` * [new branch]      main       -> origin/main` or
+    /// ` + <old>...<new> topic -> origin/topic  (forced update)`.
+    ///
+    /// Other lines (`From <url>`, remote messages, hints) are skipped.
+    pub(crate) fn from_fetch_output(output: &str) -> FetchResult {
+        let refs = output.lines().filter_map(|line| {
+            let mut chars = line.chars();
+            let (Some(' '), Some(flag), Some(' ')) = (chars.next(), chars.next(), chars.next()) else {
+                return None;
+            };
+            let rest = chars.as_str().trim_start();
+            let (summary, rest) = match rest.strip_prefix('[') {
+                Some(bracketed) => bracketed.split_once(']')?,
+                None => rest.split_once(' ')?,
+            };
+            let (remote_ref, rest) = rest.split_once(" -> ")?;
+            let (local_ref, note) = rest.split_once(' ').unwrap_or((rest, ""));
+            let note = note.trim().trim_start_matches('(').trim_end_matches(')');
+
+            let hashes = |separator| {
+                let (old, new) = summary.split_once(separator)?;
+                Some((CommitHash::from_str(old).ok()?, CommitHash::from_str(new).ok()?))
+            };
+            let (status, hashes) = match flag {
+                ' ' => (FetchRefStatus::FastForward, hashes("..")),
+                '+' => (FetchRefStatus::Forced, hashes("...")),
+                '-' => (FetchRefStatus::Pruned, None),
+                't' => (FetchRefStatus::TagUpdated, None),
+                '=' => (FetchRefStatus::UpToDate, None),
+                '!' => (FetchRefStatus::Rejected(note.to_string()), None),
+                '*' => match summary {
+                    "new branch" => (FetchRefStatus::NewBranch, None),
+                    "new tag" => (FetchRefStatus::NewTag, None),
+                    _ => (FetchRefStatus::NewRef, None),
+                },
+                _ => return None,
+            };
+            let remote_ref = remote_ref.trim();
+            Some(FetchedRef {
+                status,
+                old: hashes.as_ref().map(|(old, _)| old.clone()),
+                new: hashes.map(|(_, new)| new),
+                remote_ref: (remote_ref != "(none)").then(|| remote_ref.to_string()),
+                local_ref: local_ref.to_string(),
+            })
+        });
+        FetchResult { refs: refs.collect() }
+    }
+
+    /// Returns `true` if no ref was created, moved or deleted.
+    pub fn is_empty(&self) -> bool {
+        self.refs
+            .iter()
+            .all(|r| matches!(r.status, FetchRefStatus::UpToDate | FetchRefStatus::Rejected(_)))
+    }
+
+    /// The branches the fetch created.
+    pub fn new_branches(&self) -> impl Iterator<Item = &FetchedRef> {
+        self.refs.iter().filter(|r| r.status == FetchRefStatus::NewBranch)
+    }
+
+    /// The existing refs the fetch moved, whether fast-forward or forced.
+    pub fn updated(&self) -> impl Iterator<Item = &FetchedRef> {
+        self.refs.iter().filter(|r| {
+            matches!(r.status, FetchRefStatus::FastForward | FetchRefStatus::Forced | FetchRefStatus::TagUpdated)
+        })
+    }
+
+    /// The refs the fetch rewrote non-fast-forward.
+    pub fn forced(&self) -> impl Iterator<Item = &FetchedRef> {
+        self.refs.iter().filter(|r| r.status == FetchRefStatus::Forced)
+    }
+
+    /// The refs the fetch deleted.
+    pub fn pruned(&self) -> impl Iterator<Item = &FetchedRef> {
+        self.refs.iter().filter(|r| r.status == FetchRefStatus::Pruned)
+    }
+
+    /// The refs the fetch refused to update.
+    pub fn rejected(&self) -> impl Iterator<Item = &FetchedRef> {
+        self.refs.iter().filter(|r| matches!(r.status, FetchRefStatus::Rejected(_)))
+    }
+
+    /// Lists the rejected refs with their reasons, e.g. `refs/tags/v1 (would clobber existing tag)`.
+    pub(crate) fn rejection_summary(&self) -> String {
+        let rejected: Vec<String> = self
+            .refs
+            .iter()
+            .filter_map(|r| match &r.status {
+                FetchRefStatus::Rejected(reason) => Some(format!("{} ({})", r.local_ref, reason)),
+                _ => None,
+            })
+            .collect();
+        rejected.join(", ")
+    }
+}
+
+/// What `git push` did with one ref.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PushStatus {
+    /// The remote ref moved forward.
+    FastForward,
+    /// The remote ref was overwritten non-fast-forward.
+    Forced,
+    /// The remote ref was created.
+    New,
+    /// The remote ref was deleted.
+    Deleted,
+    /// The remote ref already had the pushed value.
+    UpToDate,
+    /// The update was refused; holds the reason, e.g. `non-fast-forward`,
+    /// `fetch first` or a hook's `pre-receive hook declined`.
+    Rejected(String),
+}
+
+/// One ref of a `git push --porcelain` report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefPushStatus {
+    pub status: PushStatus,
+    /// The local side of the refspec, e.g. `refs/heads/main` or `HEAD`; `None` for deletions.
+    pub local_ref: Option<String>,
+    /// The full name of the remote ref, e.g. `refs/heads/main`.
+    pub remote_ref: String,
+    /// The remote ref's value before the push; only known for updates.
+    pub old: Option<CommitHash>,
+    /// The remote ref's value after the push; only known for updates.
+    pub new: Option<CommitHash>,
+}
+
+/// What a push did, ref by ref, as returned by `Repository::push` and friends.
+///
+/// When some refs are rejected the push fails with `GitError::PushRejected`,
+/// which holds the whole result, including the refs that were pushed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PushResult {
+    pub refs: Vec<RefPushStatus>,
+}
+
+impl PushResult {
+    /// Parses the output of `git push --porcelain`, whose ref lines read
+    /// `<flag> TAB <from>:<to> TAB <summary> [(<reason>)]`.
+    pub(crate) fn from_porcelain(output: &str) -> PushResult {
+        let refs = output.lines().filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(flag), Some(refspec), Some(summary)) = (fields.next(), fields.next(), fields.next()) else {
+                return None;
+            };
+            let (from, to) = refspec.split_once(':')?;
+            let reason = summary
+                .split_once(" (")
+                .map(|(_, reason)| reason.trim_end_matches(')').to_string())
+                .unwrap_or_default();
+            let hashes = |separator| {
+                let (old, new) = summary.split_once(' ').map_or(summary, |(hashes, _)| hashes).split_once(separator)?;
+                Some((CommitHash::from_str(old).ok()?, CommitHash::from_str(new).ok()?))
+            };
+            let (status, hashes) = match flag {
+                " " => (PushStatus::FastForward, hashes("..")),
+                "+" => (PushStatus::Forced, hashes("...")),
+                "*" => (PushStatus::New, None),
+                "-" => (PushStatus::Deleted, None),
+                "=" => (PushStatus::UpToDate, None),
+                "!" => (PushStatus::Rejected(reason), None),
+                _ => return None,
+            };
+            Some(RefPushStatus {
+                status,
+                local_ref: (!from.is_empty()).then(|| from.to_string()),
+                remote_ref: to.to_string(),
+                old: hashes.as_ref().map(|(old, _)| old.clone()),
+                new: hashes.map(|(_, new)| new),
+            })
+        });
+        PushResult { refs: refs.collect() }
+    }
+
+    /// The refs the remote refused.
+    pub fn rejected(&self) -> impl Iterator<Item = &RefPushStatus> {
+        self.refs.iter().filter(|r| matches!(r.status, PushStatus::Rejected(_)))
+    }
+
+    /// Lists the rejected refs with their reasons, e.g. `refs/heads/main (fetch first)`.
+    pub(crate) fn rejection_summary(&self) -> String {
+        let rejected: Vec<String> = self
+            .refs
+            .iter()
+            .filter_map(|r| match &r.status {
+                PushStatus::Rejected(reason) => Some(format!("{} ({})", r.remote_ref, reason)),
+                _ => None,
+            })
+            .collect();
+        rejected.join(", ")
+    }
+
+    /// Returns `true` if nothing was pushed because every ref was already up to date.
+    pub fn is_up_to_date(&self) -> bool {
+        self.refs.iter().all(|r| r.status == PushStatus::UpToDate)
+    }
+}
+
 /// Represents a Git branch.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Branch {
     /// The name of the branch. (Already BranchName)
     pub name: BranchName,
@@ -165,11 +784,76 @@ pub struct Branch {
     pub upstream: Option<String>,
 }
 
+/// How a local branch relates to its upstream, as reported by `Repository::sync_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SyncStatus {
+    /// The branch and its upstream point to the same commit.
+    UpToDate,
+    /// The branch has this many commits the upstream lacks; it needs a push.
+    Ahead(usize),
+    /// The upstream has this many commits the branch lacks; it needs a pull.
+    Behind(usize),
+    /// Both sides have new commits: `(ahead, behind)`.
+    Diverged(usize, usize),
+    /// No upstream is configured for the branch.
+    NoUpstream,
+    /// An upstream is configured but no longer exists, typically because the
+    /// remote branch was deleted after merging and then pruned.
+    UpstreamGone,
+}
+
+impl SyncStatus {
+    /// Parses the `%(upstream)` and `%(upstream:track,nobracket)` fields of
+    /// `git for-each-ref`, e.g. `refs/remotes/origin/main` and `ahead 1, behind 2`.
+    pub(crate) fn from_upstream_track(upstream: &str, track: &str) -> SyncStatus {
+        if upstream.is_empty() {
+            return SyncStatus::NoUpstream;
+        }
+        if track == "gone" {
+            return SyncStatus::UpstreamGone;
+        }
+        let (mut ahead, mut behind) = (0, 0);
+        for part in track.split(", ") {
+            if let Some(n) = part.strip_prefix("ahead ") {
+                ahead = n.parse().unwrap_or(0);
+            } else if let Some(n) = part.strip_prefix("behind ") {
+                behind = n.parse().unwrap_or(0);
+            }
+        }
+        match (ahead, behind) {
+            (0, 0) => SyncStatus::UpToDate,
+            (ahead, 0) => SyncStatus::Ahead(ahead),
+            (0, behind) => SyncStatus::Behind(behind),
+            (ahead, behind) => SyncStatus::Diverged(ahead, behind),
+        }
+    }
+
+    /// Returns `true` if the branch has commits to push.
+    pub fn needs_push(&self) -> bool {
+        matches!(self, SyncStatus::Ahead(_) | SyncStatus::Diverged(..))
+    }
+
+    /// Returns `true` if the upstream has commits to pull.
+    pub fn needs_pull(&self) -> bool {
+        matches!(self, SyncStatus::Behind(_) | SyncStatus::Diverged(..))
+    }
+}
+
 /// Represents the result of a `git status` command.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatusResult {
-    /// The current branch name, if on a branch. (Now Option<BranchName>)
+    /// The current branch name, or `None` if HEAD is detached.
     pub branch: Option<BranchName>,
+    /// The commit HEAD points to, or `None` on an unborn branch with no commits yet.
+    pub head_commit: Option<CommitHash>,
+    /// The upstream branch being tracked (e.g., "origin/main"), if any.
+    pub upstream: Option<String>,
+    /// Number of commits the branch is ahead of its upstream (0 if there is no upstream).
+    pub ahead: usize,
+    /// Number of commits the branch is behind its upstream (0 if there is no upstream).
+    pub behind: usize,
     /// The files in the repository with their status.
     pub files: Vec<StatusEntry>,
     /// Whether the repository is in a merge state.
@@ -182,8 +866,185 @@ pub struct StatusResult {
     pub is_clean: bool,
 }
 
+impl StatusResult {
+    /// Parses the output of `git status --porcelain=v2 --branch -z`.
+    ///
+    /// Records are NUL-terminated and paths are never quoted, so names containing
+    /// spaces, quotes or newlines are read intact. Renamed and copied entries are
+    /// followed by an extra record holding the original path.
+    ///
+    /// In-progress operation flags (`merging`, `rebasing`, `cherry_picking`) are not
+    /// part of the porcelain output and are left `false` for the caller to fill in.
+    pub(crate) fn from_porcelain_v2_z(output: &str) -> StatusResult {
+        let mut branch = None;
+        let mut head_commit = None;
+        let mut upstream = None;
+        let mut ahead = 0;
+        let mut behind = 0;
+        // At most one entry per record; counting them avoids regrowing the vector
+        // for large repositories.
+        let mut files = Vec::with_capacity(output.bytes().filter(|&byte| byte == 0).count());
+
+        let mut records = output.split('\0');
+        while let Some(record) = records.next() {
+            if let Some(head) = record.strip_prefix("# branch.head ") {
+                // "(detached)" is reported when HEAD does not point at a branch
+                if head != "(detached)" {
+                    branch = BranchName::from_str(head).ok();
+                }
+            } else if let Some(oid) = record.strip_prefix("# branch.oid ") {
+                // "(initial)" is reported before the first commit
+                head_commit = CommitHash::from_str(oid).ok();
+            } else if let Some(name) = record.strip_prefix("# branch.upstream ") {
+                upstream = Some(name.to_string());
+            } else if let Some(counts) = record.strip_prefix("# branch.ab ") {
+                // Format: "+<ahead> -<behind>"
+                for count in counts.split_whitespace() {
+                    if let Some(n) = count.strip_prefix('+') {
+                        ahead = n.parse().unwrap_or(0);
+                    } else if let Some(n) = count.strip_prefix('-') {
+                        behind = n.parse().unwrap_or(0);
+                    }
+                }
+            } else if record.starts_with("1 ") || record.starts_with("2 ") || record.starts_with("u ") {
+                // Ordinary: 1 XY sub mH mI mW hH hI <path>
+                // Renamed:  2 XY sub mH mI mW hH hI <Xscore> <path>, then <origPath>
+                // Unmerged: u XY sub m1 m2 m3 mW h1 h2 h3 <path>
+                let field_count = match record.as_bytes()[0] {
+                    b'1' => 9,
+                    b'2' => 10,
+                    _ => 11,
+                };
+                let mut fields = [""; 11];
+                let mut found = 0;
+                for (slot, field) in fields.iter_mut().zip(record.splitn(field_count, ' ')) {
+                    *slot = field;
+                    found += 1;
+                }
+                if found < field_count {
+                    continue;
+                }
+                let mut xy = fields[1].chars();
+                let index_status = StatusCode::from_porcelain_char(xy.next().unwrap_or('.'));
+                let worktree_status = StatusCode::from_porcelain_char(xy.next().unwrap_or('.'));
+
+                // Modes follow the submodule field: mH mI mW, or m1 m2 m3 mW when unmerged.
+                let kind = |i: usize| EntryKind::from_mode(fields[i]);
+                let (head_kind, index_kind, worktree_kind) = if record.starts_with("u ") {
+                    (kind(4), None, kind(6))
+                } else {
+                    (kind(3), kind(4), kind(5))
+                };
+
+                let (original_path, similarity) = if record.starts_with("2 ") {
+                    // Renamed/copied entries carry a score such as "R100" or "C75".
+                    let similarity = fields[8].get(1..).and_then(|score| score.parse().ok());
+                    (records.next().map(PathBuf::from), similarity)
+                } else {
+                    (None, None)
+                };
+
+                files.push(StatusEntry {
+                    path: PathBuf::from(fields[field_count - 1]),
+                    index_status,
+                    worktree_status,
+                    original_path,
+                    similarity,
+                    head_kind,
+                    index_kind,
+                    worktree_kind,
+                    submodule: SubmoduleChanges::from_porcelain_v2(fields[2]),
+                });
+            } else if let Some(path) = record.strip_prefix("? ").filter(|p| !p.is_empty()) {
+                files.push(StatusEntry {
+                    path: PathBuf::from(path),
+                    index_status: StatusCode::Untracked,
+                    worktree_status: StatusCode::Untracked,
+                    original_path: None,
+                    similarity: None,
+                    head_kind: None,
+                    index_kind: None,
+                    worktree_kind: None,
+                    submodule: None,
+                });
+            }
+        }
+
+        // Determine if clean (ignoring untracked/ignored)
+        let is_clean = files.iter().all(|f|
+            matches!(f.status(), FileStatus::Unmodified | FileStatus::Ignored)
+        );
+
+        StatusResult {
+            branch,
+            head_commit,
+            upstream,
+            ahead,
+            behind,
+            files,
+            merging: false,
+            rebasing: false,
+            cherry_picking: false,
+            is_clean,
+        }
+    }
+}
+
+/// The multi-step operation a repository is in the middle of, as reported by
+/// `Repository::operation_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepoState {
+    /// No operation is in progress.
+    Clean,
+    /// A merge stopped before committing, usually on conflicts.
+    Merging,
+    /// A rebase stopped on a conflict, an `edit` step or a `break`.
+    Rebasing,
+    /// `git am` stopped while applying a mailbox of patches.
+    ApplyingPatches,
+    /// A cherry-pick stopped on a conflict.
+    CherryPicking,
+    /// A revert stopped on a conflict.
+    Reverting,
+    /// A bisect session is under way.
+    Bisecting,
+}
+
+impl RepoState {
+    /// Determines the state from the marker files Git leaves in the Git
+    /// directory; `exists` checks a path relative to it.
+    ///
+    /// Several markers can be present at once, e.g. a conflicted cherry-pick
+    /// during a bisect. The operation that must be finished first wins, in the
+    /// same order `git status` reports them.
+    pub(crate) fn detect(exists: impl Fn(&str) -> bool) -> RepoState {
+        if exists("rebase-apply/applying") {
+            RepoState::ApplyingPatches
+        } else if exists("rebase-apply") || exists("rebase-merge") {
+            RepoState::Rebasing
+        } else if exists("MERGE_HEAD") {
+            RepoState::Merging
+        } else if exists("CHERRY_PICK_HEAD") {
+            RepoState::CherryPicking
+        } else if exists("REVERT_HEAD") {
+            RepoState::Reverting
+        } else if exists("BISECT_LOG") {
+            RepoState::Bisecting
+        } else {
+            RepoState::Clean
+        }
+    }
+
+    /// Returns `true` if an operation is in progress.
+    pub fn is_in_progress(&self) -> bool {
+        *self != RepoState::Clean
+    }
+}
+
 /// Represents a line of blame information.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlameLine {
     /// The commit hash. (Now CommitHash)
     pub hash: CommitHash,
@@ -201,25 +1062,38 @@ pub struct BlameLine {
 
 /// Represents the result of a `git diff` command.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiffResult {
     pub files: Vec<DiffFile>,
 }
 
 /// Represents a file in a diff.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiffFile {
     pub path: PathBuf,
+    /// The path before a rename or copy.
     pub old_path: Option<PathBuf>,
+    /// The rename or copy similarity score (0-100) for files with an `old_path`.
+    pub similarity: Option<u8>,
     pub hunks: Vec<DiffHunk>,
     pub added_lines: usize,
     pub removed_lines: usize,
     pub is_binary: bool,
     pub old_mode: Option<String>,
     pub new_mode: Option<String>,
+    /// The kind of entry before the change; `None` for added files.
+    pub old_kind: Option<EntryKind>,
+    /// The kind of entry after the change; `None` for deleted files.
+    ///
+    /// A change between a symlink and a regular file is reported by Git as a
+    /// deleted file followed by an added file at the same path.
+    pub new_kind: Option<EntryKind>,
 }
 
 /// Represents a hunk in a diff.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiffHunk {
     pub old_start: usize,
     pub old_lines: usize,
@@ -229,22 +1103,309 @@ pub struct DiffHunk {
 }
 
 /// Represents a line in a diff hunk.
+///
+/// In word and character modes, `spans` holds the line's unchanged, added and removed
+/// runs in order, and `content` holds the line as it reads after the change (or before
+/// it, for lines that were removed outright).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiffLine {
     pub content: String,
     pub line_type: DiffLineType,
+    /// Word- or character-level runs making up the line; empty in line mode.
+    pub spans: Vec<DiffSpan>,
 }
 
 /// Represents the type of a diff line.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DiffLineType {
     Context,
     Added,
     Removed,
 }
 
+/// Represents a run of unchanged, added or removed text within a word-level diff line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffSpan {
+    pub content: String,
+    pub span_type: DiffLineType,
+}
+
+impl DiffResult {
+    /// Parses unified `git diff` output, or `git diff --word-diff=porcelain` output
+    /// when `word_diff` is set.
+    ///
+    /// In porcelain word-diff output each hunk line is a run prefixed with ` `, `+`
+    /// or `-`, and a line consisting of `~` ends the current line of the file.
+    pub(crate) fn from_patch(output: &str, word_diff: bool) -> DiffResult {
+        let mut files: Vec<DiffFile> = Vec::new();
+        let mut in_hunk = false;
+        let mut spans: Vec<DiffSpan> = Vec::new();
+
+        for line in output.split('\n') {
+            if let Some(header) = line.strip_prefix("diff --git ") {
+                files.push(DiffFile::from_git_header(header));
+                in_hunk = false;
+                continue;
+            }
+            let file = match files.last_mut() {
+                Some(file) => file,
+                None => continue,
+            };
+            if line.starts_with("@@") {
+                if let Some(hunk) = DiffHunk::from_header(line) {
+                    file.hunks.push(hunk);
+                    in_hunk = true;
+                }
+                continue;
+            }
+
+            if in_hunk {
+                let line_type = match line.as_bytes().first() {
+                    Some(b' ') => DiffLineType::Context,
+                    Some(b'+') => DiffLineType::Added,
+                    Some(b'-') => DiffLineType::Removed,
+                    Some(b'~') if word_diff => {
+                        let diff_line = DiffLine::from_spans(std::mem::take(&mut spans));
+                        file.count_line(&diff_line);
+                        if let Some(hunk) = file.hunks.last_mut() {
+                            hunk.lines.push(diff_line);
+                        }
+                        continue;
+                    }
+                    // `\ No newline at end of file` and the trailing empty split.
+                    _ => continue,
+                };
+                let content = line[1..].to_string();
+                if word_diff {
+                    spans.push(DiffSpan { content, span_type: line_type });
+                } else {
+                    let diff_line = DiffLine { content, line_type, spans: Vec::new() };
+                    file.count_line(&diff_line);
+                    if let Some(hunk) = file.hunks.last_mut() {
+                        hunk.lines.push(diff_line);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(mode) = line.strip_prefix("old mode ") {
+                file.old_mode = Some(mode.to_string());
+                file.old_kind = EntryKind::from_mode(mode);
+            } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+                file.old_mode = Some(mode.to_string());
+                file.old_kind = EntryKind::from_mode(mode);
+            } else if let Some(mode) = line.strip_prefix("new mode ") {
+                file.new_mode = Some(mode.to_string());
+                file.new_kind = EntryKind::from_mode(mode);
+            } else if let Some(mode) = line.strip_prefix("new file mode ") {
+                file.new_mode = Some(mode.to_string());
+                file.new_kind = EntryKind::from_mode(mode);
+            } else if let Some(index) = line.strip_prefix("index ") {
+                // `index <old>..<new> <mode>` carries the mode when it did not change.
+                if let Some((_, mode)) = index.split_once(' ') {
+                    file.old_kind = EntryKind::from_mode(mode);
+                    file.new_kind = file.old_kind;
+                }
+            } else if let Some(old) = line
+                .strip_prefix("rename from ")
+                .or_else(|| line.strip_prefix("copy from "))
+            {
+                file.old_path = Some(unquote_c_path(old));
+            } else if let Some(new) = line
+                .strip_prefix("rename to ")
+                .or_else(|| line.strip_prefix("copy to "))
+            {
+                file.path = unquote_c_path(new);
+            } else if let Some(new) = line.strip_prefix("+++ ") {
+                // Git appends a tab to names containing spaces.
+                let new = unquote_c_path(new.trim_end_matches('\t'));
+                if let Ok(new) = new.strip_prefix("b") {
+                    file.path = new.to_path_buf();
+                }
+            } else if let Some(score) = line.strip_prefix("similarity index ") {
+                file.similarity = score.trim_end_matches('%').parse().ok();
+            } else if line.starts_with("Binary files ") {
+                file.is_binary = true;
+            }
+        }
+
+        DiffResult { files }
+    }
+}
+
+impl DiffFile {
+    /// Creates an empty file entry from the `a/<old> b/<new>` part of a `diff --git` line.
+    ///
+    /// The header is ambiguous for unquoted names containing ` b/`; later
+    /// `rename to` and `+++` lines take precedence when present.
+    fn from_git_header(header: &str) -> DiffFile {
+        let path = if header.starts_with('"') || header.ends_with('"') {
+            // At least one side is C-quoted; the new path is the last token.
+            let new = match header.strip_suffix('"') {
+                Some(rest) => match rest.rfind(" \"") {
+                    Some(idx) => &header[idx + 1..],
+                    None => header,
+                },
+                None => header.rsplit(' ').next().unwrap_or(header),
+            };
+            unquote_c_path(new)
+        } else {
+            // Unquoted: usually `a/<path> b/<path>` with both paths equal.
+            let half = header.len().saturating_sub(3) / 2;
+            PathBuf::from(match (header.get(..half), header.get(half..)) {
+                (Some(old), Some(new)) if new.starts_with(" b/") && old.get(2..) == new.get(3..) => &new[1..],
+                _ => match header.find(" b/") {
+                    Some(idx) => &header[idx + 1..],
+                    None => header,
+                },
+            })
+        };
+        let path = path.strip_prefix("b").map(Path::to_path_buf).unwrap_or(path);
+
+        DiffFile {
+            path,
+            old_path: None,
+            similarity: None,
+            hunks: Vec::new(),
+            added_lines: 0,
+            removed_lines: 0,
+            is_binary: false,
+            old_mode: None,
+            new_mode: None,
+            old_kind: None,
+            new_kind: None,
+        }
+    }
+
+    /// Adds a parsed line to the file's added/removed line counts.
+    fn count_line(&mut self, line: &DiffLine) {
+        if line.spans.is_empty() {
+            match line.line_type {
+                DiffLineType::Added => self.added_lines += 1,
+                DiffLineType::Removed => self.removed_lines += 1,
+                DiffLineType::Context => {}
+            }
+        } else {
+            if line.spans.iter().any(|s| s.span_type == DiffLineType::Added) {
+                self.added_lines += 1;
+            }
+            if line.spans.iter().any(|s| s.span_type == DiffLineType::Removed) {
+                self.removed_lines += 1;
+            }
+        }
+    }
+}
+
+impl DiffHunk {
+    /// Parses a hunk header such as `@@ -1,3 +1,4 @@ fn main()`.
+    /// Omitted line counts default to 1.
+    fn from_header(line: &str) -> Option<DiffHunk> {
+        let ranges = line.strip_prefix("@@ -")?;
+        let ranges = &ranges[..ranges.find(" @@")?];
+        let (old, new) = ranges.split_once(" +")?;
+        let parse_range = |range: &str| -> Option<(usize, usize)> {
+            match range.split_once(',') {
+                Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+                None => Some((range.parse().ok()?, 1)),
+            }
+        };
+        let (old_start, old_lines) = parse_range(old)?;
+        let (new_start, new_lines) = parse_range(new)?;
+        Some(DiffHunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            lines: Vec::new(),
+        })
+    }
+}
+
+impl DiffLine {
+    /// Builds a word-level line from its spans.
+    fn from_spans(spans: Vec<DiffSpan>) -> DiffLine {
+        let has = |span_type| spans.iter().any(|s: &DiffSpan| s.span_type == span_type);
+        let line_type = if has(DiffLineType::Added) {
+            DiffLineType::Added
+        } else if has(DiffLineType::Removed) {
+            DiffLineType::Removed
+        } else {
+            DiffLineType::Context
+        };
+        let collect = |keep: &dyn Fn(DiffLineType) -> bool| -> String {
+            spans
+                .iter()
+                .filter(|s| keep(s.span_type))
+                .map(|s| s.content.as_str())
+                .collect()
+        };
+        let mut content = collect(&|t| t != DiffLineType::Removed);
+        if content.is_empty() {
+            content = collect(&|t| t == DiffLineType::Removed);
+        }
+        DiffLine { content, line_type, spans }
+    }
+}
+
+/// Decodes a path that Git may have C-quoted (`"dir/caf\303\251.txt"`).
+/// Unquoted input is returned unchanged.
+///
+/// On Unix the escaped bytes are kept exactly, so names that are not valid
+/// UTF-8 still name their file; elsewhere they are decoded lossily.
+pub(crate) fn unquote_c_path(s: &str) -> PathBuf {
+    let inner = match s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => return PathBuf::from(s),
+    };
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.bytes().peekable();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b'f') => bytes.push(0x0c),
+            Some(b'v') => bytes.push(0x0b),
+            Some(d @ b'0'..=b'7') => {
+                let mut value = u32::from(d - b'0');
+                for _ in 0..2 {
+                    match chars.peek() {
+                        Some(&d @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(d - b'0');
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
 /// Represents a stash entry.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StashEntry {
     /// The stash reference. (Now types::Stash)
     pub reference: Stash,
@@ -254,21 +1415,93 @@ pub struct StashEntry {
     pub message: String,
 }
 
+impl StashEntry {
+    /// Parses `git stash list --format=%gd%x00%gs` output.
+    ///
+    /// Each subject is `WIP on <branch>: <commit summary>` or, for stashes made
+    /// with a message, `On <branch>: <message>`; the branch is `(no branch)` when
+    /// HEAD was detached.
+    pub(crate) fn from_stash_list(output: &str) -> Vec<StashEntry> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (reference, subject) = line.split_once('\0')?;
+                let reference = Stash::from_str(reference).ok()?;
+                let rest = subject
+                    .strip_prefix("WIP on ")
+                    .or_else(|| subject.strip_prefix("On "))
+                    .and_then(|rest| rest.split_once(": "));
+                let (branch, message) = match rest {
+                    Some((branch, message)) => {
+                        let branch = Some(branch).filter(|b| *b != "(no branch)").map(str::to_string);
+                        (branch, message.to_string())
+                    }
+                    None => (None, subject.to_string()),
+                };
+                Some(StashEntry { reference, branch, message })
+            })
+            .collect()
+    }
+}
+
 /// Represents a worktree.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Worktree {
     pub path: PathBuf,
     /// The commit hash the worktree is at. (Now CommitHash)
+    /// All zeros for an unborn branch or a bare repository, as Git reports it.
     pub head: CommitHash,
     /// The branch the worktree is on, if any. Kept as String for now.
     pub branch: Option<String>,
+    /// Whether this is the main worktree, the one holding the repository's `.git`.
     pub is_main: bool,
     pub is_bare: bool,
+    /// Whether the worktree's directory is gone and `git worktree prune` would remove it.
     pub is_prunable: bool,
 }
 
+impl Worktree {
+    /// Parses the output of `git worktree list --porcelain -z`: one block of
+    /// NUL-terminated `<label> [<value>]` attributes per worktree, each block
+    /// ended by an empty record. The main worktree comes first.
+    pub(crate) fn from_porcelain_z(output: &str) -> Vec<Worktree> {
+        let mut worktrees: Vec<Worktree> = Vec::new();
+        for record in output.split('\0') {
+            let (label, value) = record.split_once(' ').unwrap_or((record, ""));
+            if label == "worktree" {
+                worktrees.push(Worktree {
+                    path: PathBuf::from(value),
+                    head: CommitHash::from_str(&"0".repeat(40)).expect("zero hash is valid"),
+                    branch: None,
+                    is_main: worktrees.is_empty(),
+                    is_bare: false,
+                    is_prunable: false,
+                });
+                continue;
+            }
+            let Some(worktree) = worktrees.last_mut() else { continue };
+            match label {
+                "HEAD" => {
+                    if let Ok(head) = CommitHash::from_str(value) {
+                        worktree.head = head;
+                    }
+                }
+                "branch" => {
+                    worktree.branch = Some(value.strip_prefix("refs/heads/").unwrap_or(value).to_string());
+                }
+                "bare" => worktree.is_bare = true,
+                "prunable" => worktree.is_prunable = true,
+                _ => {}
+            }
+        }
+        worktrees
+    }
+}
+
 /// Represents a config entry.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConfigEntry {
     pub key: String,
     pub value: String,
@@ -277,6 +1510,7 @@ pub struct ConfigEntry {
 
 /// Represents the scope of a config entry.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConfigScope {
     System,
     Global,
@@ -284,8 +1518,21 @@ pub enum ConfigScope {
     Worktree,
 }
 
+impl ConfigScope {
+    /// Returns the `git config` flag selecting this scope, e.g. `--local`.
+    pub(crate) fn as_flag(&self) -> &'static str {
+        match self {
+            ConfigScope::System => "--system",
+            ConfigScope::Global => "--global",
+            ConfigScope::Local => "--local",
+            ConfigScope::Worktree => "--worktree",
+        }
+    }
+}
+
 /// Represents a submodule.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Submodule {
     pub name: String,
     pub path: PathBuf,
@@ -297,6 +1544,7 @@ pub struct Submodule {
 
 /// Represents the result of a `git log` command.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LogResult {
     /// The commits in the log. (Now uses updated Commit model)
     pub commits: Vec<Commit>,
@@ -304,6 +1552,7 @@ pub struct LogResult {
 
 /// Represents a Git reference (branch, tag, etc.).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reference {
     /// The name of the reference. (Kept as String for generic refs)
     pub name: String,
@@ -315,10 +1564,1108 @@ pub struct Reference {
 
 /// Represents the type of a Git reference.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReferenceType {
     LocalBranch,
     RemoteBranch,
     Tag,
     Note,
     Other,
-}
\ No newline at end of file
+}
+
+/// The outcome of a Git command whose exit code carries meaning, such as
+/// `git diff --quiet` or `git merge-base --is-ancestor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExitStatusInfo {
+    /// The exit code, or `None` if the process was terminated by a signal.
+    pub code: Option<i32>,
+    /// The captured standard output.
+    pub stdout: String,
+    /// The captured standard error.
+    pub stderr: String,
+}
+
+impl ExitStatusInfo {
+    /// Returns `true` if the command exited with code 0.
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// How a submodule's checkout relates to the commit its superproject records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubmoduleState {
+    /// The submodule is not initialized (`-`).
+    Uninitialized,
+    /// The checked-out commit matches the recorded one.
+    UpToDate,
+    /// The checked-out commit differs from the recorded one (`+`).
+    CommitChanged,
+    /// The submodule has merge conflicts in the superproject (`U`).
+    Conflict,
+}
+
+/// A submodule as listed by `git submodule status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubmoduleStatus {
+    /// The submodule's path, relative to the superproject root.
+    pub path: PathBuf,
+    /// The checked-out commit, or the recorded one if the submodule is not initialized.
+    pub commit: CommitHash,
+    /// How the checkout relates to the recorded commit.
+    pub state: SubmoduleState,
+    /// The commit as described by `git describe`, e.g. `v1.2.0` or `heads/main`.
+    pub describe: Option<String>,
+}
+
+impl SubmoduleStatus {
+    /// Parses the output of `git submodule status`, one
+    /// `<state><sha1> <path>[ (<describe>)]` line per submodule.
+    pub(crate) fn from_submodule_status(output: &str) -> Vec<SubmoduleStatus> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut chars = line.chars();
+                let state = match chars.next()? {
+                    '-' => SubmoduleState::Uninitialized,
+                    '+' => SubmoduleState::CommitChanged,
+                    'U' => SubmoduleState::Conflict,
+                    _ => SubmoduleState::UpToDate,
+                };
+                let (hash, rest) = chars.as_str().split_once(' ')?;
+                let (path, describe) = match rest.strip_suffix(')').and_then(|r| r.rsplit_once(" (")) {
+                    Some((path, describe)) => (path, Some(describe.to_string())),
+                    None => (rest, None),
+                };
+                Some(SubmoduleStatus {
+                    path: PathBuf::from(path),
+                    commit: CommitHash::from_str(hash).ok()?,
+                    state,
+                    describe,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A repository's status together with the status of its submodules, from
+/// `Repository::status_recursive`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecursiveStatus {
+    /// The status of the repository itself.
+    pub status: StatusResult,
+    /// One entry per direct submodule, each carrying its own nested submodules.
+    pub submodules: Vec<SubmoduleReport>,
+}
+
+impl RecursiveStatus {
+    /// Returns `true` if this repository or any submodule, at any depth, has
+    /// uncommitted changes or a checkout that differs from the recorded commit.
+    pub fn is_dirty(&self) -> bool {
+        !self.status.is_clean
+            || self.submodules.iter().any(|sub| {
+                matches!(sub.submodule.state, SubmoduleState::CommitChanged | SubmoduleState::Conflict)
+                    || sub.status.as_ref().is_some_and(RecursiveStatus::is_dirty)
+            })
+    }
+}
+
+/// One submodule within a `RecursiveStatus`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubmoduleReport {
+    /// The submodule as reported by the superproject.
+    pub submodule: SubmoduleStatus,
+    /// The commit recorded in the superproject's index.
+    pub recorded_commit: Option<CommitHash>,
+    /// Commits checked out in the submodule that the recorded commit lacks; `None`
+    /// if the submodule is not initialized or the recorded commit is not available.
+    pub ahead: Option<usize>,
+    /// Commits in the recorded commit that the checkout lacks; `None` as for `ahead`.
+    pub behind: Option<usize>,
+    /// The submodule's own recursive status; `None` if it is not initialized.
+    pub status: Option<RecursiveStatus>,
+}
+
+/// The name and email Git records as author and committer, from `user.name`
+/// and `user.email`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Identity {
+    /// The value of `user.name`.
+    pub name: String,
+    /// The value of `user.email`.
+    pub email: String,
+}
+
+/// Object statistics from `Repository::count_objects`. Sizes are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectCountStats {
+    /// Number of loose objects.
+    pub loose_objects: u64,
+    /// Disk space used by loose objects.
+    pub loose_size: u64,
+    /// Number of objects in packs.
+    pub packed_objects: u64,
+    /// Number of pack files.
+    pub packs: u64,
+    /// Disk space used by pack files.
+    pub pack_size: u64,
+    /// Loose objects that are also in a pack and can be removed with `prune-packed`.
+    pub prune_packable: u64,
+    /// Files in the object directory that are neither valid objects nor packs.
+    pub garbage: u64,
+    /// Disk space used by garbage files.
+    pub garbage_size: u64,
+}
+
+impl ObjectCountStats {
+    /// Parses the `<key>: <value>` lines of `git count-objects -v`, whose sizes
+    /// are in KiB. Unknown keys are ignored; missing keys count as zero.
+    pub(crate) fn from_count_objects(output: &str) -> Option<ObjectCountStats> {
+        let mut stats = ObjectCountStats::default();
+        for line in output.lines() {
+            let (key, value) = line.split_once(": ")?;
+            let value: u64 = value.trim().parse().ok()?;
+            match key {
+                "count" => stats.loose_objects = value,
+                "size" => stats.loose_size = value * 1024,
+                "in-pack" => stats.packed_objects = value,
+                "packs" => stats.packs = value,
+                "size-pack" => stats.pack_size = value * 1024,
+                "prune-packable" => stats.prune_packable = value,
+                "garbage" => stats.garbage = value,
+                "size-garbage" => stats.garbage_size = value * 1024,
+                _ => {}
+            }
+        }
+        Some(stats)
+    }
+}
+
+/// Disk usage of a repository, from `Repository::repo_size`. Sizes are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepoSizeReport {
+    /// Loose and packed object statistics from `git count-objects -v`.
+    pub objects: ObjectCountStats,
+    /// Size of every file in `objects/pack`: packs plus their indexes, bitmaps
+    /// and reverse indexes.
+    pub pack_dir_size: u64,
+    /// Number of files in the local LFS object store.
+    pub lfs_objects: u64,
+    /// Size of the local LFS object store.
+    pub lfs_size: u64,
+}
+
+impl RepoSizeReport {
+    /// Returns the combined size of loose objects, the pack directory and LFS objects.
+    pub fn total_size(&self) -> u64 {
+        self.objects.loose_size + self.pack_dir_size + self.lfs_size
+    }
+}
+
+/// A blob found by `Repository::find_large_blobs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LargeBlob {
+    /// The blob's object id.
+    pub id: String,
+    /// The uncompressed size in bytes.
+    pub size: u64,
+    /// A path the blob appeared under in history. A blob stored under several
+    /// paths is reported once.
+    pub path: PathBuf,
+}
+
+impl LargeBlob {
+    /// Parses `git cat-file --batch-check='%(objecttype) %(objectname) %(objectsize) %(rest)'`
+    /// output, keeping blobs of at least `threshold` bytes.
+    pub(crate) fn from_batch_check(output: &str, threshold: u64) -> Vec<LargeBlob> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let rest = line.strip_prefix("blob ")?;
+                let (id, rest) = rest.split_once(' ')?;
+                let (size, path) = rest.split_once(' ').unwrap_or((rest, ""));
+                let size: u64 = size.parse().ok()?;
+                if size < threshold {
+                    return None;
+                }
+                Some(LargeBlob { id: id.to_string(), size, path: PathBuf::from(path) })
+            })
+            .collect()
+    }
+}
+
+/// The type of a Git object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObjectType {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+impl FromStr for ObjectType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "commit" => Ok(ObjectType::Commit),
+            "tree" => Ok(ObjectType::Tree),
+            "blob" => Ok(ObjectType::Blob),
+            "tag" => Ok(ObjectType::Tag),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An object read by `ObjectReader::read`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GitObject {
+    /// The object id.
+    pub id: String,
+    /// The object's type.
+    pub object_type: ObjectType,
+    /// The raw contents: file data for blobs, the binary tree format for trees, and
+    /// the textual headers and message for commits and tags.
+    pub data: Vec<u8>,
+}
+
+/// An object named in a `FsckReport`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FsckObject {
+    /// The object's type.
+    pub object_type: ObjectType,
+    /// The object id.
+    pub id: String,
+}
+
+/// The findings of `Repository::fsck`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FsckReport {
+    /// Objects nothing refers to, typically left behind by amends, rebases or
+    /// deleted branches. These are harmless.
+    pub dangling: Vec<FsckObject>,
+    /// Objects not reachable from any ref (only listed with `FsckOptions::unreachable`).
+    pub unreachable: Vec<FsckObject>,
+    /// Objects that are referenced but absent.
+    pub missing: Vec<FsckObject>,
+    /// Ids of objects whose stored data is damaged.
+    pub corrupt: Vec<String>,
+    /// Every `error:` line Git printed, including those explaining `corrupt` entries.
+    pub errors: Vec<String>,
+    /// The exit code of `git fsck`: 0, or a bit mask of the classes of problems found.
+    pub exit_code: i32,
+}
+
+impl FsckReport {
+    /// Returns `true` if fsck exited with 0, no objects are missing or corrupt and
+    /// no errors were reported. Dangling and unreachable objects do not affect health.
+    pub fn is_healthy(&self) -> bool {
+        self.exit_code == 0 && self.missing.is_empty() && self.corrupt.is_empty() && self.errors.is_empty()
+    }
+
+    /// Parses the stdout and stderr of `git fsck`, which exited with `exit_code`.
+    ///
+    /// Stdout holds `<kind> <type> <id>` lines such as `dangling blob <id>`; stderr
+    /// holds `error: ...` lines, where `error: <id>: object corrupt or missing: <path>`
+    /// marks a damaged object.
+    pub(crate) fn from_fsck(stdout: &str, stderr: &str, exit_code: i32) -> FsckReport {
+        let mut report = FsckReport { exit_code, ..Default::default() };
+        for line in stdout.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(kind), Some(object_type), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+                continue;
+            };
+            let Ok(object_type) = ObjectType::from_str(object_type) else {
+                continue;
+            };
+            let object = FsckObject { object_type, id: id.to_string() };
+            match kind {
+                "dangling" => report.dangling.push(object),
+                "unreachable" => report.unreachable.push(object),
+                "missing" => report.missing.push(object),
+                _ => {}
+            }
+        }
+        for line in stderr.lines() {
+            let Some(message) = line.strip_prefix("error: ") else {
+                continue;
+            };
+            if let Some((id, _)) = message.split_once(": object corrupt or missing") {
+                if !report.corrupt.iter().any(|known| known == id) {
+                    report.corrupt.push(id.to_string());
+                }
+            }
+            report.errors.push(message.to_string());
+        }
+        report
+    }
+}
+
+/// Represents a line matched by `Repository::grep`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrepMatch {
+    /// The file containing the match, relative to the repository root.
+    pub path: PathBuf,
+    /// The 1-based line number of the match.
+    pub line_number: usize,
+    /// The matching line, without its line terminator.
+    pub content: String,
+}
+
+impl GrepMatch {
+    /// Parses the output of `git grep -n --null`.
+    ///
+    /// Each match is `<path>\0<line>\0<content>\n`. The path is printed verbatim and
+    /// may itself contain newlines, so records are split field by field rather than
+    /// by line. When searching a revision, paths are prefixed with `<revision>:`,
+    /// which is stripped when `revision` is given.
+    pub(crate) fn from_grep_null(output: &str, revision: Option<&str>) -> Vec<GrepMatch> {
+        let prefix = revision.map(|rev| format!("{}:", rev));
+        let mut matches = Vec::new();
+        let mut rest = output;
+        while let Some((path, after_path)) = rest.split_once('\0') {
+            let Some((line_number, after_number)) = after_path.split_once('\0') else {
+                break;
+            };
+            let (content, after_content) = after_number.split_once('\n').unwrap_or((after_number, ""));
+            rest = after_content;
+            let path = match &prefix {
+                Some(prefix) => path.strip_prefix(prefix.as_str()).unwrap_or(path),
+                None => path,
+            };
+            if let Ok(line_number) = line_number.parse() {
+                matches.push(GrepMatch { path: PathBuf::from(path), line_number, content: content.to_string() });
+            }
+        }
+        matches
+    }
+}
+
+/// The state of a gitattributes attribute for a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttrValue {
+    /// The attribute is set (`binary`, `text`).
+    Set,
+    /// The attribute is explicitly unset (`-text`).
+    Unset,
+    /// No pattern mentions the attribute for this path.
+    Unspecified,
+    /// The attribute is set to a value (`eol=crlf`, `filter=lfs`).
+    Value(String),
+}
+
+impl AttrValue {
+    /// Returns `true` for `Set` and for any `Value`.
+    pub fn is_set(&self) -> bool {
+        matches!(self, AttrValue::Set | AttrValue::Value(_))
+    }
+
+    /// Returns the value if the attribute is set to one.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            AttrValue::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// One attribute of one path, as reported by `Repository::check_attr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttrResult {
+    /// The path, as given to `check_attr`.
+    pub path: PathBuf,
+    /// The attribute name, e.g. `eol`.
+    pub attribute: String,
+    /// The attribute's state for the path.
+    pub value: AttrValue,
+}
+
+impl AttrResult {
+    /// Parses the output of `git check-attr -z`, a sequence of
+    /// `<path>\0<attribute>\0<info>\0` records.
+    pub(crate) fn from_check_attr_z(output: &str) -> Vec<AttrResult> {
+        let fields: Vec<&str> = output.split('\0').collect();
+        fields
+            .chunks_exact(3)
+            .map(|record| AttrResult {
+                path: PathBuf::from(record[0]),
+                attribute: record[1].to_string(),
+                value: match record[2] {
+                    "set" => AttrValue::Set,
+                    "unset" => AttrValue::Unset,
+                    "unspecified" => AttrValue::Unspecified,
+                    value => AttrValue::Value(value.to_string()),
+                },
+            })
+            .collect()
+    }
+}
+
+/// A file stored in Git LFS, as listed by `Repository::lfs_ls_files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LfsFile {
+    /// The SHA-256 object id of the file's content.
+    pub oid: String,
+    /// The file, relative to the repository root.
+    pub path: PathBuf,
+    /// Whether the content is checked out (`true`) or only the pointer file is present.
+    pub downloaded: bool,
+}
+
+impl LfsFile {
+    /// Parses the output of `git lfs ls-files --long`, one `<oid> <*|-> <path>`
+    /// line per file.
+    pub(crate) fn from_ls_files_long(output: &str) -> Vec<LfsFile> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (oid, rest) = line.split_once(' ')?;
+                let (marker, path) = rest.split_once(' ')?;
+                Some(LfsFile {
+                    oid: oid.to_string(),
+                    path: PathBuf::from(path),
+                    downloaded: marker == "*",
+                })
+            })
+            .collect()
+    }
+}
+
+/// The result of a three-way file merge from `Repository::merge_file`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MergeFileResult {
+    /// The merged contents, including conflict markers for unresolved regions.
+    pub content: String,
+    /// The number of conflicting regions (Git caps this at 127).
+    pub conflicts: usize,
+}
+
+impl MergeFileResult {
+    /// Returns `true` if the merge left conflict markers in `content`.
+    pub fn has_conflicts(&self) -> bool {
+        self.conflicts > 0
+    }
+}
+
+/// How a commit of the old series relates to the new one in `Repository::range_diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RangeDiffStatus {
+    /// Both commits make the same change (`=`).
+    Unchanged,
+    /// The commits were matched, but their changes or messages differ (`!`).
+    Changed,
+    /// The old commit has no counterpart in the new series (`<`).
+    Removed,
+    /// The new commit has no counterpart in the old series (`>`).
+    Added,
+}
+
+/// One line of `git range-diff` output: a commit of the old series, of the new
+/// series, or a matched pair of both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeDiffEntry {
+    pub status: RangeDiffStatus,
+    /// The 1-based position of the commit in the old series; `None` for `Added`.
+    pub old_position: Option<usize>,
+    /// The commit in the old series; `None` for `Added`.
+    pub old_commit: Option<CommitHash>,
+    /// The 1-based position of the commit in the new series; `None` for `Removed`.
+    pub new_position: Option<usize>,
+    /// The commit in the new series; `None` for `Removed`.
+    pub new_commit: Option<CommitHash>,
+    /// The subject of the new commit, or of the old one for `Removed`.
+    pub subject: String,
+    /// For `Changed`, the diff between the two commits' patches, where each line
+    /// has an outer `+`/`-`/` ` marker for the change to the patch followed by the
+    /// patch line itself. Empty otherwise.
+    pub patch: String,
+}
+
+impl RangeDiffEntry {
+    /// Parses the output of `git -c core.abbrev=no range-diff --no-color`.
+    ///
+    /// Each pair is a `<n>:  <old> <status> <n>:  <new> <subject>` line, where a
+    /// missing side is written as `-:  ----`; the diff of a `!` pair follows,
+    /// indented by four spaces.
+    pub(crate) fn from_range_diff(output: &str) -> Vec<RangeDiffEntry> {
+        let mut entries: Vec<RangeDiffEntry> = Vec::new();
+        for line in output.lines() {
+            if let Some(entry) = RangeDiffEntry::from_header(line) {
+                entries.push(entry);
+            } else if let Some(entry) = entries.last_mut() {
+                entry.patch.push_str(line.strip_prefix("    ").unwrap_or(line));
+                entry.patch.push('\n');
+            }
+        }
+        entries
+    }
+
+    fn from_header(line: &str) -> Option<RangeDiffEntry> {
+        fn side(rest: &str) -> Option<(Option<usize>, Option<CommitHash>, &str)> {
+            let (position, rest) = rest.trim_start().split_once(':')?;
+            let (hash, rest) = rest.trim_start().split_once(' ')?;
+            if position == "-" && hash.chars().all(|c| c == '-') {
+                return Some((None, None, rest));
+            }
+            Some((Some(position.parse().ok()?), Some(CommitHash::from_str(hash).ok()?), rest))
+        }
+
+        let (old_position, old_commit, rest) = side(line)?;
+        let (status, rest) = rest.split_once(' ')?;
+        let status = match status {
+            "=" => RangeDiffStatus::Unchanged,
+            "!" => RangeDiffStatus::Changed,
+            "<" => RangeDiffStatus::Removed,
+            ">" => RangeDiffStatus::Added,
+            _ => return None,
+        };
+        let (new_position, new_commit, subject) = side(rest)?;
+        Some(RangeDiffEntry {
+            status,
+            old_position,
+            old_commit,
+            new_position,
+            new_commit,
+            subject: subject.to_string(),
+            patch: String::new(),
+        })
+    }
+}
+
+/// Whether a commit reported by `Repository::cherry` has reached upstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CherryMark {
+    /// No equivalent change exists upstream (`+`).
+    Missing,
+    /// An equivalent change already exists upstream under another id (`-`).
+    Equivalent,
+}
+
+impl CherryMark {
+    /// Parses the output of `git cherry`, one `<+|-> <commit>` line per commit.
+    pub(crate) fn from_cherry(output: &str) -> Vec<(CherryMark, CommitHash)> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (mark, hash) = line.split_once(' ')?;
+                let mark = match mark {
+                    "+" => CherryMark::Missing,
+                    "-" => CherryMark::Equivalent,
+                    _ => return None,
+                };
+                Some((mark, CommitHash::from_str(hash).ok()?))
+            })
+            .collect()
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numstat_z_parsing() {
+        let output = concat!("1\t0\t\0a.txt\0b c.txt\0", "-\t-\tbin.dat\0", "3\t2\tsrc/lib.rs\0");
+        let stats = FileStat::from_numstat_z(output);
+
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].path, PathBuf::from("b c.txt"));
+        assert_eq!(stats[0].old_path, Some(PathBuf::from("a.txt")));
+        assert_eq!((stats[0].added, stats[0].removed), (Some(1), Some(0)));
+        assert!(stats[1].is_binary());
+        assert_eq!(stats[1].path, PathBuf::from("bin.dat"));
+        assert_eq!((stats[2].added, stats[2].removed), (Some(3), Some(2)));
+    }
+
+    #[test]
+    fn test_worktree_list_parsing() {
+        let head = "4f14be897609081016195536d4f8581fcf5929da";
+        let output = format!(
+            "worktree /repo/main\0HEAD {0}\0branch refs/heads/master\0\0\
+             worktree /repo/det\0HEAD {0}\0detached\0\0\
+             worktree /repo/gone\0HEAD {0}\0branch refs/heads/g\0prunable gitdir file points to non-existent location\0\0",
+            head
+        );
+        let worktrees = Worktree::from_porcelain_z(&output);
+        assert_eq!(worktrees.len(), 3);
+        assert!(worktrees[0].is_main && !worktrees[1].is_main);
+        assert_eq!(worktrees[0].path, PathBuf::from("/repo/main"));
+        assert_eq!(worktrees[0].branch.as_deref(), Some("master"));
+        assert_eq!(worktrees[1].head.to_string(), head);
+        assert!(worktrees[1].branch.is_none());
+        assert!(worktrees[2].is_prunable);
+
+        let bare = Worktree::from_porcelain_z("worktree /repo.git\0bare\0\0");
+        assert!(bare[0].is_bare && bare[0].is_main);
+        assert_eq!(bare[0].head.to_string(), "0".repeat(40));
+    }
+
+    #[test]
+    fn test_unquote_c_path() {
+        assert_eq!(unquote_c_path("plain name.txt"), PathBuf::from("plain name.txt"));
+        assert_eq!(unquote_c_path(r#""caf\303\251 \360\237\246\200.txt""#), PathBuf::from("caf\u{e9} \u{1f980}.txt"));
+        assert_eq!(unquote_c_path(r#""quote\"d\ttab\\.txt""#), PathBuf::from("quote\"d\ttab\\.txt"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let latin1 = unquote_c_path(r#""Jos\351.txt""#);
+            assert_eq!(latin1.as_os_str().as_bytes(), b"Jos\xe9.txt");
+        }
+    }
+
+    #[test]
+    fn test_diff_stats_parsing() {
+        let output = concat!(
+            "1\t0\t\0a.txt\0b c.txt\0",
+            "-\t-\tbin.dat\0",
+            " 2 files changed, 1 insertion(+)\n"
+        );
+        let stats = DiffStats::from_numstat_shortstat_z(output);
+
+        assert_eq!(stats.files.len(), 2);
+        assert_eq!(stats.files[0].old_path, Some(PathBuf::from("a.txt")));
+        assert!(stats.files[1].is_binary());
+        assert_eq!((stats.files_changed, stats.insertions, stats.deletions), (2, 1, 0));
+
+        assert_eq!(DiffStats::from_numstat_shortstat_z(""), DiffStats::default());
+    }
+
+    #[test]
+    fn test_status_branch_tracking_parsing() {
+        let output = "# branch.oid 1234567890abcdef1234567890abcdef12345678\0\
+                      # branch.head feature/x\0\
+                      # branch.upstream origin/feature/x\0\
+                      # branch.ab +2 -5\0\
+                      ? new.txt\0";
+        let status = StatusResult::from_porcelain_v2_z(output);
+
+        assert_eq!(status.branch.unwrap().to_string(), "feature/x");
+        assert_eq!(status.head_commit.unwrap().to_string(), "1234567890abcdef1234567890abcdef12345678");
+        assert_eq!(status.upstream.as_deref(), Some("origin/feature/x"));
+        assert_eq!((status.ahead, status.behind), (2, 5));
+        assert_eq!(status.files.len(), 1);
+
+        let detached = StatusResult::from_porcelain_v2_z("# branch.oid abcdef0\0# branch.head (detached)\0");
+        assert!(detached.branch.is_none());
+        assert_eq!(detached.head_commit.unwrap().to_string(), "abcdef0");
+        assert!(detached.upstream.is_none());
+        assert_eq!((detached.ahead, detached.behind), (0, 0));
+
+        let unborn = StatusResult::from_porcelain_v2_z("# branch.oid (initial)\0# branch.head main\0");
+        assert!(unborn.head_commit.is_none());
+    }
+
+    #[test]
+    fn test_status_index_and_worktree_separation() {
+        let output = "1 MM N... 100644 100644 100644 aaaaaaa bbbbbbb both.txt\0\
+                      1 .M N... 100644 100644 100644 aaaaaaa aaaaaaa unstaged.txt\0\
+                      1 A. N... 000000 100644 100644 0000000 bbbbbbb staged.txt\0\
+                      1 .D N... 100644 100644 000000 aaaaaaa aaaaaaa gone.txt\0\
+                      u UU N... 100644 100644 100644 100644 aaaaaaa bbbbbbb ccccccc conflict.txt\0\
+                      u AA N... 000000 100644 100644 100644 0000000 bbbbbbb ccccccc both-added.txt\0";
+        let files = StatusResult::from_porcelain_v2_z(output).files;
+
+        assert_eq!(files[0].index_status, StatusCode::Modified);
+        assert_eq!(files[0].worktree_status, StatusCode::Modified);
+        assert!(files[0].is_staged() && files[0].is_unstaged());
+        assert_eq!(files[0].status(), FileStatus::Modified);
+
+        assert_eq!(files[1].status(), FileStatus::Modified);
+        assert!(!files[1].is_staged() && files[1].is_unstaged());
+
+        assert_eq!(files[2].status(), FileStatus::Added);
+        assert!(files[2].is_staged() && !files[2].is_unstaged());
+
+        assert_eq!(files[3].status(), FileStatus::Deleted);
+        assert_eq!(files[4].status(), FileStatus::UpdatedButUnmerged);
+        assert_eq!(files[5].status(), FileStatus::UpdatedButUnmerged);
+        assert!(!files[5].is_staged());
+    }
+
+    #[test]
+    fn test_status_special_path_parsing() {
+        let output = concat!(
+            "1 .M N... 100644 100644 100644 aaaaaaa aaaaaaa dir with spaces/a b.txt\0",
+            "2 R. N... 100644 100644 100644 aaaaaaa aaaaaaa R87 new\nline \"name\".txt\0old name.txt\0",
+            "? tab\there.txt\0",
+        );
+        let files = StatusResult::from_porcelain_v2_z(output).files;
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].path, PathBuf::from("dir with spaces/a b.txt"));
+        assert_eq!(files[1].path, PathBuf::from("new\nline \"name\".txt"));
+        assert_eq!(files[1].original_path, Some(PathBuf::from("old name.txt")));
+        assert_eq!(files[1].similarity, Some(87));
+        assert_eq!(files[2].path, PathBuf::from("tab\there.txt"));
+    }
+
+    #[test]
+    fn test_log_record_parsing() {
+        let record = "deadbeefdeadbeefdeadbeefdeadbeefdeadbeef\0deadbee\0Jane Doe\0jane@example.com\0\
+                      1700000000\0John Roe\0john@example.com\0\
+                      1700000500\0abcdef0 1234567\0Fix the thing\0Longer explanation.\n\nSecond paragraph.\n\0\
+                      Fix the thing\n\nLonger explanation.\n\nSecond paragraph.\n\0\
+                      \0\n1\t1\tREADME.md\0";
+        let (commit, rest) = Commit::from_log_record(record).expect("record should parse");
+
+        assert_eq!(commit.short_hash.to_string(), "deadbee");
+        assert_eq!(commit.author_name, "Jane Doe");
+        assert_eq!(commit.author_email, "jane@example.com");
+        assert_eq!(commit.timestamp, 1_700_000_000);
+        assert_eq!(commit.committer_name, "John Roe");
+        assert_eq!(commit.committer_timestamp, 1_700_000_500);
+        assert!(commit.is_committed_by_other());
+        assert_eq!(commit.signature, None);
+        assert_eq!(commit.parents.len(), 2);
+        assert_eq!(commit.subject, "Fix the thing");
+        assert_eq!(commit.body, "Longer explanation.\n\nSecond paragraph.");
+        assert_eq!(
+            commit.message,
+            "Fix the thing\n\nLonger explanation.\n\nSecond paragraph."
+        );
+        assert_eq!(rest, "\0\n1\t1\tREADME.md\0");
+    }
+
+    #[test]
+    fn test_unified_patch_parsing() {
+        let patch = concat!(
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            "index 1111111..2222222 100644\n",
+            "--- a/src/lib.rs\n",
+            "+++ b/src/lib.rs\n",
+            "@@ -1,3 +1,3 @@ fn main()\n",
+            " keep\n",
+            "-old\n",
+            "+new\n",
+            "--- not a header\n",
+            "\\ No newline at end of file\n",
+            "diff --git a/old name.txt b/new name.txt\n",
+            "similarity index 90%\n",
+            "rename from old name.txt\n",
+            "rename to new name.txt\n",
+            "diff --git a/run.sh b/run.sh\n",
+            "old mode 100644\n",
+            "new mode 100755\n",
+            "diff --git \"a/caf\\303\\251.bin\" \"b/caf\\303\\251.bin\"\n",
+            "new file mode 100644\n",
+            "Binary files /dev/null and \"b/caf\\303\\251.bin\" differ\n",
+        );
+        let result = DiffResult::from_patch(patch, false);
+        assert_eq!(result.files.len(), 4);
+
+        let lib = &result.files[0];
+        assert_eq!(lib.path, PathBuf::from("src/lib.rs"));
+        assert_eq!((lib.old_kind, lib.new_kind), (Some(EntryKind::Regular), Some(EntryKind::Regular)));
+        assert_eq!((lib.added_lines, lib.removed_lines), (1, 2));
+        let hunk = &lib.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines), (1, 3, 1, 3));
+        assert_eq!(hunk.lines.len(), 4);
+        assert_eq!(hunk.lines[2].content, "new");
+        assert_eq!(hunk.lines[3].line_type, DiffLineType::Removed);
+        assert_eq!(hunk.lines[3].content, "-- not a header");
+
+        let renamed = &result.files[1];
+        assert_eq!(renamed.path, PathBuf::from("new name.txt"));
+        assert_eq!(renamed.old_path, Some(PathBuf::from("old name.txt")));
+        assert_eq!(renamed.similarity, Some(90));
+        assert!(renamed.hunks.is_empty());
+
+        let script = &result.files[2];
+        assert_eq!(script.path, PathBuf::from("run.sh"));
+        assert_eq!(script.old_mode.as_deref(), Some("100644"));
+        assert_eq!(script.new_mode.as_deref(), Some("100755"));
+        assert_eq!((script.old_kind, script.new_kind), (Some(EntryKind::Regular), Some(EntryKind::Executable)));
+
+        let binary = &result.files[3];
+        assert_eq!(binary.path, PathBuf::from("caf\u{e9}.bin"));
+        assert!(binary.is_binary);
+        assert_eq!(binary.new_mode.as_deref(), Some("100644"));
+        assert_eq!((binary.old_kind, binary.new_kind), (None, Some(EntryKind::Regular)));
+    }
+
+    #[test]
+    fn test_grep_output_parsing() {
+        let output = "v1.0:src/a b.rs\x0012\x00let x = 1; // a\0b\nv1.0:README\x003\x00x\n";
+        let matches = GrepMatch::from_grep_null(output, Some("v1.0"));
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].path, PathBuf::from("src/a b.rs"));
+        assert_eq!(matches[0].line_number, 12);
+        assert_eq!(matches[0].content, "let x = 1; // a\0b");
+        assert_eq!(matches[1].path, PathBuf::from("README"));
+
+        // Paths are verbatim, so they may contain newlines.
+        let matches = GrepMatch::from_grep_null("two\nlines.txt\x001\x00x\nb\x002\x00y\n", None);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].path, PathBuf::from("two\nlines.txt"));
+        assert_eq!((matches[1].line_number, matches[1].content.as_str()), (2, "y"));
+    }
+
+    #[test]
+    fn test_word_diff_parsing() {
+        let patch = concat!(
+            "diff --git a/f b/f\n",
+            "--- a/f\n",
+            "+++ b/f\n",
+            "@@ -1,3 +1,2 @@\n",
+            " hello \n",
+            "+there\n",
+            "  world\n",
+            "~\n",
+            " second line\n",
+            "~\n",
+            "-third\n",
+            "~\n",
+        );
+        let file = &DiffResult::from_patch(patch, true).files[0];
+        assert_eq!((file.added_lines, file.removed_lines), (1, 1));
+
+        let lines = &file.hunks[0].lines;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].line_type, DiffLineType::Added);
+        assert_eq!(lines[0].content, "hello there world");
+        assert_eq!(
+            lines[0].spans[1],
+            DiffSpan { content: "there".to_string(), span_type: DiffLineType::Added }
+        );
+        assert_eq!(lines[1].line_type, DiffLineType::Context);
+        assert_eq!(lines[2].line_type, DiffLineType::Removed);
+        assert_eq!(lines[2].content, "third");
+    }
+
+    #[test]
+    fn test_parse_check_attr_z() {
+        let output = "a.png\0binary\0set\0a.png\0text\0unset\0a.txt\0eol\0crlf\0a.txt\0filter\0unspecified\0";
+        let results = AttrResult::from_check_attr_z(output);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].value, AttrValue::Set);
+        assert_eq!(results[1].value, AttrValue::Unset);
+        assert_eq!(results[2].path, PathBuf::from("a.txt"));
+        assert_eq!(results[2].value.value(), Some("crlf"));
+        assert!(!results[3].value.is_set());
+    }
+
+    #[test]
+    fn test_parse_lfs_ls_files_long() {
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        let output = format!("{} * assets/logo one.png\n{} - video.mp4\n", oid, oid);
+        let files = LfsFile::from_ls_files_long(&output);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].oid, oid);
+        assert_eq!(files[0].path, PathBuf::from("assets/logo one.png"));
+        assert!(files[0].downloaded);
+        assert!(!files[1].downloaded);
+    }
+
+    #[test]
+    fn test_parse_submodule_status() {
+        let output = concat!(
+            " 4d7a214614ab2935c943f9e0ff69d22eadbb8f32 libs/core (v1.2.0)\n",
+            "+5e8b325725bc3a46d054f0a1ef7ae33fbeccf043 libs/with space (heads/main)\n",
+            "-6f9c436836cd4b57e165a1b2fa8bf44acfddf154 vendor/unused\n",
+        );
+        let subs = SubmoduleStatus::from_submodule_status(output);
+        assert_eq!(subs.len(), 3);
+        assert_eq!(subs[0].state, SubmoduleState::UpToDate);
+        assert_eq!(subs[0].describe.as_deref(), Some("v1.2.0"));
+        assert_eq!(subs[1].state, SubmoduleState::CommitChanged);
+        assert_eq!(subs[1].path, PathBuf::from("libs/with space"));
+        assert_eq!(subs[2].state, SubmoduleState::Uninitialized);
+        assert_eq!(subs[2].describe, None);
+    }
+
+    #[test]
+    fn test_parse_stash_list() {
+        let output = concat!(
+            "stash@{0}\0On main: half-done refactor\n",
+            "stash@{1}\0WIP on feature/x: 301b93d Add parser\n",
+            "stash@{2}\0WIP on (no branch): 301b93d Add parser\n",
+        );
+        let stashes = StashEntry::from_stash_list(output);
+        assert_eq!(stashes.len(), 3);
+        assert_eq!(stashes[0].reference.index(), 0);
+        assert_eq!(stashes[0].branch.as_deref(), Some("main"));
+        assert_eq!(stashes[0].message, "half-done refactor");
+        assert_eq!(stashes[1].branch.as_deref(), Some("feature/x"));
+        assert_eq!(stashes[1].message, "301b93d Add parser");
+        assert_eq!(stashes[2].branch, None);
+    }
+
+    #[test]
+    fn test_parse_range_diff() {
+        let a = "0b8ff0fafea0f9850980dccf6287ba24ecaeacaa";
+        let b = "fa8f43db22e8ce4d0358038000989767e1d82916";
+        let c = "a3e6d3be1aeef1be033f0b98b766f517e9c14562";
+        let dashes = "-".repeat(40);
+        let output = format!(
+            concat!(
+                " 1:  {a} =  1:  {a} add b\n",
+                " 2:  {b} <  -:  {dashes} add  c\n",
+                " -:  {dashes} >  2:  {c} add e\n",
+                "10:  {b} ! 11:  {c} add f\n",
+                "    @@ f (new)\n",
+                "    -+fifteen\n",
+                "    ++15\n",
+            ),
+            a = a,
+            b = b,
+            c = c,
+            dashes = dashes
+        );
+        let entries = RangeDiffEntry::from_range_diff(&output);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].status, RangeDiffStatus::Unchanged);
+        assert_eq!((entries[0].old_position, entries[0].new_position), (Some(1), Some(1)));
+        assert_eq!(entries[0].old_commit.as_ref().map(ToString::to_string).as_deref(), Some(a));
+        assert_eq!(entries[1].status, RangeDiffStatus::Removed);
+        assert_eq!((entries[1].new_position, entries[1].new_commit.clone()), (None, None));
+        assert_eq!(entries[1].subject, "add  c");
+        assert_eq!(entries[2].status, RangeDiffStatus::Added);
+        assert_eq!((entries[2].old_position, entries[2].old_commit.clone()), (None, None));
+        assert_eq!(entries[3].status, RangeDiffStatus::Changed);
+        assert_eq!((entries[3].old_position, entries[3].new_position), (Some(10), Some(11)));
+        assert_eq!(entries[3].patch, "@@ f (new)\n-+fifteen\n++15\n");
+        assert!(entries[0].patch.is_empty());
+    }
+
+    #[test]
+    fn test_parse_cherry() {
+        let output = concat!(
+            "- 0b8ff0fafea0f9850980dccf6287ba24ecaeacaa\n",
+            "+ fa8f43db22e8ce4d0358038000989767e1d82916\n",
+        );
+        let marks: Vec<_> = CherryMark::from_cherry(output).into_iter().map(|(mark, _)| mark).collect();
+        assert_eq!(marks, [CherryMark::Equivalent, CherryMark::Missing]);
+    }
+
+    #[test]
+    fn test_parse_sync_status() {
+        let upstream = "refs/remotes/origin/main";
+        assert_eq!(SyncStatus::from_upstream_track("", ""), SyncStatus::NoUpstream);
+        assert_eq!(SyncStatus::from_upstream_track(upstream, "gone"), SyncStatus::UpstreamGone);
+        assert_eq!(SyncStatus::from_upstream_track(upstream, ""), SyncStatus::UpToDate);
+        assert_eq!(SyncStatus::from_upstream_track(upstream, "ahead 2"), SyncStatus::Ahead(2));
+        assert_eq!(SyncStatus::from_upstream_track(upstream, "behind 3"), SyncStatus::Behind(3));
+        let diverged = SyncStatus::from_upstream_track(upstream, "ahead 1, behind 12");
+        assert_eq!(diverged, SyncStatus::Diverged(1, 12));
+        assert!(diverged.needs_push() && diverged.needs_pull());
+    }
+
+    #[test]
+    fn test_repo_state_detect() {
+        let detect = |markers: &[&str]| RepoState::detect(|path| markers.contains(&path));
+        assert_eq!(detect(&[]), RepoState::Clean);
+        assert_eq!(detect(&["BISECT_LOG"]), RepoState::Bisecting);
+        assert_eq!(detect(&["REVERT_HEAD"]), RepoState::Reverting);
+        assert_eq!(detect(&["rebase-merge"]), RepoState::Rebasing);
+        assert_eq!(detect(&["rebase-apply"]), RepoState::Rebasing);
+        assert_eq!(detect(&["rebase-apply", "rebase-apply/applying"]), RepoState::ApplyingPatches);
+        // A conflicted cherry-pick during a bisect must be resolved first.
+        assert_eq!(detect(&["BISECT_LOG", "CHERRY_PICK_HEAD"]), RepoState::CherryPicking);
+        assert!(!RepoState::Clean.is_in_progress());
+        assert!(RepoState::Merging.is_in_progress());
+    }
+
+    #[test]
+    fn test_fetch_result_from_fetch_output() {
+        let old = "01b4f673210f8b015ba9d76606892b8526c5888e";
+        let new = "02fa59661938fca9e4cb89492be0811bff4acee3";
+        let output = format!(
+            "From /tmp/up\n \
+             - [deleted]         (none)     -> origin/gone\n   \
+             {old}..{new}  main       -> origin/main\n \
+             + {new}...{old}  topic      -> origin/topic  (forced update)\n \
+             * [new branch]      feature    -> origin/feature\n \
+             * [new tag]         v1         -> v1\n \
+             ! [rejected]        v0         -> v0  (would clobber existing tag)\n"
+        );
+        let result = FetchResult::from_fetch_output(&output);
+        let statuses: Vec<_> = result.refs.iter().map(|r| (r.status.clone(), r.local_ref.as_str())).collect();
+        assert_eq!(
+            statuses,
+            [
+                (FetchRefStatus::Pruned, "origin/gone"),
+                (FetchRefStatus::FastForward, "origin/main"),
+                (FetchRefStatus::Forced, "origin/topic"),
+                (FetchRefStatus::NewBranch, "origin/feature"),
+                (FetchRefStatus::NewTag, "v1"),
+                (FetchRefStatus::Rejected("would clobber existing tag".to_string()), "v0"),
+            ]
+        );
+        assert_eq!(result.refs[0].remote_ref, None);
+        assert_eq!(result.refs[1].remote_ref.as_deref(), Some("main"));
+        assert_eq!(result.refs[1].old.as_ref().map(|h| h.to_string()).as_deref(), Some(old));
+        assert_eq!(result.refs[1].new.as_ref().map(|h| h.to_string()).as_deref(), Some(new));
+        assert_eq!(result.refs[2].new.as_ref().map(|h| h.to_string()).as_deref(), Some(old));
+        assert_eq!(result.updated().count(), 2);
+        assert_eq!(result.forced().count(), 1);
+        assert_eq!(result.new_branches().count(), 1);
+        assert_eq!(result.pruned().count(), 1);
+        assert!(!result.is_empty());
+        assert!(FetchResult::from_fetch_output("").is_empty());
+    }
+
+    #[test]
+    fn test_push_result_from_porcelain() {
+        let old = "53bfe2624679caa581f93579a71cb17f307adb2e";
+        let new = "9ccfb3430e4161e18c526eae7a641638b5b8adc3";
+        let output = format!(
+            "To /tmp/up.git\n\
+             \x20\tHEAD:refs/heads/main\t{old}..{new}\n\
+             +\trefs/heads/x:refs/heads/x\t{new}...{old} (forced update)\n\
+             -\t:refs/heads/y\t[deleted]\n\
+             *\tHEAD:refs/heads/topic\t[new branch]\n\
+             =\trefs/heads/z:refs/heads/z\t[up to date]\n\
+             !\trefs/heads/w:refs/heads/w\t[rejected] (non-fast-forward)\n\
+             Done\n"
+        );
+        let result = PushResult::from_porcelain(&output);
+        let statuses: Vec<_> = result.refs.iter().map(|r| (r.status.clone(), r.remote_ref.as_str())).collect();
+        assert_eq!(
+            statuses,
+            [
+                (PushStatus::FastForward, "refs/heads/main"),
+                (PushStatus::Forced, "refs/heads/x"),
+                (PushStatus::Deleted, "refs/heads/y"),
+                (PushStatus::New, "refs/heads/topic"),
+                (PushStatus::UpToDate, "refs/heads/z"),
+                (PushStatus::Rejected("non-fast-forward".to_string()), "refs/heads/w"),
+            ]
+        );
+        assert_eq!(result.refs[0].local_ref.as_deref(), Some("HEAD"));
+        assert_eq!(result.refs[0].old.as_ref().map(|h| h.to_string()).as_deref(), Some(old));
+        assert_eq!(result.refs[1].new.as_ref().map(|h| h.to_string()).as_deref(), Some(old));
+        assert_eq!(result.refs[2].local_ref, None);
+        assert_eq!(result.rejected().count(), 1);
+        assert!(!result.is_up_to_date());
+    }
+
+    #[test]
+    fn test_fsck_report_requires_clean_exit() {
+        assert!(FsckReport::from_fsck("dangling blob abc\n", "", 0).is_healthy());
+        // A non-zero exit is unhealthy even when nothing recognisable was printed.
+        assert!(!FsckReport::from_fsck("", "", 2).is_healthy());
+    }
+}
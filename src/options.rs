@@ -0,0 +1,414 @@
+//! Provides option structs used to configure Git operations.
+
+use crate::types::{BranchName, Pathspec, Remote};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Options controlling which commits `Repository::rev_list` returns.
+#[derive(Debug, Clone, Default)]
+pub struct RevListOptions {
+    /// Follow only the first parent of merge commits (`--first-parent`).
+    pub first_parent: bool,
+    /// Only include merge commits (`--merges`).
+    pub merges: bool,
+    /// Exclude merge commits (`--no-merges`).
+    pub no_merges: bool,
+    /// Limit the number of commits returned (`--max-count=<n>`).
+    pub max_count: Option<usize>,
+    /// Only include commits touching these paths (`-- <pathspec>...`).
+    pub paths: Vec<Pathspec>,
+}
+
+/// Options for `Repository::rebase_onto`.
+#[derive(Debug, Clone, Default)]
+pub struct RebaseOptions {
+    /// Stash local changes before the rebase and reapply them afterwards (`--autostash`).
+    pub autostash: bool,
+    /// Recreate merge commits instead of flattening them (`--rebase-merges`).
+    pub rebase_merges: bool,
+    /// Keep commits that become empty (`--keep-empty`).
+    pub keep_empty: bool,
+    /// Options passed to the merge strategy (`--strategy-option=<opt>`), e.g. `"theirs"`.
+    pub strategy_options: Vec<String>,
+}
+
+/// Options for `Repository::ls_files`, selecting which files to list.
+///
+/// The selections combine; with none set, Git lists the cached (tracked) files.
+#[derive(Debug, Clone, Default)]
+pub struct LsFilesOptions {
+    /// Files in the index (`--cached`).
+    pub cached: bool,
+    /// Untracked files (`--others`).
+    pub others: bool,
+    /// Tracked files deleted from the working tree (`--deleted`).
+    pub deleted: bool,
+    /// Tracked files modified in the working tree, including deletions (`--modified`).
+    pub modified: bool,
+    /// Only ignored files; combine with `others` and `exclude_standard` (`--ignored`).
+    pub ignored: bool,
+    /// Apply the standard ignore rules (`--exclude-standard`).
+    pub exclude_standard: bool,
+    /// Report wholly untracked directories once instead of file by file (`--directory`).
+    pub directory: bool,
+}
+
+/// Options for `Repository::file_history`.
+#[derive(Debug, Clone, Default)]
+pub struct FileHistoryOptions {
+    /// The revision to start walking from; defaults to `HEAD`.
+    pub revision: Option<String>,
+    /// Limit the number of commits returned (`--max-count=<n>`).
+    pub max_count: Option<usize>,
+    /// Only include commits after this date (`--since=<date>`), e.g. `"2 weeks ago"`.
+    pub since: Option<String>,
+    /// Only include commits before this date (`--until=<date>`).
+    pub until: Option<String>,
+}
+
+/// The pattern syntax used by `Repository::grep`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrepPatternKind {
+    /// POSIX basic regular expressions (the default).
+    #[default]
+    Basic,
+    /// POSIX extended regular expressions (`--extended-regexp`).
+    Extended,
+    /// Perl-compatible regular expressions (`--perl-regexp`); requires Git built with PCRE.
+    Perl,
+    /// A literal string (`--fixed-strings`).
+    Fixed,
+}
+
+/// Options for `Repository::grep`.
+#[derive(Debug, Clone, Default)]
+pub struct GrepOptions {
+    /// Match case-insensitively (`--ignore-case`).
+    pub ignore_case: bool,
+    /// How the pattern is interpreted.
+    pub pattern_kind: GrepPatternKind,
+    /// Only search these paths (`-- <pathspec>...`).
+    pub paths: Vec<Pathspec>,
+    /// Search this revision instead of the working tree.
+    pub revision: Option<String>,
+}
+
+/// The granularity at which `Repository::diff` reports changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffMode {
+    /// Whole added and removed lines (the default).
+    #[default]
+    Lines,
+    /// Added and removed words within each line (`--word-diff=porcelain`).
+    Words,
+    /// Added and removed characters within each line
+    /// (`--word-diff=porcelain` with a regex matching one UTF-8 character).
+    Chars,
+}
+
+/// Options for `Repository::diff`.
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// The granularity of the reported changes.
+    pub mode: DiffMode,
+    /// Only include changes to these paths (`-- <pathspec>...`).
+    pub paths: Vec<Pathspec>,
+    /// Detect renames at this minimum similarity percentage (`--find-renames=<n>%`).
+    pub detect_renames: Option<u8>,
+    /// Detect copies at this minimum similarity percentage (`--find-copies=<n>%`).
+    pub detect_copies: Option<u8>,
+}
+
+/// Options for `Repository::init_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    /// The branch the first commit will create, instead of `init.defaultBranch`
+    /// or `master` (`--initial-branch=<name>`).
+    pub initial_branch: Option<BranchName>,
+    /// Create a bare repository, without a working tree (`--bare`).
+    pub bare: bool,
+    /// Copy hooks and other files from this template directory (`--template=<dir>`).
+    pub template_dir: Option<PathBuf>,
+    /// Put the Git directory here and leave a `.git` file pointing to it in the
+    /// working tree (`--separate-git-dir=<dir>`).
+    pub separate_git_dir: Option<PathBuf>,
+}
+
+/// Which objects a partial clone leaves out (`--filter=<spec>`). Git fetches
+/// missing objects from the promisor remote the clone came from when a command
+/// needs them; `Repository::fetch_missing_objects` fetches them in bulk.
+///
+/// The server must allow filters (`uploadpack.allowFilter`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloneFilter {
+    /// Leave out every file's contents (`blob:none`), keeping all commits and trees.
+    BlobNone,
+    /// Leave out files larger than this many bytes (`blob:limit=<n>`).
+    BlobLimit(u64),
+    /// Leave out all trees and files (`tree:0`), keeping only commits; suited to
+    /// history queries that never look at the tree.
+    TreeNone,
+    /// Any other filter specification Git accepts, e.g. `combine:blob:none+tree:3`.
+    Spec(String),
+}
+
+impl fmt::Display for CloneFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloneFilter::BlobNone => f.write_str("blob:none"),
+            CloneFilter::BlobLimit(bytes) => write!(f, "blob:limit={}", bytes),
+            CloneFilter::TreeNone => f.write_str("tree:0"),
+            CloneFilter::Spec(spec) => f.write_str(spec),
+        }
+    }
+}
+
+/// Options for `Repository::clone_with`.
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    /// Check out this branch instead of the remote's default branch (`--branch=<name>`).
+    pub branch: Option<BranchName>,
+    /// Limit history to this many commits (`--depth=<n>`).
+    pub depth: Option<u32>,
+    /// Make a partial clone that leaves out some objects (`--filter=<spec>`).
+    pub filter: Option<CloneFilter>,
+    /// Do not check out any files (`--no-checkout`), e.g. to fetch only the
+    /// objects of a few paths of a partial clone.
+    pub no_checkout: bool,
+}
+
+/// Options for `Repository::commit_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct CommitOptions {
+    /// Stage modified and deleted tracked files before committing (`--all`).
+    pub all: bool,
+    /// Allow a commit that records no changes (`--allow-empty`).
+    pub allow_empty: bool,
+    /// Skip the `pre-commit` and `commit-msg` hooks (`--no-verify`).
+    pub no_verify: bool,
+    /// Do not sign the commit, overriding `commit.gpgSign` (`--no-gpg-sign`).
+    pub no_gpg_sign: bool,
+    /// Report what would be committed without committing (`--dry-run`).
+    pub dry_run: bool,
+}
+
+/// Selects and orders the branches returned by `Repository::list_branches_filtered`.
+///
+/// With the defaults, lists all local branches sorted by name.
+#[derive(Debug, Clone, Default)]
+pub struct BranchFilter {
+    /// List remote-tracking branches instead of local ones (`--remotes`).
+    pub remotes: bool,
+    /// Only branches whose tip is reachable from this commit (`--merged=<commit>`).
+    pub merged: Option<String>,
+    /// Only branches whose tip is not reachable from this commit (`--no-merged=<commit>`).
+    pub no_merged: Option<String>,
+    /// Only branches that contain this commit (`--contains=<commit>`).
+    pub contains: Option<String>,
+    /// The field to sort by, prefixed with `-` for descending order, e.g.
+    /// `"-committerdate"` for the most recently updated first (`--sort=<key>`).
+    pub sort: Option<String>,
+    /// Only branches matching one of these shell wildcard patterns, e.g. `"feature/*"`.
+    pub patterns: Vec<String>,
+}
+
+/// Options for `Repository::add_with`.
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// The paths to add; empty means the whole tree with `update` or `all`.
+    pub paths: Vec<Pathspec>,
+    /// Only stage changes to tracked files, including deletions (`--update`).
+    pub update: bool,
+    /// Stage all changes, including new and deleted files (`--all`).
+    pub all: bool,
+    /// Record new files without their contents, so they show up in `git diff`
+    /// and are picked up by `commit -a` (`--intent-to-add`).
+    pub intent_to_add: bool,
+    /// Also add files that are ignored (`--force`).
+    pub force: bool,
+    /// Report what would be staged without changing the index (`--dry-run`).
+    pub dry_run: bool,
+}
+
+/// Options for `Repository::remove_with`.
+#[derive(Debug, Clone, Default)]
+pub struct RemoveOptions {
+    /// The paths to remove.
+    pub paths: Vec<Pathspec>,
+    /// Remove files even if they have changes (`--force`).
+    pub force: bool,
+    /// Only remove the paths from the index, keeping the files (`--cached`).
+    pub cached: bool,
+    /// Remove directories and their contents (`-r`).
+    pub recursive: bool,
+    /// Report what would be removed without removing it (`--dry-run`).
+    pub dry_run: bool,
+}
+
+/// Options for `Repository::clean`.
+///
+/// With the defaults, deletes the untracked files that are not ignored,
+/// leaving untracked directories alone.
+#[derive(Debug, Clone, Default)]
+pub struct CleanOptions {
+    /// Limit cleaning to these paths; empty means the whole tree.
+    pub paths: Vec<Pathspec>,
+    /// Also delete untracked directories (`-d`).
+    pub directories: bool,
+    /// Also delete ignored files (`-x`).
+    pub ignored: bool,
+    /// Delete only ignored files, e.g. to rebuild from scratch (`-X`).
+    pub only_ignored: bool,
+    /// Report what would be deleted without deleting it (`--dry-run`).
+    pub dry_run: bool,
+}
+
+/// Options for `Repository::stash_push`.
+#[derive(Debug, Clone, Default)]
+pub struct StashOptions {
+    /// Also stash untracked files (`--include-untracked`).
+    pub include_untracked: bool,
+    /// Leave staged changes in the index and working tree (`--keep-index`).
+    pub keep_index: bool,
+    /// Also stash untracked and ignored files (`--all`).
+    pub all: bool,
+    /// A description for the stash instead of the default `WIP on <branch>` (`--message`).
+    pub message: Option<String>,
+}
+
+/// Options for `Repository::push_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct PushOptions {
+    /// Record the pushed branch as the upstream of the local branch (`--set-upstream`).
+    pub set_upstream: bool,
+    /// Skip the `pre-push` hook (`--no-verify`).
+    pub no_verify: bool,
+    /// Do not sign the push, overriding `push.gpgSign` (`--no-signed`).
+    pub no_signed: bool,
+    /// Report what would be pushed without sending anything (`--dry-run`).
+    pub dry_run: bool,
+}
+
+/// Options for `Repository::fetch`.
+///
+/// With the defaults, fetches from the current branch's remote (usually `origin`).
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+    /// The remote to fetch from; `None` for the default remote. Ignored with `all`.
+    pub remote: Option<Remote>,
+    /// Fetch from every configured remote (`--all`).
+    pub all: bool,
+    /// Delete remote-tracking refs whose branch no longer exists on the remote (`--prune`).
+    pub prune: bool,
+    /// Also delete local tags that no longer exist on the remote; needs `prune` (`--prune-tags`).
+    pub prune_tags: bool,
+    /// Fetch all tags, not just those pointing into fetched history (`--tags`).
+    pub tags: bool,
+    /// Limit history to this many commits from each tip (`--depth=<n>`).
+    pub depth: Option<u32>,
+    /// Explicit refspecs such as `"refs/heads/main:refs/remotes/origin/main"`.
+    /// Only used together with `remote`.
+    pub refspecs: Vec<String>,
+    /// Report what would be fetched without changing any ref (`--dry-run`).
+    pub dry_run: bool,
+}
+
+/// Options for `Repository::repack`.
+#[derive(Debug, Clone, Default)]
+pub struct RepackOptions {
+    /// Pack everything into a single pack instead of only loose objects (`-a`).
+    pub all: bool,
+    /// Remove packs made redundant by the new pack (`-d`).
+    pub delete_redundant: bool,
+    /// Write a reachability bitmap to speed up clones and fetches; needs `all`
+    /// (`--write-bitmap-index`).
+    pub write_bitmap: bool,
+    /// Maximum delta chain depth (`--depth=<n>`).
+    pub depth: Option<u32>,
+    /// Number of objects considered as delta bases (`--window=<n>`).
+    pub window: Option<u32>,
+}
+
+/// A task of `git maintenance run`, as passed to `Repository::maintenance_run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaintenanceTask {
+    /// Full garbage collection (`gc`); slow on large repositories.
+    Gc,
+    /// Update the commit-graph file incrementally (`commit-graph`).
+    CommitGraph,
+    /// Fetch every remote into `refs/prefetch/`, leaving remote-tracking
+    /// branches untouched (`prefetch`).
+    Prefetch,
+    /// Pack loose objects (`loose-objects`).
+    LooseObjects,
+    /// Consolidate small pack files through the multi-pack-index (`incremental-repack`).
+    IncrementalRepack,
+    /// Pack loose refs into `packed-refs` (`pack-refs`).
+    PackRefs,
+}
+
+impl fmt::Display for MaintenanceTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MaintenanceTask::Gc => "gc",
+            MaintenanceTask::CommitGraph => "commit-graph",
+            MaintenanceTask::Prefetch => "prefetch",
+            MaintenanceTask::LooseObjects => "loose-objects",
+            MaintenanceTask::IncrementalRepack => "incremental-repack",
+            MaintenanceTask::PackRefs => "pack-refs",
+        })
+    }
+}
+
+/// Options for `Repository::fsck`.
+#[derive(Debug, Clone, Default)]
+pub struct FsckOptions {
+    /// Report every unreachable object, not just dangling ones (`--unreachable`).
+    pub unreachable: bool,
+    /// Do not report dangling objects (`--no-dangling`).
+    pub no_dangling: bool,
+    /// Only check connectivity, not object contents; much faster (`--connectivity-only`).
+    pub connectivity_only: bool,
+    /// Also flag objects with unusual but valid content, such as odd file modes (`--strict`).
+    pub strict: bool,
+}
+
+/// Options for `Repository::apply_patch`.
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// Fall back to a three-way merge if the patch does not apply cleanly (`--3way`).
+    /// This also updates the index.
+    pub three_way: bool,
+    /// Only check whether the patch applies, without changing anything (`--check`).
+    pub check: bool,
+}
+
+/// A single entry of an interactive rebase plan for `Repository::rebase_interactive`.
+///
+/// Steps are applied in the order given, so reordering the plan reorders the commits.
+/// Commits that do not appear in the plan are dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebaseStep {
+    /// Keep the commit unchanged (`pick`).
+    Pick(String),
+    /// Keep the commit but replace its message.
+    Reword { commit: String, message: String },
+    /// Meld the commit into the previous one, concatenating both messages (`squash`).
+    Squash(String),
+    /// Meld the commit into the previous one, keeping only the previous message (`fixup`).
+    Fixup(String),
+    /// Remove the commit (`drop`).
+    Drop(String),
+}
+
+/// Which side of a conflicted merge to keep in `Repository::resolve_conflict`.
+///
+/// During a rebase the sides are swapped relative to a merge: `Ours` is the branch
+/// being rebased onto and `Theirs` is the commit being replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSide {
+    /// Keep the version from the current branch (`--ours`).
+    Ours,
+    /// Keep the version from the branch being merged in (`--theirs`).
+    Theirs,
+}
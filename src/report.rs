@@ -0,0 +1,210 @@
+//! Serializes results into a stable, documented JSON schema for tools that embed GitPilot.
+//!
+//! Unlike the `serde` derives on the models, which follow the Rust field layout and may
+//! change between releases, the documents produced here only change together with
+//! [`SCHEMA_VERSION`]. Every document carries that version in `schema_version`.
+//!
+//! # Status (`StatusResult::to_json`)
+//! ```text
+//! { "schema_version": 1, "branch": "main" | null, "upstream": "origin/main" | null,
+//!   "ahead": 0, "behind": 0, "clean": true,
+//!   "in_progress": { "merge": false, "rebase": false, "cherry_pick": false },
+//!   "files": [ { "path": "src/lib.rs", "original_path": null, "similarity": null,
+//!                "index": "modified", "worktree": "unmodified" } ] }
+//! ```
+//! Status codes are `unmodified`, `modified`, `type_changed`, `added`, `deleted`,
+//! `renamed`, `copied`, `unmerged`, `untracked` and `ignored`.
+//!
+//! # Log (`LogResult::to_json`)
+//! ```text
+//! { "schema_version": 1,
+//!   "commits": [ { "hash": "...", "short_hash": "...",
+//!                  "author": { "name": "...", "email": "...", "timestamp": 1700000000 },
+//!                  "committer": { "name": "...", "email": "...", "timestamp": 1700000000 },
+//!                  "subject": "...", "body": "...", "parents": ["..."],
+//!                  "signature": "good" | null } ] }
+//! ```
+//! Timestamps are Unix seconds. Signature values are `good`, `bad`,
+//! `good_unknown_validity`, `expired_signature`, `expired_key`, `revoked_key`,
+//! `cannot_check` and `unsigned`; `null` means the signature was not checked.
+//!
+//! # Diff (`DiffResult::to_json`)
+//! ```text
+//! { "schema_version": 1,
+//!   "files": [ { "path": "...", "old_path": null, "similarity": null, "binary": false,
+//!                "old_mode": null, "new_mode": null, "added": 1, "removed": 0,
+//!                "hunks": [ { "old_start": 1, "old_lines": 1, "new_start": 1, "new_lines": 2,
+//!                             "lines": [ { "type": "added", "content": "...",
+//!                                          "spans": [ { "type": "added", "content": "..." } ] } ] } ] } ] }
+//! ```
+//! Line and span types are `context`, `added` and `removed`. `spans` is empty
+//! unless the diff was computed in word or character mode.
+//!
+//! Paths that are not valid UTF-8 are written lossily.
+
+use crate::models::{
+    Commit, DiffFile, DiffLine, DiffLineType, DiffResult, LogResult, SignatureStatus, StatusCode,
+    StatusEntry, StatusResult,
+};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// The version of the JSON schema produced by this module.
+pub const SCHEMA_VERSION: u32 = 1;
+
+impl StatusResult {
+    /// Serializes the status into the documented JSON schema.
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Serializes the status into the documented JSON schema as a `serde_json::Value`.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "schema_version": SCHEMA_VERSION,
+            "branch": self.branch.as_ref().map(|b| b.to_string()),
+            "upstream": self.upstream,
+            "ahead": self.ahead,
+            "behind": self.behind,
+            "clean": self.is_clean,
+            "in_progress": {
+                "merge": self.merging,
+                "rebase": self.rebasing,
+                "cherry_pick": self.cherry_picking,
+            },
+            "files": self.files.iter().map(status_entry_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl LogResult {
+    /// Serializes the commits into the documented JSON schema.
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Serializes the commits into the documented JSON schema as a `serde_json::Value`.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "schema_version": SCHEMA_VERSION,
+            "commits": self.commits.iter().map(commit_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl DiffResult {
+    /// Serializes the diff into the documented JSON schema.
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Serializes the diff into the documented JSON schema as a `serde_json::Value`.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "schema_version": SCHEMA_VERSION,
+            "files": self.files.iter().map(diff_file_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn status_entry_json(entry: &StatusEntry) -> Value {
+    json!({
+        "path": path_json(&entry.path),
+        "original_path": entry.original_path.as_deref().map(path_json),
+        "similarity": entry.similarity,
+        "index": status_code_name(entry.index_status),
+        "worktree": status_code_name(entry.worktree_status),
+    })
+}
+
+fn commit_json(commit: &Commit) -> Value {
+    json!({
+        "hash": commit.hash.to_string(),
+        "short_hash": commit.short_hash.to_string(),
+        "author": {
+            "name": commit.author_name,
+            "email": commit.author_email,
+            "timestamp": commit.timestamp,
+        },
+        "committer": {
+            "name": commit.committer_name,
+            "email": commit.committer_email,
+            "timestamp": commit.committer_timestamp,
+        },
+        "subject": commit.subject,
+        "body": commit.body,
+        "parents": commit.parents.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+        "signature": commit.signature.map(signature_name),
+    })
+}
+
+fn diff_file_json(file: &DiffFile) -> Value {
+    json!({
+        "path": path_json(&file.path),
+        "old_path": file.old_path.as_deref().map(path_json),
+        "similarity": file.similarity,
+        "binary": file.is_binary,
+        "old_mode": file.old_mode,
+        "new_mode": file.new_mode,
+        "added": file.added_lines,
+        "removed": file.removed_lines,
+        "hunks": file.hunks.iter().map(|hunk| json!({
+            "old_start": hunk.old_start,
+            "old_lines": hunk.old_lines,
+            "new_start": hunk.new_start,
+            "new_lines": hunk.new_lines,
+            "lines": hunk.lines.iter().map(diff_line_json).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn diff_line_json(line: &DiffLine) -> Value {
+    json!({
+        "type": line_type_name(line.line_type),
+        "content": line.content,
+        "spans": line.spans.iter().map(|span| json!({
+            "type": line_type_name(span.span_type),
+            "content": span.content,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn path_json(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn status_code_name(code: StatusCode) -> &'static str {
+    match code {
+        StatusCode::Unmodified => "unmodified",
+        StatusCode::Modified => "modified",
+        StatusCode::TypeChanged => "type_changed",
+        StatusCode::Added => "added",
+        StatusCode::Deleted => "deleted",
+        StatusCode::Renamed => "renamed",
+        StatusCode::Copied => "copied",
+        StatusCode::Unmerged => "unmerged",
+        StatusCode::Untracked => "untracked",
+        StatusCode::Ignored => "ignored",
+    }
+}
+
+fn signature_name(signature: SignatureStatus) -> &'static str {
+    match signature {
+        SignatureStatus::Good => "good",
+        SignatureStatus::Bad => "bad",
+        SignatureStatus::GoodUnknownValidity => "good_unknown_validity",
+        SignatureStatus::ExpiredSignature => "expired_signature",
+        SignatureStatus::ExpiredKey => "expired_key",
+        SignatureStatus::RevokedKey => "revoked_key",
+        SignatureStatus::CannotCheck => "cannot_check",
+        SignatureStatus::Unsigned => "unsigned",
+    }
+}
+
+fn line_type_name(line_type: DiffLineType) -> &'static str {
+    match line_type {
+        DiffLineType::Context => "context",
+        DiffLineType::Added => "added",
+        DiffLineType::Removed => "removed",
+    }
+}
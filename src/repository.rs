@@ -1,15 +1,25 @@
 //! Provides the core Repository implementation.
 
+use crate::builder::{RepositoryBuilder, Settings};
+use crate::commands;
 use crate::error::GitError;
 // Import specific types for integration
-use crate::types::{BranchName, CommitHash, GitUrl, Remote, Result}; // Added CommitHash, Remote
+use crate::types::{BranchName, CommitHash, GitUrl, HookKind, Pathspec, Remote, Result, Stash, Tag}; // Added CommitHash, Remote
 use crate::models::*;
+use crate::options::*;
+use std::collections::HashSet;
 use std::env;
-use std::ffi::OsStr;
+use std::fs;
+use std::ffi::{OsStr, OsString};
 use std::io::ErrorKind; // Needed for GitNotFound check
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::ops::Deref;
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Output, Stdio};
 use std::str::{self, FromStr}; // Added FromStr for parsing
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 
 /// Represents a local Git repository located at a specific path.
@@ -17,7 +27,7 @@ use std::str::{self, FromStr}; // Added FromStr for parsing
 /// Provides methods to execute common Git commands within that repository context.
 #[derive(Debug, Clone)]
 pub struct Repository {
-    pub(crate) location: PathBuf,
+    pub(crate) location: Location,
 }
 
 impl Repository {
@@ -30,31 +40,131 @@ impl Repository {
     /// * `p` - The path to the local repository's root directory.
     pub fn new<P: AsRef<Path>>(p: P) -> Repository {
         Repository {
-            location: PathBuf::from(p.as_ref()),
+            location: Location::new(p.as_ref()),
         }
     }
 
+    /// Starts configuring a `Repository` that runs Git with its own executable,
+    /// environment, default remote, timeout or retry policy.
+    ///
+    /// # Arguments
+    /// * `p` - The path to the local repository's root directory.
+    pub fn builder<P: AsRef<Path>>(p: P) -> RepositoryBuilder {
+        RepositoryBuilder::new(p.as_ref().to_path_buf())
+    }
+
+    /// Returns the remote set with `RepositoryBuilder::default_remote`, if any.
+    pub fn default_remote(&self) -> Option<&Remote> {
+        self.location.settings.default_remote.as_ref()
+    }
+
     /// Clones a remote Git repository into a specified local path.
     ///
-    /// Equivalent to `git clone <url> <path>`.
+    /// Equivalent to `git clone -- <url> <path>`.
     ///
     /// # Arguments
-    /// * `url` - The URL of the remote repository.
+    /// * `url` - The URL of the remote repository. Local repositories can be cloned
+    ///   through `GitUrl::from_path` or a `file://` URL.
     /// * `p` - The target local path where the repository should be cloned.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn clone<P: AsRef<Path>>(url: GitUrl, p: P) -> Result<Repository> {
-        let p_ref = p.as_ref();
         let cwd = env::current_dir().map_err(|_| GitError::WorkingDirectoryInaccessible)?;
+        let target = normalize_location(p.as_ref());
+        commands::clone(&url, &target).run(&cwd)?; // Execute in CWD, cloning *into* p
+        Ok(Repository::new(target))
+    }
 
-        let args: Vec<&OsStr> = vec!["clone".as_ref(), url.as_ref(), p_ref.as_os_str()];
+    /// Clones a remote Git repository with options such as a branch, a depth or a
+    /// partial clone filter.
+    ///
+    /// Equivalent to `git clone [--branch=<name>] [--depth=<n>] [--filter=<spec>]
+    /// [--no-checkout] <url> <path>`.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the remote repository.
+    /// * `p` - The target local path where the repository should be cloned.
+    /// * `opts` - A `CloneOptions`; `filter` makes a partial clone, whose remote
+    ///   stays its promisor for the objects left out.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn clone_with<P: AsRef<Path>>(url: &GitUrl, p: P, opts: &CloneOptions) -> Result<Repository> {
+        let cwd = env::current_dir().map_err(|_| GitError::WorkingDirectoryInaccessible)?;
+        let target = normalize_location(p.as_ref());
+        commands::clone_with(url, &target, opts).run(&cwd)?;
+        Ok(Repository::new(target))
+    }
 
-        execute_git(cwd, args)?; // Execute in CWD, cloning *into* p
+    /// Creates a bare mirror of a repository: every ref (branches, tags, notes and
+    /// anything else) is copied, and later `remote_update` calls keep them identical
+    /// to the source, including deletions.
+    ///
+    /// Equivalent to `git clone --mirror -- <url> <path>`.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the repository to mirror.
+    /// * `p` - The target path for the bare mirror, conventionally ending in `.git`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn clone_mirror<P: AsRef<Path>>(url: &GitUrl, p: P) -> Result<Repository> {
+        let cwd = env::current_dir().map_err(|_| GitError::WorkingDirectoryInaccessible)?;
+        let target = normalize_location(p.as_ref());
+        let args: Vec<&OsStr> = vec!["clone".as_ref(), "--mirror".as_ref(), "--".as_ref(), url.as_ref(), target.as_os_str()];
+        execute_git(cwd, args)?;
+        Ok(Repository::new(target))
+    }
 
-        Ok(Repository {
-            location: PathBuf::from(p_ref),
-        })
+    /// Clones a repository into a directory that may already contain files, such
+    /// as a CI workspace restored from a cache, which `git clone` refuses to do.
+    ///
+    /// Files that the checkout provides are overwritten with their committed
+    /// contents; all other files are kept and show up as untracked.
+    ///
+    /// Equivalent to `git init`, `git remote add origin <url>`, `git fetch origin`
+    /// and `git checkout -f -B <branch> --track origin/<branch>`.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the repository to clone.
+    /// * `p` - The directory to clone into; it is created if it does not exist.
+    /// * `branch` - The branch to check out, or `None` for the remote's default
+    ///   branch, which is then recorded as `origin/HEAD` as `git clone` does.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the directory is already a repository with an
+    /// `origin` remote, or the branch does not exist (including `GitNotFound`).
+    /// On failure the Git directory is removed again, unless it existed before.
+    pub fn clone_into_existing<P: AsRef<Path>>(url: GitUrl, p: P, branch: Option<&BranchName>) -> Result<Repository> {
+        let location = normalize_location(p.as_ref());
+        let git_dir = location.join(".git");
+        let existed = fs::symlink_metadata(&git_dir).is_ok();
+        fs::create_dir_all(&location)?;
+        let repo = Repository::init(&location)?;
+        let cloned = (|| {
+            let origin = Remote::from_str("origin")?;
+            repo.add_remote(&origin, &url)?;
+            repo.fetch_remote(&origin)?;
+
+            let branch = match branch {
+                Some(branch) => branch.clone(),
+                None => {
+                    let branch = repo.default_branch(&origin)?;
+                    execute_git(&location, ["remote", "set-head", "origin", branch.as_ref()])?;
+                    branch
+                }
+            };
+            let upstream = format!("origin/{}", branch);
+            execute_git(&location, ["checkout", "-q", "-f", "-B", branch.as_ref(), "--track", &upstream])
+        })();
+        if let Err(error) = cloned {
+            if !existed {
+                let _ = fs::remove_dir_all(&git_dir);
+            }
+            return Err(error);
+        }
+        Ok(repo)
     }
 
     /// Initializes a new Git repository in the specified directory.
@@ -67,11 +177,47 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn init<P: AsRef<Path>>(p: P) -> Result<Repository> {
-        let p_ref = p.as_ref();
-        execute_git(&p_ref, &["init"])?;
-        Ok(Repository {
-            location: PathBuf::from(p_ref),
-        })
+        let location = normalize_location(p.as_ref());
+        commands::init().run(&location)?;
+        Ok(Repository::new(location))
+    }
+
+    /// Initializes a new Git repository, with control over the initial branch,
+    /// bareness, template and location of the Git directory. The directory is
+    /// created if it does not exist, and relative paths in `opts` are resolved
+    /// against it.
+    ///
+    /// Equivalent to `git init <path>` plus the flags selected in `opts`.
+    ///
+    /// # Arguments
+    /// * `p` - The path to the directory to initialize.
+    /// * `opts` - Options such as `initial_branch` to choose the default branch name.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the directory cannot be created.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn init_with_options<P: AsRef<Path>>(p: P, opts: &InitOptions) -> Result<Repository> {
+        let location = normalize_location(p.as_ref());
+        fs::create_dir_all(&location)?;
+        let mut args: Vec<OsString> = vec!["init".into()];
+        if let Some(branch) = &opts.initial_branch {
+            args.push(format!("--initial-branch={}", branch).into());
+        }
+        if opts.bare {
+            args.push("--bare".into());
+        }
+        if let Some(dir) = &opts.template_dir {
+            let mut arg = OsString::from("--template=");
+            arg.push(location.join(dir));
+            args.push(arg);
+        }
+        if let Some(dir) = &opts.separate_git_dir {
+            let mut arg = OsString::from("--separate-git-dir=");
+            arg.push(location.join(dir));
+            args.push(arg);
+        }
+        execute_git(&location, args)?;
+        Ok(Repository::new(location))
     }
 
     /// Creates and checks out a new local branch.
@@ -84,10 +230,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn create_local_branch(&self, branch_name: &BranchName) -> Result<()> {
-        execute_git(
-            &self.location,
-            &["checkout", "-b", branch_name.as_ref()],
-        )
+        commands::create_branch(branch_name, None).run(&self.location)
     }
 
     /// Checks out an existing local branch.
@@ -100,46 +243,154 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn switch_branch(&self, branch_name: &BranchName) -> Result<()> {
-        execute_git(&self.location, &["checkout", branch_name.as_ref()])
+        commands::switch_branch(branch_name).run(&self.location)
     }
 
     /// Adds file contents to the Git index (staging area).
     ///
-    /// Equivalent to `git add <pathspec>...`.
+    /// Equivalent to `git add -- <pathspec>...`.
     ///
     /// # Arguments
-    /// * `pathspecs` - A vector of file paths or patterns to add.
+    /// * `pathspecs` - A vector of file paths or `Pathspec`s to add.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
-    pub fn add<S: AsRef<OsStr>>(&self, pathspecs: Vec<S>) -> Result<()> {
-        let mut args: Vec<&OsStr> = Vec::with_capacity(pathspecs.len() + 1);
-        args.push("add".as_ref());
-        for spec in pathspecs.iter() {
-            args.push(spec.as_ref());
+    pub fn add<S: Into<Pathspec>>(&self, pathspecs: Vec<S>) -> Result<()> {
+        let pathspecs: Vec<Pathspec> = pathspecs.into_iter().map(Into::into).collect();
+        commands::add(&pathspecs)?.run(&self.location)
+    }
+
+    /// Stages changes with control over which files are picked up.
+    ///
+    /// Equivalent to `git add --verbose [--update] [--all] [--intent-to-add]
+    /// [--force] [--dry-run] [-- <pathspec>...]`.
+    ///
+    /// # Arguments
+    /// * `opts` - An `AddOptions` with the paths and flags.
+    ///
+    /// # Returns
+    /// The paths whose index entry was changed, or would be with `dry_run`,
+    /// including deletions staged by `update` or `all`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`), for example if a path is
+    /// ignored and `force` is not set.
+    pub fn add_with(&self, opts: &AddOptions) -> Result<Vec<PathBuf>> {
+        let mut args: Vec<&OsStr> = vec!["add".as_ref(), "--verbose".as_ref()];
+        let flags = [
+            (opts.update, "--update"),
+            (opts.all, "--all"),
+            (opts.intent_to_add, "--intent-to-add"),
+            (opts.force, "--force"),
+            (opts.dry_run, "--dry-run"),
+        ];
+        args.extend(flags.iter().filter(|(on, _)| *on).map(|(_, flag)| OsStr::new(flag)));
+        if !opts.paths.is_empty() {
+            args.push("--".as_ref());
+            args.extend(pathspec_args(&opts.paths)?);
         }
-        execute_git(&self.location, args)
+        execute_git_fn(&self.location, args, |output| Ok(parse_add_verbose(output)))
     }
 
     /// Removes files from the working tree and the index.
     ///
-    /// Equivalent to `git rm [-f] <pathspec>...`.
+    /// Equivalent to `git rm [-f] -- <pathspec>...`.
     ///
     /// # Arguments
-    /// * `pathspecs` - A vector of file paths or patterns to remove.
+    /// * `pathspecs` - A vector of file paths or `Pathspec`s to remove.
     /// * `force` - If `true`, corresponds to the `-f` flag (force removal).
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
-    pub fn remove<S: AsRef<OsStr>>(&self, pathspecs: Vec<S>, force: bool) -> Result<()> {
-        let mut args: Vec<&OsStr> = Vec::with_capacity(pathspecs.len() + 2);
-        args.push("rm".as_ref());
-        if force {
-            args.push("-f".as_ref());
+    pub fn remove<S: Into<Pathspec>>(&self, pathspecs: Vec<S>, force: bool) -> Result<()> {
+        let pathspecs: Vec<Pathspec> = pathspecs.into_iter().map(Into::into).collect();
+        commands::remove(&pathspecs, force)?.run(&self.location)
+    }
+
+    /// Removes files from the index, and unless `cached` is set from the
+    /// working tree, with control over which files may be removed.
+    ///
+    /// Equivalent to `git rm [--force] [--cached] [-r] [--dry-run] -- <pathspec>...`.
+    ///
+    /// # Arguments
+    /// * `opts` - A `RemoveOptions` with the paths and flags.
+    ///
+    /// # Returns
+    /// The paths that were removed, or would be with `dry_run`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if a path has changes and `force` is not set, or is a
+    /// directory and `recursive` is not set (including `GitNotFound`).
+    pub fn remove_with(&self, opts: &RemoveOptions) -> Result<Vec<PathBuf>> {
+        let mut args: Vec<&OsStr> = vec!["rm".as_ref()];
+        let flags = [
+            (opts.force, "--force"),
+            (opts.cached, "--cached"),
+            (opts.recursive, "-r"),
+            (opts.dry_run, "--dry-run"),
+        ];
+        args.extend(flags.iter().filter(|(on, _)| *on).map(|(_, flag)| OsStr::new(flag)));
+        args.push("--".as_ref());
+        args.extend(pathspec_args(&opts.paths)?);
+        execute_git_fn(&self.location, args, |output| Ok(parse_verbose_paths(output, &["rm '"])))
+    }
+
+    /// Deletes untracked files from the working tree.
+    ///
+    /// Equivalent to `git clean -f|--dry-run [-d] [-x|-X] [-- <pathspec>...]`.
+    ///
+    /// # Arguments
+    /// * `opts` - A `CleanOptions` selecting what to delete.
+    ///
+    /// # Returns
+    /// The paths that were deleted, or would be with `dry_run`; directories end
+    /// with `/`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn clean(&self, opts: &CleanOptions) -> Result<Vec<PathBuf>> {
+        let mut args: Vec<&OsStr> = vec!["-c".as_ref(), "core.quotePath=false".as_ref(), "clean".as_ref()];
+        args.push(if opts.dry_run { "--dry-run" } else { "--force" }.as_ref());
+        let flags = [(opts.directories, "-d"), (opts.ignored, "-x"), (opts.only_ignored, "-X")];
+        args.extend(flags.iter().filter(|(on, _)| *on).map(|(_, flag)| OsStr::new(flag)));
+        if !opts.paths.is_empty() {
+            args.push("--".as_ref());
+            args.extend(pathspec_args(&opts.paths)?);
+        }
+        execute_git_fn(&self.location, args, |output| {
+            Ok(output
+                .lines()
+                .filter_map(|line| line.strip_prefix("Would remove ").or_else(|| line.strip_prefix("Removing ")))
+                .map(unquote_c_path)
+                .collect())
+        })
+    }
+
+    /// Moves or renames a tracked file or directory, updating the index so the
+    /// change is staged as a rename.
+    ///
+    /// Equivalent to `git mv [-f] <from> <to>`. Missing parent directories of
+    /// `to` are created first.
+    ///
+    /// # Arguments
+    /// * `from` - The tracked path to move, relative to the repository root.
+    /// * `to` - The new path, relative to the repository root.
+    /// * `force` - If `true`, overwrites an existing file at `to` (`-f`).
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the parent directories cannot be created.
+    /// Returns `GitError` if `from` is not tracked or `to` exists without `force`
+    /// (including `GitNotFound`).
+    pub fn move_path<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q, force: bool) -> Result<()> {
+        let to = to.as_ref();
+        if let Some(parent) = to.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(self.location.join(parent))?;
         }
-        for spec in pathspecs.iter() {
-            args.push(spec.as_ref());
+        let mut args: Vec<OsString> = vec!["mv".into()];
+        if force {
+            args.push("-f".into());
         }
+        args.extend(["--".into(), pathspec(from.as_ref())?, pathspec(to)?]);
         execute_git(&self.location, args)
     }
 
@@ -153,7 +404,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn stage_and_commit_all_modified(&self, message: &str) -> Result<()> {
-        execute_git(&self.location, &["commit", "-am", message])
+        commands::commit_all_modified(message).run(&self.location)
     }
 
     /// Commits files currently in the staging area (index).
@@ -166,17 +417,21 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn commit_staged(&self, message: &str) -> Result<()> {
-        execute_git(&self.location, &["commit", "-m", message])
+        commands::commit_staged(message).run(&self.location)
     }
 
     /// Pushes the current branch to its configured upstream remote branch.
     ///
     /// Equivalent to `git push`.
     ///
+    /// # Returns
+    /// A `PushResult` with the outcome for each pushed ref.
+    ///
     /// # Errors
+    /// Returns `GitError::PushRejected` if the remote refuses a ref.
     /// Returns `GitError` (including `GitNotFound`).
-    pub fn push(&self) -> Result<()> {
-        execute_git(&self.location, &["push"])
+    pub fn push(&self) -> Result<PushResult> {
+        commands::push().run(&self.location)
     }
 
     /// Pushes the current branch to a specified remote and sets the upstream configuration.
@@ -187,36 +442,187 @@ impl Repository {
     /// * `upstream_remote` - The name of the remote.
     /// * `upstream_branch` - The name of the branch on the remote.
     ///
+    /// # Returns
+    /// A `PushResult` with the outcome for each pushed ref.
+    ///
     /// # Errors
+    /// Returns `GitError::PushRejected` if the remote refuses a ref.
     /// Returns `GitError` (including `GitNotFound`).
     pub fn push_to_upstream(
         &self,
         upstream_remote: &Remote, // Changed type
         upstream_branch: &BranchName,
-    ) -> Result<()> {
-        execute_git(
+    ) -> Result<PushResult> {
+        commands::push_to_upstream(upstream_remote, upstream_branch).run(&self.location)
+    }
+
+    /// Creates a commit, with control over staging, hooks and signing.
+    ///
+    /// Equivalent to `git commit -m <message>` plus the flags selected in `opts`.
+    ///
+    /// # Arguments
+    /// * `message` - The commit message.
+    /// * `opts` - Options such as `no_verify` to bypass locally installed hooks.
+    ///
+    /// # Returns
+    /// The paths the commit records, or would record with `dry_run`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if there is nothing to commit and `allow_empty` is not
+    /// set (including `GitNotFound`).
+    pub fn commit_with_options(&self, message: &str, opts: &CommitOptions) -> Result<Vec<PathBuf>> {
+        let mut flags = Vec::new();
+        if opts.all {
+            flags.push("--all");
+        }
+        if opts.allow_empty {
+            flags.push("--allow-empty");
+        }
+        if opts.no_verify {
+            flags.push("--no-verify");
+        }
+        if opts.no_gpg_sign {
+            flags.push("--no-gpg-sign");
+        }
+        if opts.dry_run {
+            // A dry run exits with 1 when nothing is staged, even with --allow-empty.
+            let ok_codes: &[i32] = if opts.allow_empty { &[0, 1] } else { &[0] };
+            let args = ["commit", "--dry-run", "--porcelain", "-z"].into_iter().chain(flags);
+            return execute_git_fn_with(&self.location, args, None, ok_codes, |output| {
+                Ok(parse_commit_dry_run(output))
+            });
+        }
+        execute_git(&self.location, ["commit", "-m", message].into_iter().chain(flags))?;
+        // Report what the new commit recorded relative to its first parent.
+        execute_git_fn(
             &self.location,
-            &[
-                "push",
-                "-u",
-                upstream_remote.as_ref(), // Use AsRef
-                upstream_branch.as_ref(),
-            ],
+            ["diff-tree", "--no-commit-id", "--name-only", "-r", "-z", "-M", "--root", "--diff-merges=first-parent", "HEAD"],
+            |output| Ok(output.split_terminator('\0').map(PathBuf::from).collect()),
         )
     }
 
+    /// Pushes to a remote, with control over upstream tracking, hooks and signing.
+    ///
+    /// Equivalent to `git push [<remote> [<branch>]]` plus the flags selected in `opts`.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote to push to, or `None` for the repository's default
+    ///   remote (see `RepositoryBuilder::default_remote`), falling back to Git's.
+    /// * `branch` - The branch to push, or `None` for Git's default (`push.default`).
+    ///   Ignored when no remote is given or set as the default.
+    /// * `opts` - Options such as `no_verify` to bypass the `pre-push` hook.
+    ///
+    /// # Returns
+    /// A `PushResult` with the outcome for each pushed ref.
+    ///
+    /// # Errors
+    /// Returns `GitError::PushRejected` if the remote refuses a ref.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn push_with_options(
+        &self,
+        remote: Option<&Remote>,
+        branch: Option<&BranchName>,
+        opts: &PushOptions,
+    ) -> Result<PushResult> {
+        let branches = branch.map(std::slice::from_ref).unwrap_or_default();
+        let remote = remote.or(self.default_remote());
+        commands::push_with_options(remote, branches, opts).run(&self.location)
+    }
+
+    /// Pushes the same branches to several remotes, such as a public host and an
+    /// internal mirror.
+    ///
+    /// Equivalent to `git push <remote> <branch>...` for each remote in turn. A
+    /// failed push does not stop the pushes to the remaining remotes.
+    ///
+    /// # Arguments
+    /// * `branches` - The local branches to push to the branches of the same name.
+    /// * `remotes` - The remotes to push to.
+    /// * `opts` - The push options applied to every remote.
+    ///
+    /// # Returns
+    /// Each remote with the outcome of the push to it, in the order given.
+    pub fn push_to_all(
+        &self,
+        branches: &[BranchName],
+        remotes: &[Remote],
+        opts: &PushOptions,
+    ) -> Vec<(Remote, Result<PushResult>)> {
+        remotes
+            .iter()
+            .map(|remote| {
+                let result = commands::push_with_options(Some(remote), branches, opts).run(&self.location);
+                (remote.clone(), result)
+            })
+            .collect()
+    }
+
+    /// Force-pushes `branch`, but only if the remote branch is still where we
+    /// expect it to be, so commits pushed by someone else are never overwritten.
+    ///
+    /// Equivalent to `git push --force-with-lease=<branch>:<expected> <remote> <branch>`,
+    /// or `--force-with-lease=<branch>` when `expected` is `None`, in which case the
+    /// remote-tracking branch (e.g. `origin/<branch>`) supplies the expected value.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote to push to.
+    /// * `branch` - The local branch to push to the branch of the same name.
+    /// * `expected` - The commit the remote branch must point to.
+    ///
+    /// # Returns
+    /// A `PushResult` with the outcome for the branch.
+    ///
+    /// # Errors
+    /// Returns `GitError::StaleRemoteRef` if the remote branch has moved.
+    /// Returns `GitError::PushRejected` if the remote refuses the push for another reason.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn force_push_with_lease(
+        &self,
+        remote: &Remote,
+        branch: &BranchName,
+        expected: Option<&CommitHash>,
+    ) -> Result<PushResult> {
+        let lease = match expected {
+            Some(hash) => format!("--force-with-lease=refs/heads/{}:{}", branch, hash),
+            None => format!("--force-with-lease=refs/heads/{}", branch),
+        };
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+        let output = run_git(
+            &self.location,
+            ["-c", "core.abbrev=no", "push", "--porcelain", lease.as_str(), remote.as_ref(), refspec.as_str()],
+            &[],
+            None,
+        )?;
+        let result = PushResult::from_porcelain(&String::from_utf8_lossy(&output.stdout));
+        if output.status.success() {
+            return Ok(result);
+        }
+        // A moved remote branch is reported as `[rejected] (stale info)`.
+        if result.rejected().any(|r| r.status == PushStatus::Rejected("stale info".to_string())) {
+            return Err(GitError::StaleRemoteRef {
+                remote: remote.to_string(),
+                branch: branch.to_string(),
+            });
+        }
+        if result.rejected().next().is_some() {
+            return Err(GitError::PushRejected(result));
+        }
+        Err(failure_error(&output))
+    }
+
     /// Adds a new remote repository reference.
     ///
-    /// Equivalent to `git remote add <name> <url>`.
+    /// Equivalent to `git remote add -- <name> <url>`.
     ///
     /// # Arguments
     /// * `name` - The name for the new remote.
-    /// * `url` - The URL of the remote repository.
+    /// * `url` - The URL of the remote repository, which may be a local path
+    ///   created with `GitUrl::from_path`.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn add_remote(&self, name: &Remote, url: &GitUrl) -> Result<()> { // Changed type
-        execute_git(&self.location, &["remote", "add", name.as_ref(), url.as_ref()]) // Use AsRef
+        commands::add_remote(name, url).run(&self.location)
     }
 
     /// Fetches updates from a specified remote repository.
@@ -229,7 +635,138 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn fetch_remote(&self, remote: &Remote) -> Result<()> { // Changed type
-        execute_git(&self.location, &["fetch", remote.as_ref()]) // Use AsRef
+        commands::fetch_remote(remote).run(&self.location)
+    }
+
+    /// Fetches from one or all remotes, with pruning, tag, depth and refspec control.
+    ///
+    /// Equivalent to `git fetch [--all] [--prune] [--prune-tags] [--tags] [--depth=<n>]
+    /// [<remote> [<refspec>...]]`.
+    ///
+    /// # Arguments
+    /// * `opts` - What to fetch; `FetchOptions::default()` fetches the default remote,
+    ///   which is the repository's (see `RepositoryBuilder::default_remote`) if set.
+    ///
+    /// # Returns
+    /// A `FetchResult` listing the refs the fetch created, moved or deleted.
+    ///
+    /// # Errors
+    /// Returns `GitError::FetchRejected` if some refs could not be updated.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn fetch(&self, opts: &FetchOptions) -> Result<FetchResult> {
+        commands::fetch(&with_default_remote(opts, self.default_remote())).run(&self.location)
+    }
+
+    /// Fetches the file contents of `paths` at HEAD that a partial clone left
+    /// out, in one request to the promisor remote instead of one per file as Git
+    /// does when a command first reads them.
+    ///
+    /// Equivalent to listing the blobs with `git ls-tree -r HEAD -- <paths>`,
+    /// finding the missing ones with `git rev-list --objects --missing=print`, and
+    /// `git fetch <promisor> --filter=blob:none --stdin` with their ids. In a
+    /// `tree:0` clone, listing the paths fetches the trees leading to them.
+    ///
+    /// # Arguments
+    /// * `paths` - The files or directories to hydrate; empty for the whole tree.
+    ///
+    /// # Returns
+    /// The number of objects fetched; 0 if none were missing, as in a full clone.
+    ///
+    /// # Errors
+    /// Returns `GitError::NoRemoteRepositorySet` if objects are missing but no
+    /// remote is marked as a promisor, or `GitError` if the fetch fails
+    /// (including `GitNotFound`).
+    pub fn fetch_missing_objects(&self, paths: &[Pathspec]) -> Result<usize> {
+        let mut args: Vec<&OsStr> = vec!["ls-tree".as_ref(), "-r".as_ref(), "-z".as_ref(), "HEAD".as_ref(), "--".as_ref()];
+        args.extend(pathspec_args(paths)?);
+        let blobs: HashSet<String> = execute_git_fn(&self.location, args, |output| {
+            // Records are `<mode> <type> <id>\t<path>`.
+            Ok(output
+                .split('\0')
+                .filter_map(|record| record.split_once('\t'))
+                .filter_map(|(info, _)| {
+                    let mut fields = info.split(' ');
+                    match (fields.next(), fields.next(), fields.next()) {
+                        (Some(_), Some("blob"), Some(id)) => Some(id.to_string()),
+                        _ => None,
+                    }
+                })
+                .collect())
+        })?;
+        let missing: Vec<String> = execute_git_fn(
+            &self.location,
+            ["rev-list", "--objects", "--missing=print", "--no-walk", "HEAD"],
+            |output| Ok(output.lines().filter_map(|line| line.strip_prefix('?')).map(str::to_string).collect()),
+        )?;
+        let wanted: Vec<String> = missing.into_iter().filter(|id| blobs.contains(id)).collect();
+        if wanted.is_empty() {
+            return Ok(0);
+        }
+
+        let promisor = execute_git_fn_with(
+            &self.location,
+            ["config", "--get-regexp", r"^remote\..*\.promisor$"],
+            None,
+            &[0, 1],
+            |output| {
+                Ok(output
+                    .lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .find(|(_, value)| *value == "true")
+                    .and_then(|(key, _)| key.strip_prefix("remote.")?.strip_suffix(".promisor").map(str::to_string)))
+            },
+        )?
+        .ok_or(GitError::NoRemoteRepositorySet)?;
+        let stdin = wanted.iter().map(|id| format!("{}\n", id)).collect::<String>();
+        execute_git_fn_with(
+            &self.location,
+            [
+                "-c",
+                "fetch.negotiationAlgorithm=noop",
+                "fetch",
+                &promisor,
+                "--no-tags",
+                "--no-write-fetch-head",
+                "--recurse-submodules=no",
+                "--filter=blob:none",
+                "--stdin",
+            ],
+            Some(stdin.as_bytes()),
+            &[0],
+            |_| Ok(()),
+        )?;
+        Ok(wanted.len())
+    }
+
+    /// Fetches from every remote, which for a mirror brings all refs up to date.
+    ///
+    /// Equivalent to `git remote update [--prune]`.
+    ///
+    /// # Arguments
+    /// * `prune` - Also delete refs that no longer exist on the remote.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn remote_update(&self, prune: bool) -> Result<()> {
+        let mut args = vec!["remote", "update"];
+        if prune {
+            args.push("--prune");
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Pushes every ref to `remote` and deletes remote refs that do not exist
+    /// locally, making the remote an exact copy of this repository.
+    ///
+    /// Equivalent to `git push --mirror <remote>`.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote to overwrite.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn push_mirror(&self, remote: &Remote) -> Result<()> {
+        execute_git(&self.location, ["push", "--mirror", remote.as_ref()])
     }
 
     /// Creates and checks out a new branch starting from a given point (e.g., another branch, commit hash, tag).
@@ -247,15 +784,7 @@ impl Repository {
         branch_name: &BranchName,
         startpoint: &str, // Keeping as &str for flexibility
     ) -> Result<()> {
-        execute_git(
-            &self.location,
-            &[
-                "checkout",
-                "-b",
-                branch_name.as_ref(),
-                startpoint,
-            ],
-        )
+        commands::create_branch(branch_name, Some(startpoint)).run(&self.location)
     }
 
     /// Lists the names of all local branches.
@@ -268,23 +797,14 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn list_branches(&self) -> Result<Vec<BranchName>> { // Changed return type
-        execute_git_fn(
-            &self.location,
-            &["branch", "--list", "--format=%(refname:short)"],
-            |output| {
-                output
-                    .lines()
-                    .map(|line| BranchName::from_str(line.trim())) // Parse each line
-                    .collect::<Result<Vec<BranchName>>>() // Collect into Result<Vec<...>>
-            },
-        )
+        commands::list_branches().run(&self.location)
     }
 
     // Removed list_added, list_modified, list_untracked. Use status() instead.
 
     /// Lists all files currently tracked by Git in the working directory.
     ///
-    /// Equivalent to `git ls-files`.
+    /// Equivalent to `git ls-files --cached`. See `ls_files` for other selections.
     ///
     /// # Returns
     /// A `Vec<String>` containing the paths of tracked files relative to the repository root.
@@ -292,8 +812,45 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn list_tracked(&self) -> Result<Vec<String>> {
-        execute_git_fn(&self.location, &["ls-files"], |output| {
-            Ok(output.lines().map(|line| line.to_owned()).collect())
+        commands::list_tracked().run(&self.location)
+    }
+
+    /// Lists files in the index and working tree.
+    ///
+    /// Equivalent to `git ls-files -z [options]`. Paths are read NUL-delimited, so names
+    /// containing spaces, quotes or newlines are returned intact.
+    ///
+    /// # Arguments
+    /// * `opts` - An `LsFilesOptions` selecting which files to list.
+    ///
+    /// # Returns
+    /// A `Vec<PathBuf>` relative to the repository root. Directories reported because of
+    /// `directory` end with `/`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn ls_files(&self, opts: &LsFilesOptions) -> Result<Vec<PathBuf>> {
+        let mut args = vec!["ls-files", "-z"];
+        let flags = [
+            (opts.cached, "--cached"),
+            (opts.others, "--others"),
+            (opts.deleted, "--deleted"),
+            (opts.modified, "--modified"),
+            (opts.ignored, "--ignored"),
+            (opts.exclude_standard, "--exclude-standard"),
+            (opts.directory, "--directory"),
+        ];
+        args.extend(flags.iter().filter(|(on, _)| *on).map(|(_, flag)| *flag));
+
+        execute_git_fn(&self.location, args, |output| {
+            let mut paths: Vec<PathBuf> = output
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect();
+            // `--deleted` together with `--cached` or `--modified` lists a path twice.
+            paths.dedup();
+            Ok(paths)
         })
     }
 
@@ -310,17 +867,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn show_remote_uri(&self, remote_name: &Remote) -> Result<GitUrl> { // Changed args & return type
-        execute_git_fn(
-            &self.location,
-            &[
-                "config",
-                "--get",
-                // --- FIX: Pass remote_name directly ---
-                // format! uses the Display trait implementation for Remote
-                &format!("remote.{}.url", remote_name),
-            ],
-            |output| GitUrl::from_str(output.trim()), // Parse output into GitUrl
-        )
+        commands::show_remote_uri(remote_name).run(&self.location)
     }
 
     /// Lists the names of all configured remotes.
@@ -334,22 +881,7 @@ impl Repository {
     /// Returns `GitError::NoRemoteRepositorySet` if no remotes are configured.
     /// Returns `GitError` (including `GitNotFound`).
     pub fn list_remotes(&self) -> Result<Vec<Remote>> { // Changed return type
-        execute_git_fn(&self.location, &["remote"], |output| {
-            let remote_names: Vec<&str> = output.lines().map(|line| line.trim()).collect();
-            if remote_names.is_empty() {
-                let config_check = self.cmd_out(["config", "--get-regexp", r"^remote\..*\.url"]);
-                match config_check {
-                    Ok(lines) if lines.is_empty() => Err(GitError::NoRemoteRepositorySet),
-                    Ok(_) => Ok(Vec::new()),
-                    Err(e) => Err(e),
-                }
-            } else {
-                remote_names
-                    .into_iter()
-                    .map(Remote::from_str) // Parse each name
-                    .collect::<Result<Vec<Remote>>>() // Collect into Result<Vec<...>>
-            }
-        })
+        commands::list_remotes().run(&self.location)
     }
 
     /// Obtains the commit hash (SHA-1) of the current `HEAD`.
@@ -365,16 +897,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn get_hash(&self, short: bool) -> Result<CommitHash> { // Changed return type
-        let args: &[&str] = if short {
-            &["rev-parse", "--short", "HEAD"]
-        } else {
-            &["rev-parse", "HEAD"]
-        };
-        execute_git_fn(
-            &self.location,
-            args,
-            |output| CommitHash::from_str(output.trim()), // Parse output
-        )
+        commands::get_hash(short).run(&self.location)
     }
 
     /// Executes an arbitrary Git command within the repository context.
@@ -389,7 +912,7 @@ impl Repository {
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        execute_git(&self.location, args)
+        commands::cmd(args).run(&self.location)
     }
 
     /// Executes an arbitrary Git command and returns its standard output.
@@ -407,138 +930,124 @@ impl Repository {
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        execute_git_fn(&self.location, args, |output| {
-            Ok(output.lines().map(|line| line.to_owned()).collect())
-        })
+        commands::cmd_out(args).run(&self.location)
     }
 
-    // --- Operations for Structured Types ---
-
-    /// Gets detailed information about a commit.
+    /// Executes an arbitrary Git command and reports its exit status instead of
+    /// treating a non-zero exit code as an error.
+    ///
+    /// Useful for commands that signal `false` through their exit code, such as
+    /// `git diff --quiet` or `git merge-base --is-ancestor`.
     ///
     /// # Arguments
-    /// * `commit_ref` - The commit reference (hash, branch name, etc.). If `None`, uses HEAD.
+    /// * `args` - An iterator yielding command-line arguments for Git.
     ///
     /// # Returns
-    /// A `Commit` struct with commit details. (Note: Assumes Commit model fields updated)
+    /// An `ExitStatusInfo` with the exit code and captured output.
     ///
     /// # Errors
-    /// Returns `GitError` (including `GitNotFound`).
-    pub fn get_commit(&self, commit_ref: Option<&str>) -> Result<Commit> {
-        let format = "%H%n\
-                     shortcommit %h%n\
-                     author_name %an%n\
-                     author_email %ae%n\
-                     timestamp %at%n\
-                     %P%n\
-                     message %s";
-
-        let format_string = format!("--format={}", format);
-        let args = match commit_ref {
-            Some(c) => vec!["show", "--no-patch", &format_string, c],
-            None => vec!["show", "--no-patch", &format_string],
-        };
-
-        execute_git_fn(&self.location, args, |output| {
-            Commit::from_show_format(output).ok_or_else(|| GitError::GitError {
-                stdout: output.to_string(),
-                stderr: "Failed to parse commit information".to_string(),
-            })
-        })
+    /// Returns `GitError` only if Git could not be run (including `GitNotFound`)
+    /// or its output was not valid UTF-8.
+    pub fn cmd_status<I, S>(&self, args: I) -> Result<ExitStatusInfo>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        execute_git_status(&self.location, args)
+    }
+
+    // --- Operations for Structured Types ---
+
+    /// Gets detailed information about a commit.
+    ///
+    /// # Arguments
+    /// * `commit_ref` - The commit reference (hash, branch name, etc.). If `None`, uses HEAD.
+    ///
+    /// # Returns
+    /// A `Commit` struct with commit details, including the full message and the
+    /// signature verification status.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn get_commit(&self, commit_ref: Option<&str>) -> Result<Commit> {
+        commands::get_commit(commit_ref).run(&self.location)
     }
 
     /// Gets the current status of the repository.
     ///
     /// # Returns
-    /// A `StatusResult` struct with status details. (Note: Assumes StatusResult fields updated)
+    /// A `StatusResult` struct with branch, upstream tracking and file status details.
     ///
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn status(&self) -> Result<StatusResult> {
-        let porcelain_output = execute_git_fn(
-            &self.location,
-            &["status", "--porcelain=v2", "--branch"],
-            |output| Ok(output.to_string())
-        )?;
-
-        let mut branch_name_str = None;
-        let mut files = Vec::new();
-        let mut merging = false;
-        let mut rebasing = false;
-        let mut cherry_picking = false;
-
-        for line in porcelain_output.lines() {
-            if line.starts_with("# branch.head ") {
-                branch_name_str = Some(line.trim_start_matches("# branch.head ").to_string());
-            } else if line.starts_with("# branch.oid ") { // Ignore
-            } else if line.starts_with("# branch.upstream ") { // Ignore
-            } else if line.starts_with("1 ") || line.starts_with("2 ") || line.starts_with("u ") {
-                let parts: Vec<&str> = line.split(' ').collect();
-                if parts.len() >= 2 {
-                    let xy = parts[1];
-                    let status_code = if xy.len() >= 2 {
-                        (xy.chars().nth(0).unwrap(), xy.chars().nth(1).unwrap())
-                    } else {
-                        (' ', ' ')
-                    };
-                    let status = FileStatus::from_porcelain_code(status_code.0, status_code.1);
-
-                    // Simplified path parsing - assumes no NUL separators needed for now
-                    let path_part = line.split('\t').next().unwrap_or(line);
-                    let path_components: Vec<&str> = path_part.split(' ').collect();
-
-                    if let Some(path_str) = path_components.iter().rev().find(|s| !s.is_empty()) {
-                        let original_path_str = if line.contains('\t') {
-                            line.split('\t').nth(1)
-                        } else {
-                            None
-                        };
-
-                        files.push(StatusEntry {
-                            path: PathBuf::from(path_str),
-                            status,
-                            original_path: original_path_str.map(PathBuf::from),
-                        });
-                    }
-                }
-            } else if line.starts_with("? ") {
-                if line.len() > 2 {
-                    let path = line[2..].to_string();
-                    files.push(StatusEntry {
-                        path: PathBuf::from(path),
-                        status: FileStatus::Untracked,
-                        original_path: None,
-                    });
-                }
-            }
-        }
+        let result = commands::status().run(&self.location)?;
+        self.with_operation_flags(result)
+    }
 
-        // Parse the branch name string into Option<BranchName>
-        let branch = branch_name_str.and_then(|s| BranchName::from_str(&s).ok());
+    /// Gets the status of tracked files, tuned for worktrees with hundreds of
+    /// thousands of files.
+    ///
+    /// Equivalent to `git status --porcelain=v2 --branch -z --no-renames
+    /// --untracked-files=no`. Skipping the scan for untracked files and rename
+    /// detection is most of the saving; see the `status` benchmark.
+    ///
+    /// # Returns
+    /// A `StatusResult` like `status`, except that no entries are untracked and a
+    /// renamed file is reported as a deletion and an addition.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn status_fast(&self) -> Result<StatusResult> {
+        let result = commands::status_fast().run(&self.location)?;
+        self.with_operation_flags(result)
+    }
 
+    /// Sets the in-progress operation flags of a parsed status.
+    fn with_operation_flags(&self, mut result: StatusResult) -> Result<StatusResult> {
         // Check for special states
-        let git_dir = self.location.join(".git");
-        if std::path::Path::new(&git_dir.join("MERGE_HEAD")).exists() { merging = true; }
-        if std::path::Path::new(&git_dir.join("rebase-apply")).exists() || std::path::Path::new(&git_dir.join("rebase-merge")).exists() { rebasing = true; }
-        if std::path::Path::new(&git_dir.join("CHERRY_PICK_HEAD")).exists() { cherry_picking = true; }
-
-        // Determine if clean (ignoring untracked/ignored)
-        let is_clean = files.iter().all(|f|
-            matches!(f.status, FileStatus::Unmodified | FileStatus::Ignored)
-        );
+        let git_dir = self.git_dir()?;
+        result.merging = git_dir.join("MERGE_HEAD").exists();
+        result.rebasing = git_dir.join("rebase-apply").exists() || git_dir.join("rebase-merge").exists();
+        result.cherry_picking = git_dir.join("CHERRY_PICK_HEAD").exists();
 
-        // --- FIX: Removed duplicate field and incorrect mapping ---
-        Ok(StatusResult {
-            branch: branch, // Assign the Option<BranchName> directly
-            files,
-            merging,
-            rebasing,
-            cherry_picking,
-            is_clean,
-        })
-        // --- End Fix ---
+        Ok(result)
+    }
+
+    /// Resolves the repository's Git directory.
+    ///
+    /// This is `.git` in the working tree root for an ordinary repository, but
+    /// the per-worktree directory under the main repository's `.git/worktrees`
+    /// for a linked worktree, the module directory under the superproject's
+    /// `.git/modules` for a submodule, and the repository itself when bare. It
+    /// also honours `GIT_DIR`.
+    ///
+    /// Equivalent to `git rev-parse --absolute-git-dir`.
+    ///
+    /// # Returns
+    /// The absolute path of the Git directory.
+    ///
+    /// # Errors
+    /// Returns `GitError` if this is not a repository (including `GitNotFound`).
+    pub fn git_dir(&self) -> Result<PathBuf> {
+        git_dir(&self.location)
     }
 
+    /// Reports which multi-step operation, if any, the repository is in the
+    /// middle of.
+    ///
+    /// Unlike the flags in `StatusResult`, this also recognizes reverts, bisect
+    /// sessions and `git am`, and reports a single state.
+    ///
+    /// # Returns
+    /// The `RepoState`, `RepoState::Clean` when no operation is in progress.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn operation_state(&self) -> Result<RepoState> {
+        let git_dir = self.git_dir()?;
+        Ok(RepoState::detect(|path| git_dir.join(path).exists()))
+    }
 
     /// Lists branches with detailed information.
     ///
@@ -548,45 +1057,118 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn list_branches_info(&self) -> Result<Vec<Branch>> {
+        commands::list_branches_info().run(&self.location)
+    }
+
+    /// Lists branches matching a filter, for example those already merged into
+    /// the main branch.
+    ///
+    /// Equivalent to `git branch --list [--remotes] [--merged=<commit>]
+    /// [--no-merged=<commit>] [--contains=<commit>] [--sort=<key>] [<pattern>...]`.
+    ///
+    /// # Arguments
+    /// * `filter` - A `BranchFilter` selecting and ordering the branches.
+    ///
+    /// # Returns
+    /// A `Vec<Branch>` in the requested order. Remote-tracking branches are named
+    /// `<remote>/<branch>`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if a commit in the filter cannot be resolved (including
+    /// `GitNotFound`).
+    pub fn list_branches_filtered(&self, filter: &BranchFilter) -> Result<Vec<Branch>> {
+        commands::list_branches_filtered(filter).run(&self.location)
+    }
+
+    /// Deletes local branches that are fully merged into `into`, and optionally
+    /// the same branches on a remote.
+    ///
+    /// `into` itself, the current branch and the `protect`ed branches are never
+    /// deleted. On the remote, only branches whose remote-tracking branch is
+    /// also merged into `into` are deleted, so work pushed by others is kept.
+    ///
+    /// Equivalent to `git branch --merged=<into>` followed by `git branch -D
+    /// <branch>...` and `git push <remote> --delete <branch>...`.
+    ///
+    /// # Arguments
+    /// * `into` - The branch the deleted branches must be merged into, usually `main`.
+    /// * `protect` - Branches to keep even if merged, e.g. release branches.
+    /// * `remote` - The remote to delete the branches from as well, if any.
+    /// * `dry_run` - If `true`, only reports what would be deleted.
+    ///
+    /// # Returns
+    /// The local branches that were deleted, or would be with `dry_run`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`), for example if a branch is
+    /// checked out in another worktree. Local branches are deleted before
+    /// remote ones, so a failed push leaves the local deletions in place.
+    pub fn prune_merged_branches(
+        &self,
+        into: &BranchName,
+        protect: &[BranchName],
+        remote: Option<&Remote>,
+        dry_run: bool,
+    ) -> Result<Vec<BranchName>> {
+        let merged = |remotes| BranchFilter { remotes, merged: Some(into.to_string()), ..Default::default() };
+        let prunable: Vec<BranchName> = self
+            .list_branches_filtered(&merged(false))?
+            .into_iter()
+            .filter(|branch| !branch.is_head && &branch.name != into && !protect.contains(&branch.name))
+            .map(|branch| branch.name)
+            .collect();
+        if dry_run || prunable.is_empty() {
+            return Ok(prunable);
+        }
+
+        let mut args = vec!["branch", "-D"];
+        args.extend(prunable.iter().map(AsRef::<str>::as_ref));
+        execute_git(&self.location, args)?;
+
+        if let Some(remote) = remote {
+            let prefix = format!("{}/", remote);
+            let merged_remote: Vec<String> = self
+                .list_branches_filtered(&merged(true))?
+                .into_iter()
+                .filter_map(|branch| branch.name.to_string().strip_prefix(&prefix).map(str::to_string))
+                .filter(|name| prunable.iter().any(|branch| branch.to_string() == *name))
+                .collect();
+            if !merged_remote.is_empty() {
+                let mut args = vec!["push", remote.as_ref(), "--delete"];
+                args.extend(merged_remote.iter().map(String::as_str));
+                execute_git(&self.location, args)?;
+            }
+        }
+        Ok(prunable)
+    }
+
+    /// Reports whether a local branch needs to be pushed or pulled.
+    ///
+    /// Equivalent to `git for-each-ref --format=%(upstream)%(upstream:track)
+    /// refs/heads/<branch>`. The counts compare against the remote-tracking
+    /// branch, so they are only as fresh as the last fetch.
+    ///
+    /// # Arguments
+    /// * `branch` - The local branch to inspect.
+    ///
+    /// # Errors
+    /// Returns `GitError::BranchNotFound` if the branch does not exist.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn sync_status(&self, branch: &BranchName) -> Result<SyncStatus> {
+        let reference = format!("refs/heads/{}", branch);
         execute_git_fn(
             &self.location,
-            &["branch", "--list", "-v", "--format=%(refname:short) %(objectname) %(HEAD) %(upstream:short)"],
+            ["for-each-ref", "--format=%(refname)%00%(upstream)%00%(upstream:track,nobracket)", &reference],
             |output| {
-                let mut branches = Vec::new();
-
-                for line in output.lines() {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 3 {
-                        let name_str = parts[0];
-                        let commit_str = parts[1]; // &str
-                        let is_head = parts[2] == "*";
-
-                        let upstream = if parts.len() >= 4 {
-                            Some(parts[3].to_string())
-                        } else {
-                            None
-                        };
-
-                        // --- FIX: Parse commit_str into CommitHash ---
-                        if let Ok(name) = BranchName::from_str(name_str) {
-                            if let Ok(commit_hash) = CommitHash::from_str(commit_str) { // Parse here
-                                branches.push(Branch {
-                                    name,
-                                    commit: commit_hash, // Assign CommitHash
-                                    is_head,
-                                    upstream,
-                                });
-                            } else {
-                                eprintln!("Warning: Could not parse commit hash '{}' for branch '{}'", commit_str, name_str);
-                            }
-                        } else {
-                            eprintln!("Warning: Could not parse branch name '{}'", name_str);
-                        }
-                        // --- End Fix ---
-                    }
-                }
-                Ok(branches)
-            }
+                // The pattern also matches branches below it, such as `<branch>/topic`.
+                let fields = output
+                    .lines()
+                    .map(|line| line.splitn(3, '\0').collect::<Vec<_>>())
+                    .find(|fields| fields[0] == reference)
+                    .ok_or_else(|| GitError::BranchNotFound(branch.to_string()))?;
+                let field = |i: usize| fields.get(i).copied().unwrap_or("");
+                Ok(SyncStatus::from_upstream_track(field(1), field(2)))
+            },
         )
     }
 }
@@ -602,7 +1184,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn rebase(&self, target_branch: &str) -> Result<()> {
-        execute_git(&self.location, &["rebase", target_branch])
+        commands::rebase(target_branch).run(&self.location)
     }
 
     /// Continues a rebase operation after resolving conflicts.
@@ -610,7 +1192,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn rebase_continue(&self) -> Result<()> {
-        execute_git(&self.location, &["rebase", "--continue"])
+        commands::rebase_continue().run(&self.location)
     }
 
     /// Aborts a rebase operation and returns to the pre-rebase state.
@@ -618,7 +1200,136 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn rebase_abort(&self) -> Result<()> {
-        execute_git(&self.location, &["rebase", "--abort"])
+        commands::rebase_abort().run(&self.location)
+    }
+
+    /// Rebases commits onto a new base, optionally checking out a branch first.
+    ///
+    /// Equivalent to `git rebase [options] --onto <newbase> <upstream> [<branch>]`:
+    /// the commits in `upstream..branch` are replayed on top of `newbase`.
+    ///
+    /// # Arguments
+    /// * `newbase` - The commit to replay the commits onto.
+    /// * `upstream` - The commit after which the replayed range starts.
+    /// * `branch` - The branch to rebase; if `None`, the current branch is used.
+    /// * `options` - A `RebaseOptions` controlling the rebase.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the rebase stops or fails (including `GitNotFound`).
+    /// Use `rebase_stopped_at` to find the commit that caused a stop.
+    pub fn rebase_onto(
+        &self,
+        newbase: &str,
+        upstream: &str,
+        branch: Option<&str>,
+        options: &RebaseOptions,
+    ) -> Result<()> {
+        let mut args: Vec<String> = vec!["rebase".to_string()];
+        if options.autostash {
+            args.push("--autostash".to_string());
+        }
+        if options.rebase_merges {
+            args.push("--rebase-merges".to_string());
+        }
+        if options.keep_empty {
+            args.push("--keep-empty".to_string());
+        }
+        for strategy_option in &options.strategy_options {
+            args.push(format!("--strategy-option={}", strategy_option));
+        }
+        args.push("--onto".to_string());
+        args.push(newbase.to_string());
+        args.push(upstream.to_string());
+        if let Some(branch) = branch {
+            args.push(branch.to_string());
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Returns the commit whose replay stopped the rebase in progress, if any.
+    ///
+    /// Reads `REBASE_HEAD`, which Git sets when a rebase stops on a conflict
+    /// or an `edit` step.
+    ///
+    /// # Returns
+    /// `Some(CommitHash)` while a rebase is stopped, otherwise `None`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn rebase_stopped_at(&self) -> Result<Option<CommitHash>> {
+        let info = execute_git_status(
+            &self.location,
+            ["rev-parse", "--verify", "--quiet", "REBASE_HEAD^{commit}"],
+        )?;
+        if info.success() {
+            CommitHash::from_str(info.stdout.trim()).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Runs an interactive rebase non-interactively, following the given plan.
+    ///
+    /// Equivalent to `git rebase -i -- <upstream>` with the todo list replaced by `plan`.
+    /// The todo list is installed through `GIT_SEQUENCE_EDITOR`, and `GIT_EDITOR` is set
+    /// to `true` so squashes keep Git's combined message without prompting.
+    ///
+    /// Scratch files (the todo list and reworded messages) are moved into Git's rebase
+    /// state directory when the rebase starts, so a rebase stopped on a conflict can
+    /// still be resumed with `rebase_continue`, and Git deletes them once the rebase
+    /// finishes or is aborted.
+    ///
+    /// # Arguments
+    /// * `upstream` - The commit to rebase onto; the plan covers the commits after it.
+    /// * `plan` - The steps to apply, in order.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the scratch files cannot be written.
+    /// Returns `GitError` if the rebase stops or fails (including `GitNotFound`).
+    pub fn rebase_interactive(&self, upstream: &str, plan: Vec<RebaseStep>) -> Result<()> {
+        let scratch_dir = ScratchDir::new(&self.location, "rebase")?;
+
+        let mut todo = String::new();
+        for (index, step) in plan.iter().enumerate() {
+            match step {
+                RebaseStep::Pick(commit) => todo.push_str(&format!("pick {}\n", commit)),
+                RebaseStep::Squash(commit) => todo.push_str(&format!("squash {}\n", commit)),
+                RebaseStep::Fixup(commit) => todo.push_str(&format!("fixup {}\n", commit)),
+                RebaseStep::Drop(commit) => todo.push_str(&format!("drop {}\n", commit)),
+                RebaseStep::Reword { commit, message } => {
+                    // `reword` would open an editor, so pick and then amend the message.
+                    let message_name = format!("message-{}", index);
+                    fs::write(scratch_dir.path().join(&message_name), message)?;
+                    todo.push_str(&format!("pick {}\n", commit));
+                    todo.push_str(&format!(
+                        "exec git commit --amend --allow-empty --quiet -F \"$(git rev-parse --git-path rebase-merge/{}/{})\"\n",
+                        REBASE_SCRATCH_NAME, message_name
+                    ));
+                }
+            }
+        }
+        if todo.is_empty() {
+            // An empty todo list makes Git abort; `noop` drops every commit instead.
+            todo.push_str("noop\n");
+        }
+
+        fs::write(scratch_dir.path().join("git-rebase-todo"), todo)?;
+        // Git passes the todo file inside `rebase-merge` as "$1". Moving the scratch
+        // directory next to it hands its cleanup over to Git.
+        let sequence_editor = format!(
+            "mv {} \"${{1%/*}}/{name}\" && cp \"${{1%/*}}/{name}/git-rebase-todo\"",
+            shell_quote(scratch_dir.path())?,
+            name = REBASE_SCRATCH_NAME
+        );
+
+        execute_git_env(
+            &self.location,
+            ["rebase", "--interactive", "--", upstream],
+            &[
+                ("GIT_SEQUENCE_EDITOR", sequence_editor.as_ref()),
+                ("GIT_EDITOR", "true".as_ref()),
+            ],
+        )
     }
 }
 
@@ -633,12 +1344,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn cherry_pick<S: AsRef<OsStr>>(&self, commits: Vec<S>) -> Result<()> {
-        let mut args: Vec<&OsStr> = Vec::with_capacity(commits.len() + 1);
-        args.push("cherry-pick".as_ref());
-        for commit in commits.iter() {
-            args.push(commit.as_ref());
-        }
-        execute_git(&self.location, args)
+        commands::cherry_pick(&commits).run(&self.location)
     }
 
     /// Continues a cherry-pick operation after resolving conflicts.
@@ -646,7 +1352,7 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn cherry_pick_continue(&self) -> Result<()> {
-        execute_git(&self.location, &["cherry-pick", "--continue"])
+        commands::cherry_pick_continue().run(&self.location)
     }
 
     /// Aborts a cherry-pick operation.
@@ -654,64 +1360,2518 @@ impl Repository {
     /// # Errors
     /// Returns `GitError` (including `GitNotFound`).
     pub fn cherry_pick_abort(&self) -> Result<()> {
-        execute_git(&self.location, &["cherry-pick", "--abort"])
+        commands::cherry_pick_abort().run(&self.location)
     }
 }
 
-// --- Helper Functions ---
+// --- Stash Operations ---
 
-// Removed git_status helper function
+impl Repository {
+    /// Saves local changes to a new stash and reverts them in the working tree.
+    ///
+    /// Equivalent to `git stash push [--include-untracked] [--keep-index] [--all]
+    /// [--message <message>]`.
+    ///
+    /// # Arguments
+    /// * `opts` - A `StashOptions` selecting what to stash.
+    ///
+    /// # Returns
+    /// The new stash (`stash@{0}`), or `None` if there were no changes to save.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn stash_push(&self, opts: &StashOptions) -> Result<Option<Stash>> {
+        let mut args = vec!["stash", "push"];
+        let flags = [
+            (opts.include_untracked, "--include-untracked"),
+            (opts.keep_index, "--keep-index"),
+            (opts.all, "--all"),
+        ];
+        args.extend(flags.iter().filter(|(on, _)| *on).map(|(_, flag)| *flag));
+        if let Some(message) = &opts.message {
+            args.extend(["--message", message]);
+        }
 
-/// Executes a Git command, discarding successful output.
-fn execute_git<I, S, P>(p: P, args: I) -> Result<()>
-where
-    I: IntoIterator<Item = S>,
-    S: AsRef<OsStr>,
-    P: AsRef<Path>,
-{
-    execute_git_fn(p, args, |_| Ok(()))
-}
+        // `git stash push` also succeeds when there is nothing to stash, so compare
+        // the stash before and after.
+        let before = self.stash_tip()?;
+        execute_git(&self.location, args)?;
+        if self.stash_tip()? == before {
+            return Ok(None);
+        }
+        Stash::from_str("stash@{0}").map(Some)
+    }
 
-/// Executes a Git command and processes its stdout on success using a closure.
-/// Handles errors, including capturing stderr on failure.
-fn execute_git_fn<I, S, P, F, R>(p: P, args: I, process: F) -> Result<R>
-where
-    I: IntoIterator<Item = S>,
-    S: AsRef<OsStr>,
-    P: AsRef<Path>,
-    F: FnOnce(&str) -> Result<R>,
-{
-    let command_result = Command::new("git")
-        .current_dir(p.as_ref())
-        .args(args)
-        .output();
-
-    match command_result {
-        Ok(output) => {
-            if output.status.success() {
-                match str::from_utf8(&output.stdout) {
-                    Ok(stdout_str) => process(stdout_str),
-                    Err(_) => Err(GitError::Undecodable),
-                }
-            } else {
-                let stdout = str::from_utf8(&output.stdout)
-                    .map(|s| s.trim_end().to_owned())
-                    .unwrap_or_else(|_| String::from("[stdout: undecodable UTF-8]"));
-                let stderr = str::from_utf8(&output.stderr)
-                    .map(|s| s.trim_end().to_owned())
-                    .unwrap_or_else(|_| String::from("[stderr: undecodable UTF-8]"));
-                Err(GitError::GitError { stdout, stderr })
-            }
+    /// Lists the stashes, newest first.
+    ///
+    /// Equivalent to `git stash list`.
+    ///
+    /// # Returns
+    /// A `Vec<StashEntry>` whose `reference` can be passed to the other stash methods.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        execute_git_fn(&self.location, ["stash", "list", "--format=%gd%x00%gs"], |output| {
+            Ok(StashEntry::from_stash_list(output))
+        })
+    }
+
+    /// Summarizes the changes recorded in a stash, including its untracked files,
+    /// without applying it.
+    ///
+    /// Equivalent to `git stash show --include-untracked --numstat --shortstat <stash>`.
+    ///
+    /// # Arguments
+    /// * `stash` - The stash to inspect.
+    ///
+    /// # Returns
+    /// The `DiffStats` of the stash relative to the commit it was made on.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn stash_show(&self, stash: &Stash) -> Result<DiffStats> {
+        execute_git_fn(
+            &self.location,
+            ["stash", "show", "--include-untracked", "-z", "--numstat", "--shortstat", stash.as_ref()],
+            |output| Ok(DiffStats::from_numstat_shortstat_z(output)),
+        )
+    }
+
+    /// Applies a stash to the working tree, keeping it in the stash list.
+    ///
+    /// Equivalent to `git stash apply [--index] <stash>`.
+    ///
+    /// # Arguments
+    /// * `stash` - The stash to apply.
+    /// * `restore_index` - If `true`, also restores which changes were staged (`--index`).
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`), for example if the stash
+    /// conflicts with local changes.
+    pub fn stash_apply(&self, stash: &Stash, restore_index: bool) -> Result<()> {
+        let mut args = vec!["stash", "apply"];
+        if restore_index {
+            args.push("--index");
         }
-        Err(e) => {
-            // --- Restored GitNotFound Check ---
-            if e.kind() == ErrorKind::NotFound {
-                Err(GitError::GitNotFound) // Return the specific error
-            } else {
-                eprintln!("Failed to execute git command: {}", e); // Log the OS error
-                Err(GitError::Execution) // Return the original generic execution error
-            }
-            // --- End of Restored Check ---
+        args.push(stash.as_ref());
+        execute_git(&self.location, args)
+    }
+
+    /// Applies a stash and removes it from the stash list. The stash is kept if
+    /// applying it fails.
+    ///
+    /// Equivalent to `git stash pop [--index] <stash>`.
+    ///
+    /// # Arguments
+    /// * `stash` - The stash to apply.
+    /// * `restore_index` - If `true`, also restores which changes were staged (`--index`).
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn stash_pop(&self, stash: &Stash, restore_index: bool) -> Result<()> {
+        let mut args = vec!["stash", "pop"];
+        if restore_index {
+            args.push("--index");
+        }
+        args.push(stash.as_ref());
+        execute_git(&self.location, args)
+    }
+
+    /// Removes a stash from the stash list without applying it.
+    ///
+    /// Equivalent to `git stash drop <stash>`.
+    ///
+    /// # Arguments
+    /// * `stash` - The stash to remove. Later stashes are renumbered.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn stash_drop(&self, stash: &Stash) -> Result<()> {
+        execute_git(&self.location, ["stash", "drop", "-q", stash.as_ref()])
+    }
+
+    /// Creates a branch from the commit a stash was made on, checks it out and
+    /// applies the stash there, dropping it if it applies cleanly.
+    ///
+    /// Useful when the branch has moved on so far that the stash no longer
+    /// applies to it.
+    ///
+    /// Equivalent to `git stash branch <new_branch> <stash>`.
+    ///
+    /// # Arguments
+    /// * `new_branch` - The name of the branch to create.
+    /// * `stash` - The stash to restore on it.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`), for example if the branch
+    /// already exists.
+    pub fn stash_branch(&self, new_branch: &BranchName, stash: &Stash) -> Result<()> {
+        execute_git(&self.location, ["stash", "branch", new_branch.as_ref(), stash.as_ref()])
+    }
+
+    /// Restores selected files from a stash into the working tree and index,
+    /// leaving the stash and all other files untouched.
+    ///
+    /// Equivalent to `git checkout <stash> -- <path>...`. The restored files replace
+    /// any local changes to them. Untracked files saved with `include_untracked`
+    /// are not part of the stash commit itself and cannot be restored this way.
+    ///
+    /// # Arguments
+    /// * `stash` - The stash to take the files from.
+    /// * `paths` - The paths to restore, relative to the repository root.
+    ///
+    /// # Errors
+    /// Returns `GitError` if a path is not in the stash (including `GitNotFound`).
+    pub fn stash_apply_paths<P: AsRef<Path>>(&self, stash: &Stash, paths: &[P]) -> Result<()> {
+        let mut args: Vec<OsString> = vec!["checkout".into(), OsString::from(stash.to_string()), "--".into()];
+        for path in paths {
+            args.push(pathspec(path.as_ref())?);
         }
+        execute_git(&self.location, args)
+    }
+
+    /// The commit `refs/stash` points to, if any stash exists.
+    fn stash_tip(&self) -> Result<Option<String>> {
+        let info = execute_git_status(&self.location, ["rev-parse", "-q", "--verify", "refs/stash"])?;
+        Ok(Some(info.stdout.trim().to_string()).filter(|_| info.success()))
+    }
+}
+
+// --- Conflict Resolution ---
+
+impl Repository {
+    /// Resolves a conflicted file by taking one side's version and staging it.
+    ///
+    /// Equivalent to `git checkout --ours|--theirs -- <path>` followed by `git add -- <path>`.
+    /// Once all conflicts are resolved, finish with `commit_staged`, `rebase_continue` or
+    /// `cherry_pick_continue` as appropriate.
+    ///
+    /// # Arguments
+    /// * `path` - The conflicted path, relative to the repository root.
+    /// * `side` - The `ConflictSide` whose version to keep.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the chosen side has no version of the path, e.g. it was
+    /// deleted on that side (including `GitNotFound`).
+    pub fn resolve_conflict<P: AsRef<Path>>(&self, path: P, side: ConflictSide) -> Result<()> {
+        let side_flag = match side {
+            ConflictSide::Ours => "--ours",
+            ConflictSide::Theirs => "--theirs",
+        };
+        let spec = pathspec(path.as_ref())?;
+        let spec = spec.as_os_str();
+        execute_git(&self.location, ["checkout".as_ref(), side_flag.as_ref(), "--".as_ref(), spec])?;
+        execute_git(&self.location, ["add".as_ref(), "--".as_ref(), spec])
     }
-}
\ No newline at end of file
+
+    /// Marks conflicted files as resolved after they have been edited by hand.
+    ///
+    /// Equivalent to `git add -- <path>...`.
+    ///
+    /// # Arguments
+    /// * `paths` - The resolved paths, relative to the repository root.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn mark_resolved<S: Into<Pathspec>>(&self, paths: Vec<S>) -> Result<()> {
+        let paths: Vec<Pathspec> = paths.into_iter().map(Into::into).collect();
+        commands::add(&paths)?.run(&self.location)
+    }
+}
+
+// --- File Merging ---
+
+impl Repository {
+    /// Performs a three-way merge of file contents without touching the index or working tree.
+    ///
+    /// Equivalent to `git merge-file -p <ours> <base> <theirs>` on temporary copies of the inputs.
+    /// Conflicting regions are written with the usual markers, labelled `ours`, `base`
+    /// and `theirs`.
+    ///
+    /// # Arguments
+    /// * `base` - The common ancestor's contents.
+    /// * `ours` - Our version of the contents.
+    /// * `theirs` - Their version of the contents.
+    ///
+    /// # Returns
+    /// A `MergeFileResult` with the merged contents and the number of conflicts.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the temporary files cannot be written.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn merge_file(&self, base: &str, ours: &str, theirs: &str) -> Result<MergeFileResult> {
+        let scratch_dir = ScratchDir::new(&self.location, "merge-file")?;
+        merge_file_in(&self.location, scratch_dir.path(), base, ours, theirs)
+    }
+}
+
+// --- Patch Operations ---
+
+impl Repository {
+    /// Writes each commit in a range as an email-style patch file.
+    ///
+    /// Equivalent to `git format-patch -o <out_dir> <range>`.
+    ///
+    /// # Arguments
+    /// * `range` - The commits to export, e.g. `"main..topic"` or `"-3"`.
+    /// * `out_dir` - The directory to write the patches to; relative paths are
+    ///   resolved against the repository root.
+    ///
+    /// # Returns
+    /// The paths of the written patch files, in commit order.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn format_patch<P: AsRef<Path>>(&self, range: &str, out_dir: P) -> Result<Vec<PathBuf>> {
+        execute_git_fn(
+            &self.location,
+            [
+                "format-patch".as_ref(),
+                "-o".as_ref(),
+                out_dir.as_ref().as_os_str(),
+                range.as_ref(),
+            ],
+            |output| {
+                Ok(output
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(|line| self.location.join(line))
+                    .collect())
+            },
+        )
+    }
+
+    /// Applies a patch to the working tree.
+    ///
+    /// Equivalent to `git apply [--3way] [--check] <path>`.
+    ///
+    /// # Arguments
+    /// * `path` - The patch file to apply.
+    /// * `opts` - An `ApplyOptions` controlling how the patch is applied.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the patch does not apply (including `GitNotFound`).
+    pub fn apply_patch<P: AsRef<Path>>(&self, path: P, opts: &ApplyOptions) -> Result<()> {
+        let mut args: Vec<&OsStr> = vec!["apply".as_ref()];
+        if opts.three_way {
+            args.push("--3way".as_ref());
+        }
+        if opts.check {
+            args.push("--check".as_ref());
+        }
+        args.push(path.as_ref().as_os_str());
+        execute_git(&self.location, args)
+    }
+
+    /// Applies a series of patches from mailboxes, creating a commit for each.
+    ///
+    /// Equivalent to `git am <mbox>...`.
+    ///
+    /// # Arguments
+    /// * `mbox_paths` - Mailbox or patch files, such as those written by `format_patch`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if a patch fails to apply, leaving the session stopped for
+    /// `am_continue`, `am_skip` or `am_abort` (including `GitNotFound`).
+    pub fn am<P: AsRef<Path>>(&self, mbox_paths: &[P]) -> Result<()> {
+        let mut args: Vec<&OsStr> = Vec::with_capacity(mbox_paths.len() + 1);
+        args.push("am".as_ref());
+        for path in mbox_paths {
+            args.push(path.as_ref().as_os_str());
+        }
+        execute_git(&self.location, args)
+    }
+
+    /// Continues a stopped `am` session after the conflicts have been resolved and staged.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn am_continue(&self) -> Result<()> {
+        execute_git(&self.location, ["am", "--continue"])
+    }
+
+    /// Skips the patch that stopped the `am` session and continues with the next one.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn am_skip(&self) -> Result<()> {
+        execute_git(&self.location, ["am", "--skip"])
+    }
+
+    /// Aborts the `am` session and restores the branch to its original state.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn am_abort(&self) -> Result<()> {
+        execute_git(&self.location, ["am", "--abort"])
+    }
+
+    /// Compares two versions of a patch series, such as a branch before and after
+    /// a rebase, matching each old commit to the new commit making the same change.
+    ///
+    /// Equivalent to `git range-diff <old_range> <new_range>`.
+    ///
+    /// # Arguments
+    /// * `old_range` - The old series, e.g. `"main..topic@{1}"`.
+    /// * `new_range` - The new series, e.g. `"main..topic"`.
+    ///
+    /// # Returns
+    /// One entry per matched pair or unmatched commit, in Git's display order.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn range_diff(&self, old_range: &str, new_range: &str) -> Result<Vec<RangeDiffEntry>> {
+        execute_git_fn(
+            &self.location,
+            ["-c", "core.abbrev=no", "range-diff", "--no-color", old_range, new_range],
+            |output| Ok(RangeDiffEntry::from_range_diff(output)),
+        )
+    }
+}
+
+// --- History Operations ---
+
+impl Repository {
+    /// Counts the commits reachable from a reference or contained in a range.
+    ///
+    /// Equivalent to `git rev-list --count <range>`.
+    ///
+    /// # Arguments
+    /// * `range` - A reference or range (e.g., "main", "v1.0..HEAD"). If `None`, uses HEAD.
+    ///
+    /// # Returns
+    /// The number of commits.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn count_commits(&self, range: Option<&str>) -> Result<usize> {
+        execute_git_fn(
+            &self.location,
+            ["rev-list", "--count", range.unwrap_or("HEAD")],
+            |output| {
+                output.trim().parse::<usize>().map_err(|_| GitError::GitError {
+                    stdout: output.to_string(),
+                    stderr: "Failed to parse commit count".to_string(),
+                })
+            },
+        )
+    }
+
+    /// Lists the hashes of commits reachable from a reference or contained in a range,
+    /// newest first.
+    ///
+    /// Equivalent to `git rev-list [options] <range> [-- <path>...]`.
+    ///
+    /// # Arguments
+    /// * `range` - A reference or range (e.g., "main", "v1.0..HEAD"). If `None`, uses HEAD.
+    /// * `opts` - Filters such as `--first-parent`, `--merges` and path limiting.
+    ///
+    /// # Returns
+    /// A `Vec<CommitHash>` of the matching commits.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn rev_list(&self, range: Option<&str>, opts: &RevListOptions) -> Result<Vec<CommitHash>> {
+        let max_count_arg = opts.max_count.map(|n| format!("--max-count={}", n));
+
+        let mut args: Vec<&OsStr> = vec!["rev-list".as_ref()];
+        if opts.first_parent {
+            args.push("--first-parent".as_ref());
+        }
+        if opts.merges {
+            args.push("--merges".as_ref());
+        }
+        if opts.no_merges {
+            args.push("--no-merges".as_ref());
+        }
+        if let Some(arg) = &max_count_arg {
+            args.push(arg.as_ref());
+        }
+        args.push(range.unwrap_or("HEAD").as_ref());
+        if !opts.paths.is_empty() {
+            args.push("--".as_ref());
+            args.extend(pathspec_args(&opts.paths)?);
+        }
+
+        execute_git_fn(&self.location, args, |output| {
+            output
+                .lines()
+                .map(|line| CommitHash::from_str(line.trim()))
+                .collect::<Result<Vec<CommitHash>>>()
+        })
+    }
+}
+
+impl Repository {
+    /// Gets detailed information about several commits using a single `git log` invocation.
+    ///
+    /// Equivalent to `git log --no-walk=unsorted --format=... --end-of-options <ref>... --`.
+    ///
+    /// # Arguments
+    /// * `refs` - The commit references (hashes, branch names, etc.) to look up.
+    ///
+    /// # Returns
+    /// A `Vec<Commit>` in the order the references were given (duplicates are collapsed).
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn get_commits(&self, refs: &[&str]) -> Result<Vec<Commit>> {
+        if refs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let format_arg = format!("--format={}", LOG_RECORD_FORMAT);
+        let mut args: Vec<&str> = vec!["log", "--no-walk=unsorted", &format_arg, "--end-of-options"];
+        args.extend_from_slice(refs);
+        // Refs that are also file names would otherwise be ambiguous.
+        args.push("--");
+
+        execute_git_fn(&self.location, args, |output| {
+            Ok(parse_log_records(output)?
+                .into_iter()
+                .map(|(commit, _)| commit)
+                .collect())
+        })
+    }
+
+    /// Retrieves commits together with their per-file line counts using a single
+    /// `git log` invocation.
+    ///
+    /// Equivalent to `git log -z --numstat --format=... <range>`.
+    ///
+    /// # Arguments
+    /// * `range` - A reference or range (e.g., "main", "v1.0..HEAD"). If `None`, uses HEAD.
+    ///
+    /// # Returns
+    /// A `Vec<CommitWithStats>`, newest first. Merge commits have no file stats.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn log_with_stats(&self, range: Option<&str>) -> Result<Vec<CommitWithStats>> {
+        let format_arg = format!("--format={}", LOG_RECORD_FORMAT);
+        let args = ["log", "-z", "--numstat", &format_arg, range.unwrap_or("HEAD")];
+
+        execute_git_fn(&self.location, args, |output| {
+            Ok(parse_log_records(output)?
+                .into_iter()
+                .map(|(commit, rest)| {
+                    let numstat = rest.trim_start_matches('\0');
+                    let numstat = numstat.strip_prefix('\n').unwrap_or(numstat);
+                    CommitWithStats {
+                        commit,
+                        files: FileStat::from_numstat_z(numstat),
+                    }
+                })
+                .collect())
+        })
+    }
+
+    /// Retrieves the commits that changed a file, following it across renames.
+    ///
+    /// Equivalent to `git log --follow --format=... [<revision>] -- <path>`.
+    ///
+    /// # Arguments
+    /// * `path` - The file to follow, relative to the repository root.
+    /// * `opts` - A `FileHistoryOptions` limiting the walk.
+    ///
+    /// # Returns
+    /// A `Vec<Commit>`, newest first, including commits from before the file was renamed.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn file_history<P: AsRef<Path>>(&self, path: P, opts: &FileHistoryOptions) -> Result<Vec<Commit>> {
+        let mut args: Vec<String> = vec![
+            "log".to_string(),
+            "--follow".to_string(),
+            format!("--format={}", LOG_RECORD_FORMAT),
+        ];
+        if let Some(max_count) = opts.max_count {
+            args.push(format!("--max-count={}", max_count));
+        }
+        if let Some(since) = &opts.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(until) = &opts.until {
+            args.push(format!("--until={}", until));
+        }
+        if let Some(revision) = &opts.revision {
+            args.push(revision.clone());
+        }
+        let spec = pathspec(path.as_ref())?;
+        let mut args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
+        args.push("--".as_ref());
+        args.push(&spec);
+
+        execute_git_fn(&self.location, args, |output| {
+            Ok(parse_log_records(output)?
+                .into_iter()
+                .map(|(commit, _)| commit)
+                .collect())
+        })
+    }
+}
+
+// --- Ancestry Queries ---
+
+impl Repository {
+    /// Checks whether `ancestor` is an ancestor of (or the same commit as) `descendant`.
+    ///
+    /// Equivalent to `git merge-base --is-ancestor <ancestor> <descendant>`.
+    ///
+    /// # Arguments
+    /// * `ancestor` - The candidate ancestor revision.
+    /// * `descendant` - The candidate descendant revision.
+    ///
+    /// # Errors
+    /// Returns `GitError` if either revision cannot be resolved (including `GitNotFound`).
+    pub fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool> {
+        // merge-base exits 0 for "is an ancestor" and 1 for "is not".
+        let not_ancestor = exit_code_flag(execute_git_status(
+            &self.location,
+            ["merge-base", "--is-ancestor", ancestor, descendant],
+        )?)?;
+        Ok(!not_ancestor)
+    }
+
+    /// Finds the commits of `head` that have not been applied to `upstream`,
+    /// recognising commits applied under a different id (e.g. cherry-picked or
+    /// rebased) by comparing their patches.
+    ///
+    /// Equivalent to `git cherry <upstream> [<head>]`.
+    ///
+    /// # Arguments
+    /// * `upstream` - The branch the commits are expected to land in.
+    /// * `head` - The branch holding the local commits, or `None` for `HEAD`.
+    ///
+    /// # Returns
+    /// Each commit of `head` not in `upstream`, oldest first, marked `Missing` if
+    /// its change is not upstream or `Equivalent` if an equivalent change is.
+    ///
+    /// # Errors
+    /// Returns `GitError` if either revision cannot be resolved (including `GitNotFound`).
+    pub fn cherry(&self, upstream: &str, head: Option<&str>) -> Result<Vec<(CherryMark, CommitHash)>> {
+        let mut args = vec!["cherry", upstream];
+        args.extend(head);
+        execute_git_fn(&self.location, args, |output| Ok(CherryMark::from_cherry(output)))
+    }
+}
+
+// --- Ref Management ---
+
+impl Repository {
+    /// Reads the ref a symbolic ref points to, such as the branch `HEAD` is on.
+    ///
+    /// Equivalent to `git symbolic-ref <name>`.
+    ///
+    /// # Arguments
+    /// * `name` - The symbolic ref, e.g. `HEAD` or `refs/remotes/origin/HEAD`.
+    ///
+    /// # Returns
+    /// The full name of the target, e.g. `refs/heads/main`.
+    ///
+    /// # Errors
+    /// Returns `GitError` if `name` is not a symbolic ref, e.g. `HEAD` is
+    /// detached (including `GitNotFound`).
+    pub fn symbolic_ref(&self, name: &str) -> Result<String> {
+        execute_git_fn(&self.location, ["symbolic-ref", name], |output| Ok(output.trim().to_string()))
+    }
+
+    /// Finds the default branch of a remote, the branch a fresh clone checks out.
+    ///
+    /// Reads the remote's `HEAD` as recorded locally by `clone` or `git remote
+    /// set-head`, and asks the remote when it is not recorded. Equivalent to
+    /// `git symbolic-ref refs/remotes/<remote>/HEAD`, falling back to
+    /// `git ls-remote --symref <remote> HEAD`.
+    ///
+    /// # Arguments
+    /// * `remote` - The remote to inspect.
+    ///
+    /// # Returns
+    /// The default branch's name on the remote, e.g. `main`.
+    ///
+    /// # Errors
+    /// Returns `GitError::NoDefaultBranch` if the remote's `HEAD` does not point
+    /// to a branch.
+    /// Returns `GitError` if the remote cannot be reached (including `GitNotFound`).
+    pub fn default_branch(&self, remote: &Remote) -> Result<BranchName> {
+        let tracking_head = format!("refs/remotes/{}/HEAD", remote);
+        // Exit code 1 means the remote's HEAD is not recorded.
+        let recorded = execute_git_fn_with(
+            &self.location,
+            ["symbolic-ref", "--quiet", &tracking_head],
+            None,
+            &[0, 1],
+            |output| Ok(output.trim().strip_prefix(&format!("refs/remotes/{}/", remote)).map(str::to_string)),
+        )?;
+        let branch = match recorded {
+            Some(branch) => branch,
+            None => execute_git_fn(&self.location, ["ls-remote", "--symref", remote.as_ref(), "HEAD"], |output| {
+                // The symref line is `ref: refs/heads/<branch>\tHEAD`.
+                output
+                    .lines()
+                    .find_map(|line| line.strip_prefix("ref: refs/heads/")?.strip_suffix("\tHEAD"))
+                    .map(str::to_string)
+                    .ok_or_else(|| GitError::NoDefaultBranch(remote.to_string()))
+            })?,
+        };
+        BranchName::from_str(&branch)
+    }
+
+    /// Points `HEAD` at a branch without touching the index or working tree.
+    ///
+    /// Unlike `switch_branch`, the branch need not exist yet; the next commit
+    /// then creates it with no parent.
+    ///
+    /// Equivalent to `git symbolic-ref HEAD refs/heads/<branch>`.
+    ///
+    /// # Arguments
+    /// * `branch` - The branch `HEAD` should point to.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn set_head(&self, branch: &BranchName) -> Result<()> {
+        execute_git(&self.location, ["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])
+    }
+
+    /// Sets a ref to a commit, optionally only if it still has an expected value.
+    ///
+    /// Equivalent to `git update-ref <reference> <new> [<old>]`. The check and the
+    /// update happen atomically under the ref's lock, so concurrent writers cannot
+    /// be overwritten unnoticed.
+    ///
+    /// # Arguments
+    /// * `reference` - The full ref name, e.g. `refs/heads/main`.
+    /// * `new` - The commit to point the ref to.
+    /// * `old` - The value the ref must have for the update to happen; the
+    ///   all-zero hash requires that the ref does not exist yet. `None` updates
+    ///   unconditionally.
+    ///
+    /// # Errors
+    /// Returns `GitError::StaleRef` if `old` is given and does not match.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn update_ref(&self, reference: &str, new: &CommitHash, old: Option<&CommitHash>) -> Result<()> {
+        self.compare_and_swap_ref(reference, new.as_ref(), old)
+    }
+
+    /// Moves a branch to a commit, optionally only if it still points to an
+    /// expected commit, so automation racing with other writers never silently
+    /// discards their updates.
+    ///
+    /// Equivalent to `git update-ref refs/heads/<branch> <new> [<old>]`. Only the
+    /// ref moves: if the branch is checked out, the index and working tree are
+    /// left as they were.
+    ///
+    /// # Arguments
+    /// * `branch` - The branch to move; it is created if it does not exist.
+    /// * `new_commit` - The commit to point the branch to.
+    /// * `expected_old` - The commit the branch must point to for the update to
+    ///   happen; the all-zero hash requires that the branch does not exist yet.
+    ///   `None` updates unconditionally.
+    ///
+    /// # Errors
+    /// Returns `GitError::StaleRef` if `expected_old` is given and does not match.
+    /// Returns `GitError` if `new_commit` is not a commit (including `GitNotFound`).
+    pub fn update_branch(
+        &self,
+        branch: &BranchName,
+        new_commit: &CommitHash,
+        expected_old: Option<&CommitHash>,
+    ) -> Result<()> {
+        // Peeling makes Git reject trees and blobs, which update-ref would accept.
+        let new = format!("{}^{{commit}}", new_commit);
+        self.compare_and_swap_ref(&format!("refs/heads/{}", branch), &new, expected_old)
+    }
+
+    /// Runs `git update-ref <reference> <new> [<old>]`, reporting a mismatch with
+    /// `old` as `GitError::StaleRef`.
+    fn compare_and_swap_ref(&self, reference: &str, new: &str, old: Option<&CommitHash>) -> Result<()> {
+        let mut args = vec!["update-ref", reference, new];
+        if let Some(old) = old {
+            args.push(old.as_ref());
+        }
+        execute_git(&self.location, args).map_err(|err| match err {
+            GitError::GitError { ref stderr, .. }
+                if old.is_some()
+                    && ["but expected", "reference already exists", "unable to resolve reference"]
+                        .iter()
+                        .any(|reason| stderr.contains(reason)) =>
+            {
+                GitError::StaleRef { reference: reference.to_string() }
+            }
+            err => err,
+        })
+    }
+}
+
+// --- Tags ---
+
+impl Repository {
+    /// Lists all tags in version order, so `v1.10` sorts after `v1.9`.
+    ///
+    /// Equivalent to `git tag --list --sort=v:refname`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn list_tags(&self) -> Result<Vec<Tag>> {
+        self.tags(&["--list"])
+    }
+
+    /// Lists the tags matching a shell wildcard pattern, in version order.
+    ///
+    /// Equivalent to `git tag --list --sort=v:refname <pattern>`.
+    ///
+    /// # Arguments
+    /// * `pattern` - The pattern to match, e.g. `"v2.*"`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn list_tags_matching(&self, pattern: &str) -> Result<Vec<Tag>> {
+        self.tags(&["--list", pattern])
+    }
+
+    /// Lists the tags that point at a commit, in version order.
+    ///
+    /// Equivalent to `git tag --points-at <commit> --sort=v:refname`.
+    ///
+    /// # Arguments
+    /// * `commit` - The commit to look up, e.g. `HEAD` or a hash.
+    ///
+    /// # Errors
+    /// Returns `GitError` if `commit` cannot be resolved (including `GitNotFound`).
+    pub fn tags_pointing_at(&self, commit: &str) -> Result<Vec<Tag>> {
+        self.tags(&["--points-at", commit])
+    }
+
+    /// Finds the most recent tag reachable from `HEAD`, i.e. the last release
+    /// the current commit is based on.
+    ///
+    /// Equivalent to `git describe --tags --abbrev=0`.
+    ///
+    /// # Returns
+    /// The tag, or `None` if no tag is reachable from `HEAD`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn latest_tag(&self) -> Result<Option<Tag>> {
+        let info = execute_git_status(&self.location, ["describe", "--tags", "--abbrev=0"])?;
+        if info.success() {
+            return Tag::from_str(info.stdout.trim()).map(Some);
+        }
+        if info.stderr.contains("No names found") || info.stderr.contains("No tags can describe") {
+            return Ok(None);
+        }
+        Err(GitError::GitError { stdout: info.stdout, stderr: info.stderr })
+    }
+
+    /// Runs `git tag --sort=v:refname` with `args` and parses the tag names.
+    fn tags(&self, args: &[&str]) -> Result<Vec<Tag>> {
+        let mut full_args = vec!["tag", "--sort=v:refname"];
+        full_args.extend_from_slice(args);
+        execute_git_fn(&self.location, full_args, |output| output.lines().map(Tag::from_str).collect())
+    }
+}
+
+// --- Diff Operations ---
+
+impl Repository {
+    /// Computes per-file line counts and totals between two commits, or between a
+    /// commit and the working tree.
+    ///
+    /// Equivalent to `git diff -z --numstat --shortstat <from> [<to>]`.
+    ///
+    /// # Arguments
+    /// * `from` - The reference to diff from (e.g., "HEAD~1", a commit hash).
+    /// * `to` - The reference to diff to. If `None`, diffs against the working tree.
+    ///
+    /// # Returns
+    /// A `DiffStats` struct. Binary files have `None` line counts; renamed files
+    /// carry their previous path in `old_path`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn diff_stats(&self, from: &str, to: Option<&str>) -> Result<DiffStats> {
+        let mut args = vec!["diff", "-z", "--numstat", "--shortstat", from];
+        if let Some(to) = to {
+            args.push(to);
+        }
+        execute_git_fn(&self.location, args, |output| {
+            Ok(DiffStats::from_numstat_shortstat_z(output))
+        })
+    }
+
+    /// Computes a structured diff between two commits, or between a commit and the
+    /// working tree.
+    ///
+    /// Equivalent to `git diff <from> [<to>] [-- <path>...]`, with
+    /// `--word-diff=porcelain` in word and character modes.
+    ///
+    /// # Arguments
+    /// * `from` - The reference to diff from (e.g., "HEAD~1", a commit hash).
+    /// * `to` - The reference to diff to. If `None`, diffs against the working tree.
+    /// * `opts` - A `DiffOptions` selecting the granularity, paths and rename detection.
+    ///
+    /// # Returns
+    /// A `DiffResult`. In word and character modes each `DiffLine` carries its
+    /// added and removed `spans`. Renamed and copied files carry their previous path
+    /// in `old_path` and the match score in `similarity`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn diff(&self, from: &str, to: Option<&str>, opts: &DiffOptions) -> Result<DiffResult> {
+        // Keep non-ASCII names unquoted so they parse back as-is.
+        let mut args: Vec<&OsStr> = vec![
+            "-c".as_ref(),
+            "core.quotePath=false".as_ref(),
+            "diff".as_ref(),
+            "--no-color".as_ref(),
+            "--no-ext-diff".as_ref(),
+        ];
+        match opts.mode {
+            DiffMode::Lines => {}
+            DiffMode::Words => args.push("--word-diff=porcelain".as_ref()),
+            DiffMode::Chars => {
+                args.push("--word-diff=porcelain".as_ref());
+                args.push(char_diff_regex());
+            }
+        }
+        let find_renames = opts.detect_renames.map(|n| format!("--find-renames={}%", n));
+        let find_copies = opts.detect_copies.map(|n| format!("--find-copies={}%", n));
+        args.extend(find_renames.iter().chain(find_copies.iter()).map(OsStr::new));
+        args.push(from.as_ref());
+        if let Some(to) = to {
+            args.push(to.as_ref());
+        }
+        if !opts.paths.is_empty() {
+            args.push("--".as_ref());
+            args.extend(pathspec_args(&opts.paths)?);
+        }
+        let output = execute_git_lossy(&self.location, args)?;
+        Ok(DiffResult::from_patch(&output, opts.mode != DiffMode::Lines))
+    }
+
+    /// Returns the unified diff between two commits, or between a commit and the
+    /// working tree, as raw patch text.
+    ///
+    /// Equivalent to `git diff --no-color --no-ext-diff <from> [<to>] [-- <path>...]`.
+    ///
+    /// # Arguments
+    /// * `from` - The reference to diff from (e.g., "HEAD~1", a commit hash).
+    /// * `to` - The reference to diff to. If `None`, diffs against the working tree.
+    /// * `paths` - If given, limits the diff to these paths.
+    ///
+    /// # Returns
+    /// The patch text. Content that is not valid UTF-8 is replaced with `U+FFFD`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn diff_patch(&self, from: &str, to: Option<&str>, paths: Option<&[Pathspec]>) -> Result<String> {
+        let mut args: Vec<&OsStr> = vec![
+            "diff".as_ref(),
+            "--no-color".as_ref(),
+            "--no-ext-diff".as_ref(),
+            from.as_ref(),
+        ];
+        if let Some(to) = to {
+            args.push(to.as_ref());
+        }
+        if let Some(paths) = paths {
+            args.push("--".as_ref());
+            args.extend(pathspec_args(paths)?);
+        }
+        execute_git_lossy(&self.location, args)
+    }
+}
+
+// --- Status Queries ---
+
+impl Repository {
+    /// Checks whether any tracked file has staged or unstaged changes.
+    ///
+    /// Cheaper than `status()` for simple gating: it relies on the exit codes of
+    /// `git diff --quiet` and `git diff --cached --quiet` instead of parsing output.
+    /// Untracked files do not make the repository dirty; see `has_untracked_files`.
+    ///
+    /// # Returns
+    /// `true` if the working tree or the index differs from `HEAD`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn is_dirty(&self) -> Result<bool> {
+        Ok(self.has_unstaged_changes()? || self.has_staged_changes()?)
+    }
+
+    /// Checks whether the index differs from `HEAD`.
+    ///
+    /// Equivalent to `git diff --cached --quiet`. In a repository without commits,
+    /// any staged file counts as a change.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn has_staged_changes(&self) -> Result<bool> {
+        exit_code_flag(execute_git_status(&self.location, ["diff", "--cached", "--quiet"])?)
+    }
+
+    /// Checks whether tracked files in the working tree differ from the index.
+    ///
+    /// Equivalent to `git diff --quiet`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn has_unstaged_changes(&self) -> Result<bool> {
+        exit_code_flag(execute_git_status(&self.location, ["diff", "--quiet"])?)
+    }
+
+    /// Checks whether there are untracked files that are not ignored.
+    ///
+    /// Equivalent to `git ls-files --others --exclude-standard --directory --no-empty-directory`.
+    /// Untracked directories are reported as a whole, so large untracked trees are not walked.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn has_untracked_files(&self) -> Result<bool> {
+        execute_git_fn(
+            &self.location,
+            [
+                "ls-files",
+                "--others",
+                "--exclude-standard",
+                "--directory",
+                "--no-empty-directory",
+            ],
+            |output| Ok(!output.trim().is_empty()),
+        )
+    }
+
+    /// Fails unless the working tree and index match `HEAD`, so destructive
+    /// operations can be guarded with a single call.
+    ///
+    /// Ignored files never count as changes.
+    ///
+    /// # Arguments
+    /// * `allow_untracked` - Whether untracked files are acceptable.
+    ///
+    /// # Errors
+    /// Returns `GitError::DirtyWorkingTree` listing every offending path if there
+    /// are staged, unstaged or unmerged changes, or untracked files when they are
+    /// not allowed.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn ensure_clean(&self, allow_untracked: bool) -> Result<()> {
+        let files: Vec<PathBuf> = self
+            .status()?
+            .files
+            .into_iter()
+            .filter(|entry| !(allow_untracked && entry.status() == FileStatus::Untracked))
+            .map(|entry| entry.path)
+            .collect();
+        if files.is_empty() {
+            Ok(())
+        } else {
+            Err(GitError::DirtyWorkingTree { files })
+        }
+    }
+}
+
+// --- Search Operations ---
+
+impl Repository {
+    /// Searches tracked files, or the files of a revision, for lines matching a pattern.
+    ///
+    /// Equivalent to `git grep -n --null -I --full-name -e <pattern> [<revision>] [-- <path>...]`.
+    /// Binary files are skipped.
+    ///
+    /// # Arguments
+    /// * `pattern` - The pattern to search for, interpreted according to `opts.pattern_kind`.
+    /// * `opts` - A `GrepOptions` controlling the search.
+    ///
+    /// # Returns
+    /// A `Vec<GrepMatch>`; empty if nothing matched.
+    ///
+    /// # Errors
+    /// Returns `GitError` for invalid patterns or revisions (including `GitNotFound`).
+    pub fn grep(&self, pattern: &str, opts: &GrepOptions) -> Result<Vec<GrepMatch>> {
+        let mut args: Vec<&OsStr> = vec![
+            "grep".as_ref(),
+            "-n".as_ref(),
+            "--null".as_ref(),
+            "-I".as_ref(),
+            "--full-name".as_ref(),
+        ];
+        if opts.ignore_case {
+            args.push("--ignore-case".as_ref());
+        }
+        match opts.pattern_kind {
+            GrepPatternKind::Basic => {}
+            GrepPatternKind::Extended => args.push("--extended-regexp".as_ref()),
+            GrepPatternKind::Perl => args.push("--perl-regexp".as_ref()),
+            GrepPatternKind::Fixed => args.push("--fixed-strings".as_ref()),
+        }
+        args.push("-e".as_ref());
+        args.push(pattern.as_ref());
+        if let Some(revision) = &opts.revision {
+            args.push(revision.as_ref());
+        }
+        if !opts.paths.is_empty() {
+            args.push("--".as_ref());
+            args.extend(pathspec_args(&opts.paths)?);
+        }
+
+        // Exit code 1 means "no matches", which is not an error here.
+        execute_git_fn_with(&self.location, args, None, &[0, 1], |output| {
+            Ok(GrepMatch::from_grep_null(output, opts.revision.as_deref()))
+        })
+    }
+}
+
+// --- Ignore Rules ---
+
+impl Repository {
+    /// Lists untracked files and directories that are excluded by ignore rules
+    /// (`.gitignore`, `.git/info/exclude` and the global excludes file).
+    ///
+    /// Equivalent to `git ls-files -z --others --ignored --exclude-standard --directory`.
+    ///
+    /// # Returns
+    /// A `Vec<PathBuf>` relative to the repository root. Wholly ignored directories are
+    /// reported once, with a trailing `/`, rather than file by file.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn list_ignored(&self) -> Result<Vec<PathBuf>> {
+        self.ls_files(&LsFilesOptions {
+            others: true,
+            ignored: true,
+            exclude_standard: true,
+            directory: true,
+            ..Default::default()
+        })
+    }
+
+    /// Checks whether each of the given paths is excluded by ignore rules.
+    ///
+    /// Equivalent to `git check-ignore --stdin -z --verbose --non-matching`.
+    /// Paths need not exist. Paths matched only by a negated pattern (`!pattern`)
+    /// are reported as not ignored.
+    ///
+    /// # Arguments
+    /// * `paths` - Paths relative to the repository root.
+    ///
+    /// # Returns
+    /// A `Vec<(PathBuf, bool)>` pairing each path with whether it is ignored, in input order.
+    ///
+    /// # Errors
+    /// Returns `GitError::PathEncodingError` if a path is not valid UTF-8.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn check_ignore<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<(PathBuf, bool)>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut input = String::new();
+        for path in paths {
+            let path = path.as_ref();
+            let path_str = path
+                .to_str()
+                .ok_or_else(|| GitError::PathEncodingError(path.to_path_buf()))?;
+            if cfg!(windows) {
+                input.push_str(&path_str.replace('\\', "/"));
+            } else {
+                input.push_str(path_str);
+            }
+            input.push('\0');
+        }
+
+        // Exit code 1 means "no path is ignored", which is not an error here.
+        execute_git_fn_with(
+            &self.location,
+            ["check-ignore", "--stdin", "-z", "--verbose", "--non-matching"],
+            Some(input.as_bytes()),
+            &[0, 1],
+            |output| {
+                // Each record is <source> NUL <linenum> NUL <pattern> NUL <pathname> NUL;
+                // the first three fields are empty for non-matching paths.
+                let fields: Vec<&str> = output.split('\0').collect();
+                Ok(fields
+                    .chunks_exact(4)
+                    .map(|record| {
+                        let ignored = !record[0].is_empty() && !record[2].starts_with('!');
+                        (PathBuf::from(record[3]), ignored)
+                    })
+                    .collect())
+            },
+        )
+    }
+
+    /// Checks whether the repository's root `.gitignore` lists `pattern` on a
+    /// line of its own.
+    ///
+    /// This compares the text of the patterns, not what they match: `*.log`
+    /// is not reported as contained just because `debug.log` is ignored. Use
+    /// `check_ignore` to test paths.
+    ///
+    /// # Returns
+    /// `false` if there is no `.gitignore`.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the file cannot be read.
+    pub fn gitignore_contains(&self, pattern: &str) -> Result<bool> {
+        let pattern = trim_gitignore_pattern(pattern);
+        Ok(self.read_gitignore()?.lines().any(|line| trim_gitignore_pattern(line) == pattern))
+    }
+
+    /// Appends patterns to the repository's root `.gitignore`, creating it if
+    /// needed. Patterns already listed, or repeated in `patterns`, are skipped,
+    /// so running the same scaffolding twice leaves the file unchanged. The file
+    /// is not staged.
+    ///
+    /// # Arguments
+    /// * `patterns` - The patterns to add, e.g. `["target/", "*.log"]`.
+    ///
+    /// # Returns
+    /// The patterns that were added.
+    ///
+    /// # Errors
+    /// Returns `GitError::InvalidGitignorePattern` if a pattern is blank or spans
+    /// several lines.
+    /// Returns `GitError::Io` if the file cannot be read or written.
+    pub fn add_to_gitignore(&self, patterns: &[&str]) -> Result<Vec<String>> {
+        if let Some(bad) = patterns.iter().find(|p| p.trim().is_empty() || p.contains(['\n', '\r'])) {
+            return Err(GitError::InvalidGitignorePattern(bad.to_string()));
+        }
+        let mut contents = self.read_gitignore()?;
+        let mut added: Vec<String> = Vec::new();
+        for pattern in patterns.iter().map(|p| trim_gitignore_pattern(p)) {
+            let listed = contents.lines().any(|line| trim_gitignore_pattern(line) == pattern);
+            if !listed && !added.iter().any(|p| p == pattern) {
+                added.push(pattern.to_string());
+            }
+        }
+        if added.is_empty() {
+            return Ok(added);
+        }
+
+        // Keep the file's line endings.
+        let newline = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push_str(newline);
+        }
+        for pattern in &added {
+            contents.push_str(pattern);
+            contents.push_str(newline);
+        }
+        fs::write(self.location.join(".gitignore"), contents)?;
+        Ok(added)
+    }
+
+    /// Finds the user's global excludes file, which applies to every repository.
+    ///
+    /// Equivalent to `git config --get --path core.excludesFile`, falling back to
+    /// Git's default of `$XDG_CONFIG_HOME/git/ignore` or `~/.config/git/ignore`.
+    ///
+    /// # Returns
+    /// The path Git reads global ignore rules from, which need not exist, or
+    /// `None` if it is not configured and no home directory is set.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn global_excludes_file(&self) -> Result<Option<PathBuf>> {
+        // Exit code 1 means the key is not set.
+        let configured = execute_git_fn_with(
+            &self.location,
+            ["config", "--get", "--path", "core.excludesFile"],
+            None,
+            &[0, 1],
+            |output| Ok(Some(output.trim_end_matches('\n')).filter(|path| !path.is_empty()).map(PathBuf::from)),
+        )?;
+        if let Some(path) = configured {
+            return Ok(Some(self.location.join(path)));
+        }
+        let config_home = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => match env::var_os("HOME").filter(|dir| !dir.is_empty()) {
+                Some(home) => PathBuf::from(home).join(".config"),
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(config_home.join("git").join("ignore")))
+    }
+
+    /// Reads the root `.gitignore`, or an empty string if there is none.
+    fn read_gitignore(&self) -> Result<String> {
+        match fs::read_to_string(self.location.join(".gitignore")) {
+            Ok(contents) => Ok(contents),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+// --- Identity ---
+
+impl Repository {
+    /// Sets the name and email used for commits made in this repository.
+    ///
+    /// Equivalent to `git config <scope> user.name <name>` followed by
+    /// `git config <scope> user.email <email>`.
+    ///
+    /// # Arguments
+    /// * `name` - The value for `user.name`.
+    /// * `email` - The value for `user.email`.
+    /// * `scope` - The configuration file to write, usually `ConfigScope::Local`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn set_identity(&self, name: &str, email: &str, scope: ConfigScope) -> Result<()> {
+        execute_git(&self.location, ["config", scope.as_flag(), "user.name", name])?;
+        execute_git(&self.location, ["config", scope.as_flag(), "user.email", email])
+    }
+
+    /// Gets the name and email Git will use for commits in this repository,
+    /// resolving local, global and system configuration in the usual order.
+    ///
+    /// Equivalent to `git config --get user.name` and `git config --get user.email`.
+    /// Environment overrides such as `GIT_AUTHOR_NAME` are not considered.
+    ///
+    /// # Returns
+    /// `Some(Identity)` if both values are configured, `None` if either is missing,
+    /// in which case `git commit` would fail with "Please tell me who you are".
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn get_identity(&self) -> Result<Option<Identity>> {
+        let get = |key: &str| {
+            // Exit code 1 means the key is not set.
+            execute_git_fn_with(&self.location, ["config", "--get", key], None, &[0, 1], |output| {
+                let value = output.trim_end_matches('\n');
+                Ok(if value.is_empty() { None } else { Some(value.to_string()) })
+            })
+        };
+        Ok(match (get("user.name")?, get("user.email")?) {
+            (Some(name), Some(email)) => Some(Identity { name, email }),
+            _ => None,
+        })
+    }
+}
+
+// --- Submodules ---
+
+impl Repository {
+    /// Lists the direct submodules of this repository and how their checkouts
+    /// relate to the commits recorded in the superproject.
+    ///
+    /// Equivalent to `git submodule status`.
+    ///
+    /// # Returns
+    /// A `Vec<SubmoduleStatus>`, empty if there are no submodules.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn submodule_status(&self) -> Result<Vec<SubmoduleStatus>> {
+        execute_git_fn(&self.location, ["submodule", "status"], |output| {
+            Ok(SubmoduleStatus::from_submodule_status(output))
+        })
+    }
+
+    /// Gets the status of this repository and, recursively, of every initialized
+    /// submodule, so a single call shows uncommitted changes anywhere in the tree.
+    ///
+    /// For each submodule this runs `git submodule status` (and `--cached` for the
+    /// recorded commits) in the superproject, then `status_recursive` and
+    /// `git rev-list --left-right --count <recorded>...HEAD` inside the submodule.
+    ///
+    /// # Returns
+    /// A `RecursiveStatus` tree; see `RecursiveStatus::is_dirty` for a summary.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn status_recursive(&self) -> Result<RecursiveStatus> {
+        let status = self.status()?;
+        let recorded = execute_git_fn(&self.location, ["submodule", "status", "--cached"], |output| {
+            Ok(SubmoduleStatus::from_submodule_status(output))
+        })?;
+
+        let mut submodules = Vec::new();
+        for submodule in self.submodule_status()? {
+            let recorded_commit = recorded
+                .iter()
+                .find(|entry| entry.path == submodule.path)
+                .map(|entry| entry.commit.clone());
+
+            let (mut ahead, mut behind, mut nested) = (None, None, None);
+            if submodule.state != SubmoduleState::Uninitialized {
+                let sub_repo = Repository { location: self.location.join_repo(&submodule.path) };
+                if let Some(recorded) = &recorded_commit {
+                    let range = format!("{}...HEAD", recorded);
+                    // The recorded commit may not have been fetched into the submodule.
+                    let counts = execute_git_fn(
+                        &sub_repo.location,
+                        ["rev-list", "--left-right", "--count", range.as_str()],
+                        |output| {
+                            let mut counts = output.split_whitespace().map(str::parse::<usize>);
+                            match (counts.next(), counts.next()) {
+                                (Some(Ok(left)), Some(Ok(right))) => Ok((left, right)),
+                                _ => Err(GitError::GitError {
+                                    stdout: output.to_string(),
+                                    stderr: "Failed to parse commit counts".to_string(),
+                                }),
+                            }
+                        },
+                    );
+                    if let Ok((left, right)) = counts {
+                        behind = Some(left);
+                        ahead = Some(right);
+                    }
+                }
+                nested = Some(sub_repo.status_recursive()?);
+            }
+
+            submodules.push(SubmoduleReport {
+                submodule,
+                recorded_commit,
+                ahead,
+                behind,
+                status: nested,
+            });
+        }
+
+        Ok(RecursiveStatus { status, submodules })
+    }
+}
+
+// --- Attributes ---
+
+impl Repository {
+    /// Looks up gitattributes for the given paths, e.g. whether a file is marked
+    /// `binary`, tracked by LFS (`filter=lfs`) or checked out with `eol=crlf`.
+    ///
+    /// Equivalent to `git check-attr --stdin -z <attrs>...`, or `--all` when `attrs`
+    /// is empty. Paths need not exist.
+    ///
+    /// # Arguments
+    /// * `paths` - Paths relative to the repository root.
+    /// * `attrs` - The attributes to query; empty to list every attribute that is
+    ///   set, unset or has a value for each path.
+    ///
+    /// # Returns
+    /// A `Vec<AttrResult>` with one entry per path and attribute. When `attrs` is
+    /// given, unspecified attributes are reported as `AttrValue::Unspecified`.
+    ///
+    /// # Errors
+    /// Returns `GitError::PathEncodingError` if a path is not valid UTF-8.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn check_attr<P: AsRef<Path>>(&self, paths: &[P], attrs: &[&str]) -> Result<Vec<AttrResult>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut input = Vec::new();
+        for path in paths {
+            input.extend_from_slice(pathspec(path.as_ref())?.as_encoded_bytes());
+            input.push(b'\0');
+        }
+
+        let mut args = vec!["check-attr", "--stdin", "-z"];
+        if attrs.is_empty() {
+            args.push("--all");
+        } else {
+            args.extend_from_slice(attrs);
+        }
+        execute_git_fn_with(&self.location, args, Some(&input), &[0], |output| {
+            Ok(AttrResult::from_check_attr_z(output))
+        })
+    }
+}
+
+// --- Transactions ---
+
+impl Repository {
+    /// Runs a sequence of operations and, if any of them fails, rolls the repository
+    /// back to the state it was in before the first one ran.
+    ///
+    /// Before `steps` runs, the transaction records HEAD, every ref (branches, tags,
+    /// remote-tracking refs and the stash), any uncommitted changes and the set of
+    /// untracked files. If `steps` returns an error, the transaction:
+    ///
+    /// * drops any rebase, `am`, cherry-pick or revert left in progress,
+    /// * restores every ref to its recorded value and deletes refs created by `steps`,
+    /// * resets HEAD, the index and the working tree, then reapplies the recorded
+    ///   uncommitted changes,
+    /// * puts back the recorded untracked files, even if `steps` staged, changed
+    ///   or deleted them, and deletes untracked files created by `steps`.
+    ///
+    /// Ignored files, configuration and remote repositories (e.g. after a push) are
+    /// not rolled back, and other processes may observe intermediate states.
+    ///
+    /// ```no_run
+    /// # use GitPilot::Repository;
+    /// # fn main() -> GitPilot::Result<()> {
+    /// let repo = Repository::new("./my_project");
+    /// repo.transaction(|tx| {
+    ///     tx.add(vec!["CHANGELOG.md"])?;
+    ///     tx.commit_staged("Release 1.2.0")?;
+    ///     tx.cmd(["tag", "-a", "v1.2.0", "-m", "v1.2.0"])
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `steps` - A closure performing the operations against the repository.
+    ///
+    /// # Returns
+    /// The value returned by `steps`.
+    ///
+    /// # Errors
+    /// Returns the error from `steps` once the rollback has completed, or
+    /// `GitError::RollbackFailed` if restoring the original state failed too.
+    /// Returns `GitError` if the starting state cannot be recorded, e.g. because
+    /// HEAD does not point to a commit yet.
+    pub fn transaction<T, F>(&self, steps: F) -> Result<T>
+    where
+        F: FnOnce(&Repository) -> Result<T>,
+    {
+        let snapshot = RepoSnapshot::capture(self)?;
+        match steps(self) {
+            Ok(value) => Ok(value),
+            Err(error) => match snapshot.restore(self) {
+                Ok(()) => Err(error),
+                Err(rollback) => Err(GitError::RollbackFailed {
+                    error: Box::new(error),
+                    rollback: Box::new(rollback),
+                }),
+            },
+        }
+    }
+}
+
+/// The state recorded at the start of `Repository::transaction`.
+struct RepoSnapshot {
+    /// The ref HEAD points to, or `None` when HEAD is detached.
+    head_ref: Option<String>,
+    head_commit: String,
+    /// `(refname, object id)` for every ref.
+    refs: Vec<(String, String)>,
+    /// A commit holding uncommitted changes, as made by `git stash create`.
+    changes: Option<String>,
+    /// The untracked files and their contents, which `git stash create` does not record.
+    untracked: Vec<(PathBuf, SavedFile)>,
+    /// Wholly untracked directories, reported with a trailing slash.
+    untracked_dirs: Vec<PathBuf>,
+    /// The contents of the stash reflog, which holds the entries of `git stash list`.
+    stash_log: Option<Vec<u8>>,
+}
+
+impl RepoSnapshot {
+    fn capture(repo: &Repository) -> Result<RepoSnapshot> {
+        let p = &repo.location;
+        let head_commit = execute_git_fn(p, ["rev-parse", "--verify", "HEAD"], |output| {
+            Ok(output.trim().to_string())
+        })?;
+        let head_ref = execute_git_fn_with(p, ["symbolic-ref", "-q", "HEAD"], None, &[0, 1], |output| {
+            let name = output.trim();
+            Ok(if name.is_empty() { None } else { Some(name.to_string()) })
+        })?;
+        let changes = execute_git_fn(p, ["stash", "create"], |output| {
+            let hash = output.trim();
+            Ok(if hash.is_empty() { None } else { Some(hash.to_string()) })
+        })?;
+        let untracked_dirs = repo.ls_files(&LsFilesOptions {
+            others: true,
+            exclude_standard: true,
+            directory: true,
+            ..Default::default()
+        })?;
+        Ok(RepoSnapshot {
+            head_ref,
+            head_commit,
+            refs: list_refs(p)?,
+            changes,
+            untracked: repo
+                .ls_files(&LsFilesOptions { others: true, exclude_standard: true, ..Default::default() })?
+                .into_iter()
+                .map(|path| {
+                    let file = SavedFile::read(&p.join(&path))?;
+                    Ok((path, file))
+                })
+                .collect::<Result<_>>()?,
+            untracked_dirs: untracked_dirs.into_iter().filter(|dir| dir.to_string_lossy().ends_with('/')).collect(),
+            stash_log: fs::read(stash_log_path(p)?).ok(),
+        })
+    }
+
+    fn restore(&self, repo: &Repository) -> Result<()> {
+        let p = &repo.location;
+
+        // Leave in-progress operations without letting them move HEAD themselves.
+        let git_dir = git_dir(p)?;
+        if git_dir.join("rebase-apply").join("applying").exists() {
+            execute_git(p, ["am", "--quit"])?;
+        } else if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+            execute_git(p, ["rebase", "--quit"])?;
+        }
+        if git_dir.join("sequencer").exists() {
+            execute_git(p, ["cherry-pick", "--quit"])?;
+        }
+
+        // Restore all changed refs in a single atomic update.
+        let current = list_refs(p)?;
+        let mut updates = String::new();
+        for (name, _) in &current {
+            if !self.refs.iter().any(|(recorded, _)| recorded == name) {
+                updates.push_str(&format!("delete {}\n", name));
+            }
+        }
+        for (name, oid) in self.refs.iter().filter(|recorded| !current.contains(recorded)) {
+            updates.push_str(&format!("update {} {}\n", name, oid));
+        }
+        if !updates.is_empty() {
+            execute_git_fn_with(p, ["update-ref", "--stdin"], Some(updates.as_bytes()), &[0], |_| Ok(()))?;
+        }
+        // Updating refs/stash appends to its reflog, so put back the recorded entries.
+        let stash_log = stash_log_path(p)?;
+        match &self.stash_log {
+            Some(log) => fs::write(&stash_log, log)?,
+            None if stash_log.exists() => fs::remove_file(&stash_log)?,
+            None => {}
+        }
+
+        match &self.head_ref {
+            Some(name) => execute_git(p, ["symbolic-ref", "HEAD", name.as_str()])?,
+            None => execute_git(p, ["update-ref", "--no-deref", "HEAD", self.head_commit.as_str()])?,
+        }
+        execute_git(p, ["reset", "--hard", "-q", self.head_commit.as_str()])?;
+        if let Some(changes) = &self.changes {
+            execute_git(p, ["stash", "apply", "--index", "-q", changes.as_str()])?;
+        }
+        // `reset --hard` deletes files `steps` staged, and leaves edits to untracked files.
+        for (path, file) in &self.untracked {
+            file.write(&p.join(path))?;
+        }
+
+        let untracked = repo.ls_files(&LsFilesOptions {
+            others: true,
+            exclude_standard: true,
+            ..Default::default()
+        })?;
+        for path in untracked.iter().filter(|path| !self.untracked.iter().any(|(recorded, _)| recorded == *path)) {
+            fs::remove_file(p.join(path))?;
+            // Remove directories the transaction created, deepest first.
+            for dir in path.ancestors().skip(1).take_while(|dir| !dir.as_os_str().is_empty()) {
+                let recorded = self
+                    .untracked_dirs
+                    .iter()
+                    .any(|recorded| recorded.to_string_lossy().trim_end_matches('/') == dir.to_string_lossy());
+                if recorded || fs::remove_dir(p.join(dir)).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The contents of an untracked file, saved so a rollback can put it back.
+#[derive(PartialEq)]
+enum SavedFile {
+    Regular(Vec<u8>, fs::Permissions),
+    Symlink(PathBuf),
+}
+
+impl SavedFile {
+    fn read(path: &Path) -> Result<SavedFile> {
+        let metadata = fs::symlink_metadata(path)?;
+        Ok(if metadata.file_type().is_symlink() {
+            SavedFile::Symlink(fs::read_link(path)?)
+        } else {
+            SavedFile::Regular(fs::read(path)?, metadata.permissions())
+        })
+    }
+
+    /// Writes the saved file to `path`, unless it is already there unchanged.
+    fn write(&self, path: &Path) -> Result<()> {
+        if SavedFile::read(path).is_ok_and(|current| current == *self) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path)?,
+            Ok(_) => fs::remove_file(path)?,
+            Err(_) => {}
+        }
+        match self {
+            SavedFile::Regular(contents, permissions) => {
+                fs::write(path, contents)?;
+                fs::set_permissions(path, permissions.clone())?;
+            }
+            #[cfg(unix)]
+            SavedFile::Symlink(target) => std::os::unix::fs::symlink(target, path)?,
+            #[cfg(windows)]
+            SavedFile::Symlink(target) => std::os::windows::fs::symlink_file(target, path)?,
+            #[cfg(not(any(unix, windows)))]
+            SavedFile::Symlink(_) => {}
+        }
+        Ok(())
+    }
+}
+
+// --- Hook Management ---
+
+impl Repository {
+    /// Returns the directory Git loads hooks from: `core.hooksPath` if set,
+    /// otherwise `hooks` inside the Git directory.
+    ///
+    /// A relative `core.hooksPath` is resolved the way Git runs hooks: against
+    /// the root of the working tree, or the Git directory of a bare repository.
+    ///
+    /// Equivalent to `git config --get --path core.hooksPath`, falling back to
+    /// `git rev-parse --git-path hooks`.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn hooks_dir(&self) -> Result<PathBuf> {
+        // Exit code 1 means the key is not set.
+        let configured = execute_git_fn_with(
+            &self.location,
+            ["config", "--get", "--path", "core.hooksPath"],
+            None,
+            &[0, 1],
+            |output| Ok(Some(output.trim_end_matches('\n')).filter(|path| !path.is_empty()).map(PathBuf::from)),
+        )?;
+        let Some(path) = configured else {
+            return git_path(&self.location, "hooks");
+        };
+        if path.is_absolute() {
+            return Ok(path);
+        }
+        let in_work_tree = execute_git_fn(&self.location, ["rev-parse", "--is-inside-work-tree"], |output| {
+            Ok(output.trim() == "true")
+        })?;
+        let base = if in_work_tree { "--show-toplevel" } else { "--absolute-git-dir" };
+        execute_git_fn(&self.location, ["rev-parse", base], |output| {
+            Ok(Path::new(output.trim_end_matches('\n')).join(&path))
+        })
+    }
+
+    /// Installs `script` as the hook for `kind`, replacing any existing hook.
+    ///
+    /// The script is written to `hooks_dir()` and, on Unix, made executable. It
+    /// should start with a shebang line such as `#!/bin/sh`.
+    ///
+    /// # Arguments
+    /// * `kind` - The hook to install.
+    /// * `script` - The hook's contents.
+    ///
+    /// # Returns
+    /// The path of the installed hook.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the file cannot be written.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn install_hook(&self, kind: HookKind, script: &str) -> Result<PathBuf> {
+        let dir = self.hooks_dir()?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(kind.as_str());
+        fs::write(&path, script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(path)
+    }
+
+    /// Removes the hook for `kind`, if one is installed.
+    ///
+    /// # Returns
+    /// `true` if a hook was removed, `false` if none was installed.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the file cannot be removed.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn remove_hook(&self, kind: HookKind) -> Result<bool> {
+        let path = self.hooks_dir()?.join(kind.as_str());
+        match fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Lists the hooks Git would run: files in `hooks_dir()` named after a hook
+    /// and, on Unix, executable. `*.sample` files and other names are skipped.
+    ///
+    /// # Returns
+    /// A `Vec<HookKind>` in `HookKind::ALL` order.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if the directory cannot be read.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn list_hooks(&self) -> Result<Vec<HookKind>> {
+        let dir = self.hooks_dir()?;
+        let mut hooks = Vec::new();
+        for kind in HookKind::ALL {
+            let metadata = match fs::metadata(dir.join(kind.as_str())) {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            #[cfg(unix)]
+            let runnable = {
+                use std::os::unix::fs::PermissionsExt;
+                metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+            };
+            #[cfg(not(unix))]
+            let runnable = metadata.is_file();
+            if runnable {
+                hooks.push(kind);
+            }
+        }
+        Ok(hooks)
+    }
+
+    /// Runs the hook for `kind` the way Git would, without performing the
+    /// operation it guards. A missing hook succeeds without doing anything.
+    ///
+    /// Equivalent to `git hook run --ignore-missing <hook> -- <args>...`, which
+    /// requires Git 2.36 or later.
+    ///
+    /// # Arguments
+    /// * `kind` - The hook to run.
+    /// * `args` - Arguments passed to the hook, e.g. the message file for `commit-msg`.
+    ///
+    /// # Returns
+    /// The hook's `ExitStatusInfo`; a non-zero exit code means the hook rejected the operation.
+    ///
+    /// # Errors
+    /// Returns `GitError` if Git could not be run (including `GitNotFound`).
+    pub fn run_hook<S: AsRef<OsStr>>(&self, kind: HookKind, args: &[S]) -> Result<ExitStatusInfo> {
+        let mut cmd_args: Vec<&OsStr> = vec![
+            "hook".as_ref(),
+            "run".as_ref(),
+            "--ignore-missing".as_ref(),
+            kind.as_ref(),
+            "--".as_ref(),
+        ];
+        cmd_args.extend(args.iter().map(AsRef::as_ref));
+        execute_git_status(&self.location, cmd_args)
+    }
+}
+
+// --- Worktrees ---
+
+impl Repository {
+    /// Lists the repository's worktrees: the main worktree first, then every
+    /// linked worktree, whichever of them this `Repository` was opened from.
+    ///
+    /// Equivalent to `git worktree list --porcelain -z`.
+    ///
+    /// # Returns
+    /// A `Vec<Worktree>` with absolute paths; for a bare repository the first
+    /// entry is the repository itself, with `is_bare` set.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn list_worktrees(&self) -> Result<Vec<Worktree>> {
+        execute_git_fn(&self.location, ["worktree", "list", "--porcelain", "-z"], |output| {
+            Ok(Worktree::from_porcelain_z(output))
+        })
+    }
+
+    /// Opens another worktree of the same repository, such as a sibling linked
+    /// worktree, with the same settings as this handle.
+    ///
+    /// # Arguments
+    /// * `path` - The root of the worktree; relative paths are resolved against
+    ///   the root of this worktree.
+    ///
+    /// # Returns
+    /// A `Repository` rooted at the worktree.
+    ///
+    /// # Errors
+    /// Returns `GitError::NotAWorktree` if `path` is not the root of one of the
+    /// repository's worktrees, or `GitError` (including `GitNotFound`).
+    pub fn open_worktree<P: AsRef<Path>>(&self, path: P) -> Result<Repository> {
+        let path = self.location.path().join(path.as_ref());
+        // Compare resolved paths, so symlinks and `..` do not hide a match.
+        let wanted = fs::canonicalize(&path).map_err(|_| GitError::NotAWorktree(path.clone()))?;
+        let worktree = self
+            .list_worktrees()?
+            .into_iter()
+            .find(|worktree| fs::canonicalize(&worktree.path).is_ok_and(|found| found == wanted))
+            .ok_or(GitError::NotAWorktree(path))?;
+        Ok(Repository { location: self.location.join_repo(&worktree.path) })
+    }
+
+    /// Opens the main worktree, the one holding the repository's `.git`
+    /// directory, from any of its worktrees, with the same settings as this handle.
+    ///
+    /// # Returns
+    /// A `Repository` rooted at the main worktree, or at the repository itself
+    /// if it is bare. For the main worktree this is a handle on the same path.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn main_worktree(&self) -> Result<Repository> {
+        let main = self
+            .list_worktrees()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| GitError::NotAWorktree(self.location.path().to_path_buf()))?;
+        Ok(Repository { location: self.location.join_repo(&main.path) })
+    }
+}
+
+// --- Helper Functions ---
+
+/// Splits `git log --format=<LOG_RECORD_FORMAT>` output into parsed commits and the
+/// trailing per-commit output (if any).
+fn parse_log_records(output: &str) -> Result<Vec<(Commit, &str)>> {
+    output
+        .split('\x1e')
+        .filter(|record| !record.trim().is_empty())
+        .map(|record| {
+            Commit::from_log_record(record).ok_or_else(|| GitError::GitError {
+                stdout: record.to_string(),
+                stderr: "Failed to parse commit information".to_string(),
+            })
+        })
+        .collect()
+}
+
+// Removed git_status helper function
+
+/// Executes a Git command, discarding successful output.
+pub(crate) fn execute_git<I, S, P>(p: P, args: I) -> Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+{
+    execute_git_fn(p, args, |_| Ok(()))
+}
+
+/// Executes a Git command and processes its stdout on success using a closure.
+/// Handles errors, including capturing stderr on failure.
+pub(crate) fn execute_git_fn<I, S, P, F, R>(p: P, args: I, process: F) -> Result<R>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+    F: FnOnce(&str) -> Result<R>,
+{
+    execute_git_fn_with(p, args, None, &[0], process)
+}
+
+/// Like `execute_git_fn`, but optionally writes `stdin` to the process and treats
+/// any exit code in `ok_codes` as success (e.g. `check-ignore` exits 1 when nothing matches).
+pub(crate) fn execute_git_fn_with<I, S, P, F, R>(
+    p: P,
+    args: I,
+    stdin: Option<&[u8]>,
+    ok_codes: &[i32],
+    process: F,
+) -> Result<R>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+    F: FnOnce(&str) -> Result<R>,
+{
+    let decode = p.settings().decode;
+    let output = run_git(p, args, &[], stdin)?;
+
+    if output.status.code().is_some_and(|code| ok_codes.contains(&code)) {
+        process(&decode.decode(&output.stdout)?)
+    } else {
+        Err(failure_error(&output))
+    }
+}
+
+/// Executes a Git command without treating a non-zero exit code as an error.
+/// Only failures to run Git at all (or undecodable output) are reported as `Err`.
+fn execute_git_status<I, S, P>(p: P, args: I) -> Result<ExitStatusInfo>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+{
+    let decode = p.settings().decode;
+    let output = run_git(p, args, &[], None)?;
+    Ok(ExitStatusInfo {
+        code: output.status.code(),
+        stdout: decode.decode(&output.stdout)?.into_owned(),
+        stderr: decode.decode(&output.stderr)?.into_owned(),
+    })
+}
+
+/// Interprets a predicate command's exit status: 0 is `false`, 1 is `true`
+/// (e.g. "differences found"), and anything else is an error.
+fn exit_code_flag(info: ExitStatusInfo) -> Result<bool> {
+    match info.code {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(GitError::GitError {
+            stdout: info.stdout.trim_end().to_owned(),
+            stderr: info.stderr.trim_end().to_owned(),
+        }),
+    }
+}
+
+/// Executes a Git command with extra environment variables, ignoring its stdout.
+fn execute_git_env<I, S, P>(p: P, args: I, envs: &[(&str, &OsStr)]) -> Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+{
+    let output = run_git(p, args, envs, None)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(failure_error(&output))
+    }
+}
+
+/// Writes the inputs of `Repository::merge_file` into `scratch_dir` and merges them.
+fn merge_file_in(
+    p: &Location,
+    scratch_dir: &Path,
+    base: &str,
+    ours: &str,
+    theirs: &str,
+) -> Result<MergeFileResult> {
+    let base_path = scratch_dir.join("base");
+    let ours_path = scratch_dir.join("ours");
+    let theirs_path = scratch_dir.join("theirs");
+    fs::write(&base_path, base)?;
+    fs::write(&ours_path, ours)?;
+    fs::write(&theirs_path, theirs)?;
+
+    let info = execute_git_status(
+        p,
+        [
+            "merge-file".as_ref(),
+            "-p".as_ref(),
+            "-L".as_ref(),
+            "ours".as_ref(),
+            "-L".as_ref(),
+            "base".as_ref(),
+            "-L".as_ref(),
+            "theirs".as_ref(),
+            ours_path.as_os_str(),
+            base_path.as_os_str(),
+            theirs_path.as_os_str(),
+        ],
+    )?;
+    // The exit code is the number of conflicts (capped at 127); errors are negative.
+    match info.code {
+        Some(code) if (0..=127).contains(&code) => Ok(MergeFileResult {
+            content: info.stdout,
+            conflicts: code as usize,
+        }),
+        _ => Err(GitError::GitError {
+            stdout: info.stdout.trim_end().to_owned(),
+            stderr: info.stderr.trim_end().to_owned(),
+        }),
+    }
+}
+
+/// Fills in `remote` as the remote to fetch from when `opts` names none.
+pub(crate) fn with_default_remote(opts: &FetchOptions, remote: Option<&Remote>) -> FetchOptions {
+    let mut opts = opts.clone();
+    if opts.remote.is_none() && !opts.all {
+        opts.remote = remote.cloned();
+    }
+    opts
+}
+
+/// Normalizes a repository path before it is handed to Git.
+///
+/// Git for Windows does not understand verbatim paths, which `fs::canonicalize`
+/// returns on Windows, so `\\?\C:\repo` becomes `C:\repo` and
+/// `\\?\UNC\server\share` becomes `\\server\share`. Paths of `MAX_PATH` (260)
+/// characters or more keep the prefix, as only verbatim paths can exceed that
+/// limit. Other paths are returned unchanged.
+pub(crate) fn normalize_location(p: &Path) -> PathBuf {
+    let mut components = p.components();
+    let prefix = match components.next() {
+        Some(Component::Prefix(prefix)) => prefix,
+        _ => return p.to_path_buf(),
+    };
+    let root = match prefix.kind() {
+        Prefix::VerbatimDisk(letter) => format!("{}:", letter as char),
+        Prefix::VerbatimUNC(server, share) => format!(
+            "\\\\{}\\{}",
+            server.to_string_lossy(),
+            share.to_string_lossy()
+        ),
+        _ => return p.to_path_buf(),
+    };
+    let mut normalized = PathBuf::from(root);
+    normalized.extend(components);
+    if normalized.as_os_str().len() >= MAX_PATH {
+        return p.to_path_buf();
+    }
+    normalized
+}
+
+/// The longest Windows path, including its terminating NUL, that works without
+/// a verbatim prefix.
+const MAX_PATH: usize = 260;
+
+/// Converts a path into a pathspec argument.
+///
+/// Git matches pathspecs against `/`-separated paths, so on Windows backslashes are
+/// converted, which requires the path to be valid Unicode. Elsewhere the path is
+/// passed through unchanged.
+pub(crate) fn pathspec(path: &Path) -> Result<OsString> {
+    if cfg!(windows) {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| GitError::PathEncodingError(path.to_path_buf()))?;
+        Ok(OsString::from(path_str.replace('\\', "/")))
+    } else {
+        Ok(path.as_os_str().to_os_string())
+    }
+}
+
+/// Strips the trailing whitespace Git ignores in a gitignore pattern, keeping a
+/// final space escaped as `\ `.
+fn trim_gitignore_pattern(pattern: &str) -> &str {
+    let trimmed = pattern.trim_end();
+    let backslashes = trimmed.len() - trimmed.trim_end_matches('\\').len();
+    match pattern[trimmed.len()..].chars().next() {
+        Some(escaped) if backslashes % 2 == 1 => &pattern[..trimmed.len() + escaped.len_utf8()],
+        _ => trimmed,
+    }
+}
+
+/// Checks several pathspecs with `Pathspec::to_arg`.
+pub(crate) fn pathspec_args(paths: &[Pathspec]) -> Result<Vec<&OsStr>> {
+    paths.iter().map(Pathspec::to_arg).collect()
+}
+
+/// The `--word-diff-regex` matching one character for `DiffMode::Chars`.
+///
+/// Git matches bytes in the `C` locale, so `.` would split multibyte UTF-8
+/// characters; instead each lead byte is matched with its continuation bytes.
+/// Windows passes arguments as Unicode, which cannot carry these raw bytes.
+fn char_diff_regex() -> &'static OsStr {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        OsStr::from_bytes(b"--word-diff-regex=[^\x80-\xbf][\x80-\xbf]*")
+    }
+    #[cfg(not(unix))]
+    {
+        OsStr::new("--word-diff-regex=.")
+    }
+}
+
+/// Lists every ref as `(refname, object id)`.
+fn list_refs(p: &Location) -> Result<Vec<(String, String)>> {
+    execute_git_fn(p, ["for-each-ref", "--format=%(refname) %(objectname)"], |output| {
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(name, oid)| (name.to_string(), oid.to_string()))
+            .collect())
+    })
+}
+
+/// Resolves the absolute path of the repository's Git directory (`.git`, or the
+/// per-worktree directory for linked worktrees).
+fn git_dir<P: GitContext>(p: P) -> Result<PathBuf> {
+    commands::git_dir().run(p)
+}
+
+/// Returns the path of the stash reflog, which need not exist.
+fn stash_log_path(p: &Location) -> Result<PathBuf> {
+    git_path(p, "logs/refs/stash")
+}
+
+/// Resolves a path inside the Git directory, honouring the common directory
+/// of linked worktrees. Equivalent to `git rev-parse --git-path <path>`.
+pub(crate) fn git_path(p: &Location, path: &str) -> Result<PathBuf> {
+    execute_git_fn(p, ["rev-parse", "--git-path", path], |output| {
+        // Relative results are relative to the working directory Git ran in.
+        Ok(p.join(output.trim_end_matches('\n')))
+    })
+}
+
+/// A uniquely named scratch directory inside the Git directory for files handed
+/// to Git. It is removed when dropped.
+struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    fn new<P: GitContext>(p: P, purpose: &str) -> Result<ScratchDir> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let path = git_dir(p)?.join(format!("gitpilot-{}-{}-{}", purpose, std::process::id(), nanos));
+        fs::create_dir_all(&path)?;
+        Ok(ScratchDir { path })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The directory inside `rebase-merge` that `rebase_interactive` moves its scratch files to.
+const REBASE_SCRATCH_NAME: &str = "gitpilot-rebase";
+
+/// Quotes a path for use as a single word in a POSIX shell command line,
+/// as run by Git for editors and `exec` steps.
+fn shell_quote(path: &Path) -> Result<String> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| GitError::PathEncodingError(path.to_path_buf()))?;
+    Ok(format!("'{}'", path_str.replace('\'', "'\\''")))
+}
+
+/// Builds the `GitError::GitError` for a failed command from its captured output.
+pub(crate) fn failure_error(output: &Output) -> GitError {
+    let stdout = str::from_utf8(&output.stdout)
+        .map(|s| s.trim_end().to_owned())
+        .unwrap_or_else(|_| String::from("[stdout: undecodable UTF-8]"));
+    let stderr = str::from_utf8(&output.stderr)
+        .map(|s| s.trim_end().to_owned())
+        .unwrap_or_else(|_| String::from("[stderr: undecodable UTF-8]"));
+    GitError::GitError { stdout, stderr }
+}
+
+/// Executes a Git command and returns its stdout, replacing invalid UTF-8
+/// instead of failing with `GitError::Undecodable`.
+fn execute_git_lossy<I, S, P>(p: P, args: I) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+{
+    let output = run_git(p, args, &[], None)?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(failure_error(&output))
+    }
+}
+
+/// A repository's root directory together with the settings Git is run with.
+///
+/// It derefs to the root, and executes Git with its settings when passed to the
+/// `execute_git` helpers.
+#[derive(Debug, Clone)]
+pub(crate) struct Location {
+    path: PathBuf,
+    pub(crate) settings: Arc<Settings>,
+}
+
+impl Location {
+    /// A location using the default settings.
+    pub(crate) fn new(p: &Path) -> Location {
+        Location::with_settings(p, Arc::new(Settings::default()))
+    }
+
+    pub(crate) fn with_settings(p: &Path, settings: Arc<Settings>) -> Location {
+        Location { path: normalize_location(p), settings }
+    }
+
+    /// Returns the root of the repository.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The location of a nested repository, such as a submodule, or of another
+    /// worktree when `p` is absolute, run with the same settings.
+    pub(crate) fn join_repo(&self, p: &Path) -> Location {
+        Location { path: self.path.join(p), settings: Arc::clone(&self.settings) }
+    }
+}
+
+impl Deref for Location {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// The directory Git runs in and the settings it runs with.
+///
+/// Implemented by `Location`, and by plain paths, which use the default settings.
+pub(crate) trait GitContext {
+    fn dir(&self) -> &Path;
+    fn settings(&self) -> &Settings;
+}
+
+static DEFAULT_SETTINGS: Settings = Settings::DEFAULT;
+
+impl<T: AsRef<Path> + ?Sized> GitContext for T {
+    fn dir(&self) -> &Path {
+        self.as_ref()
+    }
+
+    fn settings(&self) -> &Settings {
+        &DEFAULT_SETTINGS
+    }
+}
+
+impl GitContext for Location {
+    fn dir(&self) -> &Path {
+        self.path()
+    }
+
+    fn settings(&self) -> &Settings {
+        &self.settings
+    }
+}
+
+impl GitContext for &Location {
+    fn dir(&self) -> &Path {
+        self.path()
+    }
+
+    fn settings(&self) -> &Settings {
+        &self.settings
+    }
+}
+
+/// How often `collect_output` checks whether Git has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Spawns `git` with the given arguments and extra environment variables,
+/// optionally writing `stdin` to it, and waits for it to exit.
+///
+/// The context's settings choose the executable and base environment, how much
+/// output is accepted, and whether the command is killed after a timeout or
+/// retried after a transient failure.
+pub(crate) fn run_git<I, S, P>(p: P, args: I, envs: &[(&str, &OsStr)], stdin: Option<&[u8]>) -> Result<Output>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+    P: GitContext,
+{
+    let settings = p.settings();
+    let args: Vec<OsString> = args.into_iter().map(|arg| arg.as_ref().to_os_string()).collect();
+    let mut retries = 0;
+    loop {
+        let mut command = settings.command(p.dir());
+        command.args(&args).envs(envs.iter().copied());
+        let started = Instant::now();
+        let result = run_command(command, stdin, settings);
+        settings.record(p.dir(), &args, started, result.as_ref());
+        let output = result?;
+        if output.status.success() || !settings.should_retry(retries, &output.stderr) {
+            return Ok(output);
+        }
+        retries += 1;
+        thread::sleep(settings.retry.delay);
+    }
+}
+
+/// Runs a Git command once, killing it if it outlives the settings' timeout and
+/// stopping it once its output exceeds their limit.
+fn run_command(mut command: Command, stdin: Option<&[u8]>, settings: &Settings) -> Result<Output> {
+    let (timeout, limit) = (settings.timeout, settings.max_output);
+    let command_result = match (stdin, timeout, limit) {
+        (None, None, None) => command.output(),
+        _ => {
+            command
+                .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            command.spawn().and_then(|mut child| {
+                // Write from a separate thread so a child blocked on a full stdout
+                // pipe can't deadlock against us.
+                let writer = stdin.map(|input| {
+                    let mut child_stdin = child.stdin.take().expect("stdin is piped");
+                    let input = input.to_vec();
+                    thread::spawn(move || child_stdin.write_all(&input))
+                });
+                let output = collect_output(child, timeout, limit);
+                // A write error here means git exited early; its status tells the story.
+                if let Some(writer) = writer {
+                    let _ = writer.join();
+                }
+                output
+            })
+            .and_then(|output| output.ok_or_else(|| std::io::Error::from(ErrorKind::TimedOut)))
+        }
+    };
+
+    let output = command_result.map_err(|e| {
+        // --- Restored GitNotFound Check ---
+        if e.kind() == ErrorKind::NotFound {
+            GitError::GitNotFound // Return the specific error
+        } else if e.kind() == ErrorKind::TimedOut {
+            GitError::Timeout(timeout.unwrap_or_default())
+        } else {
+            eprintln!("Failed to execute git command: {}", e); // Log the OS error
+            GitError::Execution // Return the original generic execution error
+        }
+        // --- End of Restored Check ---
+    })?;
+    match limit {
+        Some(limit) if output.stdout.len() > limit || output.stderr.len() > limit => {
+            Err(GitError::OutputTooLarge { limit })
+        }
+        _ => Ok(output),
+    }
+}
+
+/// Collects a child's output like `Child::wait_with_output`, but kills it and
+/// returns `None` once `timeout` has passed.
+///
+/// At most `limit + 1` bytes are read from stdout and stderr each; the pipe is
+/// then closed, which stops Git the next time it writes.
+fn collect_output(mut child: Child, timeout: Option<Duration>, limit: Option<usize>) -> std::io::Result<Option<Output>> {
+    let limit = limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+    fn drain(pipe: Option<impl Read + Send + 'static>, limit: u64) -> thread::JoinHandle<std::io::Result<Vec<u8>>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(pipe) = pipe {
+                pipe.take(limit).read_to_end(&mut buf)?;
+            }
+            Ok(buf)
+        })
+    }
+    let stdout = drain(child.stdout.take(), limit);
+    let stderr = drain(child.stderr.take(), limit);
+    let status = match timeout {
+        None => child.wait()?,
+        Some(timeout) => {
+            let deadline = Instant::now() + timeout;
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break status;
+                }
+                if Instant::now() >= deadline {
+                    // The readers finish once Git and anything it spawned close the pipes.
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Ok(None);
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    };
+    let join = |reader: thread::JoinHandle<std::io::Result<Vec<u8>>>| {
+        reader.join().unwrap_or_else(|e| std::panic::resume_unwind(e))
+    };
+    Ok(Some(Output { status, stdout: join(stdout)?, stderr: join(stderr)? }))
+}
+
+/// Parses the `add '<path>'` and `remove '<path>'` lines of `git add --verbose`.
+///
+/// Paths are printed unquoted, so a record only ends at a `'` followed by a
+/// newline and the start of the next record (or the end of the output).
+fn parse_add_verbose(output: &str) -> Vec<PathBuf> {
+    parse_verbose_paths(output, &["add '", "remove '"])
+}
+
+/// Parses records of the form `<prefix><path>'`, one per line, as printed by
+/// `git add --verbose` and `git rm`; each prefix ends with the opening quote.
+fn parse_verbose_paths(output: &str, prefixes: &[&str]) -> Vec<PathBuf> {
+    let starts_record = |s: &str| s.is_empty() || prefixes.iter().any(|prefix| s.starts_with(prefix));
+    let mut paths = Vec::new();
+    let mut rest = output;
+    while let Some(record) = prefixes.iter().find_map(|prefix| rest.strip_prefix(prefix)) {
+        let Some(end) = record.match_indices("'\n").map(|(i, _)| i).find(|&i| starts_record(&record[i + 2..]))
+        else {
+            // The last record, without a trailing newline.
+            paths.push(PathBuf::from(record.trim_end_matches('\'')));
+            break;
+        };
+        paths.push(PathBuf::from(&record[..end]));
+        rest = &record[end + 2..];
+    }
+    paths
+}
+
+/// Parses `git commit --dry-run --porcelain -z`, returning the paths the
+/// commit would record: those with a staged change, and for renames the new path.
+fn parse_commit_dry_run(output: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let (Some(staged), Some(path)) = (record.chars().next(), record.get(3..)) else {
+            continue;
+        };
+        if matches!(staged, 'R' | 'C') {
+            // Followed by the original path.
+            records.next();
+        }
+        if !matches!(staged, ' ' | '?' | '!') {
+            paths.push(PathBuf::from(path));
+        }
+    }
+    paths
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_add_verbose() {
+        let output = "add 'a.txt'\nadd 'it's here'\nadd 'two\nlines'\nremove 'old dir/b.txt'\n";
+        let paths = parse_add_verbose(output);
+        assert_eq!(
+            paths,
+            ["a.txt", "it's here", "two\nlines", "old dir/b.txt"].map(PathBuf::from)
+        );
+        assert!(parse_add_verbose("").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_location_keeps_plain_paths() {
+        assert_eq!(normalize_location(Path::new("repo/sub")), PathBuf::from("repo/sub"));
+        assert_eq!(normalize_location(Path::new("/tmp/repo")), PathBuf::from("/tmp/repo"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_normalize_location_strips_verbatim_prefixes() {
+        assert_eq!(
+            normalize_location(Path::new(r"\\?\C:\work\repo")),
+            PathBuf::from(r"C:\work\repo")
+        );
+        assert_eq!(
+            normalize_location(Path::new(r"\\?\UNC\server\share\repo")),
+            PathBuf::from(r"\\server\share\repo")
+        );
+        assert_eq!(normalize_location(Path::new(r"C:\work")), PathBuf::from(r"C:\work"));
+
+        let long = format!(r"\\?\C:\work\{}", "d".repeat(300));
+        assert_eq!(normalize_location(Path::new(&long)), PathBuf::from(&long));
+    }
+
+    #[test]
+    fn test_pathspec_separators() {
+        let spec = pathspec(Path::new("src/lib.rs")).unwrap();
+        assert_eq!(spec, OsString::from("src/lib.rs"));
+        if cfg!(windows) {
+            assert_eq!(pathspec(Path::new(r"src\lib.rs")).unwrap(), OsString::from("src/lib.rs"));
+        }
+    }
+
+    #[test]
+    fn test_parse_commit_dry_run() {
+        let output = "M  staged.txt\0 M unstaged.txt\0R  new name.txt\0old.txt\0A  added.txt\0?? untracked.txt\0";
+        assert_eq!(parse_commit_dry_run(output), ["staged.txt", "new name.txt", "added.txt"].map(PathBuf::from));
+    }
+}
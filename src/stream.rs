@@ -0,0 +1,194 @@
+//! Reading a Git command's output as it is produced, for output too large to
+//! hold in memory, such as `log -p` over a long history.
+//!
+//! The other methods collect the whole output before parsing it, and fail with
+//! `GitError::OutputTooLarge` past the repository's output limit. The methods
+//! here are not limited: `cmd_lines` hands out one line at a time, and
+//! `cmd_to_writer` copies the output straight into a file or socket.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # fn main() -> GitPilot::Result<()> {
+//! let repo = Repository::new("./my_project");
+//! let mut additions = 0;
+//! for line in repo.cmd_lines(["log", "-p", "--format="])? {
+//!     if line?.starts_with('+') {
+//!         additions += 1;
+//!     }
+//! }
+//! println!("{} lines added over the whole history", additions);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::GitError;
+use crate::repository::{failure_error, Location, Repository};
+use crate::types::Result;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::process::{Child, ChildStdout, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// A running Git command whose stdout is read by the caller, while stderr is
+/// collected in the background for the error report.
+#[derive(Debug)]
+struct StreamingProcess {
+    child: Child,
+    stderr: Option<JoinHandle<io::Result<Vec<u8>>>>,
+    location: Location,
+    args: Vec<OsString>,
+    started: Instant,
+}
+
+impl StreamingProcess {
+    fn spawn<I, S>(location: &Location, args: I) -> Result<(StreamingProcess, ChildStdout)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let args: Vec<OsString> = args.into_iter().map(|arg| arg.as_ref().to_os_string()).collect();
+        let started = Instant::now();
+        let spawned = location
+            .settings
+            .command(location)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == ErrorKind::NotFound {
+                    GitError::GitNotFound
+                } else {
+                    GitError::Execution
+                }
+            });
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                location.settings.record(location, &args, started, Err(&e));
+                return Err(e);
+            }
+        };
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let stderr = thread::spawn(move || {
+            let mut buf = Vec::new();
+            stderr.read_to_end(&mut buf)?;
+            Ok(buf)
+        });
+        let process = StreamingProcess { child, stderr: Some(stderr), location: location.clone(), args, started };
+        Ok((process, stdout))
+    }
+
+    /// Waits for Git to exit after its stdout has been read to the end, and
+    /// reports a failure with its stderr.
+    fn finish(&mut self) -> Result<()> {
+        let status = self.child.wait()?;
+        let stderr = match self.stderr.take() {
+            Some(reader) => reader.join().unwrap_or_else(|e| std::panic::resume_unwind(e))?,
+            None => Vec::new(),
+        };
+        let output = Output { status, stdout: Vec::new(), stderr };
+        self.location.settings.record(&self.location, &self.args, self.started, Ok(&output));
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(failure_error(&output))
+        }
+    }
+}
+
+impl Drop for StreamingProcess {
+    fn drop(&mut self) {
+        // Stopped before the end of the output: Git has nothing left to do.
+        if self.stderr.is_some() {
+            let _ = self.child.kill();
+            let _ = self.finish();
+        }
+    }
+}
+
+/// The lines of a Git command's stdout, created by `Repository::cmd_lines`.
+///
+/// Each line is read when the iterator reaches it. If Git fails, its error is
+/// the last item. Dropping the iterator early stops Git.
+#[derive(Debug)]
+pub struct GitLines {
+    process: StreamingProcess,
+    stdout: Option<BufReader<ChildStdout>>,
+}
+
+impl Iterator for GitLines {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Result<String>> {
+        let stdout = self.stdout.as_mut()?;
+        let mut line = Vec::new();
+        match stdout.read_until(b'\n', &mut line) {
+            Ok(0) => {
+                self.stdout = None;
+                self.process.finish().err().map(Err)
+            }
+            Ok(_) => {
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                }
+                let decode = self.process.location.settings.decode;
+                Some(decode.decode(&line).map(|line| line.into_owned()))
+            }
+            Err(e) => {
+                self.stdout = None;
+                Some(Err(e.into()))
+            }
+        }
+    }
+}
+
+impl Repository {
+    /// Executes an arbitrary Git command and iterates over the lines of its
+    /// standard output as Git produces them, without the output limit.
+    ///
+    /// # Arguments
+    /// * `args` - An iterator yielding command-line arguments for Git.
+    ///
+    /// # Returns
+    /// A `GitLines` iterator; a failure of Git is its last item.
+    ///
+    /// # Errors
+    /// Returns `GitError` if Git cannot be started (including `GitNotFound`).
+    pub fn cmd_lines<I, S>(&self, args: I) -> Result<GitLines>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let (process, stdout) = StreamingProcess::spawn(&self.location, args)?;
+        Ok(GitLines { process, stdout: Some(BufReader::new(stdout)) })
+    }
+
+    /// Executes an arbitrary Git command and copies its standard output into
+    /// `writer` as Git produces it, without the output limit.
+    ///
+    /// # Arguments
+    /// * `args` - An iterator yielding command-line arguments for Git.
+    /// * `writer` - Where to write the output, e.g. a `File`.
+    ///
+    /// # Returns
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    /// Returns `GitError::Io` if writing fails, which stops Git.
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn cmd_to_writer<I, S, W>(&self, args: I, writer: &mut W) -> Result<u64>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        W: Write + ?Sized,
+    {
+        let (mut process, mut stdout) = StreamingProcess::spawn(&self.location, args)?;
+        let written = io::copy(&mut stdout, writer)?;
+        process.finish()?;
+        Ok(written)
+    }
+}
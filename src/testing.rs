@@ -0,0 +1,97 @@
+//! Scratch repositories for tests, enabled by the `testing` feature.
+//!
+//! ```no_run
+//! use GitPilot::testing::TempRepo;
+//!
+//! let repo = TempRepo::new();
+//! repo.commit_file("a.txt", "one\n", "first")
+//!     .commit_file("b.txt", "two\n", "second");
+//! assert!(!repo.is_dirty().unwrap());
+//! ```
+//!
+//! Helpers panic instead of returning errors, since a broken fixture should
+//! fail the test that uses it.
+
+use crate::repository::Repository;
+use std::fs;
+use std::ops::Deref;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A freshly initialized repository in a temporary directory that is removed
+/// when the `TempRepo` is dropped.
+///
+/// The repository has a test identity configured and commit signing disabled, so
+/// commits succeed regardless of the user's global Git configuration. It derefs
+/// to `Repository`, so every repository operation is available directly.
+#[derive(Debug)]
+pub struct TempRepo {
+    // Declared first so the repository handle is dropped before its directory.
+    repo: Repository,
+    dir: TempDir,
+}
+
+impl TempRepo {
+    /// Creates a temporary directory, runs `git init` in it and configures
+    /// `user.name`, `user.email` and `commit.gpgsign`.
+    ///
+    /// # Panics
+    /// Panics if the directory cannot be created or Git fails.
+    pub fn new() -> TempRepo {
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let repo = Repository::init(dir.path()).expect("git init failed");
+        repo.cmd(["config", "user.name", "GitPilot Test"]).expect("git config failed");
+        repo.cmd(["config", "user.email", "test@gitpilot.invalid"]).expect("git config failed");
+        repo.cmd(["config", "commit.gpgsign", "false"]).expect("git config failed");
+        TempRepo { repo, dir }
+    }
+
+    /// Returns the root of the working tree.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Returns the underlying repository handle.
+    pub fn repo(&self) -> &Repository {
+        &self.repo
+    }
+
+    /// Writes `contents` to `name`, relative to the working tree root, creating
+    /// parent directories as needed. The file is not staged.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be written.
+    pub fn write_file(&self, name: &str, contents: &str) -> &TempRepo {
+        let path = self.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("failed to create parent directories");
+        }
+        fs::write(&path, contents).expect("failed to write file");
+        self
+    }
+
+    /// Writes `contents` to `name`, stages it and commits it with `message`.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be written or Git fails.
+    pub fn commit_file(&self, name: &str, contents: &str, message: &str) -> &TempRepo {
+        self.write_file(name, contents);
+        self.repo.add(vec![name]).expect("git add failed");
+        self.repo.commit_staged(message).expect("git commit failed");
+        self
+    }
+}
+
+impl Default for TempRepo {
+    fn default() -> Self {
+        TempRepo::new()
+    }
+}
+
+impl Deref for TempRepo {
+    type Target = Repository;
+
+    fn deref(&self) -> &Repository {
+        &self.repo
+    }
+}
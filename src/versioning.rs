@@ -0,0 +1,304 @@
+//! Semantic-version release tags: finding the last release, computing the
+//! next version and tagging it.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # use GitPilot::versioning::Bump;
+//! # fn main() -> GitPilot::Result<()> {
+//! let repo = Repository::new("./my_project");
+//! let next = repo.next_version(Some("v"), Bump::Minor)?;
+//! repo.tag_release(Some("v"), &next, &format!("Release {}", next), false)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::GitError;
+use crate::repository::{execute_git, Repository};
+use crate::types::{Result, Tag};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A semantic version, `<major>.<minor>.<patch>[-<pre-release>][+<build>]`.
+///
+/// Versions order by precedence as defined by Semantic Versioning 2.0.0: a
+/// pre-release sorts before the release it precedes, and build metadata is
+/// ignored (two versions differing only in build metadata compare equal).
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// The pre-release identifiers, e.g. `rc.1`.
+    pub pre: Option<String>,
+    /// The build metadata, e.g. `build.42`.
+    pub build: Option<String>,
+}
+
+/// Which part of a version to increment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bump {
+    /// Incompatible changes: `1.4.2` becomes `2.0.0`.
+    Major,
+    /// New, backwards-compatible features: `1.4.2` becomes `1.5.0`.
+    Minor,
+    /// Backwards-compatible fixes: `1.4.2` becomes `1.4.3`.
+    Patch,
+}
+
+impl Version {
+    /// Creates a release version with no pre-release or build metadata.
+    pub fn new(major: u64, minor: u64, patch: u64) -> Version {
+        Version { major, minor, patch, pre: None, build: None }
+    }
+
+    /// Returns the next release version.
+    ///
+    /// The pre-release and build metadata are dropped. Bumping a pre-release
+    /// releases it instead where that is enough: `2.0.0-rc.1` becomes `2.0.0`
+    /// for a major bump, as it already is the next major version.
+    pub fn bump(&self, bump: Bump) -> Version {
+        let release = self.pre.is_some();
+        match bump {
+            Bump::Major if release && self.minor == 0 && self.patch == 0 => Version::new(self.major, 0, 0),
+            Bump::Major => Version::new(self.major + 1, 0, 0),
+            Bump::Minor if release && self.patch == 0 => Version::new(self.major, self.minor, 0),
+            Bump::Minor => Version::new(self.major, self.minor + 1, 0),
+            Bump::Patch if release => Version::new(self.major, self.minor, self.patch),
+            Bump::Patch => Version::new(self.major, self.minor, self.patch + 1),
+        }
+    }
+
+    /// Returns `true` if this is a pre-release such as `1.0.0-beta.2`.
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some()
+    }
+}
+
+impl FromStr for Version {
+    type Err = GitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || GitError::InvalidVersion(s.to_string());
+        let (rest, build) = match s.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (s, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (rest, None),
+        };
+
+        let mut numbers = core.split('.').map(|part| {
+            // Leading zeros are not allowed.
+            if part.is_empty() || (part.len() > 1 && part.starts_with('0')) {
+                return None;
+            }
+            part.parse::<u64>().ok()
+        });
+        let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) =
+            (numbers.next(), numbers.next(), numbers.next(), numbers.next())
+        else {
+            return Err(invalid());
+        };
+
+        let valid_identifiers = |ids: &str| {
+            ids.split('.')
+                .all(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        };
+        if !pre.is_none_or(valid_identifiers) || !build.is_none_or(valid_identifiers) {
+            return Err(invalid());
+        }
+
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre: pre.map(str::to_string),
+            build: build.map(str::to_string),
+        })
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Version) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Version) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_prerelease(a, b),
+            })
+    }
+}
+
+/// Compares dot-separated pre-release identifiers: numeric ones numerically and
+/// below alphanumeric ones, and a shorter list first when one is a prefix of
+/// the other.
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_ids = a.split('.');
+    let mut b_ids = b.split('.');
+    loop {
+        let ordering = match (a_ids.next(), b_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+impl Repository {
+    /// Finds the highest semantic version among the release tags.
+    ///
+    /// Tags that do not parse as a version after the prefix are ignored. Unlike
+    /// `latest_tag`, this considers every tag, not only those reachable from `HEAD`.
+    ///
+    /// # Arguments
+    /// * `prefix` - The text before the version in tag names, usually `Some("v")`;
+    ///   `None` for tags such as `1.2.3`.
+    ///
+    /// # Returns
+    /// The tag and its version, or `None` if no tag holds a version.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn latest_semver_tag(&self, prefix: Option<&str>) -> Result<Option<(Tag, Version)>> {
+        let prefix = prefix.unwrap_or("");
+        let tags = self.list_tags_matching(&format!("{}[0-9]*", prefix))?;
+        Ok(tags
+            .into_iter()
+            .filter_map(|tag| {
+                let version = AsRef::<str>::as_ref(&tag).strip_prefix(prefix)?.parse::<Version>().ok()?;
+                Some((tag, version))
+            })
+            .max_by(|(_, a), (_, b)| a.cmp(b)))
+    }
+
+    /// Computes the version of the next release from the latest release tag.
+    ///
+    /// # Arguments
+    /// * `prefix` - The tag prefix, as for `latest_semver_tag`.
+    /// * `bump` - Which part of the version to increment.
+    ///
+    /// # Returns
+    /// The bumped version; without any release tag, `0.0.0` is bumped.
+    ///
+    /// # Errors
+    /// Returns `GitError` (including `GitNotFound`).
+    pub fn next_version(&self, prefix: Option<&str>, bump: Bump) -> Result<Version> {
+        let latest = self.latest_semver_tag(prefix)?.map(|(_, version)| version);
+        Ok(latest.unwrap_or_else(|| Version::new(0, 0, 0)).bump(bump))
+    }
+
+    /// Creates an annotated release tag on `HEAD`.
+    ///
+    /// Equivalent to `git tag -a|-s <prefix><version> -m <message>`.
+    ///
+    /// # Arguments
+    /// * `prefix` - The tag prefix, as for `latest_semver_tag`.
+    /// * `version` - The version being released.
+    /// * `message` - The tag message.
+    /// * `sign` - If `true`, signs the tag with the configured GPG key (`-s`).
+    ///
+    /// # Returns
+    /// The created tag.
+    ///
+    /// # Errors
+    /// Returns `GitError` if the tag already exists or signing fails (including
+    /// `GitNotFound`).
+    pub fn tag_release(&self, prefix: Option<&str>, version: &Version, message: &str, sign: bool) -> Result<Tag> {
+        let tag = Tag::from_str(&format!("{}{}", prefix.unwrap_or(""), version))?;
+        let mode = if sign { "-s" } else { "-a" };
+        execute_git(&self.location, ["tag", mode, tag.as_ref(), "-m", message])?;
+        Ok(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_versions() {
+        let version: Version = "1.2.3-rc.1+build.5".parse().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (1, 2, 3));
+        assert_eq!(version.pre.as_deref(), Some("rc.1"));
+        assert_eq!(version.build.as_deref(), Some("build.5"));
+        assert_eq!(version.to_string(), "1.2.3-rc.1+build.5");
+
+        for invalid in ["1.2", "1.2.3.4", "01.2.3", "1.2.x", "1.2.3-", "1.2.3-a..b", "v1.2.3"] {
+            assert!(invalid.parse::<Version>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_version_precedence() {
+        // The example ordering from the Semantic Versioning specification.
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.9.0",
+            "1.10.0",
+        ];
+        let versions: Vec<Version> = ordered.iter().map(|v| v.parse().unwrap()).collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!("1.0.0+a".parse::<Version>().unwrap(), "1.0.0+b".parse::<Version>().unwrap());
+    }
+
+    #[test]
+    fn test_bump() {
+        let version = Version::new(1, 4, 2);
+        assert_eq!(version.bump(Bump::Major).to_string(), "2.0.0");
+        assert_eq!(version.bump(Bump::Minor).to_string(), "1.5.0");
+        assert_eq!(version.bump(Bump::Patch).to_string(), "1.4.3");
+
+        let rc: Version = "2.0.0-rc.1".parse().unwrap();
+        assert_eq!(rc.bump(Bump::Major).to_string(), "2.0.0");
+        assert_eq!(rc.bump(Bump::Patch).to_string(), "2.0.0");
+        let rc: Version = "1.4.3-rc.1".parse().unwrap();
+        assert_eq!(rc.bump(Bump::Minor).to_string(), "1.5.0");
+    }
+}
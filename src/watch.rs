@@ -0,0 +1,225 @@
+//! Live notification of changes to HEAD, branches and the index, for editors
+//! and TUIs that keep a view of the repository up to date.
+//!
+//! `Repository::watch` watches the Git directory itself rather than the working
+//! tree, so it reports what Git commands and other tools did to the repository:
+//! a checkout, a commit moving a branch, files being staged. Events are
+//! delivered as a `futures_core::Stream`, which works with any async runtime.
+//!
+//! ```no_run
+//! # use GitPilot::Repository;
+//! # use GitPilot::watch::{EventMask, RepoEvent};
+//! # use futures_core::Stream;
+//! # use std::pin::Pin;
+//! # async fn example() -> GitPilot::Result<()> {
+//! let repo = Repository::new("./my_project");
+//! let mut events = repo.watch(EventMask::HEAD | EventMask::BRANCHES)?;
+//! while let Some(event) = std::future::poll_fn(|cx| Pin::new(&mut events).poll_next(cx)).await {
+//!     match event {
+//!         RepoEvent::HeadChanged => println!("HEAD moved"),
+//!         RepoEvent::BranchUpdated(Some(branch)) => println!("{} updated", branch),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::GitError;
+use crate::repository::{execute_git_fn, Repository};
+use crate::types::{BranchName, Result};
+use futures_core::Stream;
+use notify::event::{CreateKind, RemoveKind};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::VecDeque;
+use std::ops::BitOr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// The kinds of change `Repository::watch` reports; combine them with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventMask(u8);
+
+impl EventMask {
+    /// `HEAD` was switched to another branch or commit.
+    pub const HEAD: EventMask = EventMask(1);
+    /// A local branch was created, moved or deleted.
+    pub const BRANCHES: EventMask = EventMask(1 << 1);
+    /// The index changed, e.g. files were staged.
+    pub const INDEX: EventMask = EventMask(1 << 2);
+    /// Every kind of change.
+    pub const ALL: EventMask = EventMask(0b111);
+
+    /// Returns `true` if every kind in `other` is also in this mask.
+    pub fn contains(self, other: EventMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for EventMask {
+    type Output = EventMask;
+
+    fn bitor(self, other: EventMask) -> EventMask {
+        EventMask(self.0 | other.0)
+    }
+}
+
+/// A change to the repository reported by a `RepoWatch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepoEvent {
+    /// `HEAD` was rewritten. This happens on checkouts, and on commits while
+    /// `HEAD` is detached.
+    HeadChanged,
+    /// A local branch was created, moved or deleted. `None` when the packed
+    /// refs changed, after which any branch may point elsewhere.
+    BranchUpdated(Option<BranchName>),
+    /// The index was rewritten.
+    IndexChanged,
+}
+
+/// The events received from the watcher and not yet taken from the stream.
+#[derive(Debug, Default)]
+struct Queue {
+    events: VecDeque<RepoEvent>,
+    waker: Option<Waker>,
+}
+
+/// A stream of `RepoEvent`s, created by `Repository::watch`.
+///
+/// Watching stops when the `RepoWatch` is dropped; until then the stream never
+/// ends. A change Git makes in several steps can be reported more than once.
+#[derive(Debug)]
+pub struct RepoWatch {
+    queue: Arc<Mutex<Queue>>,
+    // Kept alive for as long as events are wanted.
+    _watcher: RecommendedWatcher,
+}
+
+impl RepoWatch {
+    /// Takes the next event if one is waiting, without blocking.
+    pub fn try_next(&mut self) -> Option<RepoEvent> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).events.pop_front()
+    }
+}
+
+impl Stream for RepoWatch {
+    type Item = RepoEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<RepoEvent>> {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        match queue.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Repository {
+    /// Watches the Git directory and reports changes to `HEAD`, local branches
+    /// and the index.
+    ///
+    /// In a linked worktree, `HEAD` and the index are the worktree's own, while
+    /// branch updates from every worktree are reported.
+    ///
+    /// # Arguments
+    /// * `events` - The kinds of change to report.
+    ///
+    /// # Returns
+    /// A `RepoWatch` stream of the changes.
+    ///
+    /// # Errors
+    /// Returns `GitError::Watch` if the file system watcher cannot be set up.
+    /// Returns `GitError` if this is not a repository (including `GitNotFound`).
+    pub fn watch(&self, events: EventMask) -> Result<RepoWatch> {
+        let git_dir = self.git_dir()?;
+        let common_dir = execute_git_fn(
+            &self.location,
+            ["rev-parse", "--path-format=absolute", "--git-common-dir"],
+            |output| Ok(PathBuf::from(output.trim_end_matches('\n'))),
+        )?;
+        let classifier = Classifier { git_dir: git_dir.clone(), common_dir: common_dir.clone(), events };
+
+        let queue = Arc::new(Mutex::new(Queue::default()));
+        let sender = Arc::clone(&queue);
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else { return };
+            let mut queue = sender.lock().unwrap_or_else(|e| e.into_inner());
+            for repo_event in classifier.classify(&event) {
+                // Git renames a lock file over its target, which is often
+                // reported as several events; collapse the repeats.
+                if queue.events.back() != Some(&repo_event) {
+                    queue.events.push_back(repo_event);
+                }
+            }
+            if !queue.events.is_empty() {
+                if let Some(waker) = queue.waker.take() {
+                    waker.wake();
+                }
+            }
+        })
+        .map_err(|e| GitError::Watch(e.to_string()))?;
+
+        let watch = |watcher: &mut RecommendedWatcher, path: &Path, mode| {
+            watcher.watch(path, mode).map_err(|e| GitError::Watch(format!("{}: {}", path.display(), e)))
+        };
+        let worktree_files = events.contains(EventMask::HEAD) || events.contains(EventMask::INDEX);
+        if worktree_files {
+            watch(&mut watcher, &git_dir, RecursiveMode::NonRecursive)?;
+        }
+        if events.contains(EventMask::BRANCHES) {
+            // `packed-refs` lives in the common directory, loose branches below it.
+            if !worktree_files || common_dir != git_dir {
+                watch(&mut watcher, &common_dir, RecursiveMode::NonRecursive)?;
+            }
+            watch(&mut watcher, &common_dir.join("refs").join("heads"), RecursiveMode::Recursive)?;
+        }
+        Ok(RepoWatch { queue, _watcher: watcher })
+    }
+}
+
+/// Maps file system events in the Git directory to `RepoEvent`s.
+struct Classifier {
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+    events: EventMask,
+}
+
+impl Classifier {
+    fn classify(&self, event: &Event) -> Vec<RepoEvent> {
+        match event.kind {
+            EventKind::Access(_) | EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder) => {
+                return Vec::new()
+            }
+            _ => {}
+        }
+        let heads = self.common_dir.join("refs").join("heads");
+        event
+            .paths
+            .iter()
+            // Git writes `<file>.lock` and renames it into place.
+            .filter(|path| path.extension().is_none_or(|extension| extension != "lock"))
+            .filter_map(|path| {
+                if *path == self.git_dir.join("HEAD") {
+                    Some((EventMask::HEAD, RepoEvent::HeadChanged))
+                } else if *path == self.git_dir.join("index") {
+                    Some((EventMask::INDEX, RepoEvent::IndexChanged))
+                } else if *path == self.common_dir.join("packed-refs") {
+                    Some((EventMask::BRANCHES, RepoEvent::BranchUpdated(None)))
+                } else {
+                    let name = path.strip_prefix(&heads).ok()?.to_str()?;
+                    let branch = BranchName::from_str(&name.replace(std::path::MAIN_SEPARATOR, "/")).ok()?;
+                    Some((EventMask::BRANCHES, RepoEvent::BranchUpdated(Some(branch))))
+                }
+            })
+            .filter(|(kind, _)| self.events.contains(*kind))
+            .map(|(_, event)| event)
+            .collect()
+    }
+}
@@ -0,0 +1,162 @@
+//! Running the same operation across many repositories at once.
+
+use crate::async_git::runtime;
+use crate::async_git::AsyncRepository;
+use crate::models::{FetchResult, StatusResult};
+use crate::options::FetchOptions;
+use crate::types::Result;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// The outcome of an operation on one repository of a `RepoSet`.
+#[derive(Debug)]
+pub struct RepoResult<T> {
+    /// The root of the repository the operation ran in.
+    pub path: PathBuf,
+    /// What the operation returned for this repository.
+    pub result: Result<T>,
+}
+
+/// A collection of repositories operated on together, such as every checkout
+/// in a monorepo workspace or every project on a developer's machine.
+///
+/// Operations run concurrently, with at most `concurrency` Git processes at a
+/// time, and report one `RepoResult` per repository in the order the
+/// repositories were added. A failure in one repository does not stop the
+/// others.
+///
+/// ```no_run
+/// # use GitPilot::workspace::RepoSet;
+/// # async fn run() {
+/// let repos = RepoSet::from_paths(["./api", "./web", "./docs"]);
+/// for outcome in repos.status_all(4).await {
+///     match outcome.result {
+///         Ok(status) if !status.is_clean => println!("{}: dirty", outcome.path.display()),
+///         Ok(_) => {}
+///         Err(e) => eprintln!("{}: {}", outcome.path.display(), e),
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RepoSet {
+    repos: Vec<AsyncRepository>,
+}
+
+impl RepoSet {
+    /// Creates an empty `RepoSet`.
+    pub fn new() -> RepoSet {
+        RepoSet::default()
+    }
+
+    /// Creates a `RepoSet` of existing repositories, one per path.
+    ///
+    /// Like `AsyncRepository::new`, this does not check that the paths are
+    /// Git repositories; operations on those that are not fail individually.
+    pub fn from_paths<I, P>(paths: I) -> RepoSet
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        RepoSet { repos: paths.into_iter().map(AsyncRepository::new).collect() }
+    }
+
+    /// Adds a repository to the set.
+    pub fn push(&mut self, repo: AsyncRepository) {
+        self.repos.push(repo);
+    }
+
+    /// Returns the repositories in the set, in the order they were added.
+    pub fn repos(&self) -> &[AsyncRepository] {
+        &self.repos
+    }
+
+    /// Returns the number of repositories in the set.
+    pub fn len(&self) -> usize {
+        self.repos.len()
+    }
+
+    /// Returns `true` if the set holds no repositories.
+    pub fn is_empty(&self) -> bool {
+        self.repos.is_empty()
+    }
+
+    /// Runs `f` on every repository, at most `concurrency` at a time.
+    ///
+    /// # Arguments
+    /// * `concurrency` - The maximum number of repositories processed at once;
+    ///   `0` is treated as `1`.
+    /// * `f` - Builds the operation to run for one repository.
+    ///
+    /// # Returns
+    /// One `RepoResult` per repository, in the order the repositories were added.
+    ///
+    /// # Panics
+    /// Panics if an operation panics, or if called outside a Tokio runtime.
+    pub async fn for_each_concurrent<F, Fut, T>(&self, concurrency: usize, f: F) -> Vec<RepoResult<T>>
+    where
+        F: Fn(AsyncRepository) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        let tasks: Vec<_> = self
+            .repos
+            .iter()
+            .map(|repo| {
+                let permits = Arc::clone(&permits);
+                let operation = f(repo.clone());
+                runtime::spawn(async move {
+                    let _permit = permits.acquire_owned().await.expect("semaphore is never closed");
+                    operation.await
+                })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(tasks.len());
+        for (repo, task) in self.repos.iter().zip(tasks) {
+            results.push(RepoResult { path: repo.path().to_path_buf(), result: task.join().await });
+        }
+        results
+    }
+
+    /// Fetches every repository with the same options.
+    ///
+    /// Equivalent to running `git fetch` in each repository.
+    ///
+    /// # Arguments
+    /// * `concurrency` - The maximum number of fetches running at once.
+    /// * `opts` - The fetch options applied to every repository.
+    pub async fn fetch_all(&self, concurrency: usize, opts: &FetchOptions) -> Vec<RepoResult<FetchResult>> {
+        let opts = Arc::new(opts.clone());
+        self.for_each_concurrent(concurrency, |repo| {
+            let opts = Arc::clone(&opts);
+            async move { repo.fetch(&opts).await }
+        })
+        .await
+    }
+
+    /// Reads the status of every repository.
+    ///
+    /// Equivalent to running `git status` in each repository.
+    ///
+    /// # Arguments
+    /// * `concurrency` - The maximum number of `git status` processes running at once.
+    pub async fn status_all(&self, concurrency: usize) -> Vec<RepoResult<StatusResult>> {
+        self.for_each_concurrent(concurrency, |repo| async move { repo.status().await }).await
+    }
+}
+
+impl FromIterator<AsyncRepository> for RepoSet {
+    fn from_iter<I: IntoIterator<Item = AsyncRepository>>(iter: I) -> RepoSet {
+        RepoSet { repos: iter.into_iter().collect() }
+    }
+}
+
+impl Extend<AsyncRepository> for RepoSet {
+    fn extend<I: IntoIterator<Item = AsyncRepository>>(&mut self, iter: I) {
+        self.repos.extend(iter);
+    }
+}
@@ -0,0 +1,33 @@
+use GitPilot::models::AttrValue;
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_check_attr() {
+    let repo = TempRepo::new();
+    repo.commit_file(
+        ".gitattributes",
+        "*.png binary\n*.psd filter=lfs diff=lfs merge=lfs -text\n*.bat eol=crlf\n",
+        "attributes",
+    );
+
+    let results = repo.check_attr(&["logo.png", "art/cover.psd", "run.bat"], &["binary", "filter", "eol"]).unwrap();
+    assert_eq!(results.len(), 9);
+    let value = |path: &str, attr: &str| {
+        results
+            .iter()
+            .find(|r| r.path.as_os_str() == path && r.attribute == attr)
+            .map(|r| r.value.clone())
+            .unwrap()
+    };
+    assert_eq!(value("logo.png", "binary"), AttrValue::Set);
+    assert_eq!(value("art/cover.psd", "filter"), AttrValue::Value("lfs".to_string()));
+    assert_eq!(value("run.bat", "eol"), AttrValue::Value("crlf".to_string()));
+    assert_eq!(value("run.bat", "binary"), AttrValue::Unspecified);
+
+    // Without attribute names, only attributes that are mentioned are listed.
+    let all = repo.check_attr(&["art/cover.psd"], &[]).unwrap();
+    let mut names: Vec<&str> = all.iter().map(|r| r.attribute.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["diff", "filter", "merge", "text"]);
+    assert!(all.iter().any(|r| r.attribute == "text" && r.value == AttrValue::Unset));
+}
@@ -0,0 +1,58 @@
+use GitPilot::models::{AttrValue, ObjectType};
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_object_reader_answers_many_lookups() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "alpha\n", "first");
+    repo.commit_file("dir/with space.bin", "\0binary\nwith newlines\n\n", "second");
+
+    let mut reader = repo.object_reader().unwrap();
+    let blob = reader.read("HEAD:a.txt").unwrap().unwrap();
+    assert_eq!(blob.object_type, ObjectType::Blob);
+    assert_eq!(blob.data, b"alpha\n");
+    assert_eq!(blob.id, repo.cmd_out(["rev-parse", "HEAD:a.txt"]).unwrap()[0]);
+
+    // Binary content and trailing newlines survive, and the stream stays in sync.
+    let binary = reader.read("HEAD:dir/with space.bin").unwrap().unwrap();
+    assert_eq!(binary.data, b"\0binary\nwith newlines\n\n");
+
+    assert!(reader.read("HEAD:does-not-exist").unwrap().is_none());
+
+    let commit = reader.read("HEAD").unwrap().unwrap();
+    assert_eq!(commit.object_type, ObjectType::Commit);
+    assert!(String::from_utf8(commit.data).unwrap().ends_with("\nsecond\n"));
+    assert_eq!(reader.read("HEAD^{tree}").unwrap().unwrap().object_type, ObjectType::Tree);
+
+    assert!(reader.read("HEAD\nHEAD").is_err());
+    // A rejected query must not desynchronize later reads.
+    assert_eq!(reader.read("HEAD~1:a.txt").unwrap().unwrap().data, b"alpha\n");
+}
+
+#[test]
+fn test_batched_ignore_attr_and_rev_queries() {
+    let repo = TempRepo::new();
+    repo.commit_file(".gitignore", "*.log\n!keep.log\n", "ignore");
+    repo.commit_file(".gitattributes", "*.png binary\n*.bat eol=crlf\n", "attributes");
+
+    let mut ignore = repo.ignore_checker().unwrap();
+    assert!(ignore.is_ignored("debug.log").unwrap());
+    assert!(!ignore.is_ignored("keep.log").unwrap());
+    assert!(!ignore.is_ignored("src/with space.rs").unwrap());
+    assert!(ignore.is_ignored("nested/dir/trace.log").unwrap());
+
+    let mut attrs = repo.attr_checker(&["binary", "eol"]).unwrap();
+    let png = attrs.check("img/logo.png").unwrap();
+    assert_eq!(png.len(), 2);
+    assert_eq!(png[0].value, AttrValue::Set);
+    assert_eq!(png[1].value, AttrValue::Unspecified);
+    assert_eq!(attrs.check("run.bat").unwrap()[1].value, AttrValue::Value("crlf".to_string()));
+    assert!(repo.attr_checker(&[]).is_err());
+
+    let mut revs = repo.rev_resolver().unwrap();
+    let head = repo.get_hash(false).unwrap();
+    assert_eq!(revs.resolve("HEAD").unwrap().as_deref(), Some(head.as_ref()));
+    assert!(revs.resolve("HEAD~1").unwrap().is_some());
+    assert_eq!(revs.resolve("no-such-branch").unwrap(), None);
+    assert_eq!(revs.resolve("HEAD").unwrap().as_deref(), Some(head.as_ref()));
+}
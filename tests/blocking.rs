@@ -0,0 +1,19 @@
+#![cfg(feature = "async")]
+
+use GitPilot::testing::TempRepo;
+
+#[tokio::test]
+async fn test_run_blocking_bridges_sync_and_async() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+
+    let sync = repo.repo().clone();
+    let count = sync.run_blocking(|r| r.count_commits(None)).await.unwrap();
+    assert_eq!(count, 1);
+
+    let repo_async = sync.to_async();
+    assert_eq!(repo_async.path(), repo.path());
+    repo.write_file("a.txt", "two\n");
+    assert!(repo_async.run_blocking(|r| r.is_dirty()).await.unwrap());
+    assert_eq!(repo_async.to_sync().get_hash(false).unwrap(), repo_async.get_hash(false).await.unwrap());
+}
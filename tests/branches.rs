@@ -0,0 +1,119 @@
+use std::str::FromStr;
+use GitPilot::error::GitError;
+use GitPilot::models::SyncStatus;
+use GitPilot::options::BranchFilter;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, GitUrl, Remote};
+
+fn names(repo: &TempRepo, filter: &BranchFilter) -> Vec<String> {
+    repo.list_branches_filtered(filter).unwrap().into_iter().map(|b| b.name.to_string()).collect()
+}
+
+#[test]
+fn test_list_branches_filtered() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let main = repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0].clone();
+    let base = repo.get_hash(false).unwrap().to_string();
+    repo.cmd(["branch", "merged"]).unwrap();
+    for branch in ["feature/a", "feature/b"] {
+        repo.cmd(["checkout", "-q", "-b", branch, &main]).unwrap();
+        repo.commit_file("b.txt", branch, branch);
+    }
+    repo.cmd(["checkout", "-q", &main]).unwrap();
+
+    let mut merged = names(&repo, &BranchFilter { merged: Some(main.clone()), ..Default::default() });
+    merged.sort();
+    let mut expected = vec![main.clone(), "merged".to_string()];
+    expected.sort();
+    assert_eq!(merged, expected);
+
+    let filter = BranchFilter { no_merged: Some(main.clone()), ..Default::default() };
+    assert_eq!(names(&repo, &filter), ["feature/a", "feature/b"]);
+
+    let filter = BranchFilter { contains: Some(base), patterns: vec!["feature/*".to_string()], ..Default::default() };
+    assert_eq!(names(&repo, &filter), ["feature/a", "feature/b"]);
+
+    let filter = BranchFilter {
+        sort: Some("-refname".to_string()),
+        patterns: vec!["feature/*".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(names(&repo, &filter), ["feature/b", "feature/a"]);
+    assert!(repo.list_branches_filtered(&filter).unwrap().iter().all(|b| !b.is_head));
+
+    let filter = BranchFilter { merged: Some("nope".to_string()), ..Default::default() };
+    assert!(repo.list_branches_filtered(&filter).is_err());
+}
+
+#[test]
+fn test_prune_merged_branches() {
+    let upstream = TempRepo::new();
+    upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let main = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    for branch in ["done", "release", "local-only"] {
+        repo.cmd(["branch", branch]).unwrap();
+    }
+    repo.cmd(["checkout", "-q", "-b", "wip"]).unwrap();
+    repo.commit_file("b.txt", "wip\n", "wip");
+    repo.cmd(["checkout", "-q", main.as_ref()]).unwrap();
+
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    repo.cmd(["push", "-q", "origin", "done", "release", "wip", main.as_ref()]).unwrap();
+    repo.fetch_remote(&origin).unwrap();
+
+    let protect = [BranchName::from_str("release").unwrap()];
+    let planned = repo.prune_merged_branches(&main, &protect, Some(&origin), true).unwrap();
+    assert_eq!(planned.iter().map(|b| b.to_string()).collect::<Vec<_>>(), ["done", "local-only"]);
+    assert_eq!(repo.list_branches().unwrap().len(), 5);
+
+    let pruned = repo.prune_merged_branches(&main, &protect, Some(&origin), false).unwrap();
+    assert_eq!(pruned, planned);
+    let mut local: Vec<String> = repo.list_branches().unwrap().iter().map(|b| b.to_string()).collect();
+    local.sort();
+    let mut expected = vec![main.to_string(), "release".to_string(), "wip".to_string()];
+    expected.sort();
+    assert_eq!(local, expected);
+    let mut remote: Vec<String> = upstream.list_branches().unwrap().iter().map(|b| b.to_string()).collect();
+    remote.sort();
+    assert_eq!(remote, expected);
+}
+
+#[test]
+fn test_sync_status() {
+    let upstream = TempRepo::new();
+    upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let branch = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    let status = || repo.sync_status(&branch).unwrap();
+
+    assert_eq!(status(), SyncStatus::NoUpstream);
+    repo.push_to_upstream(&origin, &branch).unwrap();
+    assert_eq!(status(), SyncStatus::UpToDate);
+
+    repo.commit_file("a.txt", "two\n", "second");
+    repo.commit_file("a.txt", "three\n", "third");
+    assert_eq!(status(), SyncStatus::Ahead(2));
+    assert!(status().needs_push() && !status().needs_pull());
+    repo.cmd(["reset", "-q", "--hard", "HEAD~2"]).unwrap();
+    upstream.cmd(["commit", "-q", "--allow-empty", "-m", "theirs"]).unwrap();
+    repo.fetch_remote(&origin).unwrap();
+    assert_eq!(status(), SyncStatus::Behind(1));
+    repo.commit_file("a.txt", "mine\n", "mine");
+    assert_eq!(status(), SyncStatus::Diverged(1, 1));
+
+    repo.cmd(["update-ref", "-d", &format!("refs/remotes/origin/{}", branch)]).unwrap();
+    assert_eq!(status(), SyncStatus::UpstreamGone);
+    let missing = BranchName::from_str("missing").unwrap();
+    assert!(matches!(repo.sync_status(&missing), Err(GitError::BranchNotFound(name)) if name == "missing"));
+
+    // Only the exact branch counts, not branches nested below its name.
+    repo.cmd(["branch", "missing/topic"]).unwrap();
+    assert!(matches!(repo.sync_status(&missing), Err(GitError::BranchNotFound(_))));
+}
@@ -0,0 +1,242 @@
+use std::str::FromStr;
+use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use GitPilot::builder::{CommandOutcome, CommandRecord, DecodePolicy, RetryPolicy};
+use GitPilot::options::FetchOptions;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, GitUrl, Remote};
+use GitPilot::{GitError, Repository};
+
+#[test]
+fn test_builder_sets_environment() {
+    let repo = TempRepo::new();
+    repo.write_file("a.txt", "one\n");
+    let built = Repository::builder(repo.path())
+        .env("GIT_AUTHOR_NAME", "Release Bot")
+        .env("GIT_AUTHOR_EMAIL", "bot@gitpilot.invalid")
+        .build();
+    built.add(vec!["a.txt"]).unwrap();
+    built.commit_staged("first").unwrap();
+
+    let author = repo.cmd_out(["log", "-1", "--format=%an <%ae>"]).unwrap();
+    assert_eq!(author[0], "Release Bot <bot@gitpilot.invalid>");
+    // Handles created without the builder are unaffected.
+    repo.commit_file("b.txt", "two\n", "second");
+    assert_eq!(repo.cmd_out(["log", "-1", "--format=%an"]).unwrap()[0], "GitPilot Test");
+}
+
+#[test]
+fn test_builder_default_remote() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    let repo = TempRepo::new();
+    let remote = Remote::from_str("upstream").unwrap();
+    repo.add_remote(&remote, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+
+    let built = Repository::builder(repo.path()).default_remote(remote.clone()).build();
+    assert_eq!(built.default_remote(), Some(&remote));
+    assert_eq!(repo.default_remote(), None);
+
+    let fetched = built.fetch(&FetchOptions::default()).unwrap();
+    assert_eq!(fetched.new_branches().count(), 1);
+    assert!(fetched.refs[0].local_ref.starts_with("upstream/"));
+}
+
+#[test]
+fn test_on_command_records_every_command() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let records: Arc<Mutex<Vec<CommandRecord>>> = Arc::default();
+    let sink = Arc::clone(&records);
+    let built = Repository::builder(repo.path()).on_command(move |record| sink.lock().unwrap().push(record.clone())).build();
+
+    built.get_hash(false).unwrap();
+    assert!(built.switch_branch(&BranchName::from_str("missing").unwrap()).is_err());
+    let mut resolver = built.rev_resolver().unwrap();
+    resolver.resolve("HEAD").unwrap();
+    drop(resolver);
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].args, ["rev-parse", "HEAD"]);
+    assert_eq!(records[0].dir, repo.path());
+    assert_eq!(records[0].outcome, CommandOutcome::Succeeded);
+    match &records[1].outcome {
+        CommandOutcome::Failed { code, message } => {
+            assert_eq!(*code, Some(1));
+            assert!(message.contains("missing"), "{}", message);
+        }
+        other => panic!("expected a failure, got {:?}", other),
+    }
+    assert_eq!(records[2].args, ["cat-file", "--batch-check=%(objectname)"]);
+    assert_eq!(records[2].outcome, CommandOutcome::Succeeded);
+}
+
+#[test]
+fn test_builder_missing_git_binary() {
+    let repo = TempRepo::new();
+    let built = Repository::builder(repo.path()).git_binary("/nonexistent/git").build();
+    assert!(matches!(built.status(), Err(GitError::GitNotFound)));
+}
+
+#[cfg(unix)]
+fn write_script(repo: &TempRepo, name: &str, body: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = repo.path().join(".git").join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{}", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_timeout() {
+    let repo = TempRepo::new();
+    let slow_git = write_script(&repo, "slow-git", "sleep 5\n");
+    let built = Repository::builder(repo.path()).git_binary(slow_git).timeout(Duration::from_millis(200)).build();
+    let started = std::time::Instant::now();
+    assert!(matches!(built.get_hash(false), Err(GitError::Timeout(timeout)) if timeout == Duration::from_millis(200)));
+    assert!(started.elapsed() < Duration::from_secs(4));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_locale() {
+    let repo = TempRepo::new();
+    let env_git = write_script(&repo, "env-git", "echo \"${LC_ALL-unset} ${LANG-unset} ${LANGUAGE-unset}\"\n");
+    let locale_of = |builder: GitPilot::builder::RepositoryBuilder| builder.git_binary(&env_git).build().cmd_out(["x"]).unwrap();
+
+    assert_eq!(locale_of(Repository::builder(repo.path())), ["C C unset"]);
+    assert_eq!(locale_of(Repository::builder(repo.path()).locale("C.UTF-8")), ["C.UTF-8 C.UTF-8 unset"]);
+    // Variables set explicitly win over the locale.
+    assert_eq!(locale_of(Repository::builder(repo.path()).env("LC_ALL", "POSIX")), ["POSIX C unset"]);
+    assert_eq!(locale_of(Repository::builder(repo.path()).env("LANGUAGE", "fr")), ["C C fr"]);
+    let inherited = format!(
+        "{} {} {}",
+        std::env::var("LC_ALL").unwrap_or("unset".into()),
+        std::env::var("LANG").unwrap_or("unset".into()),
+        std::env::var("LANGUAGE").unwrap_or("unset".into())
+    );
+    assert_eq!(locale_of(Repository::builder(repo.path()).inherit_locale()), [inherited]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_retries_transient_failures() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    // Fails as if `index.lock` were held on the first two runs, then runs Git.
+    let flaky_git = write_script(
+        &repo,
+        "flaky-git",
+        "count=$(cat \"$0.count\" 2>/dev/null || echo 0)\n\
+         echo $((count + 1)) > \"$0.count\"\n\
+         if [ \"$count\" -lt 2 ]; then\n\
+         \x20 echo \"fatal: Unable to create '/repo/.git/index.lock': File exists.\" >&2\n\
+         \x20 exit 128\n\
+         fi\n\
+         exec git \"$@\"\n",
+    );
+    let counter = repo.path().join(".git").join("flaky-git.count");
+
+    let impatient = Repository::builder(repo.path())
+        .git_binary(&flaky_git)
+        .retry(RetryPolicy { max_retries: 1, delay: Duration::from_millis(10) })
+        .build();
+    assert!(matches!(impatient.get_hash(false), Err(GitError::GitError { .. })));
+    assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "2");
+
+    std::fs::remove_file(&counter).unwrap();
+    let patient = Repository::builder(repo.path())
+        .git_binary(&flaky_git)
+        .retry(RetryPolicy { max_retries: 2, delay: Duration::from_millis(10) })
+        .build();
+    assert_eq!(patient.get_hash(false).unwrap(), repo.get_hash(false).unwrap());
+    assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "3");
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_builder_async_shares_settings() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let remote = Remote::from_str("upstream").unwrap();
+    let built = Repository::builder(repo.path()).default_remote(remote.clone()).git_binary("/nonexistent/git").build_async();
+    assert_eq!(built.default_remote(), Some(&remote));
+    assert!(matches!(built.get_hash(false).await, Err(GitError::GitNotFound)));
+    assert!(matches!(built.to_sync().get_hash(false), Err(GitError::GitNotFound)));
+}
+
+#[test]
+fn test_builder_output_limit() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", &"line\n".repeat(10_000), "first");
+
+    let limited = Repository::builder(repo.path()).max_output_bytes(1024).build();
+    assert!(matches!(
+        limited.cmd_out(["show", "HEAD"]),
+        Err(GitError::OutputTooLarge { limit: 1024 })
+    ));
+    assert_eq!(limited.cmd_out(["rev-parse", "HEAD"]).unwrap().len(), 1);
+
+    let unlimited = Repository::builder(repo.path()).max_output_bytes(1024).unlimited_output().build();
+    assert!(unlimited.cmd_out(["show", "HEAD"]).unwrap().len() > 10_000);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_builder_output_limit_async() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", &"line\n".repeat(10_000), "first");
+    let limited = Repository::builder(repo.path()).max_output_bytes(1024).build_async();
+    assert!(matches!(
+        limited.cmd_out(["show", "HEAD"]).await,
+        Err(GitError::OutputTooLarge { limit: 1024 })
+    ));
+    assert_eq!(limited.cmd_out(["rev-parse", "HEAD"]).await.unwrap().len(), 1);
+}
+
+#[test]
+fn test_builder_decode_policy() {
+    let repo = TempRepo::new();
+    // A file written in Latin-1.
+    std::fs::write(repo.path().join("a.txt"), b"Jos\xe9\n").unwrap();
+    repo.add(vec!["a.txt"]).unwrap();
+    repo.commit_staged("first").unwrap();
+    let show = ["show", "HEAD:a.txt"];
+
+    assert_eq!(repo.cmd_out(show).unwrap(), ["Jos\u{FFFD}"]);
+    let strict = Repository::builder(repo.path()).decode(DecodePolicy::Strict).build();
+    assert!(matches!(strict.cmd_out(show), Err(GitError::Undecodable)));
+    let bytes = Repository::builder(repo.path()).decode(DecodePolicy::Bytes).build();
+    assert_eq!(bytes.cmd_out(show).unwrap(), ["Jos\u{e9}"]);
+    assert_eq!(bytes.cmd_lines(show).unwrap().next().unwrap().unwrap(), "Jos\u{e9}");
+
+    assert_eq!(DecodePolicy::Bytes.decode("José".as_bytes()).unwrap(), "José");
+}
+
+#[test]
+fn test_explain_records_commands_of_a_call() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let seen: Arc<Mutex<usize>> = Arc::default();
+    let counter = Arc::clone(&seen);
+    let built = Repository::builder(repo.path()).on_command(move |_| *counter.lock().unwrap() += 1).build();
+
+    let (hash, commands) = built.explain(|repo| repo.get_hash(false));
+    assert_eq!(hash.unwrap(), repo.get_hash(false).unwrap());
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].args, ["rev-parse", "HEAD"]);
+    assert_eq!(*seen.lock().unwrap(), 1);
+
+    let (result, commands) = built.explain(|repo| {
+        repo.get_hash(false)?;
+        repo.switch_branch(&BranchName::from_str("missing").unwrap())
+    });
+    assert!(result.is_err());
+    assert_eq!(commands.len(), 2);
+    assert!(matches!(commands[1].outcome, CommandOutcome::Failed { .. }));
+    // The callback sees every command, inside `explain` or not.
+    built.get_hash(false).unwrap();
+    assert_eq!(*seen.lock().unwrap(), 4);
+}
@@ -0,0 +1,59 @@
+use std::fs;
+use GitPilot::options::ConflictSide;
+use GitPilot::testing::TempRepo;
+
+/// Leaves `a.txt` and `b.txt` conflicted by merging `topic` into the initial branch.
+fn conflicted_merge(repo: &TempRepo) {
+    repo.commit_file("a.txt", "base\n", "base a");
+    repo.commit_file("b.txt", "base\n", "base b");
+    repo.cmd(["checkout", "-q", "-b", "topic"]).unwrap();
+    repo.commit_file("a.txt", "topic\n", "topic a");
+    repo.commit_file("b.txt", "topic\n", "topic b");
+    repo.cmd(["checkout", "-q", "-"]).unwrap();
+    repo.commit_file("a.txt", "main\n", "main a");
+    repo.commit_file("b.txt", "main\n", "main b");
+    assert!(repo.cmd(["merge", "topic"]).is_err());
+}
+
+#[test]
+fn test_resolve_conflicts_and_commit() {
+    let repo = TempRepo::new();
+    conflicted_merge(&repo);
+    assert!(repo.status().unwrap().files.iter().all(|f| f.is_unmerged()));
+
+    repo.resolve_conflict("a.txt", ConflictSide::Theirs).unwrap();
+    fs::write(repo.path().join("b.txt"), "merged by hand\n").unwrap();
+    repo.mark_resolved(vec!["b.txt"]).unwrap();
+
+    let status = repo.status().unwrap();
+    assert!(status.files.iter().all(|f| !f.is_unmerged()));
+    repo.commit_staged("merge topic").unwrap();
+
+    assert_eq!(fs::read_to_string(repo.path().join("a.txt")).unwrap(), "topic\n");
+    assert_eq!(fs::read_to_string(repo.path().join("b.txt")).unwrap(), "merged by hand\n");
+    assert_eq!(repo.get_commit(None).unwrap().parents.len(), 2);
+}
+
+#[test]
+fn test_resolve_conflict_rejects_unknown_path() {
+    let repo = TempRepo::new();
+    conflicted_merge(&repo);
+    assert!(repo.resolve_conflict("missing.txt", ConflictSide::Ours).is_err());
+    // The failed call must not have staged anything.
+    assert!(repo.status().unwrap().files.iter().all(|f| f.is_unmerged()));
+}
+
+#[test]
+fn test_merge_file_in_memory() {
+    let repo = TempRepo::new();
+    let base = "one\ntwo\nthree\n";
+
+    let clean = repo.merge_file(base, "ONE\ntwo\nthree\n", "one\ntwo\nTHREE\n").unwrap();
+    assert!(!clean.has_conflicts());
+    assert_eq!(clean.content, "ONE\ntwo\nTHREE\n");
+
+    let conflicted = repo.merge_file(base, "one\nours\nthree\n", "one\ntheirs\nthree\n").unwrap();
+    assert_eq!(conflicted.conflicts, 1);
+    assert!(conflicted.content.contains("<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n"));
+    assert!(repo.status().unwrap().is_clean);
+}
@@ -0,0 +1,378 @@
+use std::path::PathBuf;
+use GitPilot::models::{CherryMark, DiffLineType};
+use GitPilot::options::{DiffMode, DiffOptions, FileHistoryOptions, RebaseOptions, RebaseStep, RevListOptions};
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_count_commits_and_rev_list() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one", "first");
+    repo.commit_file("b.txt", "two", "second");
+    repo.commit_file("a.txt", "three", "third");
+
+    assert_eq!(repo.count_commits(None).unwrap(), 3);
+    assert_eq!(repo.count_commits(Some("HEAD~1..HEAD")).unwrap(), 1);
+
+    let all = repo.rev_list(None, &RevListOptions::default()).unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0], repo.get_hash(false).unwrap());
+
+    let limited = repo
+        .rev_list(None, &RevListOptions { max_count: Some(2), ..Default::default() })
+        .unwrap();
+    assert_eq!(limited.len(), 2);
+
+    let touching_a = repo
+        .rev_list(None, &RevListOptions { paths: vec!["a.txt".into()], ..Default::default() })
+        .unwrap();
+    assert_eq!(touching_a.len(), 2);
+
+    let merges = repo
+        .rev_list(None, &RevListOptions { merges: true, ..Default::default() })
+        .unwrap();
+    assert!(merges.is_empty());
+}
+
+#[test]
+fn test_get_commits_and_log_with_stats() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\ntwo\n", "first");
+    repo.commit_file("dir/b c.txt", "x\n", "second");
+    repo.commit_file("a.txt", "one\n", "third");
+
+    let commits = repo.get_commits(&["HEAD~2", "HEAD"]).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].message, "first");
+    assert!(commits[0].parents.is_empty());
+    assert_eq!(commits[1].message, "third");
+    assert_eq!(commits[1].parents.len(), 1);
+    assert!(repo.get_commits(&[]).unwrap().is_empty());
+    // A branch named like a file is not ambiguous, and a ref cannot pass an option.
+    repo.cmd(["branch", "a.txt", "HEAD~1"]).unwrap();
+    assert_eq!(repo.get_commits(&["a.txt"]).unwrap()[0].message, "second");
+    assert!(repo.get_commits(&["--all"]).is_err());
+
+    let history = repo.log_with_stats(None).unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].commit.message, "third");
+    assert_eq!((history[0].added_lines(), history[0].removed_lines()), (0, 1));
+    assert_eq!(history[1].files[0].path, PathBuf::from("dir/b c.txt"));
+    assert_eq!(history[2].added_lines(), 2);
+}
+
+#[test]
+fn test_diff_stats_between_commits_and_worktree() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\ntwo\nthree\n", "first");
+    std::fs::write(repo.path().join("bin.dat"), [0u8, 1, 2]).unwrap();
+    repo.add(vec!["bin.dat"]).unwrap();
+    repo.cmd(["mv", "a.txt", "renamed.txt"]).unwrap();
+    repo.commit_staged("second").unwrap();
+
+    let stats = repo.diff_stats("HEAD~1", Some("HEAD")).unwrap();
+    assert_eq!(stats.files_changed, 2);
+    let renamed = stats.files.iter().find(|f| f.path.as_path() == std::path::Path::new("renamed.txt")).unwrap();
+    assert_eq!(renamed.old_path, Some(PathBuf::from("a.txt")));
+    assert!(stats.files.iter().any(|f| f.is_binary()));
+
+    std::fs::write(repo.path().join("renamed.txt"), "one\n").unwrap();
+    let worktree = repo.diff_stats("HEAD", None).unwrap();
+    assert_eq!((worktree.files_changed, worktree.insertions, worktree.deletions), (1, 0, 2));
+}
+
+#[test]
+fn test_get_commit_keeps_multiline_message() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one", "root");
+    std::fs::write(repo.path().join("a.txt"), "two").unwrap();
+    repo.stage_and_commit_all_modified("Subject line\n\nFirst body paragraph.\n\nSecond paragraph.")
+        .unwrap();
+
+    let commit = repo.get_commit(None).unwrap();
+    assert_eq!(commit.subject, "Subject line");
+    assert_eq!(commit.body, "First body paragraph.\n\nSecond paragraph.");
+    assert_eq!(commit.message, "Subject line\n\nFirst body paragraph.\n\nSecond paragraph.");
+    assert_eq!(commit.parents.len(), 1);
+    assert_eq!(commit.parents[0], repo.get_commit(Some("HEAD~1")).unwrap().hash);
+
+    let root = repo.get_commit(Some("HEAD~1")).unwrap();
+    assert_eq!(root.message, "root");
+    assert!(root.body.is_empty());
+    assert!(root.parents.is_empty());
+
+    // A subject wrapped over two lines is joined by `subject` but kept in `message`.
+    repo.cmd(["commit", "-q", "--allow-empty", "-m", "Wrapped\nsubject\n\nBody."]).unwrap();
+    let wrapped = repo.get_commit(None).unwrap();
+    assert_eq!(wrapped.subject, "Wrapped subject");
+    assert_eq!(wrapped.message, "Wrapped\nsubject\n\nBody.");
+}
+
+#[test]
+fn test_commit_distinguishes_author_and_committer() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one", "root");
+    std::fs::write(repo.path().join("a.txt"), "two").unwrap();
+    repo.cmd([
+        "commit",
+        "-am",
+        "patch from someone else",
+        "--author=Original Author <author@example.com>",
+        "--date=2001-02-03T04:05:06Z",
+    ])
+    .unwrap();
+
+    let commit = repo.get_commit(None).unwrap();
+    assert_eq!(commit.author_name, "Original Author");
+    assert_eq!(commit.author_email, "author@example.com");
+    assert_eq!(commit.committer_name, "GitPilot Test");
+    assert_eq!(commit.committer_email, "test@gitpilot.invalid");
+    assert!(commit.committer_timestamp > commit.timestamp);
+    assert!(commit.is_committed_by_other());
+    assert_eq!(commit.signature, Some(GitPilot::models::SignatureStatus::Unsigned));
+
+    let batched = repo.get_commits(&["HEAD"]).unwrap();
+    assert_eq!(batched[0].signature, None);
+}
+
+#[test]
+fn test_exit_code_predicates() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "a\n", "first");
+    repo.commit_file("b.txt", "b\n", "second");
+
+    assert!(repo.is_ancestor("HEAD~1", "HEAD").unwrap());
+    assert!(repo.is_ancestor("HEAD", "HEAD").unwrap());
+    assert!(!repo.is_ancestor("HEAD", "HEAD~1").unwrap());
+    assert!(repo.is_ancestor("no-such-rev", "HEAD").is_err());
+
+    let info = repo.cmd_status(["diff", "--quiet", "HEAD~1", "HEAD"]).unwrap();
+    assert_eq!(info.code, Some(1));
+    assert!(!info.success());
+}
+
+#[test]
+fn test_rebase_interactive_plan() {
+    let repo = TempRepo::new();
+    repo.commit_file("base.txt", "base\n", "base");
+    repo.commit_file("a.txt", "a\n", "add a");
+    repo.commit_file("b.txt", "b\n", "add b");
+    repo.commit_file("c.txt", "c\n", "add c");
+    repo.commit_file("d.txt", "d\n", "add d");
+
+    let hash = |rev: &str| repo.cmd_out(["rev-parse", rev]).unwrap()[0].clone();
+    let (a, b, c, d) = (hash("HEAD~3"), hash("HEAD~2"), hash("HEAD~1"), hash("HEAD"));
+
+    repo.rebase_interactive(
+        "HEAD~4",
+        vec![
+            RebaseStep::Reword { commit: c, message: "add c first\n\nwith a body".to_string() },
+            RebaseStep::Pick(a),
+            RebaseStep::Fixup(b),
+            RebaseStep::Drop(d),
+        ],
+    )
+    .unwrap();
+
+    let commits = repo.log_with_stats(Some("HEAD~2..HEAD")).unwrap();
+    let subjects: Vec<&str> = commits.iter().map(|c| c.commit.subject.as_str()).collect();
+    assert_eq!(subjects, vec!["add a", "add c first"]);
+    assert_eq!(commits[1].commit.body, "with a body");
+    assert_eq!(commits[0].files.len(), 2);
+    assert!(!repo.path().join("d.txt").exists());
+    assert!(!repo.status().unwrap().rebasing);
+}
+
+#[test]
+fn test_rebase_interactive_failure_removes_scratch_files() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "a\n", "add a");
+    let head = repo.cmd_out(["rev-parse", "HEAD"]).unwrap()[0].clone();
+    let scratch_dirs = || {
+        std::fs::read_dir(repo.path().join(".git"))
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("gitpilot-rebase-"))
+            .count()
+    };
+
+    let plan = vec![RebaseStep::Reword { commit: head, message: "reworded".to_string() }];
+    assert!(repo.rebase_interactive("no-such-upstream", plan).is_err());
+    assert_eq!(scratch_dirs(), 0);
+}
+
+#[test]
+fn test_stopped_rebase_interactive_leaves_no_scratch_files() {
+    let repo = TempRepo::new();
+    repo.commit_file("base.txt", "base\n", "base");
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("a.txt", "two\n", "second");
+    repo.commit_file("b.txt", "b\n", "third");
+    let hash = |rev: &str| repo.cmd_out(["rev-parse", rev]).unwrap()[0].clone();
+    let (first, second, third) = (hash("HEAD~2"), hash("HEAD~1"), hash("HEAD"));
+    let scratch_dirs = || {
+        std::fs::read_dir(repo.path().join(".git"))
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("gitpilot-"))
+            .count()
+    };
+    // Dropping `first` makes `second` modify a deleted file, which stops the rebase.
+    let plan = || {
+        vec![
+            RebaseStep::Drop(first.clone()),
+            RebaseStep::Pick(second.clone()),
+            RebaseStep::Reword { commit: third.clone(), message: "reworded".to_string() },
+        ]
+    };
+
+    assert!(repo.rebase_interactive("HEAD~3", plan()).is_err());
+    assert!(repo.status().unwrap().rebasing);
+    assert_eq!(scratch_dirs(), 0);
+    repo.rebase_abort().unwrap();
+    assert_eq!(hash("HEAD"), third);
+    assert!(!repo.path().join(".git/rebase-merge").exists());
+
+    // The reworded message is still found after the rebase resumes.
+    assert!(repo.rebase_interactive("HEAD~3", plan()).is_err());
+    repo.cmd(["add", "a.txt"]).unwrap();
+    repo.cmd(["-c", "core.editor=true", "rebase", "--continue"]).unwrap();
+    assert_eq!(repo.cmd_out(["log", "-1", "--format=%s"]).unwrap(), ["reworded"]);
+    assert_eq!(scratch_dirs(), 0);
+    assert!(!repo.path().join(".git/rebase-merge").exists());
+}
+
+#[test]
+fn test_rebase_onto_reports_stopping_commit() {
+    let repo = TempRepo::new();
+    repo.commit_file("shared.txt", "base\n", "base");
+    let main = repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0].clone();
+    let main_parent = format!("{}~1", main);
+    repo.cmd(["branch", "topic"]).unwrap();
+    repo.commit_file("shared.txt", "main\n", "main change");
+    repo.cmd(["checkout", "-q", "topic"]).unwrap();
+    repo.commit_file("shared.txt", "topic\n", "topic change");
+    let topic_head = repo.cmd_out(["rev-parse", "HEAD"]).unwrap()[0].clone();
+
+    assert_eq!(repo.rebase_stopped_at().unwrap(), None);
+    let options = RebaseOptions { autostash: true, ..Default::default() };
+    assert!(repo.rebase_onto(&main, &main_parent, None, &options).is_err());
+    let stopped = repo.rebase_stopped_at().unwrap().expect("rebase should be stopped");
+    assert_eq!(stopped.to_string(), topic_head);
+    repo.rebase_abort().unwrap();
+
+    let options = RebaseOptions {
+        strategy_options: vec!["theirs".to_string()],
+        ..Default::default()
+    };
+    repo.rebase_onto(&main, &main_parent, Some("topic"), &options).unwrap();
+    assert_eq!(std::fs::read_to_string(repo.path().join("shared.txt")).unwrap(), "topic\n");
+    assert_eq!(repo.count_commits(Some(&format!("{}..topic", main))).unwrap(), 1);
+}
+
+#[test]
+fn test_diff_patch_text() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "b\n", "second");
+    std::fs::write(repo.path().join("a.txt"), b"one\n\xff\n").unwrap();
+
+    let patch = repo.diff_patch("HEAD~1", Some("HEAD"), None).unwrap();
+    assert!(patch.starts_with("diff --git a/b.txt b/b.txt\n"));
+    assert!(patch.ends_with("+b\n"));
+
+    let worktree = repo.diff_patch("HEAD", None, Some(&["a.txt".into()])).unwrap();
+    assert!(worktree.contains("+\u{FFFD}\n"));
+    assert!(!worktree.contains("b.txt"));
+}
+
+#[test]
+fn test_structured_diff_modes() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "let total = count;\n", "first");
+    std::fs::write(repo.path().join("a.txt"), "let total = count + 1;\n").unwrap();
+
+    let lines = repo.diff("HEAD", None, &DiffOptions::default()).unwrap();
+    let file = &lines.files[0];
+    assert_eq!((file.added_lines, file.removed_lines), (1, 1));
+    assert!(file.hunks[0].lines.iter().all(|l| l.spans.is_empty()));
+
+    let words = DiffOptions { mode: DiffMode::Words, ..Default::default() };
+    let line = &repo.diff("HEAD", None, &words).unwrap().files[0].hunks[0].lines[0];
+    assert_eq!(line.content, "let total = count + 1;");
+    let added: Vec<&str> = line
+        .spans
+        .iter()
+        .filter(|s| s.span_type == DiffLineType::Added)
+        .map(|s| s.content.as_str())
+        .collect();
+    assert_eq!(added, vec!["count + 1;"]);
+
+    let chars = DiffOptions { mode: DiffMode::Chars, ..Default::default() };
+    let line = &repo.diff("HEAD", None, &chars).unwrap().files[0].hunks[0].lines[0];
+    assert_eq!(line.content, "let total = count + 1;");
+    assert!(line.spans.iter().any(|s| s.span_type == DiffLineType::Added && s.content == " + 1"));
+
+    // Multibyte characters are compared whole, not byte by byte. Windows cannot
+    // pass the byte-oriented regex this needs.
+    if cfg!(unix) {
+        repo.commit_file("b.txt", "café crème\n", "second");
+        std::fs::write(repo.path().join("b.txt"), "cafè crème\n").unwrap();
+        let file = repo.diff("HEAD", None, &chars).unwrap().files.into_iter().find(|f| f.path.ends_with("b.txt")).unwrap();
+        let spans: Vec<(DiffLineType, &str)> =
+            file.hunks[0].lines[0].spans.iter().map(|s| (s.span_type, s.content.as_str())).collect();
+        assert_eq!(
+            spans,
+            vec![
+                (DiffLineType::Context, "caf"),
+                (DiffLineType::Removed, "é"),
+                (DiffLineType::Added, "è"),
+                (DiffLineType::Context, " crème"),
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_file_history_follows_renames() {
+    let repo = TempRepo::new();
+    let contents = "line one\nline two\nline three\nline four\n";
+    repo.commit_file("old.txt", contents, "create");
+    repo.commit_file("other.txt", "x\n", "unrelated");
+    repo.cmd(["mv", "old.txt", "new.txt"]).unwrap();
+    repo.commit_staged("rename").unwrap();
+    repo.commit_file("new.txt", &format!("{}line five\n", contents), "extend");
+
+    let history = repo.file_history("new.txt", &FileHistoryOptions::default()).unwrap();
+    let subjects: Vec<&str> = history.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, vec!["extend", "rename", "create"]);
+
+    let opts = FileHistoryOptions {
+        revision: Some("HEAD~1".to_string()),
+        max_count: Some(1),
+        ..Default::default()
+    };
+    let history = repo.file_history("new.txt", &opts).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].subject, "rename");
+}
+
+#[test]
+fn test_cherry() {
+    let repo = TempRepo::new();
+    repo.commit_file("base.txt", "base\n", "base");
+    repo.cmd(["branch", "upstream"]).unwrap();
+    repo.commit_file("a.txt", "a\n", "add a");
+    repo.commit_file("b.txt", "b\n", "add b");
+    let added_b = repo.get_hash(false).unwrap();
+
+    // `add a` reaches upstream under another id.
+    repo.cmd(["checkout", "-q", "upstream"]).unwrap();
+    repo.commit_file("c.txt", "c\n", "add c");
+    repo.cmd(["cherry-pick", "HEAD@{2}~1"]).unwrap();
+    repo.cmd(["checkout", "-q", "-"]).unwrap();
+
+    let cherry = repo.cherry("upstream", None).unwrap();
+    let marks: Vec<_> = cherry.iter().map(|(mark, _)| *mark).collect();
+    assert_eq!(marks, [CherryMark::Equivalent, CherryMark::Missing]);
+    assert_eq!(cherry[1].1, added_b);
+    assert!(repo.cherry("upstream", Some("upstream")).unwrap().is_empty());
+    assert!(repo.cherry("no-such-branch", None).is_err());
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::str::FromStr;
+use tempfile::TempDir;
+use GitPilot::options::{CommitOptions, InitOptions, PushOptions};
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, GitUrl, HookKind, Remote};
+use GitPilot::Repository;
+
+#[test]
+fn test_install_list_and_remove_hooks() {
+    let repo = TempRepo::new();
+    // Fresh repositories ship only `*.sample` files, which Git never runs.
+    assert!(repo.list_hooks().unwrap().is_empty());
+
+    let path = repo.install_hook(HookKind::PrePush, "#!/bin/sh\nexit 0\n").unwrap();
+    repo.install_hook(HookKind::CommitMsg, "#!/bin/sh\nexit 0\n").unwrap();
+    assert_eq!(path, repo.hooks_dir().unwrap().join("pre-push"));
+    assert_eq!(repo.list_hooks().unwrap(), vec![HookKind::CommitMsg, HookKind::PrePush]);
+
+    assert!(repo.remove_hook(HookKind::PrePush).unwrap());
+    assert!(!repo.remove_hook(HookKind::PrePush).unwrap());
+    assert_eq!(repo.list_hooks().unwrap(), vec![HookKind::CommitMsg]);
+}
+
+#[test]
+fn test_hooks_follow_core_hooks_path() {
+    let repo = TempRepo::new();
+    repo.cmd(["config", "core.hooksPath", "shared-hooks"]).unwrap();
+    repo.install_hook(HookKind::PreCommit, "#!/bin/sh\nexit 0\n").unwrap();
+    assert!(repo.path().join("shared-hooks").join("pre-commit").exists());
+    assert_eq!(repo.list_hooks().unwrap(), vec![HookKind::PreCommit]);
+}
+
+#[test]
+fn test_relative_hooks_path_resolves_against_repository_root() {
+    let repo = TempRepo::new();
+    repo.cmd(["config", "core.hooksPath", "shared-hooks"]).unwrap();
+    fs::create_dir_all(repo.path().join("src/nested")).unwrap();
+    let expected = fs::canonicalize(repo.path()).unwrap().join("shared-hooks");
+    let nested = Repository::new(repo.path().join("src/nested"));
+    assert_eq!(nested.hooks_dir().unwrap(), expected);
+
+    let dir = TempDir::new().unwrap();
+    let bare = Repository::init_with_options(dir.path().join("bare.git"), &InitOptions { bare: true, ..Default::default() })
+        .unwrap();
+    bare.cmd(["config", "core.hooksPath", "shared-hooks"]).unwrap();
+    let expected = fs::canonicalize(dir.path().join("bare.git")).unwrap().join("shared-hooks");
+    assert_eq!(bare.hooks_dir().unwrap(), expected);
+}
+
+#[test]
+fn test_run_hook_reports_rejection() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "a\n", "first");
+    let script = "#!/bin/sh\ngrep -q 'JIRA-' \"$1\" || { echo 'missing ticket' >&2; exit 1; }\n";
+    repo.install_hook(HookKind::CommitMsg, script).unwrap();
+
+    fs::write(repo.path().join("msg.txt"), "no ticket\n").unwrap();
+    let rejected = repo.run_hook(HookKind::CommitMsg, &["msg.txt"]).unwrap();
+    assert!(!rejected.success());
+    assert!(rejected.stderr.contains("missing ticket"));
+
+    fs::write(repo.path().join("msg.txt"), "JIRA-1 fix\n").unwrap();
+    assert!(repo.run_hook(HookKind::CommitMsg, &["msg.txt"]).unwrap().success());
+
+    // Missing hooks are not an error.
+    assert!(repo.run_hook::<&str>(HookKind::PrePush, &[]).unwrap().success());
+}
+
+#[test]
+fn test_commit_and_push_can_bypass_hooks() {
+    let upstream = TempRepo::new();
+    upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "a\n", "first");
+    repo.install_hook(HookKind::PreCommit, "#!/bin/sh\nexit 1\n").unwrap();
+    repo.install_hook(HookKind::PrePush, "#!/bin/sh\nexit 1\n").unwrap();
+
+    fs::write(repo.path().join("a.txt"), "b\n").unwrap();
+    let commit = CommitOptions { all: true, ..Default::default() };
+    assert!(repo.commit_with_options("blocked", &commit).is_err());
+    // Signing is forced on with a key that does not exist.
+    repo.cmd(["config", "commit.gpgsign", "true"]).unwrap();
+    repo.cmd(["config", "user.signingkey", "0000000000000000"]).unwrap();
+    let commit = CommitOptions { all: true, no_verify: true, no_gpg_sign: true, ..Default::default() };
+    repo.commit_with_options("bypassed", &commit).unwrap();
+    assert_eq!(repo.get_commit(None).unwrap().subject, "bypassed");
+
+    let remote = Remote::from_str("origin").unwrap();
+    repo.add_remote(&remote, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let branch = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    let push = PushOptions { set_upstream: true, ..Default::default() };
+    assert!(repo.push_with_options(Some(&remote), Some(&branch), &push).is_err());
+    let push = PushOptions { set_upstream: true, no_verify: true, ..Default::default() };
+    repo.push_with_options(Some(&remote), Some(&branch), &push).unwrap();
+    assert_eq!(upstream.count_commits(Some(branch.as_ref())).unwrap(), 2);
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "json")]
+
+use std::str::FromStr;
+use GitPilot::error::GitError;
+use GitPilot::hosting::{CliProvider, HostingProvider, HostingService, NewPullRequest, PullRequestState};
+use GitPilot::testing::TempRepo;
+use GitPilot::types::BranchName;
+
+#[test]
+fn test_missing_cli() {
+    let repo = TempRepo::new();
+    let provider = CliProvider::with_program(HostingService::GitHub, "gitpilot-no-such-client");
+    assert!(!provider.is_available());
+    let branch = BranchName::from_str("topic").unwrap();
+    let err = provider.pull_requests_for_branch(&repo, &branch).unwrap_err();
+    assert!(matches!(err, GitError::ToolNotFound(program) if program == "gitpilot-no-such-client"));
+    assert!(CliProvider::new(HostingService::Bitbucket).is_none());
+}
+
+/// Drives a stand-in for `gh` that records its arguments and answers like the real client.
+#[cfg(unix)]
+#[test]
+fn test_cli_provider_with_fake_gh() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TempRepo::new();
+    let script = repo.path().join("fake-gh");
+    std::fs::write(
+        &script,
+        concat!(
+            "#!/bin/sh\n",
+            "echo \"$@\" >> args.log\n",
+            "case \"$1 $2\" in\n",
+            "  'pr create') echo 'https://github.com/owner/project/pull/42' ;;\n",
+            "  'pr list') echo '[{\"number\":42,\"title\":\"Release\",\"url\":\"https://github.com/owner/project/pull/42\",",
+            "\"headRefName\":\"topic\",\"baseRefName\":\"main\",\"state\":\"OPEN\"}]' ;;\n",
+            "  *) exit 1 ;;\n",
+            "esac\n",
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let provider = CliProvider::with_program(HostingService::GitHub, script.to_str().unwrap());
+
+    let request = NewPullRequest {
+        title: "Release".to_string(),
+        body: "Notes".to_string(),
+        head: "topic".to_string(),
+        base: "main".to_string(),
+        draft: true,
+    };
+    let created = provider.create_pull_request(&repo, &request).unwrap();
+    assert_eq!(created.number, 42);
+    assert_eq!(created.url, "https://github.com/owner/project/pull/42");
+
+    let listed = provider.pull_requests_for_branch(&repo, &BranchName::from_str("topic").unwrap()).unwrap();
+    assert_eq!(listed, vec![created]);
+    assert_eq!(listed[0].state, PullRequestState::Open);
+
+    let log = std::fs::read_to_string(repo.path().join("args.log")).unwrap();
+    let calls: Vec<&str> = log.lines().collect();
+    assert_eq!(calls[0], "pr create --title Release --body Notes --head topic --base main --draft");
+    assert!(calls[1].starts_with("pr list --state all --head topic --json "));
+}
@@ -0,0 +1,24 @@
+use GitPilot::models::{ConfigScope, Identity};
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_set_and_get_identity() {
+    // Keep the user's own configuration out of the picture; this is the only
+    // test in this binary, so changing the environment is safe.
+    std::env::set_var("GIT_CONFIG_GLOBAL", if cfg!(windows) { "NUL" } else { "/dev/null" });
+    std::env::set_var("GIT_CONFIG_NOSYSTEM", "1");
+
+    let repo = TempRepo::new();
+    repo.cmd(["config", "--unset", "user.name"]).unwrap();
+    repo.cmd(["config", "--unset", "user.email"]).unwrap();
+    assert_eq!(repo.get_identity().unwrap(), None);
+
+    repo.set_identity("Release Bot", "bot@example.invalid", ConfigScope::Local).unwrap();
+    let expected = Identity { name: "Release Bot".to_string(), email: "bot@example.invalid".to_string() };
+    assert_eq!(repo.get_identity().unwrap(), Some(expected));
+
+    repo.commit_file("a.txt", "a\n", "first");
+    let commit = repo.get_commit(None).unwrap();
+    assert_eq!(commit.author_name, "Release Bot");
+    assert_eq!(commit.author_email, "bot@example.invalid");
+}
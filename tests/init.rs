@@ -0,0 +1,72 @@
+use std::fs;
+use std::str::FromStr;
+use tempfile::TempDir;
+use GitPilot::options::InitOptions;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, GitUrl};
+use GitPilot::Repository;
+
+#[test]
+fn test_init_with_options() {
+    let dir = TempDir::new().unwrap();
+
+    let opts = InitOptions { initial_branch: Some(BranchName::from_str("trunk").unwrap()), ..Default::default() };
+    let repo = Repository::init_with_options(dir.path().join("nested/work"), &opts).unwrap();
+    assert_eq!(repo.symbolic_ref("HEAD").unwrap(), "refs/heads/trunk");
+
+    let bare = Repository::init_with_options(dir.path().join("bare.git"), &InitOptions { bare: true, ..Default::default() })
+        .unwrap();
+    assert_eq!(bare.cmd_out(["rev-parse", "--is-bare-repository"]).unwrap(), ["true"]);
+
+    let template = dir.path().join("template");
+    fs::create_dir_all(template.join("info")).unwrap();
+    fs::write(template.join("info/exclude"), "from-template\n").unwrap();
+    let opts = InitOptions {
+        template_dir: Some(template),
+        separate_git_dir: Some("../separate.git".into()),
+        ..Default::default()
+    };
+    let repo = Repository::init_with_options(dir.path().join("split"), &opts).unwrap();
+    let git_dir = dir.path().join("separate.git");
+    assert_eq!(fs::read_to_string(git_dir.join("info/exclude")).unwrap(), "from-template\n");
+    assert!(fs::read_to_string(dir.path().join("split/.git")).unwrap().starts_with("gitdir: "));
+    assert_eq!(repo.cmd_out(["rev-parse", "--is-inside-work-tree"]).unwrap(), ["true"]);
+}
+
+#[test]
+fn test_clone_into_existing() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("cache/keep.txt", "committed\n", "first");
+    upstream.cmd(["branch", "-m", "trunk"]).unwrap();
+    upstream.cmd(["branch", "release"]).unwrap();
+    upstream.commit_file("new.txt", "new\n", "second");
+    let url = GitUrl::from_path(upstream.path()).unwrap();
+
+    let dir = TempDir::new().unwrap();
+    let target = dir.path().join("workspace");
+    fs::create_dir_all(target.join("cache")).unwrap();
+    fs::write(target.join("cache/keep.txt"), "stale\n").unwrap();
+    fs::write(target.join("cache/blob.bin"), "cached\n").unwrap();
+
+    let repo = Repository::clone_into_existing(url.clone(), &target, None).unwrap();
+    assert_eq!(repo.get_hash(false).unwrap(), upstream.get_hash(false).unwrap());
+    assert_eq!(repo.symbolic_ref("HEAD").unwrap(), "refs/heads/trunk");
+    assert_eq!(repo.symbolic_ref("refs/remotes/origin/HEAD").unwrap(), "refs/remotes/origin/trunk");
+    assert_eq!(fs::read_to_string(target.join("cache/keep.txt")).unwrap(), "committed\n");
+    assert_eq!(fs::read_to_string(target.join("cache/blob.bin")).unwrap(), "cached\n");
+    assert_eq!(repo.cmd_out(["rev-parse", "--abbrev-ref", "@{upstream}"]).unwrap(), ["origin/trunk"]);
+
+    let release = BranchName::from_str("release").unwrap();
+    let repo = Repository::clone_into_existing(url.clone(), dir.path().join("fresh"), Some(&release)).unwrap();
+    assert_eq!(repo.symbolic_ref("HEAD").unwrap(), "refs/heads/release");
+    assert!(!dir.path().join("fresh/new.txt").exists());
+
+    // A failed clone leaves no repository behind, but keeps the existing files.
+    let missing = BranchName::from_str("missing").unwrap();
+    let failed = dir.path().join("failed");
+    fs::create_dir_all(&failed).unwrap();
+    fs::write(failed.join("keep.txt"), "mine\n").unwrap();
+    assert!(Repository::clone_into_existing(url, &failed, Some(&missing)).is_err());
+    assert!(!failed.join(".git").exists());
+    assert!(failed.join("keep.txt").exists());
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::Path;
+use GitPilot::error::GitError;
+use GitPilot::testing::TempRepo;
+use GitPilot::Repository;
+
+#[test]
+fn test_lfs_track_and_list() {
+    let repo = TempRepo::new();
+    if !repo.is_lfs_available().unwrap() {
+        // Every operation reports the missing extension with a typed error.
+        assert!(matches!(repo.lfs_install(), Err(GitError::LfsNotInstalled)));
+        assert!(matches!(repo.lfs_track("*.bin"), Err(GitError::LfsNotInstalled)));
+        assert!(matches!(repo.lfs_ls_files(), Err(GitError::LfsNotInstalled)));
+        return;
+    }
+
+    repo.lfs_install().unwrap();
+    repo.lfs_track("*.bin").unwrap();
+    repo.add(vec![".gitattributes"]).unwrap();
+    repo.commit_file("data/blob one.bin", "binary contents\n", "add blob");
+
+    let files = repo.lfs_ls_files().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path.as_os_str(), "data/blob one.bin");
+    assert_eq!(files[0].oid.len(), 64);
+    assert!(files[0].downloaded);
+}
+
+#[test]
+fn test_lfs_pull_paths() {
+    let upstream = TempRepo::new();
+    if !upstream.is_lfs_available().unwrap() {
+        let repo = TempRepo::new();
+        assert!(matches!(repo.lfs_pull(&["a.bin"]), Err(GitError::LfsNotInstalled)));
+        return;
+    }
+    upstream.lfs_install().unwrap();
+    upstream.lfs_track("*.bin").unwrap();
+    upstream.add(vec![".gitattributes"]).unwrap();
+    upstream.commit_file("a,b.bin", "first\n", "add a,b");
+    upstream.commit_file("c.bin", "second\n", "add c");
+    let branch = upstream.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap().remove(0);
+
+    // Check out pointer files only, then download one path.
+    let repo = TempRepo::new();
+    repo.lfs_install().unwrap();
+    let url = format!("file://{}", upstream.path().display());
+    repo.cmd(["remote", "add", "origin", url.as_str()]).unwrap();
+    let skip_smudge = Repository::builder(repo.path()).env("GIT_LFS_SKIP_SMUDGE", "1").build();
+    skip_smudge.cmd(["pull", "-q", "origin", branch.as_str()]).unwrap();
+    assert!(repo.lfs_ls_files().unwrap().iter().all(|file| !file.downloaded));
+
+    repo.lfs_pull(&["a,b.bin"]).unwrap();
+    let downloaded: Vec<_> = repo
+        .lfs_ls_files()
+        .unwrap()
+        .into_iter()
+        .filter(|file| file.downloaded)
+        .map(|file| file.path)
+        .collect();
+    assert_eq!(downloaded, [Path::new("a,b.bin")]);
+    assert_eq!(fs::read_to_string(repo.path().join("a,b.bin")).unwrap(), "first\n");
+}
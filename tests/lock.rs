@@ -0,0 +1,78 @@
+use std::fs;
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use GitPilot::testing::TempRepo;
+use GitPilot::GitError;
+
+#[test]
+fn test_lock_is_exclusive_until_released() {
+    let repo = TempRepo::new();
+    let guard = repo.lock().unwrap();
+    assert_eq!(guard.path(), repo.path().join(".git").join("GitPilot.lock"));
+    assert!(guard.path().exists());
+
+    match repo.lock() {
+        Err(GitError::RepositoryLocked { pid, .. }) => assert_eq!(pid, Some(std::process::id())),
+        other => panic!("expected RepositoryLocked, got {:?}", other),
+    }
+    assert!(matches!(
+        repo.lock_wait(Duration::from_millis(100)),
+        Err(GitError::RepositoryLocked { .. })
+    ));
+
+    let path = guard.path().to_path_buf();
+    drop(guard);
+    assert!(!path.exists());
+    repo.lock().unwrap().release().unwrap();
+    assert!(!path.exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_stale_lock_is_taken_over() {
+    let repo = TempRepo::new();
+    let path = repo.path().join(".git").join("GitPilot.lock");
+    // A pid above the kernel's maximum can never belong to a running process.
+    fs::write(&path, "4294967295\n0\n").unwrap();
+
+    let guard = repo.lock().unwrap();
+    assert!(fs::read_to_string(&path).unwrap().starts_with(&format!("{}\n", std::process::id())));
+    drop(guard);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_old_lock_of_live_process_is_kept() {
+    let repo = TempRepo::new();
+    let path = repo.path().join(".git").join("GitPilot.lock");
+    fs::write(&path, format!("{}\n0\n", std::process::id())).unwrap();
+    let file = fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(24 * 60 * 60)).unwrap();
+
+    assert!(matches!(repo.lock(), Err(GitError::RepositoryLocked { .. })));
+    assert!(path.exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_stale_lock_is_taken_over_once() {
+    let repo = TempRepo::new();
+    let path = repo.path().join(".git").join("GitPilot.lock");
+    fs::write(&path, "4294967295\n0\n").unwrap();
+
+    let barrier = Barrier::new(8);
+    let guards: Vec<_> = thread::scope(|scope| {
+        let attempts: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| {
+                    barrier.wait();
+                    repo.lock()
+                })
+            })
+            .collect();
+        attempts.into_iter().map(|attempt| attempt.join().unwrap()).collect()
+    });
+    assert_eq!(guards.iter().filter(|guard| guard.is_ok()).count(), 1);
+    assert!(guards.iter().all(|guard| matches!(guard, Ok(_) | Err(GitError::RepositoryLocked { .. }))));
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::time::Duration;
+use GitPilot::models::{FsckObject, ObjectType};
+use GitPilot::options::{FsckOptions, MaintenanceTask, RepackOptions};
+use GitPilot::testing::TempRepo;
+use GitPilot::Repository;
+
+#[test]
+fn test_repack_prune_and_count_objects() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "two\n", "second");
+
+    let loose = repo.count_objects().unwrap();
+    // Two commits, two trees and two blobs.
+    assert_eq!(loose.loose_objects, 6);
+    assert_eq!(loose.packs, 0);
+    assert!(loose.loose_size > 0);
+
+    repo.repack(&RepackOptions { all: true, delete_redundant: true, ..Default::default() }).unwrap();
+    let packed = repo.count_objects().unwrap();
+    assert_eq!(packed.packs, 1);
+    assert_eq!(packed.packed_objects, 6);
+    assert_eq!(packed.loose_objects, 0);
+    assert!(packed.pack_size > 0);
+
+    // An unreachable object survives a prune with a grace period but not one without.
+    fs::write(repo.path().join("orphan.txt"), "orphan\n").unwrap();
+    repo.cmd(["hash-object", "-w", "orphan.txt"]).unwrap();
+    assert_eq!(repo.count_objects().unwrap().loose_objects, 1);
+    repo.prune(Some(Duration::from_secs(3600))).unwrap();
+    assert_eq!(repo.count_objects().unwrap().loose_objects, 1);
+    repo.prune(None).unwrap();
+    assert_eq!(repo.count_objects().unwrap().loose_objects, 0);
+
+    repo.gc(false).unwrap();
+    assert_eq!(repo.count_objects().unwrap().packed_objects, 6);
+}
+
+#[test]
+fn test_fsck_reports_dangling_missing_and_corrupt_objects() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "two\n", "second");
+    assert!(repo.fsck(&FsckOptions::default()).unwrap().is_healthy());
+
+    fs::write(repo.path().join("orphan.txt"), "orphan\n").unwrap();
+    let orphan = repo.cmd_out(["hash-object", "-w", "orphan.txt"]).unwrap()[0].clone();
+    let report = repo.fsck(&FsckOptions::default()).unwrap();
+    assert!(report.is_healthy());
+    assert_eq!(report.exit_code, 0);
+    assert_eq!(report.dangling, vec![FsckObject { object_type: ObjectType::Blob, id: orphan }]);
+    assert!(repo.fsck(&FsckOptions { no_dangling: true, ..Default::default() }).unwrap().dangling.is_empty());
+
+    let object_path = |id: &str| repo.path().join(".git/objects").join(&id[..2]).join(&id[2..]);
+    let blob = repo.cmd_out(["rev-parse", "HEAD:b.txt"]).unwrap()[0].clone();
+    fs::remove_file(object_path(&blob)).unwrap();
+    let tree = repo.cmd_out(["rev-parse", "HEAD~1^{tree}"]).unwrap()[0].clone();
+    let mut permissions = fs::metadata(object_path(&tree)).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(object_path(&tree), permissions).unwrap();
+    fs::write(object_path(&tree), "garbage").unwrap();
+
+    let report = repo.fsck(&FsckOptions::default()).unwrap();
+    assert!(!report.is_healthy());
+    assert!(report.missing.contains(&FsckObject { object_type: ObjectType::Blob, id: blob }));
+    assert_eq!(report.corrupt, vec![tree]);
+    assert!(!report.errors.is_empty());
+    assert_ne!(report.exit_code, 0);
+}
+
+#[test]
+fn test_repo_size() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let loose = repo.repo_size().unwrap();
+    assert_eq!(loose.pack_dir_size, 0);
+    assert_eq!(loose.lfs_objects, 0);
+    assert_eq!(loose.total_size(), loose.objects.loose_size);
+
+    repo.repack(&RepackOptions { all: true, delete_redundant: true, ..Default::default() }).unwrap();
+    // LFS stores objects under lfs/objects/<aa>/<bb>/<oid>.
+    let lfs_dir = repo.path().join(".git/lfs/objects/4d/7a");
+    fs::create_dir_all(&lfs_dir).unwrap();
+    fs::write(lfs_dir.join("4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"), vec![0u8; 2048]).unwrap();
+
+    let packed = repo.repo_size().unwrap();
+    assert_eq!(packed.objects.loose_objects, 0);
+    // The pack directory also holds the pack's index.
+    assert!(packed.pack_dir_size > 0);
+    assert_eq!((packed.lfs_objects, packed.lfs_size), (1, 2048));
+    assert_eq!(packed.total_size(), packed.pack_dir_size + 2048);
+}
+
+#[test]
+fn test_find_large_blobs() {
+    let repo = TempRepo::new();
+    repo.commit_file("small.txt", "tiny\n", "small");
+    repo.commit_file("assets/big file.bin", &"x".repeat(50_000), "big");
+    repo.commit_file("medium.bin", &"y".repeat(20_000), "medium");
+    // Deleted files still count: they remain in history.
+    repo.remove(vec!["assets/big file.bin"], false).unwrap();
+    repo.commit_staged("remove big").unwrap();
+
+    let blobs = repo.find_large_blobs(10_000, 10).unwrap();
+    let found: Vec<(&str, u64)> = blobs.iter().map(|b| (b.path.to_str().unwrap(), b.size)).collect();
+    assert_eq!(found, vec![("assets/big file.bin", 50_000), ("medium.bin", 20_000)]);
+    assert_eq!(blobs[0].id.len(), 40);
+
+    assert_eq!(repo.find_large_blobs(0, 1).unwrap().len(), 1);
+}
+
+#[test]
+fn test_untracked_cache_and_index_refresh() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let config = |key: &str| repo.cmd_out(["config", "--get", key]).unwrap();
+
+    repo.enable_untracked_cache().unwrap();
+    assert_eq!(config("core.untrackedCache"), ["true"]);
+    fs::write(repo.path().join("new.txt"), "new\n").unwrap();
+    assert_eq!(repo.status().unwrap().files.len(), 1);
+    repo.disable_untracked_cache().unwrap();
+    assert_eq!(config("core.untrackedCache"), ["false"]);
+
+    // Touching a file without changing it leaves its index entry stale.
+    let file = fs::File::options().write(true).open(repo.path().join("a.txt")).unwrap();
+    file.set_modified(std::time::SystemTime::now() + Duration::from_secs(60)).unwrap();
+    assert_eq!(repo.cmd_out(["diff-files", "--name-only"]).unwrap(), ["a.txt"]);
+    repo.update_index_refresh().unwrap();
+    assert!(repo.cmd_out(["diff-files", "--name-only"]).unwrap().is_empty());
+}
+
+#[test]
+fn test_fsmonitor_toggle() {
+    let repo = TempRepo::new();
+    let result = repo.enable_fsmonitor();
+    let configured = repo.cmd_out(["config", "--get", "core.fsmonitor"]);
+    // Git has no built-in monitor on Linux.
+    if cfg!(target_os = "linux") {
+        assert!(matches!(result, Err(GitPilot::GitError::GitError { .. })));
+        assert!(configured.is_err());
+    } else if result.is_ok() {
+        assert_eq!(configured.unwrap(), ["true"]);
+    }
+    repo.disable_fsmonitor().unwrap();
+    assert!(repo.cmd_out(["config", "--get", "core.fsmonitor"]).is_err());
+}
+
+#[test]
+fn test_commit_graph_and_maintenance_run() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "two\n", "second");
+    let info = repo.path().join(".git/objects/info");
+
+    repo.write_commit_graph(true).unwrap();
+    assert!(info.join("commit-graph").exists());
+    repo.cmd(["commit-graph", "verify"]).unwrap();
+
+    repo.maintenance_run(&[MaintenanceTask::PackRefs, MaintenanceTask::LooseObjects]).unwrap();
+    assert!(repo.path().join(".git/packed-refs").exists());
+    repo.maintenance_run(&[]).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_maintenance_start_and_stop() {
+    use std::os::unix::fs::PermissionsExt;
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    // Git's test hook replaces `crontab` with a script, and a scratch global
+    // configuration keeps the registration away from the user's.
+    let dir = repo.path().join(".git");
+    let crontab = dir.join("crontab");
+    let fake_crontab = dir.join("fake-crontab");
+    fs::write(
+        &fake_crontab,
+        format!(
+            "#!/bin/sh\nif [ \"$1\" = -l ]; then cat {0} 2>/dev/null; else cp \"$1\" {0}; fi\n",
+            crontab.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&fake_crontab, fs::Permissions::from_mode(0o755)).unwrap();
+    let global = dir.join("global-config");
+    let scheduled = Repository::builder(repo.path())
+        .env("GIT_TEST_MAINT_SCHEDULER", format!("crontab:{}", fake_crontab.display()))
+        .env("GIT_CONFIG_GLOBAL", &global)
+        .build();
+
+    scheduled.maintenance_start().unwrap();
+    assert!(fs::read_to_string(&crontab).unwrap().contains("maintenance run"));
+    assert!(fs::read_to_string(&global).unwrap().contains("repo = "));
+    assert_eq!(repo.cmd_out(["config", "maintenance.strategy"]).unwrap(), ["incremental"]);
+
+    scheduled.maintenance_stop().unwrap();
+    assert!(!fs::read_to_string(&crontab).unwrap().contains("maintenance run"));
+}
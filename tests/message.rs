@@ -0,0 +1,74 @@
+use GitPilot::error::GitError;
+use GitPilot::message::CommitMessage;
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_commit_message() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+
+    repo.write_file("a.txt", "two\n");
+    repo.add(vec!["a.txt"]).unwrap();
+    let message = CommitMessage::new("Update a").conventional(true);
+    assert!(matches!(repo.commit_message(&message), Err(GitError::InvalidCommitMessage(_))));
+    assert_eq!(repo.count_commits(None).unwrap(), 1);
+
+    // Lines starting with `#` are kept as written.
+    let message = CommitMessage::new("docs(a): update a")
+        .body("# Heading\n\nMore text.")
+        .trailer("Refs", "#12")
+        .conventional(true)
+        .max_subject_length(50);
+    repo.commit_message(&message).unwrap();
+    let commit = repo.get_commit(None).unwrap();
+    assert_eq!(commit.subject, "docs(a): update a");
+    assert_eq!(commit.message.trim_end(), message.to_string());
+}
+
+#[test]
+fn test_trailers() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+
+    let message = repo
+        .interpret_trailers("Fix a\n\nDetails.\n", &[("Signed-off-by", "A <a@example.com>"), ("Refs", "#1")])
+        .unwrap();
+    assert_eq!(message, "Fix a\n\nDetails.\n\nSigned-off-by: A <a@example.com>\nRefs: #1\n");
+    // Appended to the existing trailer block.
+    let message = repo.interpret_trailers(&message, &[("Co-authored-by", "B <b@example.com>")]).unwrap();
+    assert!(message.ends_with("Refs: #1\nCo-authored-by: B <b@example.com>\n"));
+
+    repo.cmd(["config", "user.name", "Committer"]).unwrap();
+    repo.cmd(["config", "user.email", "committer@example.com"]).unwrap();
+    repo.write_file("a.txt", "two\n");
+    let message = CommitMessage::new("Update a").co_authored_by("B <b@example.com>").sign_off(true);
+    repo.add(vec!["a.txt"]).unwrap();
+    repo.commit_message(&message).unwrap();
+    assert_eq!(
+        repo.get_commit(None).unwrap().body.trim_end(),
+        "Co-authored-by: B <b@example.com>\nSigned-off-by: Committer <committer@example.com>"
+    );
+}
+
+#[test]
+fn test_commit_template_and_message_file() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    assert_eq!(repo.commit_template().unwrap(), None);
+
+    repo.write_file(".gitmessage", "# Shared template\n");
+    assert_eq!(repo.commit_template().unwrap().as_deref(), Some("# Shared template\n"));
+    // The configured template wins, with paths relative to the repository root.
+    repo.write_file("templates/commit.txt", "Subject\n\n# Why?\n");
+    repo.cmd(["config", "commit.template", "templates/commit.txt"]).unwrap();
+    assert_eq!(repo.commit_template().unwrap().as_deref(), Some("Subject\n\n# Why?\n"));
+    repo.cmd(["config", "commit.template", "missing.txt"]).unwrap();
+    assert!(repo.commit_template().is_err());
+
+    repo.write_file("a.txt", "two\n");
+    repo.add(vec!["a.txt"]).unwrap();
+    repo.write_file("MSG", "Update a\n\n# Why?\nBecause.\n");
+    repo.commit_from_file("MSG").unwrap();
+    let commit = repo.get_commit(None).unwrap();
+    assert_eq!((commit.subject.as_str(), commit.body.trim_end()), ("Update a", "Because."));
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use GitPilot::models::RangeDiffStatus;
+use GitPilot::options::ApplyOptions;
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_format_patch_and_am_roundtrip() {
+    let src = TempRepo::new();
+    src.commit_file("a.txt", "one\n", "base");
+    src.commit_file("a.txt", "two\n", "change a");
+    src.commit_file("b.txt", "b\n", "add b");
+
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let patches = src.format_patch("HEAD~2..HEAD", out_dir.path()).unwrap();
+    assert_eq!(patches.len(), 2);
+    assert!(patches.iter().all(|p| p.is_file()));
+
+    let dst = TempRepo::new();
+    dst.commit_file("a.txt", "one\n", "base");
+    dst.am(&patches).unwrap();
+    assert_eq!(dst.get_commit(None).unwrap().subject, "add b");
+    assert_eq!(fs::read_to_string(dst.path().join("a.txt")).unwrap(), "two\n");
+}
+
+#[test]
+fn test_am_stops_on_conflict() {
+    let src = TempRepo::new();
+    src.commit_file("a.txt", "one\n", "base");
+    src.commit_file("a.txt", "two\n", "change a");
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let patches = src.format_patch("-1", out_dir.path()).unwrap();
+
+    let dst = TempRepo::new();
+    dst.commit_file("a.txt", "different\n", "base");
+    assert!(dst.am(&patches).is_err());
+    dst.am_skip().unwrap();
+    assert_eq!(dst.get_commit(None).unwrap().subject, "base");
+}
+
+#[test]
+fn test_apply_patch_check_and_apply() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "base");
+    fs::write(repo.path().join("a.txt"), "two\n").unwrap();
+    let patch = repo.path().join("change.patch");
+    fs::write(&patch, repo.cmd_out(["diff"]).unwrap().join("\n") + "\n").unwrap();
+    repo.cmd(["checkout", "--", "a.txt"]).unwrap();
+
+    let check = ApplyOptions { check: true, ..Default::default() };
+    repo.apply_patch(&patch, &check).unwrap();
+    assert_eq!(fs::read_to_string(repo.path().join("a.txt")).unwrap(), "one\n");
+
+    repo.apply_patch(&patch, &ApplyOptions::default()).unwrap();
+    assert_eq!(fs::read_to_string(repo.path().join("a.txt")).unwrap(), "two\n");
+    assert!(repo.apply_patch(&patch, &check).is_err());
+}
+
+#[test]
+fn test_range_diff() {
+    let repo = TempRepo::new();
+    repo.commit_file("base.txt", "base\n", "base");
+    repo.cmd(["branch", "base"]).unwrap();
+    let lines: String = (1..=30).map(|n| format!("{}\n", n)).collect();
+    repo.commit_file("b.txt", "b\n", "add b");
+    repo.commit_file("f.txt", &lines.replace("15\n", "fifteen\n"), "add f");
+    repo.commit_file("d.txt", "d\n", "add d");
+    repo.cmd(["branch", "old"]).unwrap();
+
+    // Rewrite the series: keep the first commit, change the second, drop the third
+    // and add a new one.
+    repo.cmd(["reset", "-q", "--hard", "HEAD~2"]).unwrap();
+    repo.commit_file("f.txt", &lines, "add f");
+    repo.commit_file("e.txt", "e\n", "add e");
+
+    let entries = repo.range_diff("base..old", "base..HEAD").unwrap();
+    let statuses: Vec<_> = entries.iter().map(|entry| (entry.status, entry.subject.as_str())).collect();
+    assert_eq!(
+        statuses,
+        [
+            (RangeDiffStatus::Unchanged, "add b"),
+            (RangeDiffStatus::Changed, "add f"),
+            (RangeDiffStatus::Removed, "add d"),
+            (RangeDiffStatus::Added, "add e"),
+        ]
+    );
+    assert_eq!(entries[1].old_commit.as_ref().unwrap().to_string(), repo.cmd_out(["rev-parse", "old~1"]).unwrap()[0]);
+    assert!(entries[1].patch.contains("-+fifteen\n"));
+}
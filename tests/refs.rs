@@ -0,0 +1,69 @@
+use std::str::FromStr;
+use GitPilot::error::GitError;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, CommitHash};
+
+#[test]
+fn test_update_ref_compare_and_swap() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let first = repo.get_hash(false).unwrap();
+    repo.commit_file("a.txt", "two\n", "second");
+    let second = repo.get_hash(false).unwrap();
+    let zero = CommitHash::from_str(&"0".repeat(40)).unwrap();
+
+    repo.update_ref("refs/heads/topic", &first, Some(&zero)).unwrap();
+    let err = repo.update_ref("refs/heads/topic", &second, Some(&zero)).unwrap_err();
+    assert!(matches!(err, GitError::StaleRef { .. }));
+
+    let err = repo.update_ref("refs/heads/topic", &second, Some(&second)).unwrap_err();
+    assert!(matches!(err, GitError::StaleRef { reference } if reference == "refs/heads/topic"));
+    repo.update_ref("refs/heads/topic", &second, Some(&first)).unwrap();
+    assert_eq!(repo.cmd_out(["rev-parse", "topic"]).unwrap(), vec![second.to_string()]);
+
+    repo.update_ref("refs/heads/topic", &first, None).unwrap();
+    assert_eq!(repo.cmd_out(["rev-parse", "topic"]).unwrap(), vec![first.to_string()]);
+}
+
+#[test]
+fn test_symbolic_ref_and_set_head() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let initial = repo.symbolic_ref("HEAD").unwrap();
+    assert!(initial.starts_with("refs/heads/"));
+
+    // HEAD may point to a branch that does not exist yet.
+    let orphan = BranchName::from_str("orphan").unwrap();
+    repo.set_head(&orphan).unwrap();
+    assert_eq!(repo.symbolic_ref("HEAD").unwrap(), "refs/heads/orphan");
+    repo.commit_staged("root").unwrap();
+    assert!(repo.get_commit(None).unwrap().parents.is_empty());
+
+    assert!(repo.symbolic_ref(&initial).is_err());
+    repo.cmd(["checkout", "-q", "--detach"]).unwrap();
+    assert!(repo.symbolic_ref("HEAD").is_err());
+}
+
+#[test]
+fn test_update_branch() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let first = repo.get_hash(false).unwrap();
+    repo.commit_file("a.txt", "two\n", "second");
+    let second = repo.get_hash(false).unwrap();
+    let zero = CommitHash::from_str(&"0".repeat(40)).unwrap();
+    let topic = BranchName::from_str("topic").unwrap();
+
+    repo.update_branch(&topic, &first, Some(&zero)).unwrap();
+    assert_eq!(repo.cmd_out(["rev-parse", "topic"]).unwrap(), vec![first.to_string()]);
+    // Another writer moved the branch in between.
+    repo.cmd(["branch", "-f", "topic", "HEAD"]).unwrap();
+    let err = repo.update_branch(&topic, &first, Some(&first)).unwrap_err();
+    assert!(matches!(err, GitError::StaleRef { reference } if reference == "refs/heads/topic"));
+    repo.update_branch(&topic, &first, Some(&second)).unwrap();
+    assert_eq!(repo.cmd_out(["rev-parse", "topic"]).unwrap(), vec![first.to_string()]);
+
+    let tree = CommitHash::from_str(&repo.cmd_out(["rev-parse", "HEAD^{tree}"]).unwrap()[0]).unwrap();
+    assert!(repo.update_branch(&topic, &tree, None).is_err());
+    assert_eq!(repo.cmd_out(["rev-parse", "topic"]).unwrap(), vec![first.to_string()]);
+}
@@ -0,0 +1,454 @@
+use std::str::FromStr;
+use tempfile::TempDir;
+use GitPilot::error::GitError;
+use GitPilot::hosting::HostingService;
+use GitPilot::models::{FetchRefStatus, PushStatus};
+use GitPilot::options::{CloneFilter, CloneOptions, FetchOptions, PushOptions};
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, GitUrl, Pathspec, Remote};
+use GitPilot::Repository;
+
+#[test]
+fn test_clone_from_local_path_and_file_url() {
+    let origin = TempRepo::new();
+    origin.commit_file("README.md", "hello\n", "initial");
+    let head = origin.get_hash(false).unwrap();
+
+    let target = TempDir::new().unwrap();
+    let url = GitUrl::from_path(origin.path()).unwrap();
+    let clone = Repository::clone(url, target.path().join("by-path")).unwrap();
+    assert_eq!(clone.get_hash(false).unwrap(), head);
+
+    let clone = Repository::clone(file_url(&origin), target.path().join("by-url")).unwrap();
+    assert_eq!(clone.get_hash(false).unwrap(), head);
+}
+
+/// Windows drive paths need an extra slash: file:///C:/...
+fn file_url(repo: &TempRepo) -> GitUrl {
+    let path = repo.path().to_str().unwrap().replace('\\', "/");
+    let slash = if path.starts_with('/') { "" } else { "/" };
+    GitUrl::parse_lenient(&format!("file://{}{}", slash, path)).unwrap()
+}
+
+#[test]
+fn test_partial_clone_and_fetch_missing_objects() {
+    let origin = TempRepo::new();
+    origin.commit_file("a.txt", "one\n", "first");
+    origin.commit_file("docs/b.txt", "two\n", "second");
+    origin.commit_file("docs/c.txt", "three\n", "third");
+    origin.cmd(["config", "uploadpack.allowFilter", "true"]).unwrap();
+    let target = TempDir::new().unwrap();
+    let missing = |repo: &Repository| {
+        repo.cmd_out(["rev-list", "--objects", "--missing=print", "--no-walk", "HEAD"])
+            .unwrap()
+            .into_iter()
+            .filter(|line| line.starts_with('?'))
+            .count()
+    };
+
+    let opts = CloneOptions { filter: Some(CloneFilter::BlobNone), no_checkout: true, ..Default::default() };
+    let clone = Repository::clone_with(&file_url(&origin), target.path().join("blobless"), &opts).unwrap();
+    assert_eq!(missing(&clone), 3);
+    assert_eq!(clone.fetch_missing_objects(&[Pathspec::new("docs")]).unwrap(), 2);
+    assert_eq!(missing(&clone), 1);
+    assert_eq!(clone.cmd_out(["config", "remote.origin.partialclonefilter"]).unwrap(), ["blob:none"]);
+    assert_eq!(clone.fetch_missing_objects(&[]).unwrap(), 1);
+    assert_eq!(clone.fetch_missing_objects(&[]).unwrap(), 0);
+
+    let opts = CloneOptions { filter: Some(CloneFilter::TreeNone), no_checkout: true, ..Default::default() };
+    let treeless = Repository::clone_with(&file_url(&origin), target.path().join("treeless"), &opts).unwrap();
+    assert_eq!(treeless.fetch_missing_objects(&[Pathspec::new("a.txt")]).unwrap(), 1);
+
+    let branch = BranchName::from_str(&origin.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    let opts = CloneOptions { branch: Some(branch), depth: Some(1), ..Default::default() };
+    let shallow = Repository::clone_with(&file_url(&origin), target.path().join("shallow"), &opts).unwrap();
+    assert_eq!(shallow.cmd_out(["rev-list", "--count", "HEAD"]).unwrap(), ["1"]);
+    assert_eq!(shallow.fetch_missing_objects(&[]).unwrap(), 0);
+    assert_eq!(CloneFilter::BlobLimit(1024).to_string(), "blob:limit=1024");
+}
+
+#[test]
+fn test_add_and_fetch_local_remote() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "a\n", "upstream");
+    let repo = TempRepo::new();
+
+    let name = Remote::from_str("fixture").unwrap();
+    let url = GitUrl::from_path(upstream.path()).unwrap();
+    repo.add_remote(&name, &url).unwrap();
+    repo.fetch_remote(&name).unwrap();
+
+    assert_eq!(repo.list_remotes().unwrap(), vec![name.clone()]);
+    assert_eq!(repo.show_remote_uri(&name).unwrap(), url);
+}
+
+#[test]
+fn test_force_push_with_lease() {
+    let upstream = TempRepo::new();
+    upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let branch = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    repo.push_to_upstream(&origin, &branch).unwrap();
+    let pushed = repo.get_hash(false).unwrap();
+
+    // Someone else moves the remote branch.
+    upstream.cmd(["commit", "-q", "--allow-empty", "-m", "theirs"]).unwrap();
+    let theirs = upstream.get_hash(false).unwrap();
+
+    repo.cmd(["commit", "-q", "--amend", "-m", "rewritten"]).unwrap();
+    let err = repo.force_push_with_lease(&origin, &branch, Some(&pushed)).unwrap_err();
+    assert!(matches!(err, GitError::StaleRemoteRef { .. }));
+    // The remote-tracking branch is equally stale.
+    let err = repo.force_push_with_lease(&origin, &branch, None).unwrap_err();
+    assert!(matches!(err, GitError::StaleRemoteRef { .. }));
+    assert_eq!(upstream.get_hash(false).unwrap(), theirs);
+
+    repo.force_push_with_lease(&origin, &branch, Some(&theirs)).unwrap();
+    assert_eq!(upstream.get_hash(false).unwrap(), repo.get_hash(false).unwrap());
+}
+
+#[test]
+fn test_fetch_options() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    upstream.commit_file("a.txt", "two\n", "second");
+    upstream.cmd(["branch", "feature"]).unwrap();
+    upstream.cmd(["tag", "-a", "v1", "-m", "v1", "HEAD~1"]).unwrap();
+    let repo = TempRepo::new();
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let remote_refs = || repo.cmd_out(["for-each-ref", "--format=%(refname)", "refs/remotes", "refs/tags"]).unwrap();
+
+    // A single explicit refspec with a shallow depth.
+    let branch = upstream.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0].clone();
+    let opts = FetchOptions {
+        remote: Some(origin.clone()),
+        depth: Some(1),
+        refspecs: vec!["refs/heads/feature:refs/remotes/origin/feature".to_string()],
+        ..Default::default()
+    };
+    repo.fetch(&opts).unwrap();
+    assert_eq!(remote_refs(), vec!["refs/remotes/origin/feature"]);
+    assert_eq!(repo.count_commits(Some("origin/feature")).unwrap(), 1);
+
+    // Everything from all remotes, with tags; then pruning removes the deleted branch.
+    repo.fetch(&FetchOptions { all: true, tags: true, ..Default::default() }).unwrap();
+    assert!(remote_refs().contains(&format!("refs/remotes/origin/{}", branch)));
+    assert!(remote_refs().contains(&"refs/tags/v1".to_string()));
+
+    upstream.cmd(["branch", "-D", "feature"]).unwrap();
+    upstream.cmd(["tag", "-d", "v1"]).unwrap();
+    let opts = FetchOptions { remote: Some(origin), prune: true, prune_tags: true, ..Default::default() };
+    repo.fetch(&opts).unwrap();
+    assert_eq!(remote_refs(), vec![format!("refs/remotes/origin/{}", branch)]);
+}
+
+#[test]
+fn test_fetch_result() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    upstream.cmd(["branch", "stable"]).unwrap();
+    upstream.cmd(["branch", "gone"]).unwrap();
+    let repo = TempRepo::new();
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let opts = FetchOptions { remote: Some(origin), prune: true, ..Default::default() };
+
+    let first = repo.fetch(&opts).unwrap();
+    assert_eq!(first.new_branches().count(), 3);
+    assert!(repo.fetch(&opts).unwrap().is_empty());
+
+    // Move `stable` forward, rewrite the default branch and delete `gone`.
+    let before = upstream.get_hash(false).unwrap();
+    let branch = upstream.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap().remove(0);
+    upstream.cmd(["checkout", "-q", "stable"]).unwrap();
+    upstream.commit_file("a.txt", "two\n", "second");
+    upstream.cmd(["checkout", "-q", branch.as_str()]).unwrap();
+    upstream.cmd(["commit", "-q", "--amend", "-m", "rewritten"]).unwrap();
+    upstream.cmd(["branch", "-D", "gone"]).unwrap();
+
+    let result = repo.fetch(&opts).unwrap();
+    let stable: Vec<_> = result.updated().filter(|r| r.status == FetchRefStatus::FastForward).collect();
+    assert_eq!(stable.len(), 1);
+    assert_eq!(stable[0].local_ref, "origin/stable");
+    assert_eq!(stable[0].old.as_ref(), Some(&before));
+    let forced: Vec<_> = result.forced().collect();
+    assert_eq!(forced.len(), 1);
+    assert_eq!(forced[0].remote_ref.as_deref(), Some(branch.as_str()));
+    assert_eq!(forced[0].new.as_ref(), Some(&upstream.get_hash(false).unwrap()));
+    let pruned: Vec<_> = result.pruned().map(|r| r.local_ref.as_str()).collect();
+    assert_eq!(pruned, ["origin/gone"]);
+}
+
+#[test]
+fn test_fetch_rejected() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    upstream.cmd(["tag", "v1"]).unwrap();
+    let repo = TempRepo::new();
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let opts = FetchOptions { remote: Some(origin), tags: true, ..Default::default() };
+    repo.fetch(&opts).unwrap();
+
+    // Move the branch forward and the tag with it.
+    let branch = upstream.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap().remove(0);
+    upstream.commit_file("a.txt", "two\n", "second");
+    upstream.cmd(["tag", "-f", "v1"]).unwrap();
+
+    let Err(GitError::FetchRejected(result)) = repo.fetch(&opts) else {
+        panic!("expected the tag update to be rejected");
+    };
+    let rejected: Vec<_> = result.rejected().collect();
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].local_ref, "v1");
+    assert_eq!(rejected[0].status, FetchRefStatus::Rejected("would clobber existing tag".to_string()));
+    let updated: Vec<_> = result.updated().map(|r| r.local_ref.clone()).collect();
+    assert_eq!(updated, [format!("origin/{}", branch)]);
+}
+
+#[test]
+fn test_push_result() {
+    let upstream = TempRepo::new();
+    upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.cmd(["branch", "stale"]).unwrap();
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let branch = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    let stale = BranchName::from_str("stale").unwrap();
+    let opts = PushOptions::default();
+
+    let result = repo.push_with_options(Some(&origin), Some(&branch), &opts).unwrap();
+    assert_eq!(result.refs.len(), 1);
+    assert_eq!(result.refs[0].status, PushStatus::New);
+    assert_eq!(result.refs[0].remote_ref, format!("refs/heads/{}", branch));
+    repo.push_with_options(Some(&origin), Some(&stale), &opts).unwrap();
+
+    // A fast-forward reports both hashes, and pushing again changes nothing.
+    let before = repo.get_hash(false).unwrap();
+    repo.commit_file("a.txt", "two\n", "second");
+    let result = repo.push_with_options(Some(&origin), Some(&branch), &opts).unwrap();
+    assert_eq!(result.refs[0].status, PushStatus::FastForward);
+    assert_eq!(result.refs[0].old.as_ref(), Some(&before));
+    assert_eq!(result.refs[0].new.as_ref(), Some(&repo.get_hash(false).unwrap()));
+    assert!(repo.push_with_options(Some(&origin), Some(&branch), &opts).unwrap().is_up_to_date());
+
+    // Rewriting a pushed branch is rejected, while the other branch still goes through.
+    repo.cmd(["commit", "-q", "--amend", "-m", "rewritten"]).unwrap();
+    repo.cmd(["checkout", "-q", "stale"]).unwrap();
+    repo.commit_file("b.txt", "b\n", "on stale");
+    repo.cmd(["checkout", "-q", branch.as_ref()]).unwrap();
+    let pushes = repo.push_to_all(&[branch.clone(), stale], &[origin], &opts);
+    match &pushes[0].1 {
+        Err(GitError::PushRejected(result)) => {
+            let rejected: Vec<_> = result.rejected().map(|r| r.remote_ref.as_str()).collect();
+            assert_eq!(rejected, [format!("refs/heads/{}", branch)]);
+            assert!(result.refs.iter().any(|r| r.remote_ref == "refs/heads/stale" && r.status == PushStatus::FastForward));
+        }
+        other => panic!("expected PushRejected, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_push_and_fetch_dry_run() {
+    let upstream = TempRepo::new();
+    upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let branch = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+
+    let opts = PushOptions { dry_run: true, ..Default::default() };
+    let result = repo.push_with_options(Some(&origin), Some(&branch), &opts).unwrap();
+    assert_eq!(result.refs[0].status, PushStatus::New);
+    assert!(upstream.cmd_out(["for-each-ref"]).unwrap().is_empty());
+
+    repo.push_with_options(Some(&origin), Some(&branch), &PushOptions::default()).unwrap();
+    upstream.cmd(["branch", "other", branch.as_ref()]).unwrap();
+    let opts = FetchOptions { remote: Some(origin.clone()), dry_run: true, ..Default::default() };
+    let result = repo.fetch(&opts).unwrap();
+    let new: Vec<_> = result.new_branches().map(|r| r.local_ref.as_str()).collect();
+    assert_eq!(new, ["origin/other"]);
+    assert!(repo.cmd_out(["for-each-ref", "refs/remotes/origin/other"]).unwrap().is_empty());
+}
+
+#[test]
+fn test_mirror_maintenance() {
+    let source = TempRepo::new();
+    source.commit_file("a.txt", "one\n", "first");
+    source.cmd(["branch", "old"]).unwrap();
+    source.cmd(["tag", "v1"]).unwrap();
+    let dir = TempDir::new().unwrap();
+    let refs = |repo: &Repository| repo.cmd_out(["for-each-ref", "--format=%(refname) %(objectname)"]).unwrap();
+
+    let mirror = Repository::clone_mirror(&GitUrl::from_path(source.path()).unwrap(), dir.path().join("mirror.git"))
+        .unwrap();
+    assert_eq!(refs(&mirror), refs(&source));
+
+    source.commit_file("a.txt", "two\n", "second");
+    source.cmd(["branch", "-D", "old"]).unwrap();
+    mirror.remote_update(true).unwrap();
+    assert_eq!(refs(&mirror), refs(&source));
+
+    std::fs::create_dir(dir.path().join("backup")).unwrap();
+    let backup = Repository::init(dir.path().join("backup")).unwrap();
+    backup.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+    let name = Remote::from_str("backup").unwrap();
+    mirror.add_remote(&name, &GitUrl::from_path(&dir.path().join("backup")).unwrap()).unwrap();
+    mirror.push_mirror(&name).unwrap();
+    assert_eq!(refs(&backup), refs(&source));
+}
+
+/// Adds two pushable remotes and, between them, one whose URL does not exist.
+fn mirrors(repo: &TempRepo) -> (Vec<TempRepo>, Vec<Remote>) {
+    let upstreams = vec![TempRepo::new(), TempRepo::new()];
+    let mut remotes = Vec::new();
+    for (name, upstream) in ["github", "mirror"].into_iter().zip(&upstreams) {
+        upstream.cmd(["config", "receive.denyCurrentBranch", "ignore"]).unwrap();
+        let remote = Remote::from_str(name).unwrap();
+        repo.add_remote(&remote, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+        remotes.push(remote);
+    }
+    let broken = Remote::from_str("broken").unwrap();
+    repo.add_remote(&broken, &GitUrl::from_path(&repo.path().join("missing")).unwrap()).unwrap();
+    remotes.insert(1, broken);
+    (upstreams, remotes)
+}
+
+#[test]
+fn test_push_to_all() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.cmd(["branch", "release"]).unwrap();
+    let (upstreams, remotes) = mirrors(&repo);
+
+    let release = BranchName::from_str("release").unwrap();
+    let results = repo.push_to_all(&[release], &remotes, &PushOptions::default());
+    let outcomes: Vec<_> = results.iter().map(|(remote, result)| (remote.to_string(), result.is_ok())).collect();
+    assert_eq!(outcomes, [("github".to_string(), true), ("broken".to_string(), false), ("mirror".to_string(), true)]);
+    for upstream in &upstreams {
+        assert_eq!(upstream.cmd_out(["rev-parse", "release"]).unwrap(), repo.cmd_out(["rev-parse", "release"]).unwrap());
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_push_to_all_async() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let (upstreams, remotes) = mirrors(&repo);
+
+    let branch = BranchName::from_str(&repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap()[0]).unwrap();
+    let opts = PushOptions { set_upstream: true, ..Default::default() };
+    let async_repo = GitPilot::AsyncRepository::new(repo.path());
+    let results = async_repo.push_to_all(std::slice::from_ref(&branch), &remotes, &opts).await;
+    let outcomes: Vec<_> = results.iter().map(|(remote, result)| (remote.to_string(), result.is_ok())).collect();
+    assert_eq!(outcomes, [("github".to_string(), true), ("broken".to_string(), false), ("mirror".to_string(), true)]);
+    // The pushes ran in turn, so the last successful one set the upstream.
+    let key = format!("branch.{}.remote", branch);
+    assert_eq!(repo.cmd_out(["config", key.as_str()]).unwrap(), ["mirror"]);
+    for upstream in &upstreams {
+        assert_eq!(upstream.get_hash(false).unwrap(), repo.get_hash(false).unwrap());
+    }
+}
+
+#[test]
+fn test_default_branch() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    upstream.cmd(["branch", "-m", "trunk"]).unwrap();
+    let dir = TempDir::new().unwrap();
+    let url = GitUrl::from_path(upstream.path()).unwrap();
+    let origin = Remote::from_str("origin").unwrap();
+
+    // Recorded by clone.
+    let clone = Repository::clone(url.clone(), dir.path().join("clone")).unwrap();
+    assert_eq!(clone.default_branch(&origin).unwrap().to_string(), "trunk");
+
+    // Not recorded: asked from the remote.
+    let repo = TempRepo::new();
+    repo.add_remote(&origin, &url).unwrap();
+    assert_eq!(repo.default_branch(&origin).unwrap().to_string(), "trunk");
+    upstream.cmd(["symbolic-ref", "HEAD", "refs/heads/develop"]).unwrap();
+    upstream.cmd(["branch", "develop", "trunk"]).unwrap();
+    assert_eq!(repo.default_branch(&origin).unwrap().to_string(), "develop");
+    // The recorded value wins until it is refreshed.
+    assert_eq!(clone.default_branch(&origin).unwrap().to_string(), "trunk");
+
+    let missing = Remote::from_str("missing").unwrap();
+    assert!(repo.default_branch(&missing).is_err());
+
+    // An empty repository has no branch for HEAD to point to.
+    let empty = TempRepo::new();
+    let bare = Remote::from_str("empty").unwrap();
+    repo.add_remote(&bare, &GitUrl::from_path(empty.path()).unwrap()).unwrap();
+    assert!(matches!(repo.default_branch(&bare), Err(GitError::NoDefaultBranch(name)) if name == "empty"));
+}
+
+#[test]
+fn test_hosted_repo() {
+    let repo = TempRepo::new();
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_str("git@github.com:owner/project.git").unwrap()).unwrap();
+    let web = repo.hosted_repo(&origin).unwrap().unwrap();
+    assert_eq!(web.service(), HostingService::GitHub);
+    assert_eq!(web.file_url("main", "README.md"), "https://github.com/owner/project/blob/main/README.md");
+
+    let local = Remote::from_str("local").unwrap();
+    repo.add_remote(&local, &GitUrl::from_path(repo.path()).unwrap()).unwrap();
+    assert_eq!(repo.hosted_repo(&local).unwrap(), None);
+    assert!(repo.hosted_repo(&Remote::from_str("missing").unwrap()).is_err());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_spawn_auto_fetch() {
+    use std::time::Duration;
+
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    let repo = TempRepo::new();
+    let origin = Remote::from_str("origin").unwrap();
+    repo.add_remote(&origin, &GitUrl::from_path(upstream.path()).unwrap()).unwrap();
+    let branch = upstream.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap().remove(0);
+    let tracking = format!("refs/remotes/origin/{}", branch);
+
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let async_repo = GitPilot::AsyncRepository::new(repo.path());
+    let (auto_fetch, mut events) = async_repo.spawn_auto_fetch(Duration::from_millis(50), origin.clone(), async {
+        let _ = stopped.await;
+    });
+
+    // The first fetch happens right away.
+    let first = events.recv().await.unwrap();
+    assert_eq!(first.remote, origin);
+    assert_eq!(first.result.as_ref().unwrap().new_branches().count(), 1);
+    assert_eq!(repo.cmd_out(["rev-parse", tracking.as_str()]).unwrap()[0], upstream.get_hash(false).unwrap().to_string());
+
+    // A later fetch picks up new upstream commits.
+    upstream.commit_file("a.txt", "two\n", "second");
+    let expected = upstream.get_hash(false).unwrap().to_string();
+    let mut later = auto_fetch.subscribe();
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while repo.cmd_out(["rev-parse", tracking.as_str()]).unwrap()[0] != expected {
+            later.recv().await.unwrap();
+        }
+    })
+    .await
+    .unwrap();
+
+    stop.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while auto_fetch.is_running() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "json")]
+
+use serde_json::Value;
+use GitPilot::models::LogResult;
+use GitPilot::options::DiffOptions;
+use GitPilot::report::SCHEMA_VERSION;
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_reports_follow_documented_schema() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    std::fs::write(repo.path().join("a.txt"), "one\ntwo\n").unwrap();
+
+    let status: Value = serde_json::from_str(&repo.status().unwrap().to_json()).unwrap();
+    assert_eq!(status["schema_version"], SCHEMA_VERSION);
+    assert_eq!(status["clean"], false);
+    assert_eq!(status["in_progress"]["rebase"], false);
+    assert_eq!(status["files"][0]["path"], "a.txt");
+    assert_eq!(status["files"][0]["index"], "unmodified");
+    assert_eq!(status["files"][0]["worktree"], "modified");
+
+    let log = LogResult { commits: vec![repo.get_commit(None).unwrap()] }.to_json_value();
+    assert_eq!(log["commits"][0]["subject"], "first");
+    assert_eq!(log["commits"][0]["author"]["name"], "GitPilot Test");
+    assert!(log["commits"][0]["timestamp"].is_null());
+
+    let diff = repo.diff("HEAD", None, &DiffOptions::default()).unwrap().to_json_value();
+    let file = &diff["files"][0];
+    assert_eq!((file["added"].as_u64(), file["removed"].as_u64()), (Some(1), Some(0)));
+    let lines = file["hunks"][0]["lines"].as_array().unwrap();
+    assert_eq!(lines.last().unwrap()["type"], "added");
+    assert_eq!(lines.last().unwrap()["content"], "two");
+}
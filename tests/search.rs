@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::PathBuf;
+use GitPilot::options::{GrepOptions, GrepPatternKind};
+use GitPilot::testing::TempRepo;
+use GitPilot::types::Pathspec;
+
+#[test]
+fn test_grep_worktree_and_revision() {
+    let repo = TempRepo::new();
+    repo.commit_file("src/main file.rs", "fn main() {\n    todo!();\n}\n", "first");
+    repo.commit_file("notes.txt", "TODO: tests\n", "second");
+    fs::write(repo.path().join("notes.txt"), "nothing left\n").unwrap();
+
+    let matches = repo.grep("todo", &GrepOptions::default()).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, PathBuf::from("src/main file.rs"));
+    assert_eq!(matches[0].line_number, 2);
+    assert_eq!(matches[0].content, "    todo!();");
+
+    let opts = GrepOptions {
+        ignore_case: true,
+        revision: Some("HEAD".to_string()),
+        ..Default::default()
+    };
+    let mut paths: Vec<PathBuf> = repo.grep("todo", &opts).unwrap().into_iter().map(|m| m.path).collect();
+    paths.sort();
+    assert_eq!(paths, vec![PathBuf::from("notes.txt"), PathBuf::from("src/main file.rs")]);
+
+    let opts = GrepOptions {
+        pattern_kind: GrepPatternKind::Fixed,
+        paths: vec!["src".into()],
+        ..Default::default()
+    };
+    assert_eq!(repo.grep("todo!(", &opts).unwrap().len(), 1);
+    assert!(repo.grep("no such text", &GrepOptions::default()).unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_grep_path_with_newline() {
+    let repo = TempRepo::new();
+    repo.commit_file("two\nlines.txt", "needle\n", "first");
+    repo.commit_file("other.txt", "hay\nneedle\n", "second");
+
+    let mut matches = repo.grep("needle", &GrepOptions::default()).unwrap();
+    matches.sort_by(|a, b| a.path.cmp(&b.path));
+    let found: Vec<_> = matches.iter().map(|m| (m.path.clone(), m.line_number)).collect();
+    assert_eq!(found, [(PathBuf::from("other.txt"), 2), (PathBuf::from("two\nlines.txt"), 1)]);
+}
+
+#[test]
+fn test_pathspec_magic_in_add_and_grep() {
+    let repo = TempRepo::new();
+    repo.commit_file("README.md", "needle\n", "first");
+    repo.write_file("src/a.rs", "needle\n").write_file("src/deep/b.rs", "needle\n");
+    repo.write_file("src/deep/b_test.rs", "needle\n").write_file("[literal].txt", "needle\n");
+
+    // Exclusions and globs, with no quoting by the caller.
+    repo.add(vec![Pathspec::new("src/**/*.rs").glob(), Pathspec::new("*_test.rs").exclude()]).unwrap();
+    let status = repo.status().unwrap();
+    let mut staged: Vec<PathBuf> = status.files.iter().filter(|f| f.is_staged()).map(|f| f.path.clone()).collect();
+    staged.sort();
+    assert_eq!(staged, vec![PathBuf::from("src/a.rs"), PathBuf::from("src/deep/b.rs")]);
+
+    // `[literal]` would otherwise be a character class.
+    repo.add(vec![Pathspec::new("[literal].txt").literal()]).unwrap();
+    repo.commit_staged("second").unwrap();
+
+    let opts = GrepOptions { paths: vec![Pathspec::new("readme.MD").icase()], ..Default::default() };
+    assert_eq!(repo.grep("needle", &opts).unwrap().len(), 1);
+    let opts = GrepOptions { paths: vec![Pathspec::new("src").exclude()], ..Default::default() };
+    let mut paths: Vec<PathBuf> = repo.grep("needle", &opts).unwrap().into_iter().map(|m| m.path).collect();
+    paths.sort();
+    assert_eq!(paths, vec![PathBuf::from("README.md"), PathBuf::from("[literal].txt")]);
+}
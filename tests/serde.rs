@@ -0,0 +1,97 @@
+#![cfg(feature = "serde")]
+
+use std::str::FromStr;
+use GitPilot::hosting::{HostedRepo, HostingService};
+use GitPilot::message::CommitMessage;
+use GitPilot::models::{Commit, StatusResult};
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{BranchName, CommitHash, GitUrl, GitUrlKind, HookKind, Pathspec};
+use GitPilot::versioning::Bump;
+
+#[test]
+fn test_models_roundtrip_through_json() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "a\n", "first\n\nwith a body");
+    std::fs::write(repo.path().join("a.txt"), "changed\n").unwrap();
+
+    let commit = repo.get_commit(None).unwrap();
+    let json = serde_json::to_value(&commit).unwrap();
+    assert_eq!(json["subject"], "first");
+    assert_eq!(json["hash"], commit.hash.to_string());
+    let back: Commit = serde_json::from_value(json).unwrap();
+    assert_eq!(back.hash, commit.hash);
+    assert_eq!(back.body, "with a body");
+
+    let status = repo.status().unwrap();
+    let json = serde_json::to_string(&status).unwrap();
+    let back: StatusResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.files.len(), 1);
+    assert_eq!(back.files[0].worktree_status, status.files[0].worktree_status);
+    assert_eq!(back.branch.map(|b| b.to_string()), status.branch.map(|b| b.to_string()));
+}
+
+#[test]
+fn test_types_are_validated_on_deserialize() {
+    let branch: BranchName = serde_json::from_str("\"feature/x\"").unwrap();
+    assert_eq!(serde_json::to_string(&branch).unwrap(), "\"feature/x\"");
+    assert!(serde_json::from_str::<BranchName>("\"bad..name\"").is_err());
+    assert!(serde_json::from_str::<CommitHash>("\"not-hex\"").is_err());
+}
+
+#[test]
+fn test_git_url_kind_roundtrips() {
+    let json = serde_json::to_string(&GitUrlKind::ScpLike).unwrap();
+    assert_eq!(serde_json::from_str::<GitUrlKind>(&json).unwrap(), GitUrlKind::ScpLike);
+}
+
+#[test]
+fn test_hook_kind_roundtrips() {
+    let json = serde_json::to_string(&HookKind::PrePush).unwrap();
+    assert_eq!(serde_json::from_str::<HookKind>(&json).unwrap(), HookKind::PrePush);
+}
+
+#[test]
+fn test_pathspec_roundtrips_with_its_magic() {
+    let spec = Pathspec::new("src/**/*_test.rs").glob().exclude();
+    let json = serde_json::to_value(&spec).unwrap();
+    assert_eq!(json["path"], "src/**/*_test.rs");
+    assert_eq!(json["glob"], true);
+    let back: Pathspec = serde_json::from_value(json).unwrap();
+    assert_eq!(back, spec);
+    assert_eq!(back.to_string(), ":(exclude,glob)src/**/*_test.rs");
+}
+
+#[test]
+fn test_bump_roundtrips() {
+    let json = serde_json::to_string(&Bump::Minor).unwrap();
+    assert_eq!(serde_json::from_str::<Bump>(&json).unwrap(), Bump::Minor);
+}
+
+#[test]
+fn test_commit_message_roundtrips() {
+    let message = CommitMessage::new("feat: add parser").body("Details.").trailer("Refs", "#12");
+    let json = serde_json::to_string(&message).unwrap();
+    let back: CommitMessage = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, message);
+    assert_eq!(back.to_string(), message.to_string());
+}
+
+#[test]
+fn test_hosted_repo_roundtrips() {
+    let url = GitUrl::from_str("https://github.com/user/project.git").unwrap();
+    let hosted = HostedRepo::from_url(&url).unwrap();
+    let json = serde_json::to_string(&hosted).unwrap();
+    let back: HostedRepo = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, hosted);
+    assert_eq!(serde_json::from_str::<HostingService>("\"GitLab\"").unwrap(), HostingService::GitLab);
+}
+
+#[cfg(feature = "notify")]
+#[test]
+fn test_repo_event_roundtrips() {
+    use GitPilot::watch::RepoEvent;
+
+    let event = RepoEvent::BranchUpdated(Some(BranchName::from_str("main").unwrap()));
+    let json = serde_json::to_string(&event).unwrap();
+    assert_eq!(serde_json::from_str::<RepoEvent>(&json).unwrap(), event);
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::str::FromStr;
+use GitPilot::options::StashOptions;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::BranchName;
+
+#[test]
+fn test_stash_push_show_and_pop() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    assert_eq!(repo.stash_push(&StashOptions::default()).unwrap(), None);
+
+    repo.write_file("a.txt", "one\ntwo\n").write_file("new.txt", "untracked\n");
+    let opts = StashOptions { include_untracked: true, message: Some("wip".to_string()), ..Default::default() };
+    let stash = repo.stash_push(&opts).unwrap().unwrap();
+    assert!(repo.status().unwrap().is_clean);
+
+    let list = repo.stash_list().unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].reference, stash);
+    assert_eq!(list[0].message, "wip");
+
+    let stats = repo.stash_show(&stash).unwrap();
+    assert_eq!(stats.files_changed, 2);
+    assert_eq!(stats.insertions, 2);
+
+    repo.stash_pop(&stash, false).unwrap();
+    assert!(repo.stash_list().unwrap().is_empty());
+    assert_eq!(fs::read_to_string(repo.path().join("new.txt")).unwrap(), "untracked\n");
+}
+
+#[test]
+fn test_stash_keep_index_apply_and_drop() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "one\n", "second");
+    repo.write_file("a.txt", "staged\n");
+    repo.add(vec!["a.txt"]).unwrap();
+    repo.write_file("b.txt", "unstaged\n");
+
+    let opts = StashOptions { keep_index: true, ..Default::default() };
+    let stash = repo.stash_push(&opts).unwrap().unwrap();
+    assert!(repo.has_staged_changes().unwrap());
+    assert!(!repo.has_unstaged_changes().unwrap());
+
+    repo.cmd(["reset", "-q", "--hard"]).unwrap();
+    repo.stash_apply(&stash, true).unwrap();
+    assert!(repo.has_staged_changes().unwrap());
+    assert_eq!(fs::read_to_string(repo.path().join("b.txt")).unwrap(), "unstaged\n");
+
+    repo.stash_drop(&stash).unwrap();
+    assert!(repo.stash_list().unwrap().is_empty());
+}
+
+#[test]
+fn test_stash_branch_and_partial_apply() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "one\n", "second");
+    repo.write_file("a.txt", "stashed a\n").write_file("b.txt", "stashed b\n");
+    let stash = repo.stash_push(&StashOptions::default()).unwrap().unwrap();
+
+    repo.stash_apply_paths(&stash, &["a.txt"]).unwrap();
+    assert_eq!(fs::read_to_string(repo.path().join("a.txt")).unwrap(), "stashed a\n");
+    assert_eq!(fs::read_to_string(repo.path().join("b.txt")).unwrap(), "one\n");
+    assert_eq!(repo.stash_list().unwrap().len(), 1);
+    assert!(repo.stash_apply_paths(&stash, &["missing.txt"]).is_err());
+
+    repo.cmd(["reset", "-q", "--hard"]).unwrap();
+    repo.commit_file("a.txt", "moved on\n", "third");
+    let branch = BranchName::from_str("recovered").unwrap();
+    repo.stash_branch(&branch, &stash).unwrap();
+    assert_eq!(repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap(), vec!["recovered"]);
+    assert_eq!(fs::read_to_string(repo.path().join("b.txt")).unwrap(), "stashed b\n");
+    assert!(repo.stash_list().unwrap().is_empty());
+}
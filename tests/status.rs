@@ -0,0 +1,540 @@
+use std::fs;
+use std::path::PathBuf;
+use GitPilot::error::GitError;
+use GitPilot::models::{EntryKind, FileStatus, RepoState, StatusCode};
+use GitPilot::options::{AddOptions, CleanOptions, CommitOptions, DiffOptions, LsFilesOptions, RemoveOptions};
+use GitPilot::types::Pathspec;
+use GitPilot::testing::TempRepo;
+use GitPilot::Repository;
+
+#[test]
+fn test_status_separates_staged_and_unstaged_changes() {
+    let repo = TempRepo::new();
+    repo.commit_file("staged.txt", "one\n", "first");
+    repo.commit_file("unstaged.txt", "one\n", "second");
+
+    fs::write(repo.path().join("staged.txt"), "two\n").unwrap();
+    repo.add(vec!["staged.txt"]).unwrap();
+    fs::write(repo.path().join("staged.txt"), "three\n").unwrap();
+    fs::write(repo.path().join("unstaged.txt"), "two\n").unwrap();
+    fs::write(repo.path().join("new.txt"), "new\n").unwrap();
+
+    let status = repo.status().unwrap();
+    let entry = |name: &str| status.files.iter().find(|f| f.path.as_os_str() == name).unwrap();
+
+    let staged = entry("staged.txt");
+    assert_eq!(staged.index_status, StatusCode::Modified);
+    assert_eq!(staged.worktree_status, StatusCode::Modified);
+
+    let unstaged = entry("unstaged.txt");
+    assert_eq!(unstaged.index_status, StatusCode::Unmodified);
+    assert_eq!(unstaged.status(), FileStatus::Modified);
+    assert!(!unstaged.is_staged());
+
+    assert_eq!(entry("new.txt").status(), FileStatus::Untracked);
+    assert!(!status.is_clean);
+    assert_eq!(status.head_commit, Some(repo.get_hash(false).unwrap()));
+}
+
+#[test]
+fn test_ignore_rules() {
+    let repo = TempRepo::new();
+    repo.commit_file(".gitignore", "*.log\n!keep.log\ntarget/\n", "ignore");
+    fs::write(repo.path().join("debug.log"), "x").unwrap();
+    fs::write(repo.path().join("keep.log"), "x").unwrap();
+    fs::create_dir(repo.path().join("target")).unwrap();
+    fs::write(repo.path().join("target/out.bin"), "x").unwrap();
+
+    let mut ignored = repo.list_ignored().unwrap();
+    ignored.sort();
+    assert_eq!(ignored, vec![PathBuf::from("debug.log"), PathBuf::from("target/")]);
+
+    let checked = repo.check_ignore(&["debug.log", "keep.log", "src/main.rs", "target/x"]).unwrap();
+    assert_eq!(
+        checked,
+        vec![
+            (PathBuf::from("debug.log"), true),
+            (PathBuf::from("keep.log"), false),
+            (PathBuf::from("src/main.rs"), false),
+            (PathBuf::from("target/x"), true),
+        ]
+    );
+
+    // Nothing matching is a successful answer, not an error.
+    assert_eq!(repo.check_ignore(&["README.md"]).unwrap(), vec![(PathBuf::from("README.md"), false)]);
+}
+
+#[test]
+fn test_dirty_predicates() {
+    let repo = TempRepo::new();
+    repo.commit_file("tracked.txt", "one\n", "first");
+    assert!(!repo.is_dirty().unwrap());
+    assert!(!repo.has_untracked_files().unwrap());
+
+    fs::write(repo.path().join("new.txt"), "new\n").unwrap();
+    assert!(repo.has_untracked_files().unwrap());
+    assert!(!repo.is_dirty().unwrap());
+
+    fs::write(repo.path().join("tracked.txt"), "two\n").unwrap();
+    assert!(repo.is_dirty().unwrap());
+    assert!(repo.has_unstaged_changes().unwrap());
+    assert!(!repo.has_staged_changes().unwrap());
+
+    repo.add(vec!["tracked.txt"]).unwrap();
+    assert!(repo.has_staged_changes().unwrap());
+    assert!(!repo.has_unstaged_changes().unwrap());
+    assert!(repo.is_dirty().unwrap());
+}
+
+#[test]
+fn test_renames_in_status_and_diff() {
+    let repo = TempRepo::new();
+    let contents = "line one\nline two\nline three\nline four\n";
+    repo.commit_file("before.txt", contents, "first");
+    repo.cmd(["mv", "before.txt", "after.txt"]).unwrap();
+
+    let status = repo.status().unwrap();
+    let entry = &status.files[0];
+    assert_eq!(entry.path, PathBuf::from("after.txt"));
+    assert_eq!(entry.original_path, Some(PathBuf::from("before.txt")));
+    assert_eq!(entry.index_status, StatusCode::Renamed);
+    assert_eq!(entry.similarity, Some(100));
+
+    repo.commit_staged("rename").unwrap();
+    let opts = DiffOptions { detect_renames: Some(50), ..Default::default() };
+    let diff = repo.diff("HEAD~1", Some("HEAD"), &opts).unwrap();
+    assert_eq!(diff.files.len(), 1);
+    assert_eq!(diff.files[0].path, PathBuf::from("after.txt"));
+    assert_eq!(diff.files[0].old_path, Some(PathBuf::from("before.txt")));
+    assert_eq!(diff.files[0].similarity, Some(100));
+
+    // Copies are found from sources modified in the same change.
+    fs::write(repo.path().join("copy.txt"), contents).unwrap();
+    fs::write(repo.path().join("after.txt"), format!("{}line five\n", contents)).unwrap();
+    repo.add(vec!["copy.txt", "after.txt"]).unwrap();
+    repo.commit_staged("copy").unwrap();
+    let opts = DiffOptions { detect_copies: Some(50), ..Default::default() };
+    let diff = repo.diff("HEAD~1", Some("HEAD"), &opts).unwrap();
+    let copy = diff.files.iter().find(|f| f.path.as_os_str() == "copy.txt").unwrap();
+    assert_eq!(copy.old_path, Some(PathBuf::from("after.txt")));
+    assert_eq!(copy.similarity, Some(100));
+}
+
+#[test]
+fn test_status_fast_skips_untracked_files_and_renames() {
+    let repo = TempRepo::new();
+    repo.commit_file("before.txt", "one\ntwo\n", "first");
+    repo.commit_file("kept.txt", "one\n", "second");
+    repo.cmd(["mv", "before.txt", "after.txt"]).unwrap();
+    fs::write(repo.path().join("kept.txt"), "two\n").unwrap();
+    fs::write(repo.path().join("new.txt"), "new\n").unwrap();
+
+    let fast = repo.status_fast().unwrap();
+    let mut changes: Vec<(String, StatusCode, StatusCode)> = fast
+        .files
+        .iter()
+        .map(|f| (f.path.to_string_lossy().into_owned(), f.index_status, f.worktree_status))
+        .collect();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        changes,
+        [
+            ("after.txt".to_string(), StatusCode::Added, StatusCode::Unmodified),
+            ("before.txt".to_string(), StatusCode::Deleted, StatusCode::Unmodified),
+            ("kept.txt".to_string(), StatusCode::Unmodified, StatusCode::Modified),
+        ]
+    );
+    assert!(fast.files.iter().all(|f| f.original_path.is_none()));
+    assert!(!fast.is_clean);
+
+    let full = repo.status().unwrap();
+    assert_eq!((fast.branch, fast.head_commit), (full.branch, full.head_commit));
+}
+
+// Windows does not allow newlines or quotes in file names.
+#[cfg(unix)]
+#[test]
+fn test_ls_files_selections() {
+    let repo = TempRepo::new();
+    repo.commit_file("kept.txt", "a\n", "first");
+    repo.commit_file("with space\nand newline.txt", "b\n", "second");
+    repo.commit_file("gone.txt", "c\n", "third");
+    repo.commit_file(".gitignore", "*.log\n", "ignore");
+    fs::write(repo.path().join("kept.txt"), "changed\n").unwrap();
+    fs::remove_file(repo.path().join("gone.txt")).unwrap();
+    fs::write(repo.path().join("untracked \"quoted\".txt"), "d\n").unwrap();
+    fs::write(repo.path().join("debug.log"), "e\n").unwrap();
+
+    let tracked = repo.list_tracked().unwrap();
+    assert_eq!(tracked.len(), 4);
+    assert!(tracked.contains(&"with space\nand newline.txt".to_string()));
+
+    let select = |opts: LsFilesOptions| repo.ls_files(&opts).unwrap();
+    assert_eq!(
+        select(LsFilesOptions { deleted: true, ..Default::default() }),
+        vec![PathBuf::from("gone.txt")]
+    );
+    assert_eq!(
+        select(LsFilesOptions { modified: true, ..Default::default() }),
+        vec![PathBuf::from("gone.txt"), PathBuf::from("kept.txt")]
+    );
+    assert_eq!(
+        select(LsFilesOptions { cached: true, deleted: true, ..Default::default() }).len(),
+        4
+    );
+    assert_eq!(
+        select(LsFilesOptions { others: true, exclude_standard: true, ..Default::default() }),
+        vec![PathBuf::from("untracked \"quoted\".txt")]
+    );
+    assert_eq!(repo.list_ignored().unwrap(), vec![PathBuf::from("debug.log")]);
+}
+
+// Windows does not allow newlines or quotes in file names.
+#[cfg(unix)]
+#[test]
+fn test_status_with_pathological_filenames() {
+    let repo = TempRepo::new();
+    let names = ["with space.txt", "quote\"d.txt", "new\nline.txt", "caf\u{e9}.txt"];
+    let contents = "one\ntwo\nthree\nfour\n";
+    for name in names {
+        repo.commit_file(name, contents, name);
+        fs::write(repo.path().join(name), format!("{}five\n", contents)).unwrap();
+    }
+    fs::write(repo.path().join("untracked file\twith tab"), "x").unwrap();
+    repo.cmd(["mv", "with space.txt", "renamed with space.txt"]).unwrap();
+
+    let status = repo.status().unwrap();
+    let mut paths: Vec<PathBuf> = status.files.iter().map(|f| f.path.clone()).collect();
+    paths.sort();
+    let mut expected: Vec<PathBuf> = ["renamed with space.txt", "quote\"d.txt", "new\nline.txt", "caf\u{e9}.txt", "untracked file\twith tab"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    expected.sort();
+    assert_eq!(paths, expected);
+
+    let renamed = status.files.iter().find(|f| f.original_path.is_some()).unwrap();
+    assert_eq!(renamed.original_path, Some(PathBuf::from("with space.txt")));
+
+    let diff = repo.diff("HEAD", None, &DiffOptions::default()).unwrap();
+    let mut diff_paths: Vec<PathBuf> = diff.files.iter().map(|f| f.path.clone()).collect();
+    diff_paths.sort();
+    let mut expected: Vec<PathBuf> = ["renamed with space.txt", "quote\"d.txt", "new\nline.txt", "caf\u{e9}.txt"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    expected.sort();
+    assert_eq!(diff_paths, expected);
+}
+
+#[test]
+fn test_ensure_clean() {
+    let repo = TempRepo::new();
+    repo.commit_file("tracked.txt", "one\n", "first");
+    repo.commit_file(".gitignore", "*.log\n", "ignore");
+    fs::write(repo.path().join("debug.log"), "x").unwrap();
+    repo.ensure_clean(false).unwrap();
+
+    fs::write(repo.path().join("new.txt"), "new\n").unwrap();
+    repo.ensure_clean(true).unwrap();
+    match repo.ensure_clean(false) {
+        Err(GitError::DirtyWorkingTree { files }) => assert_eq!(files, vec![PathBuf::from("new.txt")]),
+        other => panic!("expected DirtyWorkingTree, got {:?}", other),
+    }
+
+    fs::write(repo.path().join("tracked.txt"), "two\n").unwrap();
+    match repo.ensure_clean(true) {
+        Err(GitError::DirtyWorkingTree { files }) => assert_eq!(files, vec![PathBuf::from("tracked.txt")]),
+        other => panic!("expected DirtyWorkingTree, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_add_with_options() {
+    let repo = TempRepo::new();
+    repo.commit_file("tracked.txt", "one\n", "first");
+    repo.commit_file("doomed.txt", "one\n", "second");
+    repo.commit_file(".gitignore", "*.log\n", "ignore logs");
+    fs::remove_file(repo.path().join("doomed.txt")).unwrap();
+    repo.write_file("tracked.txt", "two\n").write_file("new.txt", "new\n").write_file("debug.log", "log\n");
+
+    let dry_run = AddOptions { all: true, dry_run: true, ..Default::default() };
+    let mut paths = repo.add_with(&dry_run).unwrap();
+    paths.sort();
+    assert_eq!(paths, ["doomed.txt", "new.txt", "tracked.txt"].map(PathBuf::from));
+    assert!(!repo.has_staged_changes().unwrap());
+
+    let update = AddOptions { update: true, ..Default::default() };
+    let mut paths = repo.add_with(&update).unwrap();
+    paths.sort();
+    assert_eq!(paths, ["doomed.txt", "tracked.txt"].map(PathBuf::from));
+
+    let intent = AddOptions { intent_to_add: true, paths: vec!["new.txt".into()], ..Default::default() };
+    repo.add_with(&intent).unwrap();
+    assert!(repo.list_tracked().unwrap().contains(&"new.txt".to_string()));
+    assert!(repo.has_unstaged_changes().unwrap());
+
+    let ignored = AddOptions { paths: vec!["debug.log".into()], ..Default::default() };
+    assert!(repo.add_with(&ignored).is_err());
+    let forced = AddOptions { force: true, ..ignored };
+    assert_eq!(repo.add_with(&forced).unwrap(), vec![PathBuf::from("debug.log")]);
+}
+
+#[test]
+fn test_add_and_remove_paths_that_look_like_options() {
+    let repo = TempRepo::new();
+    repo.write_file("-n.txt", "one\n").write_file("--force", "one\n");
+    repo.add(vec![Pathspec::new("-n.txt"), Pathspec::new("--force").literal()]).unwrap();
+    assert_eq!(repo.list_tracked().unwrap(), ["--force", "-n.txt"]);
+
+    repo.commit_staged("dashes").unwrap();
+    repo.remove(vec!["-n.txt"], false).unwrap();
+    assert!(!repo.path().join("-n.txt").exists());
+    assert_eq!(repo.list_tracked().unwrap(), ["--force"]);
+}
+
+#[test]
+fn test_move_path() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "two\n", "second");
+
+    repo.move_path("a.txt", "docs/nested/a.txt", false).unwrap();
+    let status = repo.status().unwrap();
+    assert_eq!(status.files.len(), 1);
+    assert_eq!(status.files[0].path, PathBuf::from("docs/nested/a.txt"));
+    assert_eq!(status.files[0].original_path, Some(PathBuf::from("a.txt")));
+
+    assert!(repo.move_path("docs/nested/a.txt", "b.txt", false).is_err());
+    repo.move_path("docs/nested/a.txt", "b.txt", true).unwrap();
+    assert_eq!(fs::read_to_string(repo.path().join("b.txt")).unwrap(), "one\n");
+    assert!(repo.move_path("untracked.txt", "c.txt", false).is_err());
+}
+
+#[test]
+fn test_gitignore_helpers() {
+    let repo = TempRepo::new();
+    assert!(!repo.gitignore_contains("*.log").unwrap());
+    assert_eq!(repo.add_to_gitignore(&["*.log", "target/", "*.log"]).unwrap(), ["*.log", "target/"]);
+    assert!(repo.gitignore_contains("target/").unwrap());
+    assert_eq!(repo.add_to_gitignore(&["target/"]).unwrap(), Vec::<String>::new());
+    assert_eq!(fs::read_to_string(repo.path().join(".gitignore")).unwrap(), "*.log\ntarget/\n");
+
+    // A missing final newline and CRLF line endings are respected.
+    repo.write_file(".gitignore", "*.log\r\n.env");
+    assert_eq!(repo.add_to_gitignore(&[".env", "dist/"]).unwrap(), ["dist/"]);
+    assert_eq!(fs::read_to_string(repo.path().join(".gitignore")).unwrap(), "*.log\r\n.env\r\ndist/\r\n");
+    assert!(repo.check_ignore(&["dist/app.js"]).unwrap()[0].1);
+    assert!(matches!(repo.add_to_gitignore(&["a\nb"]), Err(GitError::InvalidGitignorePattern(p)) if p == "a\nb"));
+    assert!(matches!(repo.add_to_gitignore(&[" "]), Err(GitError::InvalidGitignorePattern(_))));
+
+    // Unescaped trailing spaces are dropped, but an escaped one is part of the pattern.
+    assert_eq!(repo.add_to_gitignore(&["notes  ", "trailing\\ "]).unwrap(), ["notes", "trailing\\ "]);
+    assert!(repo.gitignore_contains("trailing\\ ").unwrap());
+    assert!(!repo.gitignore_contains("trailing").unwrap());
+    repo.write_file("trailing ", "x\n");
+    assert!(repo.check_ignore(&["trailing "]).unwrap()[0].1);
+
+    repo.cmd(["config", "core.excludesFile", "/etc/gitpilot-ignore"]).unwrap();
+    assert_eq!(repo.global_excludes_file().unwrap(), Some(PathBuf::from("/etc/gitpilot-ignore")));
+}
+
+#[test]
+fn test_operation_state() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("a.txt", "two\n", "second");
+    repo.commit_file("a.txt", "three\n", "third");
+    assert_eq!(repo.operation_state().unwrap(), RepoState::Clean);
+
+    // Reverting "second" conflicts with "third".
+    assert!(repo.cmd(["revert", "--no-edit", "HEAD~1"]).is_err());
+    assert_eq!(repo.operation_state().unwrap(), RepoState::Reverting);
+    repo.cmd(["revert", "--abort"]).unwrap();
+
+    repo.cmd(["bisect", "start", "HEAD", "HEAD~2"]).unwrap();
+    assert_eq!(repo.operation_state().unwrap(), RepoState::Bisecting);
+    repo.cmd(["bisect", "reset"]).unwrap();
+    assert_eq!(repo.operation_state().unwrap(), RepoState::Clean);
+}
+
+/// Adds a linked worktree beside `repo` and leaves it in a conflicted merge.
+fn conflicted_linked_worktree(repo: &TempRepo) -> Repository {
+    repo.commit_file("a.txt", "base\n", "base");
+    repo.cmd(["branch", "topic"]).unwrap();
+    repo.commit_file("a.txt", "main\n", "main change");
+
+    let linked = repo.path().join("linked");
+    repo.cmd(["worktree", "add", "-q", "linked", "topic"]).unwrap();
+    let worktree = Repository::new(&linked);
+    fs::write(linked.join("a.txt"), "topic\n").unwrap();
+    worktree.cmd(["commit", "-qam", "topic change"]).unwrap();
+    let main = repo.cmd_out(["symbolic-ref", "--short", "HEAD"]).unwrap().remove(0);
+    assert!(worktree.cmd(["merge", main.as_str()]).is_err());
+    worktree
+}
+
+#[test]
+fn test_state_detection_in_linked_worktree() {
+    let repo = TempRepo::new();
+    let worktree = conflicted_linked_worktree(&repo);
+
+    // `.git` is a file in a linked worktree; the state lives under the main `.git/worktrees`.
+    assert!(repo.path().join("linked").join(".git").is_file());
+    let git_dir = worktree.git_dir().unwrap();
+    assert_eq!(git_dir, repo.git_dir().unwrap().join("worktrees").join("linked"));
+
+    let status = worktree.status().unwrap();
+    assert!(status.merging);
+    assert_eq!(worktree.operation_state().unwrap(), RepoState::Merging);
+    assert!(!repo.status().unwrap().merging);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_state_detection_in_linked_worktree() {
+    let repo = TempRepo::new();
+    let worktree = conflicted_linked_worktree(&repo).to_async();
+    assert_eq!(worktree.git_dir().await.unwrap(), repo.git_dir().unwrap().join("worktrees").join("linked"));
+    assert!(worktree.status().await.unwrap().merging);
+}
+
+#[test]
+fn test_dry_run_commit_remove_and_clean() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("dir/b.txt", "b\n", "second");
+    repo.write_file("a.txt", "two\n");
+    repo.write_file("new.txt", "new\n");
+    repo.write_file("build/out.o", "obj\n");
+    repo.write_file(".gitignore", "*.log\n");
+    repo.write_file("debug.log", "log\n");
+    let head = repo.get_hash(false).unwrap();
+
+    // Committing reports what it records, and a dry run records nothing.
+    let dry = CommitOptions { all: true, dry_run: true, ..Default::default() };
+    assert_eq!(repo.commit_with_options("wip", &dry).unwrap(), [PathBuf::from("a.txt")]);
+    assert_eq!(repo.get_hash(false).unwrap(), head);
+
+    let rm = RemoveOptions { paths: vec![Pathspec::new("dir")], recursive: true, dry_run: true, ..Default::default() };
+    assert_eq!(repo.remove_with(&rm).unwrap(), [PathBuf::from("dir/b.txt")]);
+    assert!(repo.path().join("dir/b.txt").exists());
+
+    let clean = CleanOptions { directories: true, dry_run: true, ..Default::default() };
+    let mut would_remove = repo.clean(&clean).unwrap();
+    would_remove.sort();
+    assert_eq!(would_remove, [".gitignore", "build/", "new.txt"].map(PathBuf::from));
+    assert!(repo.path().join("new.txt").exists());
+
+    // The real operations do what the dry runs announced.
+    let removed = repo.remove_with(&RemoveOptions { dry_run: false, ..rm }).unwrap();
+    assert_eq!(removed, [PathBuf::from("dir/b.txt")]);
+    assert!(!repo.path().join("dir/b.txt").exists());
+    let ignored = repo.clean(&CleanOptions { only_ignored: true, ..Default::default() }).unwrap();
+    assert_eq!(ignored, [PathBuf::from("debug.log")]);
+    assert!(!repo.path().join("debug.log").exists());
+    let commit = CommitOptions { all: true, ..Default::default() };
+    let mut committed = repo.commit_with_options("wip", &commit).unwrap();
+    committed.sort();
+    assert_eq!(committed, ["a.txt", "dir/b.txt"].map(PathBuf::from));
+    assert!(repo.commit_with_options("nothing", &commit).is_err());
+}
+
+#[test]
+fn test_commit_allow_empty() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let head = repo.get_hash(false).unwrap();
+
+    let dry = CommitOptions { allow_empty: true, dry_run: true, ..Default::default() };
+    assert!(repo.commit_with_options("empty", &dry).unwrap().is_empty());
+    assert_eq!(repo.get_hash(false).unwrap(), head);
+
+    let empty = CommitOptions { allow_empty: true, ..Default::default() };
+    assert!(repo.commit_with_options("empty", &empty).unwrap().is_empty());
+    assert_ne!(repo.get_hash(false).unwrap(), head);
+    assert!(repo.commit_with_options("nothing", &CommitOptions::default()).is_err());
+}
+
+#[test]
+fn test_non_ascii_paths_round_trip() {
+    let repo = TempRepo::new();
+    let names = ["caf\u{e9}.txt", "\u{1f980} crab.txt", "na\u{ef}ve dir/r\u{e9}sum\u{e9}.md"];
+    for name in names {
+        repo.commit_file(name, "one\n", name);
+        fs::write(repo.path().join(name), "one\ntwo\n").unwrap();
+    }
+    fs::write(repo.path().join("\u{65e5}\u{672c}.txt"), "new").unwrap();
+    repo.cmd(["mv", "caf\u{e9}.txt", "\u{e9}t\u{e9} \"quoted\".txt"]).unwrap();
+    let sorted = |mut paths: Vec<PathBuf>| {
+        paths.sort();
+        paths
+    };
+    let expected = sorted(
+        ["\u{e9}t\u{e9} \"quoted\".txt", "\u{1f980} crab.txt", "na\u{ef}ve dir/r\u{e9}sum\u{e9}.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect(),
+    );
+
+    let status = repo.status().unwrap();
+    let mut status_paths = sorted(status.files.iter().map(|f| f.path.clone()).collect());
+    status_paths.retain(|path| path != &PathBuf::from("\u{65e5}\u{672c}.txt"));
+    assert_eq!(status_paths, expected);
+    let renamed = status.files.iter().find(|f| f.original_path.is_some()).unwrap();
+    assert_eq!(renamed.original_path, Some(PathBuf::from("caf\u{e9}.txt")));
+
+    let tracked = sorted(repo.ls_files(&LsFilesOptions { cached: true, ..Default::default() }).unwrap());
+    assert_eq!(tracked, expected);
+    assert!(repo.list_tracked().unwrap().contains(&"\u{1f980} crab.txt".to_string()));
+    let untracked = repo
+        .ls_files(&LsFilesOptions { others: true, exclude_standard: true, ..Default::default() })
+        .unwrap();
+    assert_eq!(untracked, [PathBuf::from("\u{65e5}\u{672c}.txt")]);
+
+    let stats = repo.diff_stats("HEAD", None).unwrap();
+    assert_eq!(sorted(stats.files.iter().map(|f| f.path.clone()).collect()), expected);
+
+    let diff = repo.diff("HEAD", None, &DiffOptions::default()).unwrap();
+    assert_eq!(sorted(diff.files.iter().map(|f| f.path.clone()).collect()), expected);
+    let renamed = diff.files.iter().find(|f| f.old_path.is_some()).unwrap();
+    assert_eq!(renamed.path, PathBuf::from("\u{e9}t\u{e9} \"quoted\".txt"));
+    assert_eq!(renamed.old_path, Some(PathBuf::from("caf\u{e9}.txt")));
+
+    let cleaned = repo.clean(&CleanOptions { dry_run: true, ..Default::default() }).unwrap();
+    assert_eq!(cleaned, [PathBuf::from("\u{65e5}\u{672c}.txt")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_entry_kinds_in_status_and_diff() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+    let repo = TempRepo::new();
+    repo.commit_file("run.sh", "echo hi\n", "script");
+    repo.commit_file("target.txt", "x\n", "target");
+    symlink("target.txt", repo.path().join("link")).unwrap();
+    repo.add(vec!["link"]).unwrap();
+    repo.commit_staged("link").unwrap();
+
+    fs::set_permissions(repo.path().join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+    fs::remove_file(repo.path().join("link")).unwrap();
+    fs::write(repo.path().join("link"), "now a file\n").unwrap();
+
+    let status = repo.status().unwrap();
+    let entry = |name: &str| status.files.iter().find(|f| f.path.as_path() == std::path::Path::new(name)).unwrap().clone();
+    let script = entry("run.sh");
+    assert_eq!(script.head_kind, Some(EntryKind::Regular));
+    assert_eq!(script.worktree_kind, Some(EntryKind::Executable));
+    let link = entry("link");
+    assert_eq!(link.worktree_status, StatusCode::TypeChanged);
+    assert_eq!((link.head_kind, link.index_kind), (Some(EntryKind::Symlink), Some(EntryKind::Symlink)));
+    assert_eq!(link.kind(), Some(EntryKind::Regular));
+
+    let diff = repo.diff("HEAD", None, &DiffOptions::default()).unwrap();
+    let script = diff.files.iter().find(|f| f.path.as_path() == std::path::Path::new("run.sh")).unwrap();
+    assert_eq!((script.old_kind, script.new_kind), (Some(EntryKind::Regular), Some(EntryKind::Executable)));
+    let link: Vec<_> = diff.files.iter().filter(|f| f.path.as_path() == std::path::Path::new("link")).collect();
+    assert_eq!(link.len(), 2);
+    assert_eq!((link[0].old_kind, link[0].new_kind), (Some(EntryKind::Symlink), None));
+    assert_eq!((link[1].old_kind, link[1].new_kind), (None, Some(EntryKind::Regular)));
+}
@@ -0,0 +1,39 @@
+use std::fs::File;
+use GitPilot::testing::TempRepo;
+use GitPilot::{GitError, Repository};
+
+#[test]
+fn test_cmd_lines() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first").commit_file("b.txt", "two\n", "second");
+
+    let subjects: Vec<String> = repo.cmd_lines(["log", "--format=%s"]).unwrap().map(Result::unwrap).collect();
+    assert_eq!(subjects, ["second", "first"]);
+
+    // Streaming is not subject to the output limit.
+    repo.commit_file("big.txt", &"line\n".repeat(10_000), "big");
+    let limited = Repository::builder(repo.path()).max_output_bytes(1024).build();
+    assert_eq!(limited.cmd_lines(["show", "HEAD:big.txt"]).unwrap().count(), 10_000);
+
+    // Stopping early is fine.
+    assert_eq!(limited.cmd_lines(["show", "HEAD:big.txt"]).unwrap().next().unwrap().unwrap(), "line");
+
+    let mut lines = repo.cmd_lines(["show", "HEAD:missing.txt"]).unwrap();
+    assert!(matches!(lines.next(), Some(Err(GitError::GitError { .. }))));
+    assert!(lines.next().is_none());
+}
+
+#[test]
+fn test_cmd_to_writer() {
+    let repo = TempRepo::new();
+    repo.commit_file("big.txt", &"line\n".repeat(10_000), "big");
+    let target = repo.path().join(".git").join("big.out");
+
+    let limited = Repository::builder(repo.path()).max_output_bytes(1024).build();
+    let written = limited.cmd_to_writer(["show", "HEAD:big.txt"], &mut File::create(&target).unwrap()).unwrap();
+    assert_eq!(written, 50_000);
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "line\n".repeat(10_000));
+
+    let mut sink = Vec::new();
+    assert!(matches!(repo.cmd_to_writer(["show", "HEAD:missing.txt"], &mut sink), Err(GitError::GitError { .. })));
+}
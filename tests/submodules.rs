@@ -0,0 +1,88 @@
+use std::fs;
+use GitPilot::models::{EntryKind, SubmoduleChanges, SubmoduleState};
+use GitPilot::testing::TempRepo;
+use GitPilot::types::GitUrl;
+use GitPilot::Repository;
+
+/// Creates a superproject with `lib` checked out from a separate fixture repository.
+fn superproject() -> (TempRepo, TempRepo) {
+    let lib = TempRepo::new();
+    lib.commit_file("lib.rs", "pub fn one() {}\n", "lib");
+    let project = TempRepo::new();
+    project.commit_file("main.rs", "fn main() {}\n", "main");
+    let url = GitUrl::from_path(lib.path()).unwrap();
+    // Recent Git refuses file:// submodules unless explicitly allowed.
+    project
+        .cmd(["-c", "protocol.file.allow=always", "submodule", "add", "-q", url.as_ref(), "lib"])
+        .unwrap();
+    project.commit_staged("add lib").unwrap();
+    (project, lib)
+}
+
+#[test]
+fn test_status_recursive_reports_submodule_state() {
+    let (project, _lib) = superproject();
+
+    let clean = project.status_recursive().unwrap();
+    assert!(!clean.is_dirty());
+    assert_eq!(clean.submodules.len(), 1);
+    let sub = &clean.submodules[0];
+    assert_eq!(sub.submodule.path.as_os_str(), "lib");
+    assert_eq!(sub.submodule.state, SubmoduleState::UpToDate);
+    assert_eq!(sub.recorded_commit.as_ref(), Some(&sub.submodule.commit));
+    assert_eq!((sub.ahead, sub.behind), (Some(0), Some(0)));
+
+    // Uncommitted changes inside the submodule show up in its nested status.
+    fs::write(project.path().join("lib/lib.rs"), "pub fn two() {}\n").unwrap();
+    let dirty = project.status_recursive().unwrap();
+    assert!(dirty.is_dirty());
+    assert!(!dirty.submodules[0].status.as_ref().unwrap().status.is_clean);
+
+    // A new commit in the submodule moves it ahead of the recorded commit.
+    let lib = Repository::new(project.path().join("lib"));
+    lib.cmd(["-c", "user.name=Test", "-c", "user.email=test@gitpilot.invalid", "commit", "-qam", "two"])
+        .unwrap();
+    let ahead = project.status_recursive().unwrap();
+    let sub = &ahead.submodules[0];
+    assert_eq!(sub.submodule.state, SubmoduleState::CommitChanged);
+    assert_eq!((sub.ahead, sub.behind), (Some(1), Some(0)));
+    assert!(ahead.is_dirty());
+}
+
+#[test]
+fn test_status_recursive_without_submodules() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "a\n", "first");
+    let status = repo.status_recursive().unwrap();
+    assert!(status.submodules.is_empty());
+    assert!(!status.is_dirty());
+}
+
+#[test]
+fn test_status_entry_submodule_changes() {
+    let (project, _lib) = superproject();
+    let entry = |status: &GitPilot::models::StatusResult| {
+        status.files.iter().find(|f| f.path.as_os_str() == "lib").cloned()
+    };
+    assert!(entry(&project.status().unwrap()).is_none());
+
+    fs::write(project.path().join("lib/lib.rs"), "pub fn two() {}\n").unwrap();
+    fs::write(project.path().join("lib/notes.txt"), "x\n").unwrap();
+    let dirty = entry(&project.status().unwrap()).unwrap();
+    assert_eq!(dirty.worktree_kind, Some(EntryKind::Submodule));
+    let changes = dirty.submodule.unwrap();
+    assert!(!changes.commit_changed);
+    assert!(changes.has_modifications);
+    assert!(changes.has_untracked);
+
+    let lib = Repository::new(project.path().join("lib"));
+    lib.cmd(["-c", "user.name=Test", "-c", "user.email=test@gitpilot.invalid", "commit", "-qam", "two"])
+        .unwrap();
+    let moved = entry(&project.status().unwrap()).unwrap().submodule.unwrap();
+    assert_eq!(moved, SubmoduleChanges { commit_changed: true, has_modifications: false, has_untracked: true });
+
+    fs::write(project.path().join("main.rs"), "fn main() { }\n").unwrap();
+    let status = project.status().unwrap();
+    let main = status.files.iter().find(|f| f.path.as_os_str() == "main.rs").unwrap();
+    assert!(main.submodule.is_none());
+}
@@ -0,0 +1,59 @@
+use GitPilot::testing::TempRepo;
+use GitPilot::versioning::{Bump, Version};
+
+fn names<T: ToString>(tags: Vec<T>) -> Vec<String> {
+    tags.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_tag_queries() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    assert_eq!(repo.latest_tag().unwrap(), None);
+    assert!(repo.list_tags().unwrap().is_empty());
+
+    repo.cmd(["tag", "v1.9.0"]).unwrap();
+    repo.cmd(["tag", "-a", "-m", "release", "v1.10.0"]).unwrap();
+    repo.commit_file("a.txt", "two\n", "second");
+    repo.cmd(["tag", "v2.0.0"]).unwrap();
+    repo.cmd(["tag", "nightly"]).unwrap();
+    repo.commit_file("a.txt", "three\n", "third");
+
+    // Version order, not byte order.
+    assert_eq!(names(repo.list_tags_matching("v1.*").unwrap()), ["v1.9.0", "v1.10.0"]);
+    assert_eq!(names(repo.list_tags().unwrap()), ["nightly", "v1.9.0", "v1.10.0", "v2.0.0"]);
+    assert_eq!(names(repo.tags_pointing_at("HEAD~1").unwrap()), ["nightly", "v2.0.0"]);
+    assert_eq!(names(repo.tags_pointing_at("HEAD~2").unwrap()), ["v1.9.0", "v1.10.0"]);
+    assert!(repo.tags_pointing_at("HEAD").unwrap().is_empty());
+    assert!(repo.tags_pointing_at("no-such-commit").is_err());
+
+    repo.cmd(["tag", "-d", "nightly"]).unwrap();
+    assert_eq!(repo.latest_tag().unwrap().unwrap().to_string(), "v2.0.0");
+}
+
+#[test]
+fn test_semver_release_tags() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    assert_eq!(repo.latest_semver_tag(Some("v")).unwrap(), None);
+    assert_eq!(repo.next_version(Some("v"), Bump::Minor).unwrap(), Version::new(0, 1, 0));
+
+    repo.cmd(["tag", "v1.9.0"]).unwrap();
+    repo.cmd(["tag", "v1.10.0"]).unwrap();
+    repo.cmd(["tag", "v2.0.0-rc.1"]).unwrap();
+    repo.cmd(["tag", "v1.11"]).unwrap();
+    repo.cmd(["tag", "3.0.0"]).unwrap();
+
+    let (tag, version) = repo.latest_semver_tag(Some("v")).unwrap().unwrap();
+    assert_eq!((tag.to_string(), version.to_string()), ("v2.0.0-rc.1".to_string(), "2.0.0-rc.1".to_string()));
+    assert_eq!(repo.latest_semver_tag(None).unwrap().unwrap().1, Version::new(3, 0, 0));
+
+    let next = repo.next_version(Some("v"), Bump::Major).unwrap();
+    assert_eq!(next, Version::new(2, 0, 0));
+    repo.commit_file("a.txt", "two\n", "second");
+    let tag = repo.tag_release(Some("v"), &next, "Release 2.0.0", false).unwrap();
+    assert_eq!(tag.to_string(), "v2.0.0");
+    assert_eq!(repo.cmd_out(["cat-file", "-t", "v2.0.0"]).unwrap(), ["tag"]);
+    assert_eq!(repo.next_version(Some("v"), Bump::Patch).unwrap(), Version::new(2, 0, 1));
+    assert!(repo.tag_release(Some("v"), &next, "again", false).is_err());
+}
@@ -0,0 +1,119 @@
+use std::fs;
+use GitPilot::error::GitError;
+use GitPilot::testing::TempRepo;
+
+#[test]
+fn test_transaction_keeps_successful_steps() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+
+    let count = repo
+        .transaction(|tx| {
+            tx.cmd(["commit", "-q", "--allow-empty", "-m", "release"])?;
+            tx.cmd(["tag", "v1.0.0"])?;
+            tx.count_commits(None)
+        })
+        .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(repo.cmd_out(["tag"]).unwrap(), vec!["v1.0.0"]);
+}
+
+#[test]
+fn test_transaction_rolls_back_on_failure() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.commit_file("b.txt", "b\n", "second");
+    let head = repo.get_hash(false).unwrap();
+    // Pre-existing uncommitted work must survive the rollback.
+    fs::write(repo.path().join("a.txt"), "staged\n").unwrap();
+    repo.add(vec!["a.txt"]).unwrap();
+    fs::write(repo.path().join("b.txt"), "unstaged\n").unwrap();
+    fs::write(repo.path().join("notes.txt"), "mine\n").unwrap();
+
+    let result: GitPilot::Result<()> = repo.transaction(|tx| {
+        tx.stage_and_commit_all_modified("release")?;
+        tx.cmd(["tag", "v1.0.0"])?;
+        tx.cmd(["branch", "release"])?;
+        tx.cmd(["reset", "-q", "--hard", "HEAD~2"])?;
+        fs::write(repo.path().join("generated.txt"), "x\n")?;
+        tx.cmd(["checkout", "-q", "--detach"])?;
+        tx.cmd(["tag", "v1.0.0"])
+    });
+    assert!(matches!(result, Err(GitError::GitError { .. })));
+
+    assert_eq!(repo.get_hash(false).unwrap(), head);
+    assert!(repo.cmd_out(["symbolic-ref", "-q", "HEAD"]).is_ok());
+    assert!(repo.cmd_out(["tag"]).unwrap().is_empty());
+    assert_eq!(repo.list_branches().unwrap().len(), 1);
+    assert!(repo.has_staged_changes().unwrap());
+    assert!(repo.has_unstaged_changes().unwrap());
+    assert_eq!(fs::read_to_string(repo.path().join("b.txt")).unwrap(), "unstaged\n");
+    assert!(repo.path().join("notes.txt").exists());
+    assert!(!repo.path().join("generated.txt").exists());
+}
+
+#[test]
+fn test_transaction_abandons_conflicted_cherry_pick() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "base\n", "base");
+    repo.cmd(["checkout", "-q", "-b", "topic"]).unwrap();
+    repo.commit_file("a.txt", "topic\n", "topic");
+    repo.commit_file("a.txt", "topic 2\n", "topic 2");
+    repo.cmd(["checkout", "-q", "-"]).unwrap();
+    repo.commit_file("a.txt", "main\n", "main");
+    let head = repo.get_hash(false).unwrap();
+
+    let result = repo.transaction(|tx| tx.cherry_pick(vec!["topic~1", "topic"]));
+    assert!(result.is_err());
+    assert_eq!(repo.get_hash(false).unwrap(), head);
+    assert!(repo.status().unwrap().is_clean);
+    // A new sequence can start, so no cherry-pick is left in progress.
+    assert!(repo.cmd(["cherry-pick", "--abort"]).is_err());
+}
+
+#[test]
+fn test_transaction_restores_stash_list_and_directories() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    for content in ["two\n", "three\n"] {
+        repo.write_file("a.txt", content);
+        repo.cmd(["stash", "push", "-q"]).unwrap();
+    }
+    let stashes = repo.cmd_out(["stash", "list"]).unwrap();
+    fs::create_dir(repo.path().join("empty")).unwrap();
+
+    let result: GitPilot::Result<()> = repo.transaction(|tx| {
+        tx.cmd(["stash", "drop", "-q"])?;
+        fs::write(repo.path().join("a.txt"), "four\n")?;
+        tx.cmd(["stash", "push", "-q"])?;
+        fs::create_dir_all(repo.path().join("empty/new/deeper"))?;
+        fs::write(repo.path().join("empty/new/deeper/out.txt"), "x\n")?;
+        fs::create_dir_all(repo.path().join("generated/nested"))?;
+        fs::write(repo.path().join("generated/nested/out.txt"), "x\n")?;
+        tx.cmd(["tag", "v1.0.0", "does-not-exist"])
+    });
+    assert!(result.is_err());
+
+    assert_eq!(repo.cmd_out(["stash", "list"]).unwrap(), stashes);
+    assert!(!repo.path().join("generated").exists());
+    assert!(!repo.path().join("empty/new").exists());
+    assert!(repo.path().join("empty").is_dir());
+}
+
+#[test]
+fn test_transaction_restores_untracked_files_it_staged_or_changed() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    repo.write_file("CHANGELOG.md", "## 1.2.0\n").write_file("notes.txt", "mine\n");
+
+    let result: GitPilot::Result<()> = repo.transaction(|tx| {
+        tx.add(vec!["CHANGELOG.md"])?;
+        fs::write(repo.path().join("notes.txt"), "overwritten\n")?;
+        tx.cmd(["tag", "v1.2.0", "does-not-exist"])
+    });
+    assert!(result.is_err());
+
+    assert_eq!(fs::read_to_string(repo.path().join("CHANGELOG.md")).unwrap(), "## 1.2.0\n");
+    assert_eq!(fs::read_to_string(repo.path().join("notes.txt")).unwrap(), "mine\n");
+    assert!(!repo.has_staged_changes().unwrap());
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "notify")]
+
+use futures_core::Stream;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Duration;
+use GitPilot::testing::TempRepo;
+use GitPilot::watch::{EventMask, RepoEvent, RepoWatch};
+use GitPilot::BranchName;
+
+/// Collects events until `expected` arrives, failing after a few seconds.
+async fn wait_for(events: &mut RepoWatch, expected: RepoEvent) -> Vec<RepoEvent> {
+    let mut seen = Vec::new();
+    let collect = async {
+        while let Some(event) = std::future::poll_fn(|cx| Pin::new(&mut *events).poll_next(cx)).await {
+            let done = event == expected;
+            seen.push(event);
+            if done {
+                return;
+            }
+        }
+    };
+    if tokio::time::timeout(Duration::from_secs(5), collect).await.is_err() {
+        panic!("no {:?} event; saw {:?}", expected, seen);
+    }
+    seen
+}
+
+#[tokio::test]
+async fn test_watch_reports_head_branch_and_index_changes() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let mut events = repo.watch(EventMask::ALL).unwrap();
+
+    repo.cmd(["checkout", "-q", "-b", "topic"]).unwrap();
+    wait_for(&mut events, RepoEvent::HeadChanged).await;
+
+    repo.write_file("a.txt", "two\n");
+    repo.add(vec!["a.txt"]).unwrap();
+    wait_for(&mut events, RepoEvent::IndexChanged).await;
+
+    repo.cmd(["commit", "-qm", "second"]).unwrap();
+    let topic = BranchName::from_str("topic").unwrap();
+    wait_for(&mut events, RepoEvent::BranchUpdated(Some(topic))).await;
+}
+
+#[tokio::test]
+async fn test_watch_filters_by_mask() {
+    let repo = TempRepo::new();
+    repo.commit_file("a.txt", "one\n", "first");
+    let mut events = repo.watch(EventMask::BRANCHES).unwrap();
+
+    // Only the branch creation is reported, not HEAD switching to it.
+    repo.cmd(["checkout", "-q", "-b", "feature/x"]).unwrap();
+    let seen = wait_for(&mut events, RepoEvent::BranchUpdated(Some(BranchName::from_str("feature/x").unwrap()))).await;
+    assert!(seen.iter().all(|event| matches!(event, RepoEvent::BranchUpdated(_))));
+
+    repo.cmd(["pack-refs", "--all"]).unwrap();
+    wait_for(&mut events, RepoEvent::BranchUpdated(None)).await;
+    assert_eq!(events.try_next().filter(|event| !matches!(event, RepoEvent::BranchUpdated(_))), None);
+}
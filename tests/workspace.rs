@@ -0,0 +1,51 @@
+#![cfg(feature = "async")]
+
+use tempfile::TempDir;
+use GitPilot::options::FetchOptions;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::{GitUrl, Remote};
+use GitPilot::workspace::RepoSet;
+use std::str::FromStr;
+
+#[tokio::test]
+async fn test_status_all_reports_each_repo_in_order() {
+    let clean = TempRepo::new();
+    clean.commit_file("a.txt", "one\n", "first");
+    let dirty = TempRepo::new();
+    dirty.commit_file("a.txt", "one\n", "first").write_file("a.txt", "two\n");
+    let missing = TempDir::new().unwrap();
+
+    let repos = RepoSet::from_paths([clean.path(), missing.path(), dirty.path()]);
+    let results = repos.status_all(2).await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].path, clean.path());
+    assert!(results[0].result.as_ref().unwrap().is_clean);
+    assert!(results[1].result.is_err());
+    assert!(!results[2].result.as_ref().unwrap().is_clean);
+}
+
+#[tokio::test]
+async fn test_fetch_all_and_for_each_concurrent() {
+    let upstream = TempRepo::new();
+    upstream.commit_file("a.txt", "one\n", "first");
+    let url = GitUrl::from_path(upstream.path()).unwrap();
+    let origin = Remote::from_str("origin").unwrap();
+    let clones: Vec<TempRepo> = (0..3).map(|_| TempRepo::new()).collect();
+    for clone in &clones {
+        clone.add_remote(&origin, &url).unwrap();
+    }
+
+    let repos = RepoSet::from_paths(clones.iter().map(|c| c.path()));
+    let opts = FetchOptions { remote: Some(origin), ..Default::default() };
+    assert!(repos.fetch_all(0, &opts).await.iter().all(|r| r.result.is_ok()));
+
+    let head = upstream.get_hash(false).unwrap();
+    let results = repos
+        .for_each_concurrent(2, |repo| async move { repo.cmd_out(["rev-parse", "FETCH_HEAD"]).await })
+        .await;
+    for (outcome, clone) in results.iter().zip(&clones) {
+        assert_eq!(outcome.path, clone.path());
+        assert_eq!(outcome.result.as_ref().unwrap()[0], head.to_string());
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::str::FromStr;
+use GitPilot::error::GitError;
+use GitPilot::testing::TempRepo;
+use GitPilot::types::Remote;
+use GitPilot::Repository;
+
+/// Creates a repository with a linked worktree on `topic` next to it, and
+/// returns the linked worktree's path.
+fn with_linked_worktree(repo: &TempRepo) -> std::path::PathBuf {
+    repo.commit_file("a.txt", "one\n", "first");
+    let linked = repo.path().join("linked");
+    repo.cmd(["worktree", "add", "-q", "-b", "topic", "linked"]).unwrap();
+    linked
+}
+
+#[test]
+fn test_list_worktrees() {
+    let repo = TempRepo::new();
+    let linked = with_linked_worktree(&repo);
+
+    let worktrees = repo.list_worktrees().unwrap();
+    assert_eq!(worktrees.len(), 2);
+    assert!(worktrees[0].is_main);
+    assert_eq!(fs::canonicalize(&worktrees[0].path).unwrap(), fs::canonicalize(repo.path()).unwrap());
+    assert_eq!(fs::canonicalize(&worktrees[1].path).unwrap(), fs::canonicalize(&linked).unwrap());
+    assert_eq!(worktrees[1].branch.as_deref(), Some("topic"));
+    assert_eq!(worktrees[1].head, repo.get_hash(false).unwrap());
+
+    // The same list, in the same order, from the linked worktree.
+    let from_linked = Repository::new(&linked).list_worktrees().unwrap();
+    assert_eq!(from_linked[0].path, worktrees[0].path);
+
+    fs::remove_dir_all(&linked).unwrap();
+    assert!(repo.list_worktrees().unwrap()[1].is_prunable);
+}
+
+#[test]
+fn test_navigate_between_worktrees() {
+    let repo = TempRepo::new();
+    let linked_path = with_linked_worktree(&repo);
+    let remote = Remote::from_str("upstream").unwrap();
+    let linked = Repository::builder(&linked_path).default_remote(remote.clone()).build();
+
+    // `.git` is a file in the linked worktree; the main worktree has the directory.
+    let main = linked.main_worktree().unwrap();
+    assert_eq!(main.git_dir().unwrap(), repo.git_dir().unwrap());
+    assert_eq!(main.cmd_out(["branch", "--show-current"]).unwrap(), repo.cmd_out(["branch", "--show-current"]).unwrap());
+    assert_eq!(main.default_remote(), Some(&remote));
+
+    let sibling = main.open_worktree("linked").unwrap();
+    assert_eq!(sibling.cmd_out(["branch", "--show-current"]).unwrap(), ["topic"]);
+    assert_eq!(sibling.git_dir().unwrap(), repo.git_dir().unwrap().join("worktrees").join("linked"));
+    assert_eq!(sibling.default_remote(), Some(&remote));
+    assert_eq!(linked.open_worktree(&linked_path).unwrap().git_dir().unwrap(), sibling.git_dir().unwrap());
+    assert_eq!(repo.main_worktree().unwrap().git_dir().unwrap(), repo.git_dir().unwrap());
+
+    fs::create_dir(repo.path().join("plain")).unwrap();
+    assert!(matches!(repo.open_worktree("plain"), Err(GitError::NotAWorktree(_))));
+    assert!(matches!(repo.open_worktree("missing"), Err(GitError::NotAWorktree(_))));
+}
+
+#[test]
+fn test_main_worktree_of_bare_repository() {
+    let origin = TempRepo::new();
+    origin.commit_file("a.txt", "one\n", "first");
+    let bare_path = origin.path().join("bare.git");
+    origin.cmd(["clone", "-q", "--bare", ".", bare_path.to_str().unwrap()]).unwrap();
+    let bare = Repository::new(&bare_path);
+    let branch = origin.cmd_out(["branch", "--show-current"]).unwrap().remove(0);
+    bare.cmd(["worktree", "add", "-q", "../checkout", &branch]).unwrap();
+
+    let checkout = bare.open_worktree("../checkout").unwrap();
+    let worktrees = checkout.list_worktrees().unwrap();
+    assert!(worktrees[0].is_bare);
+    assert_eq!(
+        fs::canonicalize(checkout.main_worktree().unwrap().git_dir().unwrap()).unwrap(),
+        fs::canonicalize(&bare_path).unwrap()
+    );
+}